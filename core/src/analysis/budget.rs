@@ -0,0 +1,118 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::data_provider_twiggy::DataProviderTwiggy;
+
+/// Size limits the analyzed binaries are checked against.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SizeBudgets {
+    /// Budgets applied to whole files, matched by file name.
+    pub files: Vec<SizeBudget>,
+
+    /// Budgets applied to the sum of all functions attributed to a crate.
+    pub crates: Vec<SizeBudget>,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SizeBudget {
+    /// File name (e.g. `app.wasm`) or crate name (e.g. `serde_json`).
+    pub name: String,
+    pub max_bytes: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BudgetViolation {
+    File {
+        name: String,
+        size: u64,
+        max_bytes: u64,
+    },
+    Crate {
+        name: String,
+        size: u64,
+        max_bytes: u64,
+    },
+}
+
+impl BudgetViolation {
+    pub fn is_crate(&self, crate_name: &str) -> bool {
+        matches!(self, BudgetViolation::Crate { name, .. } if name == crate_name)
+    }
+}
+
+impl std::fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetViolation::File {
+                name,
+                size,
+                max_bytes,
+            } => write!(
+                f,
+                "File '{}' is {} bytes, over its budget of {} bytes",
+                name, size, max_bytes
+            ),
+            BudgetViolation::Crate {
+                name,
+                size,
+                max_bytes,
+            } => write!(
+                f,
+                "Crate '{}' is {} bytes, over its budget of {} bytes",
+                name, size, max_bytes
+            ),
+        }
+    }
+}
+
+impl SizeBudgets {
+    /// Checks the analyzed file against all the budgets, returning the
+    /// ones that were exceeded.
+    ///
+    /// This doesn't depend on any UI state so it can be used both to
+    /// highlight rows in the explorer and by headless runs.
+    pub fn check(&self, path: &Path, data_provider: &DataProviderTwiggy) -> Vec<BudgetViolation> {
+        let mut violations = Vec::new();
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let file_size = data_provider.wasm_data.bytes.len() as u64;
+
+        for budget in &self.files {
+            if budget.name == file_name && file_size > budget.max_bytes {
+                violations.push(BudgetViolation::File {
+                    name: budget.name.clone(),
+                    size: file_size,
+                    max_bytes: budget.max_bytes,
+                });
+            }
+        }
+
+        if self.crates.is_empty() {
+            return violations;
+        }
+
+        let mut crate_sizes: HashMap<&str, u64> = HashMap::new();
+        for function_data in data_provider.raw_data.iter() {
+            let property = &function_data.function_property;
+            if let Some(crate_name) = property.crate_name {
+                *crate_sizes.entry(crate_name).or_default() += property.shallow_size_bytes as u64;
+            }
+        }
+
+        for budget in &self.crates {
+            let size = crate_sizes.get(budget.name.as_str()).copied().unwrap_or(0);
+            if size > budget.max_bytes {
+                violations.push(BudgetViolation::Crate {
+                    name: budget.name.clone(),
+                    size,
+                    max_bytes: budget.max_bytes,
+                });
+            }
+        }
+
+        violations
+    }
+}
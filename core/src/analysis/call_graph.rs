@@ -0,0 +1,217 @@
+use wasmparser::Operator;
+
+use crate::{
+    arena::{Arena, array::Array, scratch::scratch_arena},
+    data_provider::FunctionOp,
+    data_provider_twiggy::DataProviderTwiggy,
+};
+
+/// The static call graph between the functions defined in the module.
+///
+/// Only direct `call`s (and `return_call`s) are taken into account, calls
+/// to imported functions and indirect calls through tables are ignored.
+///
+/// Edges are stored in two CSR-style arrays: the callees of function `idx`
+/// are `callees[callee_offsets[idx]..callee_offsets[idx + 1]]`, and likewise
+/// for callers.
+pub struct CallGraph<'a> {
+    callee_offsets: Array<'a, u32>,
+    callees: Array<'a, u32>,
+    caller_offsets: Array<'a, u32>,
+    callers: Array<'a, u32>,
+}
+
+impl<'a> CallGraph<'a> {
+    /// Builds the call graph from the decoded operators of each function.
+    ///
+    /// `function_ops(idx)` returns the operators of function `idx` of the
+    /// code section.
+    pub fn new<'b>(
+        arena: &'a Arena,
+        function_count: usize,
+        imported_function_count: u32,
+        function_ops: impl Fn(usize) -> &'b [FunctionOp<'b>],
+    ) -> Self {
+        let scratch = scratch_arena(&[arena]);
+
+        let mut edge_count = 0;
+        for idx in 0..function_count {
+            edge_count += function_ops(idx)
+                .iter()
+                .filter(|op| call_target(&op.op, imported_function_count, function_count).is_some())
+                .count();
+        }
+
+        let mut callee_offsets = Array::new(arena, function_count + 1);
+        let mut callees = Array::new(arena, edge_count);
+        let mut in_degree = Array::new(&scratch, function_count);
+        for _ in 0..function_count {
+            in_degree.push(0u32);
+        }
+
+        for idx in 0..function_count {
+            callee_offsets.push(callees.len() as u32);
+
+            let start = callees.len();
+            for op in function_ops(idx) {
+                if let Some(callee) = call_target(&op.op, imported_function_count, function_count) {
+                    callees.push(callee);
+                }
+            }
+
+            // A function calling another one several times is a single edge.
+            callees[start..].sort_unstable();
+            let mut unique_end = start;
+            for callee_idx in start..callees.len() {
+                if callee_idx == start || callees[callee_idx] != callees[unique_end - 1] {
+                    callees[unique_end] = callees[callee_idx];
+                    unique_end += 1;
+                }
+            }
+            while callees.len() > unique_end {
+                callees.pop();
+            }
+
+            for &callee in &callees[start..] {
+                in_degree[callee as usize] += 1;
+            }
+        }
+        callee_offsets.push(callees.len() as u32);
+        callees.shrink_to_fit();
+
+        let mut caller_offsets = Array::new(arena, function_count + 1);
+        let mut offset = 0;
+        for idx in 0..function_count {
+            caller_offsets.push(offset);
+            offset += in_degree[idx];
+        }
+        caller_offsets.push(offset);
+
+        let mut callers = Array::new(arena, callees.len());
+        for _ in 0..callees.len() {
+            callers.push(0u32);
+        }
+
+        // Reuse the in-degree array as the insertion cursor of each function.
+        for idx in 0..function_count {
+            in_degree[idx] = caller_offsets[idx];
+        }
+        for caller in 0..function_count {
+            let range = callee_offsets[caller] as usize..callee_offsets[caller + 1] as usize;
+            for &callee in &callees[range] {
+                let cursor = &mut in_degree[callee as usize];
+                callers[*cursor as usize] = caller as u32;
+                *cursor += 1;
+            }
+        }
+
+        Self {
+            callee_offsets,
+            callees,
+            caller_offsets,
+            callers,
+        }
+    }
+
+    /// The functions directly called by function `idx`.
+    pub fn callees(&self, idx: usize) -> &[u32] {
+        &self.callees[self.callee_offsets[idx] as usize..self.callee_offsets[idx + 1] as usize]
+    }
+
+    /// The functions directly calling function `idx`.
+    pub fn callers(&self, idx: usize) -> &[u32] {
+        &self.callers[self.caller_offsets[idx] as usize..self.caller_offsets[idx + 1] as usize]
+    }
+
+    /// Whether each function is called by function `idx`, directly or not,
+    /// `idx` itself being reached.
+    pub fn reachable_from(&self, idx: usize) -> Vec<bool> {
+        let mut reached = vec![false; self.callee_offsets.len() - 1];
+        reached[idx] = true;
+        let mut pending = vec![idx];
+        while let Some(caller) = pending.pop() {
+            for &callee in self.callees(caller) {
+                if !reached[callee as usize] {
+                    reached[callee as usize] = true;
+                    pending.push(callee as usize);
+                }
+            }
+        }
+        reached
+    }
+}
+
+/// Whether each function is in the exported call graphs: the ones `from`
+/// reaches, or all of them without it, of `min_size` bytes or more. `from`
+/// is kept whatever its size.
+pub fn exported_functions(
+    data_provider: &DataProviderTwiggy,
+    from: Option<usize>,
+    min_size: u32,
+) -> Vec<bool> {
+    let mut exported = match from {
+        Some(idx) => data_provider.call_graph.reachable_from(idx),
+        None => vec![true; data_provider.raw_data.len()],
+    };
+    for (idx, function_data) in data_provider.raw_data.iter().enumerate() {
+        if Some(idx) != from && function_data.function_property.shallow_size_bytes < min_size {
+            exported[idx] = false;
+        }
+    }
+    exported
+}
+
+/// Returns the index (in the code section) of the function called by `op`.
+pub fn call_target(
+    op: &Operator<'_>,
+    imported_function_count: u32,
+    function_count: usize,
+) -> Option<u32> {
+    match *op {
+        Operator::Call { function_index } | Operator::ReturnCall { function_index } => {
+            function_index
+                .checked_sub(imported_function_count)
+                .filter(|&idx| (idx as usize) < function_count)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arena::memory::MB;
+
+    #[test]
+    fn edges_are_deduplicated_and_reversed() {
+        let arena = Arena::new(MB);
+        let call = |function_index| FunctionOp::new(0, Operator::Call { function_index });
+
+        // One imported function, so code section function `idx` is `idx + 1`.
+        let function_ops = [
+            vec![call(2), call(2), call(0), call(3)],
+            vec![call(3)],
+            vec![],
+        ];
+        let call_graph = CallGraph::new(&arena, function_ops.len(), 1, |idx| &function_ops[idx]);
+
+        assert_eq!(call_graph.callees(0), &[1, 2]);
+        assert_eq!(call_graph.callees(1), &[2]);
+        assert_eq!(call_graph.callees(2), &[] as &[u32]);
+        assert_eq!(call_graph.callers(0), &[] as &[u32]);
+        assert_eq!(call_graph.callers(1), &[0]);
+        assert_eq!(call_graph.callers(2), &[0, 1]);
+    }
+
+    #[test]
+    fn reachable_functions_follow_the_callees() {
+        let arena = Arena::new(MB);
+        let call = |function_index| FunctionOp::new(0, Operator::Call { function_index });
+
+        let function_ops = [vec![call(1)], vec![call(2)], vec![call(1)], vec![call(0)]];
+        let call_graph = CallGraph::new(&arena, function_ops.len(), 0, |idx| &function_ops[idx]);
+
+        assert_eq!(call_graph.reachable_from(1), [false, true, true, false]);
+        assert_eq!(call_graph.reachable_from(3), [true; 4]);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, path::Path, process::Command};
+
+use anyhow::{Context, bail};
+
+/// The crate dependency graph of a cargo workspace, from `cargo metadata`.
+#[derive(Default)]
+pub struct CrateGraph {
+    pub crates: Vec<CrateNode>,
+}
+
+pub struct CrateNode {
+    /// Name of the library target, as it appears in symbol paths (`serde_json`, not `serde-json`).
+    pub name: String,
+    pub version: String,
+    pub is_workspace_member: bool,
+    /// Indices of the crates this one depends on.
+    pub dependencies: Vec<usize>,
+}
+
+impl CrateGraph {
+    /// Runs `cargo metadata` on the workspace the manifest belongs to.
+    pub fn from_manifest(manifest_path: &Path) -> anyhow::Result<Self> {
+        let output = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--manifest-path")
+            .arg(manifest_path)
+            .output()
+            .context("Failed to run `cargo metadata`")?;
+
+        if !output.status.success() {
+            bail!(
+                "`cargo metadata` failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Self::from_json(&output.stdout)
+    }
+
+    pub fn from_json(json: &[u8]) -> anyhow::Result<Self> {
+        let metadata: Metadata =
+            serde_json::from_slice(json).context("Failed to parse `cargo metadata` output")?;
+
+        let package_lookup: HashMap<&str, usize> = metadata
+            .packages
+            .iter()
+            .enumerate()
+            .map(|(idx, package)| (package.id.as_str(), idx))
+            .collect();
+
+        let mut crates: Vec<CrateNode> = metadata
+            .packages
+            .iter()
+            .map(|package| CrateNode {
+                name: package.lib_name(),
+                version: package.version.clone(),
+                is_workspace_member: metadata.workspace_members.contains(&package.id),
+                dependencies: Vec::new(),
+            })
+            .collect();
+
+        // The resolved graph only exists if dependencies were resolved (no `--no-deps`).
+        for node in metadata.resolve.iter().flat_map(|resolve| &resolve.nodes) {
+            let Some(&idx) = package_lookup.get(node.id.as_str()) else {
+                continue;
+            };
+
+            crates[idx].dependencies = node
+                .deps
+                .iter()
+                .filter_map(|dep| package_lookup.get(dep.pkg.as_str()).copied())
+                .collect();
+        }
+
+        Ok(Self { crates })
+    }
+
+    pub fn find(&self, crate_name: &str) -> Option<usize> {
+        self.crates
+            .iter()
+            .position(|crate_node| crate_node.name == crate_name)
+    }
+}
+
+/// The subset of the `cargo metadata` output we need.
+#[derive(serde::Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    resolve: Option<Resolve>,
+}
+
+#[derive(serde::Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    version: String,
+    targets: Vec<Target>,
+}
+
+impl Package {
+    fn lib_name(&self) -> String {
+        self.targets
+            .iter()
+            .find(|target| {
+                target
+                    .kind
+                    .iter()
+                    .any(|kind| kind.ends_with("lib") || kind == "proc-macro")
+            })
+            .map(|target| target.name.clone())
+            .unwrap_or_else(|| self.name.clone())
+            .replace('-', "_")
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Resolve {
+    nodes: Vec<Node>,
+}
+
+#[derive(serde::Deserialize)]
+struct Node {
+    id: String,
+    deps: Vec<NodeDep>,
+}
+
+#[derive(serde::Deserialize)]
+struct NodeDep {
+    pkg: String,
+}
@@ -0,0 +1,291 @@
+use std::{collections::BTreeSet, ops::Range};
+
+use wasmparser::Operator;
+
+use crate::{data_provider::FunctionOp, data_provider_twiggy::DataProviderTwiggy};
+
+/// A run of instructions only entered at its first one and only left after
+/// its last one.
+pub struct BasicBlock {
+    /// Indices of the instructions in the function's ops.
+    pub ops: Range<usize>,
+    pub address: u64,
+    pub size_bytes: u64,
+    /// Number of structured blocks (`block`, `loop`, `if`...) the first
+    /// instruction is in.
+    pub depth: usize,
+    /// Blocks control can go to after this one, in increasing order. The
+    /// ones not after this one are the branches back to the start of a loop.
+    pub successors: Vec<usize>,
+}
+
+/// Basic blocks of a function, split at the branches and at their targets.
+pub struct ControlFlowGraph {
+    pub function: usize,
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl ControlFlowGraph {
+    pub fn new(data_provider: &DataProviderTwiggy, function: usize) -> Self {
+        let body_end = data_provider.wasm_data.functions_section.function_bodies[function]
+            .range()
+            .end as u64;
+        let ops = &data_provider.raw_data[function].debug_info.function_ops;
+
+        Self {
+            function,
+            blocks: basic_blocks(ops, body_end),
+        }
+    }
+
+    pub fn size_bytes(&self) -> u64 {
+        self.blocks.iter().map(|block| block.size_bytes).sum()
+    }
+}
+
+/// Where control goes after an instruction, as indices of instructions.
+struct Jumps {
+    targets: Vec<usize>,
+    /// Whether control can also go to the next instruction.
+    falls_through: bool,
+}
+
+fn opens_block(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. }
+            | Operator::TryTable { .. }
+    )
+}
+
+fn closes_block(op: &Operator) -> bool {
+    matches!(op, Operator::End | Operator::Delegate { .. })
+}
+
+/// Branches of `br`, `br_if` and `br_table` instructions, as the index of the
+/// branch with the index of the instruction it goes to: the `end` of a block
+/// or the start of a loop.
+pub fn branches(ops: &[FunctionOp]) -> Vec<(usize, usize)> {
+    let (jumps, _) = jumps(ops);
+    let mut branches = Vec::new();
+    for (idx, (op, jump)) in ops.iter().zip(&jumps).enumerate() {
+        let is_branch = matches!(
+            op.op,
+            Operator::Br { .. } | Operator::BrIf { .. } | Operator::BrTable { .. }
+        );
+        if let Some(jump) = jump
+            && is_branch
+        {
+            branches.extend(jump.targets.iter().map(|&target| (idx, target)));
+        }
+    }
+
+    // The targets of a `br_table` can repeat.
+    branches.sort_unstable();
+    branches.dedup();
+    branches
+}
+
+/// Where control goes after each instruction, with the number of structured
+/// blocks each one is in.
+fn jumps(ops: &[FunctionOp]) -> (Vec<Option<Jumps>>, Vec<usize>) {
+    // The `else` and `end` of each instruction opening a block, a branch to a
+    // block going to its `end` and one to a loop going back to its start.
+    let mut else_of = vec![None; ops.len()];
+    let mut end_of = vec![None; ops.len()];
+    let mut open_blocks = Vec::new();
+    for (idx, op) in ops.iter().enumerate() {
+        if opens_block(&op.op) {
+            open_blocks.push(idx);
+        } else if let Operator::Else = op.op {
+            if let Some(&opener) = open_blocks.last() {
+                else_of[opener] = Some(idx);
+            }
+        } else if closes_block(&op.op)
+            && let Some(opener) = open_blocks.pop()
+        {
+            end_of[opener] = Some(idx);
+        }
+    }
+
+    // The function body is the outermost label, branching to it returns.
+    let mut labels: Vec<Option<usize>> = vec![None];
+    let branch_target = |labels: &[Option<usize>], relative_depth: u32| {
+        let opener = labels
+            .len()
+            .checked_sub(relative_depth as usize + 1)
+            .and_then(|idx| labels[idx])?;
+        match ops[opener].op {
+            Operator::Loop { .. } => Some(opener),
+            _ => end_of[opener],
+        }
+    };
+
+    let mut jumps: Vec<Option<Jumps>> = Vec::with_capacity(ops.len());
+    let mut depths = Vec::with_capacity(ops.len());
+    for (idx, op) in ops.iter().enumerate() {
+        depths.push(labels.len() - 1);
+
+        let jump = match &op.op {
+            Operator::If { .. } => {
+                let otherwise = else_of[idx].map(|else_idx| else_idx + 1).or(end_of[idx]);
+                Some(Jumps {
+                    targets: otherwise.into_iter().collect(),
+                    falls_through: true,
+                })
+            }
+            Operator::Else => Some(Jumps {
+                targets: labels
+                    .last()
+                    .copied()
+                    .flatten()
+                    .and_then(|opener| end_of[opener])
+                    .into_iter()
+                    .collect(),
+                falls_through: false,
+            }),
+            Operator::Br { relative_depth } => Some(Jumps {
+                targets: branch_target(&labels, *relative_depth)
+                    .into_iter()
+                    .collect(),
+                falls_through: false,
+            }),
+            Operator::BrIf { relative_depth } => Some(Jumps {
+                targets: branch_target(&labels, *relative_depth)
+                    .into_iter()
+                    .collect(),
+                falls_through: true,
+            }),
+            Operator::BrTable { targets } => Some(Jumps {
+                targets: targets
+                    .targets()
+                    .filter_map(Result::ok)
+                    .chain(std::iter::once(targets.default()))
+                    .filter_map(|relative_depth| branch_target(&labels, relative_depth))
+                    .collect(),
+                falls_through: false,
+            }),
+            Operator::Return | Operator::Unreachable => Some(Jumps {
+                targets: Vec::new(),
+                falls_through: false,
+            }),
+            _ => None,
+        };
+        jumps.push(jump);
+
+        if opens_block(&op.op) {
+            labels.push(Some(idx));
+        } else if closes_block(&op.op) && labels.len() > 1 {
+            labels.pop();
+        }
+    }
+
+    (jumps, depths)
+}
+
+fn basic_blocks(ops: &[FunctionOp], body_end: u64) -> Vec<BasicBlock> {
+    if ops.is_empty() {
+        return Vec::new();
+    }
+
+    let (jumps, depths) = jumps(ops);
+
+    // Blocks start at the first instruction, at the branch targets and after
+    // the branches.
+    let mut leaders = BTreeSet::from([0]);
+    for (idx, jump) in jumps.iter().enumerate() {
+        if let Some(jump) = jump {
+            leaders.extend(jump.targets.iter().copied());
+            leaders.insert(idx + 1);
+        }
+    }
+    // The loops are entered at their start.
+    leaders.extend(
+        ops.iter()
+            .enumerate()
+            .filter(|(_, op)| matches!(op.op, Operator::Loop { .. }))
+            .map(|(idx, _)| idx),
+    );
+    let leaders: Vec<usize> = leaders.into_iter().filter(|&idx| idx < ops.len()).collect();
+
+    let block_of = |op_idx: usize| leaders.partition_point(|&leader| leader <= op_idx) - 1;
+    leaders
+        .iter()
+        .enumerate()
+        .map(|(block_idx, &start)| {
+            let end = leaders.get(block_idx + 1).copied().unwrap_or(ops.len());
+            let end_address = ops.get(end).map_or(body_end, |op| op.address);
+
+            let last = end - 1;
+            let (targets, falls_through) = match &jumps[last] {
+                Some(jump) => (jump.targets.as_slice(), jump.falls_through),
+                None => (&[][..], true),
+            };
+            let mut successors: Vec<usize> = targets
+                .iter()
+                .chain((falls_through && end < ops.len()).then_some(&end))
+                .map(|&op_idx| block_of(op_idx))
+                .collect();
+            successors.sort_unstable();
+            successors.dedup();
+
+            BasicBlock {
+                ops: start..end,
+                address: ops[start].address,
+                size_bytes: end_address.saturating_sub(ops[start].address),
+                depth: depths[start],
+                successors,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use wasmparser::{BlockType, Operator};
+
+    use super::{basic_blocks, branches};
+    use crate::data_provider::FunctionOp;
+
+    #[test]
+    fn blocks_are_split_at_branches_and_targets() {
+        let ops = [
+            (
+                0,
+                Operator::Loop {
+                    blockty: BlockType::Empty,
+                },
+            ),
+            (2, Operator::LocalGet { local_index: 0 }),
+            (
+                4,
+                Operator::If {
+                    blockty: BlockType::Empty,
+                },
+            ),
+            (6, Operator::Call { function_index: 1 }),
+            (8, Operator::Else),
+            (9, Operator::Br { relative_depth: 1 }),
+            (11, Operator::End),
+            (12, Operator::End),
+            (13, Operator::End),
+        ]
+        .map(|(address, op)| FunctionOp::new(address, op));
+
+        let blocks = basic_blocks(&ops, 14);
+        let starts: Vec<usize> = blocks.iter().map(|block| block.ops.start).collect();
+        // The loop, the then branch, the else branch and the code after the `if`.
+        assert_eq!(starts, [0, 3, 5, 6]);
+        assert_eq!(blocks[0].successors, [1, 2]);
+        assert_eq!(blocks[1].successors, [3]);
+        // `br 1` goes back to the start of the loop.
+        assert_eq!(blocks[2].successors, [0]);
+        assert_eq!(blocks[3].size_bytes, 3);
+        assert_eq!(blocks[2].depth, 2);
+
+        assert_eq!(branches(&ops), [(5, 0)]);
+    }
+}
@@ -0,0 +1,238 @@
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use crate::{analysis::cargo_metadata::CrateGraph, data_provider_twiggy::DataProviderTwiggy};
+
+/// Crates shipped with the Rust toolchain.
+const STD_CRATES: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "compiler_builtins",
+    "panic_abort",
+    "panic_unwind",
+    "proc_macro",
+    "std_detect",
+    "unwind",
+    "test",
+];
+
+/// Functions without a crate (C code, intrinsics, etc.).
+const UNATTRIBUTED: &str = "<unattributed>";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CodeOrigin {
+    /// The standard library and the rest of the toolchain (libc, allocator).
+    Std,
+    /// Dependencies from crates.io, git, or vendored.
+    ThirdParty,
+    Workspace,
+    /// Code without a crate or debug info, and crates that can't be told apart
+    /// without the debug info or the crate graph.
+    Unknown,
+}
+
+impl CodeOrigin {
+    pub const ALL: [CodeOrigin; 4] = [
+        CodeOrigin::Std,
+        CodeOrigin::ThirdParty,
+        CodeOrigin::Workspace,
+        CodeOrigin::Unknown,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CodeOrigin::Std => "std/core/alloc",
+            CodeOrigin::ThirdParty => "third-party",
+            CodeOrigin::Workspace => "workspace",
+            CodeOrigin::Unknown => "unknown",
+        }
+    }
+}
+
+pub struct CrateOrigin {
+    pub name: String,
+    pub origin: CodeOrigin,
+    pub size_bytes: u64,
+    pub function_count: u32,
+    /// The largest function, to select when clicked.
+    pub largest_function: usize,
+}
+
+/// Splits the code between the standard library, the dependencies and the
+/// workspace crates.
+///
+/// The crate graph from `cargo metadata` tells the workspace crates apart.
+/// Without it, the directories of the DWARF compilation units are used:
+/// dependencies are built from the cargo registry, git checkouts, or a
+/// `vendor` directory.
+#[derive(Default)]
+pub struct CodeOriginReport {
+    /// Origin of each function.
+    pub function_origins: Vec<CodeOrigin>,
+    /// Crates by decreasing size. Functions without a crate are grouped by origin.
+    pub crates: Vec<CrateOrigin>,
+    /// Bytes of each origin, in the order of `CodeOrigin::ALL`.
+    pub origin_bytes: [u64; 4],
+}
+
+impl CodeOriginReport {
+    pub fn new(data_provider: &DataProviderTwiggy, crate_graph: Option<&CrateGraph>) -> Self {
+        // Where the functions of each crate were compiled. Generic functions
+        // are compiled in the crates using them, so a single function in a
+        // dependency directory is enough.
+        let mut crate_directories: HashMap<&str, Option<CodeOrigin>> = HashMap::new();
+        for (function, function_data) in data_provider.raw_data.iter().enumerate() {
+            let Some(crate_name) = function_data.function_property.crate_name else {
+                continue;
+            };
+            let directory_origin = data_provider
+                .compilation_unit_of(function)
+                .map(|compilation_unit| directory_origin(compilation_unit.comp_dir));
+
+            let origin = crate_directories.entry(crate_name).or_insert(None);
+            *origin = match (*origin, directory_origin) {
+                (Some(CodeOrigin::Std), _) | (_, Some(CodeOrigin::Std)) => Some(CodeOrigin::Std),
+                (Some(CodeOrigin::ThirdParty), _) | (_, Some(CodeOrigin::ThirdParty)) => {
+                    Some(CodeOrigin::ThirdParty)
+                }
+                (origin, directory_origin) => origin.or(directory_origin),
+            };
+        }
+
+        let crate_origin = |crate_name: &str| {
+            if STD_CRATES.contains(&crate_name) {
+                return CodeOrigin::Std;
+            }
+            if let Some(crate_graph) = crate_graph
+                && let Some(node) = crate_graph.find(crate_name)
+            {
+                return if crate_graph.crates[node].is_workspace_member {
+                    CodeOrigin::Workspace
+                } else {
+                    CodeOrigin::ThirdParty
+                };
+            }
+            crate_directories
+                .get(crate_name)
+                .copied()
+                .flatten()
+                .unwrap_or(CodeOrigin::Unknown)
+        };
+
+        let mut crate_lookup: HashMap<(&str, CodeOrigin), usize> = HashMap::new();
+        let mut crates: Vec<CrateOrigin> = Vec::new();
+        let mut function_origins = Vec::with_capacity(data_provider.raw_data.len());
+        let mut origin_bytes = [0; 4];
+
+        for (function, function_data) in data_provider.raw_data.iter().enumerate() {
+            let property = &function_data.function_property;
+            let (name, origin) = match property.crate_name {
+                Some(crate_name) => (crate_name, crate_origin(crate_name)),
+                None => (
+                    UNATTRIBUTED,
+                    data_provider
+                        .compilation_unit_of(function)
+                        .map_or(CodeOrigin::Unknown, |compilation_unit| {
+                            directory_origin(compilation_unit.comp_dir)
+                        }),
+                ),
+            };
+
+            function_origins.push(origin);
+            origin_bytes[origin as usize] += property.shallow_size_bytes as u64;
+
+            let crate_idx = *crate_lookup.entry((name, origin)).or_insert_with(|| {
+                crates.push(CrateOrigin {
+                    name: name.to_string(),
+                    origin,
+                    size_bytes: 0,
+                    function_count: 0,
+                    largest_function: function,
+                });
+                crates.len() - 1
+            });
+
+            let crate_origin = &mut crates[crate_idx];
+            crate_origin.size_bytes += property.shallow_size_bytes as u64;
+            crate_origin.function_count += 1;
+            if data_provider.raw_data[crate_origin.largest_function]
+                .function_property
+                .shallow_size_bytes
+                < property.shallow_size_bytes
+            {
+                crate_origin.largest_function = function;
+            }
+        }
+        crates.sort_by_key(|crate_origin| Reverse(crate_origin.size_bytes));
+
+        Self {
+            function_origins,
+            crates,
+            origin_bytes,
+        }
+    }
+
+    pub fn bytes_of(&self, origin: CodeOrigin) -> u64 {
+        self.origin_bytes[origin as usize]
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.origin_bytes.iter().sum()
+    }
+}
+
+/// Origin of the code compiled in the `comp_dir` of a DWARF compilation unit.
+///
+/// The toolchain is built in `/rustc/<commit>` (and `/rust/deps` for the
+/// dependencies of std), and the WASI libc in a `wasisdk://` directory.
+fn directory_origin(comp_dir: &Path) -> CodeOrigin {
+    let comp_dir = comp_dir.to_string_lossy().replace('\\', "/");
+
+    if comp_dir.starts_with("/rustc/")
+        || comp_dir.starts_with("/rust/deps/")
+        || comp_dir.starts_with("wasisdk://")
+    {
+        CodeOrigin::Std
+    } else if comp_dir.contains("/.cargo/registry/")
+        || comp_dir.contains("/.cargo/git/")
+        || comp_dir.contains("/vendor/")
+    {
+        CodeOrigin::ThirdParty
+    } else {
+        CodeOrigin::Workspace
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{CodeOrigin, directory_origin};
+
+    #[test]
+    fn directory_origin_works() {
+        let origin_of = |comp_dir: &str| directory_origin(Path::new(comp_dir));
+
+        assert_eq!(
+            origin_of("/rustc/17067e9ac6d7ecb70e50f92c1944e545188d2359"),
+            CodeOrigin::Std
+        );
+        assert_eq!(
+            origin_of("wasisdk://v25.0/build/sysroot/wasi-libc-wasm32-wasip1"),
+            CodeOrigin::Std
+        );
+        assert_eq!(
+            origin_of(
+                "/home/user/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.219"
+            ),
+            CodeOrigin::ThirdParty
+        );
+        assert_eq!(
+            origin_of(
+                "C:\\Users\\user\\.cargo\\git\\checkouts\\egui-1a2b3c\\0123abc\\crates\\egui"
+            ),
+            CodeOrigin::ThirdParty
+        );
+        assert_eq!(origin_of("G:\\Rust\\wasm_test"), CodeOrigin::Workspace);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::Write;
+
+use crate::{
+    analysis::symbol,
+    arena::tree::Tree,
+    data_provider_twiggy::DataProviderTwiggy,
+    dwarf::{DwNode, DwNodeType},
+};
+
+/// Frame of the functions without a crate when there is no debug info.
+const UNATTRIBUTED: &str = "<unattributed>";
+
+/// Writes the size tree in the collapsed stack format of `flamegraph.pl`,
+/// one `namespace;type;function bytes` line per function, to be rendered by
+/// inferno or speedscope.
+///
+/// Without debug info the functions are grouped by crate.
+pub fn collapsed_stacks(data_provider: &DataProviderTwiggy) -> String {
+    let tree = &data_provider.dominator_state.tree;
+    if tree.len() > 1 {
+        return collapsed_tree(tree);
+    }
+
+    let mut output = String::new();
+    for function_data in data_provider.raw_data.iter() {
+        let property = &function_data.function_property;
+        if property.shallow_size_bytes == 0 {
+            continue;
+        }
+
+        push_frame(&mut output, property.crate_name.unwrap_or(UNATTRIBUTED));
+        output.push(';');
+        push_frame(&mut output, symbol::strip_hash(property.raw_name));
+        writeln!(output, " {}", property.shallow_size_bytes).unwrap();
+    }
+    output
+}
+
+/// Writes a line for every node of `tree` with bytes of its own, the bytes of
+/// the inlined functions being taken out of the functions they are inlined in.
+fn collapsed_tree(tree: &Tree<DwNode>) -> String {
+    let mut output = String::new();
+
+    // The root isn't a frame.
+    let mut pending: Vec<(usize, usize)> = tree.get_children(0).map(|child| (child, 0)).collect();
+    let mut stack: Vec<&str> = Vec::new();
+
+    while let Some((node, depth)) = pending.pop() {
+        let dw_node = &tree[node].value;
+        stack.truncate(depth);
+        stack.push(dw_node.name.as_str());
+
+        let is_function = matches!(
+            dw_node.ty,
+            DwNodeType::FunctionInstance | DwNodeType::FunctionInlinedInstance
+        );
+        let inlined_bytes: u32 = tree
+            .get_children(node)
+            .map(|child| &tree[child].value)
+            .filter(|child| child.ty == DwNodeType::FunctionInlinedInstance)
+            .map(|child| child.size)
+            .sum();
+        let self_bytes = dw_node.size.saturating_sub(inlined_bytes);
+
+        if is_function && self_bytes > 0 {
+            for (idx, frame) in stack.iter().enumerate() {
+                if idx > 0 {
+                    output.push(';');
+                }
+                push_frame(&mut output, frame);
+            }
+            writeln!(output, " {}", self_bytes).unwrap();
+        }
+
+        pending.extend(tree.get_children(node).map(|child| (child, depth + 1)));
+    }
+
+    output
+}
+
+/// Frames are separated by `;` and the line ends with the count, array types
+/// like `[u8; 4]` need their `;` replaced.
+fn push_frame(output: &mut String, frame: &str) {
+    output.extend(frame.chars().map(|c| match c {
+        ';' => ',',
+        '\n' | '\r' => ' ',
+        c => c,
+    }));
+}
+
+#[cfg(test)]
+mod test {
+    use super::collapsed_tree;
+    use crate::{
+        arena::{Arena, memory::MB, tree::Tree},
+        dwarf::{DwNode, DwNodeType, SymbolName},
+    };
+
+    #[test]
+    fn inlined_bytes_are_taken_out_of_their_function() {
+        let node = |ty, name, size| DwNode {
+            ty,
+            name: SymbolName::new_with_parent(SymbolName::root(), name),
+            size,
+        };
+
+        let arena = Arena::new(MB);
+        let mut tree = Tree::new(
+            &arena,
+            8,
+            DwNode {
+                ty: DwNodeType::Namespace,
+                name: SymbolName::root(),
+                size: 0,
+            },
+        );
+        tree.add_child(0, node(DwNodeType::Namespace, "app", 0));
+        tree.add_child(1, node(DwNodeType::FunctionInstance, "run", 100));
+        tree.add_child(
+            2,
+            node(DwNodeType::FunctionInlinedInstance, "[u8; 4]::eq", 30),
+        );
+        tree.add_child(1, node(DwNodeType::FunctionInstance, "main", 0));
+
+        let output = collapsed_tree(&tree);
+        let mut lines: Vec<&str> = output.lines().collect();
+        lines.sort();
+
+        assert_eq!(lines, ["app;run 70", "app;run;[u8, 4]::eq 30"]);
+    }
+}
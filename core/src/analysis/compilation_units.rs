@@ -0,0 +1,129 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::data_provider_twiggy::DataProviderTwiggy;
+
+/// Separates the source file from the codegen unit in the Rust unit names,
+/// `src/lib.rs/@/crate.hash-cgu.0`.
+const CODEGEN_UNIT_SEPARATOR: &str = "/@/";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CompilationUnitSortKey {
+    #[default]
+    Size,
+    FunctionCount,
+    Name,
+}
+
+pub struct CompilationUnitSize {
+    /// Main source file of the unit.
+    pub source_file: String,
+    /// Codegen unit the source file was compiled in, for Rust.
+    pub codegen_unit: Option<String>,
+    pub comp_dir: PathBuf,
+    pub size_bytes: u64,
+    pub function_count: u32,
+    /// The largest function, to select when clicked.
+    pub largest_function: usize,
+}
+
+/// Size of the functions of each DWARF compilation unit, that is of each
+/// object file given to the linker.
+#[derive(Default)]
+pub struct CompilationUnitReport {
+    pub units: Vec<CompilationUnitSize>,
+    /// Functions outside of the units, without debug info.
+    pub unattributed_bytes: u64,
+    pub unattributed_function_count: u32,
+}
+
+impl CompilationUnitReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let mut unit_lookup: HashMap<(&str, &Path), usize> = HashMap::new();
+        let mut units: Vec<CompilationUnitSize> = Vec::new();
+        let mut unattributed_bytes = 0;
+        let mut unattributed_function_count = 0;
+
+        for (function, function_data) in data_provider.raw_data.iter().enumerate() {
+            let size_bytes = function_data.function_property.shallow_size_bytes;
+
+            let Some(compilation_unit) = data_provider.compilation_unit_of(function) else {
+                unattributed_bytes += size_bytes as u64;
+                unattributed_function_count += 1;
+                continue;
+            };
+
+            let unit_idx = *unit_lookup
+                .entry((compilation_unit.name, compilation_unit.comp_dir))
+                .or_insert_with(|| {
+                    let (source_file, codegen_unit) = split_unit_name(compilation_unit.name);
+                    units.push(CompilationUnitSize {
+                        source_file: source_file.to_string(),
+                        codegen_unit: codegen_unit.map(str::to_string),
+                        comp_dir: compilation_unit.comp_dir.to_path_buf(),
+                        size_bytes: 0,
+                        function_count: 0,
+                        largest_function: function,
+                    });
+                    units.len() - 1
+                });
+
+            let unit = &mut units[unit_idx];
+            unit.size_bytes += size_bytes as u64;
+            unit.function_count += 1;
+            if data_provider.raw_data[unit.largest_function]
+                .function_property
+                .shallow_size_bytes
+                < size_bytes
+            {
+                unit.largest_function = function;
+            }
+        }
+
+        let mut report = Self {
+            units,
+            unattributed_bytes,
+            unattributed_function_count,
+        };
+        report.sort(CompilationUnitSortKey::Size);
+        report
+    }
+
+    /// Sorts the units by the given key, the sizes and counts in descending order.
+    pub fn sort(&mut self, sort_key: CompilationUnitSortKey) {
+        match sort_key {
+            CompilationUnitSortKey::Size => self.units.sort_by_key(|unit| Reverse(unit.size_bytes)),
+            CompilationUnitSortKey::FunctionCount => {
+                self.units.sort_by_key(|unit| Reverse(unit.function_count))
+            }
+            CompilationUnitSortKey::Name => {
+                self.units.sort_by(|a, b| a.source_file.cmp(&b.source_file))
+            }
+        }
+    }
+}
+
+/// Splits a unit name into its source file and codegen unit.
+fn split_unit_name(name: &str) -> (&str, Option<&str>) {
+    match name.split_once(CODEGEN_UNIT_SEPARATOR) {
+        Some((source_file, codegen_unit)) => (source_file, Some(codegen_unit)),
+        None => (name, None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::split_unit_name;
+
+    #[test]
+    fn split_unit_name_works() {
+        assert_eq!(
+            split_unit_name("src/main.rs/@/app.2f1c5a-cgu.0"),
+            ("src/main.rs", Some("app.2f1c5a-cgu.0"))
+        );
+        assert_eq!(split_unit_name("malloc.c"), ("malloc.c", None));
+    }
+}
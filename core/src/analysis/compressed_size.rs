@@ -0,0 +1,204 @@
+use std::ops::Range;
+
+/// Compression level used for the estimate, the gzip default.
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Parameters of the LZ77 model, the same as deflate.
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: u32 = 15;
+
+enum Token {
+    Literal(u8),
+    Match { length: usize, distance: usize },
+}
+
+/// Estimates how many bytes each function adds to the gzip compressed size
+/// of the code.
+///
+/// The code is parsed with a deflate-like LZ77 model, where each literal and
+/// match costs the bits of an entropy coder. A function is charged the bits of
+/// the tokens starting in it: repeated code is cheap, unique code is not. The
+/// estimates are then scaled so they add up to the real deflate size.
+///
+/// `function_ranges` are offsets in `bytes`, sorted and not overlapping.
+pub fn compressed_function_sizes(bytes: &[u8], function_ranges: &[Range<usize>]) -> Vec<u32> {
+    let (Some(first), Some(last)) = (function_ranges.first(), function_ranges.last()) else {
+        return Vec::new();
+    };
+    let code = &bytes[first.start..last.end];
+    let tokens = lz77(code);
+
+    let mut literal_counts = [0u32; 256];
+    let mut match_count = 0u32;
+    for (_, token) in &tokens {
+        match token {
+            Token::Literal(byte) => literal_counts[*byte as usize] += 1,
+            Token::Match { .. } => match_count += 1,
+        }
+    }
+    let symbol_count = tokens.len() as f64;
+
+    let mut function_bits = vec![0.0f64; function_ranges.len()];
+    let mut function = 0;
+    for (offset, token) in &tokens {
+        let position = first.start + offset;
+        while function_ranges[function].end <= position {
+            function += 1;
+        }
+        // The function body sizes, between the functions.
+        if position < function_ranges[function].start {
+            continue;
+        }
+
+        function_bits[function] += match token {
+            Token::Literal(byte) => (symbol_count / literal_counts[*byte as usize] as f64).log2(),
+            Token::Match { length, distance } => {
+                (symbol_count / match_count as f64).log2()
+                    + length_extra_bits(*length) as f64
+                    + distance_bits(*distance) as f64
+            }
+        };
+    }
+
+    let total_bits: f64 = function_bits.iter().sum();
+    let compressed_bits =
+        miniz_oxide::deflate::compress_to_vec(code, COMPRESSION_LEVEL).len() as f64 * 8.0;
+    let scale = if total_bits > 0.0 {
+        compressed_bits / total_bits
+    } else {
+        0.0
+    };
+
+    function_bits
+        .iter()
+        .map(|bits| (bits * scale / 8.0).round() as u32)
+        .collect()
+}
+
+/// Greedy LZ77 parse of `data` with hash chains, returns the tokens and their offset.
+fn lz77(data: &[u8]) -> Vec<(usize, Token)> {
+    let hash = |position: usize| {
+        let bytes = &data[position..position + MIN_MATCH];
+        (((bytes[0] as usize) << 10) ^ ((bytes[1] as usize) << 5) ^ bytes[2] as usize)
+            & ((1 << HASH_BITS) - 1)
+    };
+
+    // Most recent position of each hash, and previous position with the same hash.
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let mut tokens = Vec::new();
+
+    let mut position = 0;
+    while position < data.len() {
+        let max_length = MAX_MATCH.min(data.len() - position);
+        let mut best_length = 0;
+        let mut best_distance = 0;
+
+        if max_length >= MIN_MATCH {
+            let mut candidate = head[hash(position)];
+            let mut chain_length = 0;
+            while candidate != usize::MAX
+                && position - candidate <= WINDOW_SIZE
+                && chain_length < MAX_CHAIN
+            {
+                let length = data[candidate..]
+                    .iter()
+                    .zip(&data[position..position + max_length])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length > best_length {
+                    best_length = length;
+                    best_distance = position - candidate;
+                    if length == max_length {
+                        break;
+                    }
+                }
+
+                candidate = previous[candidate];
+                chain_length += 1;
+            }
+        }
+
+        let token_length = if best_length >= MIN_MATCH {
+            tokens.push((
+                position,
+                Token::Match {
+                    length: best_length,
+                    distance: best_distance,
+                },
+            ));
+            best_length
+        } else {
+            tokens.push((position, Token::Literal(data[position])));
+            1
+        };
+
+        // Only the positions with enough bytes left can be hashed.
+        let hashed_end = (position + token_length).min((data.len() + 1).saturating_sub(MIN_MATCH));
+        for (inserted, previous_position) in previous
+            .iter_mut()
+            .enumerate()
+            .take(hashed_end)
+            .skip(position)
+        {
+            let hash = hash(inserted);
+            *previous_position = head[hash];
+            head[hash] = inserted;
+        }
+        position += token_length;
+    }
+
+    tokens
+}
+
+/// Extra bits of a deflate length code.
+fn length_extra_bits(length: usize) -> u32 {
+    if length <= 10 || length == MAX_MATCH {
+        0
+    } else {
+        ((length - 3).ilog2() - 2).min(5)
+    }
+}
+
+/// Bits of a deflate distance: the code (30 of them, ~5 bits) and its extra bits.
+fn distance_bits(distance: usize) -> u32 {
+    let extra_bits = if distance <= 4 {
+        0
+    } else {
+        (distance - 1).ilog2() - 1
+    };
+
+    5 + extra_bits
+}
+
+#[cfg(test)]
+mod test {
+    use super::compressed_function_sizes;
+
+    #[test]
+    fn repeated_code_is_cheap() {
+        // A pseudo random function, the same function again and a run of zeroes.
+        let mut seed = 0x2545f491u32;
+        let unique: Vec<u8> = (0..512)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as u8
+            })
+            .collect();
+
+        let mut bytes = unique.clone();
+        bytes.extend(&unique);
+        bytes.extend([0; 512]);
+
+        let sizes = compressed_function_sizes(&bytes, &[0..512, 512..1024, 1024..1536]);
+
+        assert!(sizes[0] > 400);
+        assert!(sizes[1] < 16);
+        assert!(sizes[2] < 16);
+    }
+}
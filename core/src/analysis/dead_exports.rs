@@ -0,0 +1,245 @@
+use std::{cmp::Reverse, collections::HashSet};
+
+use wasmparser::ExternalKind;
+
+use crate::{
+    analysis::recursion::strongly_connected_components, data_provider_twiggy::DataProviderTwiggy,
+};
+
+/// Exports the runtime calls by convention.
+const ENTRY_POINTS: &[&str] = &["main", "_start", "_initialize", "__main_void"];
+
+/// Prefix of the functions wasm-bindgen generates to describe a binding, they
+/// are only present before running `wasm-bindgen` on the binary.
+const WASM_BINDGEN_DESCRIBE_PREFIX: &str = "__wbindgen_describe_";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportUsage {
+    /// Nothing uses the export as far as we can tell.
+    Unused,
+    /// Also called from inside the module, or through a table.
+    Internal,
+    /// Called by the JS glue wasm-bindgen generates.
+    WasmBindgen,
+    EntryPoint,
+}
+
+impl ExportUsage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportUsage::Unused => "unused",
+            ExportUsage::Internal => "called internally",
+            ExportUsage::WasmBindgen => "wasm-bindgen",
+            ExportUsage::EntryPoint => "entry point",
+        }
+    }
+}
+
+pub struct ExportEntry {
+    pub name: String,
+    pub function: usize,
+    pub usage: ExportUsage,
+    /// Size of the functions reachable from this export only, i.e. what
+    /// removing the export would save.
+    pub exclusive_size_bytes: u64,
+}
+
+#[derive(Default)]
+pub struct DeadExportsReport {
+    /// Exported functions, the unused ones first, then by decreasing exclusive size.
+    pub exports: Vec<ExportEntry>,
+    pub dead_size_bytes: u64,
+}
+
+impl DeadExportsReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let wasm_data = &data_provider.wasm_data;
+        let function_count = data_provider.raw_data.len();
+        let to_function_idx = |function_index: u32| {
+            function_index
+                .checked_sub(wasm_data.imported_function_count)
+                .map(|idx| idx as usize)
+                .filter(|&idx| idx < function_count)
+        };
+
+        let exported: Vec<(&str, usize)> = wasm_data
+            .exports
+            .iter()
+            .filter(|export| export.kind == ExternalKind::Func)
+            .filter_map(|export| Some((export.name, to_function_idx(export.index)?)))
+            .collect();
+
+        // Everything that can be called without going through the exports.
+        let other_roots: Vec<usize> = wasm_data
+            .start_function
+            .iter()
+            .chain(wasm_data.element_functions.iter())
+            .filter_map(|&function_index| to_function_idx(function_index))
+            .collect();
+        let table_functions: HashSet<usize> = other_roots.iter().copied().collect();
+
+        let bound_by_wasm_bindgen: HashSet<&str> = data_provider
+            .raw_data
+            .iter()
+            .filter_map(|function_data| {
+                function_data
+                    .function_property
+                    .raw_name
+                    .strip_prefix(WASM_BINDGEN_DESCRIBE_PREFIX)
+            })
+            .collect();
+
+        let mut exports: Vec<ExportEntry> = exported
+            .iter()
+            .map(|&(name, function)| {
+                let usage = if ENTRY_POINTS.contains(&name) {
+                    ExportUsage::EntryPoint
+                } else if name.starts_with("__wbindgen_")
+                    || name.starts_with("__wbg_")
+                    || bound_by_wasm_bindgen.contains(name)
+                {
+                    ExportUsage::WasmBindgen
+                } else if !data_provider.call_graph.callers(function).is_empty()
+                    || table_functions.contains(&function)
+                {
+                    ExportUsage::Internal
+                } else {
+                    ExportUsage::Unused
+                };
+
+                ExportEntry {
+                    name: name.to_string(),
+                    function,
+                    usage,
+                    exclusive_size_bytes: 0,
+                }
+            })
+            .collect();
+
+        // The functions only one export reaches are what removing it saves.
+        let roots = exported
+            .iter()
+            .enumerate()
+            .map(|(export_idx, &(_, function))| (function, Reach::Only(export_idx)))
+            .chain(
+                other_roots
+                    .iter()
+                    .map(|&function| (function, Reach::Several)),
+            );
+        let reach = reaching_roots(function_count, roots, |function| {
+            data_provider.call_graph.callees(function)
+        });
+        for (function, reach) in reach.into_iter().enumerate() {
+            if let Reach::Only(export_idx) = reach {
+                exports[export_idx].exclusive_size_bytes += data_provider.raw_data[function]
+                    .function_property
+                    .shallow_size_bytes
+                    as u64;
+            }
+        }
+
+        exports.sort_by_key(|export| {
+            (
+                export.usage != ExportUsage::Unused,
+                Reverse(export.exclusive_size_bytes),
+            )
+        });
+
+        let dead_size_bytes = exports
+            .iter()
+            .filter(|export| export.usage == ExportUsage::Unused)
+            .map(|export| export.exclusive_size_bytes)
+            .sum();
+
+        Self {
+            exports,
+            dead_size_bytes,
+        }
+    }
+
+    pub fn dead_exports(&self) -> impl Iterator<Item = &ExportEntry> {
+        self.exports
+            .iter()
+            .filter(|export| export.usage == ExportUsage::Unused)
+    }
+}
+
+/// Roots reaching a function through direct calls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Reach {
+    None,
+    Only(usize),
+    Several,
+}
+
+impl Reach {
+    fn merge(self, other: Reach) -> Reach {
+        match (self, other) {
+            (Reach::None, reach) | (reach, Reach::None) => reach,
+            (Reach::Only(a), Reach::Only(b)) if a == b => Reach::Only(a),
+            _ => Reach::Several,
+        }
+    }
+}
+
+/// Finds the roots reaching each function in a single pass over the call
+/// graph, condensed to its strongly connected components: the functions of
+/// a component are reached by the same roots, the ones of its callers.
+fn reaching_roots<'g>(
+    function_count: usize,
+    roots: impl IntoIterator<Item = (usize, Reach)>,
+    callees: impl Fn(usize) -> &'g [u32],
+) -> Vec<Reach> {
+    let (component_of, component_count) = strongly_connected_components(function_count, &callees);
+
+    let mut component_reach = vec![Reach::None; component_count];
+    for (function, reach) in roots {
+        let component = component_of[function] as usize;
+        component_reach[component] = component_reach[component].merge(reach);
+    }
+
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); component_count];
+    for (function, &component) in component_of.iter().enumerate() {
+        members[component as usize].push(function);
+    }
+
+    // The components come callees first, the callers are done before them.
+    for component in (0..component_count).rev() {
+        let reach = component_reach[component];
+        if reach == Reach::None {
+            continue;
+        }
+        for &function in &members[component] {
+            for &callee in callees(function) {
+                let callee_component = component_of[callee as usize] as usize;
+                component_reach[callee_component] = component_reach[callee_component].merge(reach);
+            }
+        }
+    }
+
+    component_of
+        .iter()
+        .map(|&component| component_reach[component as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Reach, reaching_roots};
+
+    #[test]
+    fn functions_are_owned_by_the_only_root_reaching_them() {
+        // 0 -> 2 -> 3 -> 2, 1 -> 3, 1 -> 4, 5 -> 0.
+        let edges: [&[u32]; 6] = [&[2], &[3, 4], &[3], &[2], &[], &[0]];
+        let roots = [(0, Reach::Only(0)), (1, Reach::Only(1))];
+
+        let reach = reaching_roots(edges.len(), roots, |function| edges[function]);
+
+        assert_eq!(reach[0], Reach::Only(0));
+        // The cycle is reached by both roots.
+        assert_eq!(reach[2], Reach::Several);
+        assert_eq!(reach[3], Reach::Several);
+        assert_eq!(reach[4], Reach::Only(1));
+        assert_eq!(reach[5], Reach::None);
+    }
+}
@@ -0,0 +1,377 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{analysis::symbol, data_provider_twiggy::DataProviderTwiggy};
+
+/// Pairs whose structural similarity is below this are not considered the same function.
+const MIN_STRUCTURAL_SCORE: f32 = 0.8;
+
+/// Functions without callees only have their size to go by, so it has to be (almost) the same.
+const MIN_LEAF_SIZE_SIMILARITY: f32 = 0.95;
+
+/// The functions of one build, as seen by the symbol matcher.
+pub trait SymbolSet {
+    fn symbol_count(&self) -> usize;
+    fn symbol_name(&self, idx: usize) -> &str;
+    fn symbol_size(&self, idx: usize) -> u32;
+    /// The functions directly called by function `idx`.
+    fn symbol_callees(&self, idx: usize) -> &[u32];
+}
+
+impl SymbolSet for DataProviderTwiggy<'_> {
+    fn symbol_count(&self) -> usize {
+        self.raw_data.len()
+    }
+
+    fn symbol_name(&self, idx: usize) -> &str {
+        self.raw_data[idx].function_property.raw_name
+    }
+
+    fn symbol_size(&self, idx: usize) -> u32 {
+        self.raw_data[idx].function_property.shallow_size_bytes
+    }
+
+    fn symbol_callees(&self, idx: usize) -> &[u32] {
+        self.call_graph.callees(idx)
+    }
+}
+
+/// How two functions of different builds were paired.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Same symbol name.
+    Name,
+    /// Same symbol name once the mangling hash is removed.
+    NameWithoutHash,
+    /// Different names, but similar size and calling the same functions.
+    Structural,
+}
+
+impl MatchKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MatchKind::Name => "name",
+            MatchKind::NameWithoutHash => "name w/o hash",
+            MatchKind::Structural => "structural",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FunctionMatch {
+    pub old: usize,
+    pub new: usize,
+    pub kind: MatchKind,
+}
+
+#[derive(Default)]
+pub struct SymbolMatches {
+    pub matches: Vec<FunctionMatch>,
+    /// Functions of the old build without a counterpart (removed).
+    pub old_only: Vec<usize>,
+    /// Functions of the new build without a counterpart (added).
+    pub new_only: Vec<usize>,
+}
+
+/// Pairs the functions of two builds of the same program.
+///
+/// Symbol hashes change between compilations, so matching is done in passes
+/// of decreasing confidence: exact names, names without the hash suffix, and
+/// finally structural similarity (size and matched callees) for the
+/// functions left, which catches renamed symbols.
+pub fn match_symbols(old: &impl SymbolSet, new: &impl SymbolSet) -> SymbolMatches {
+    let mut matching = Matching {
+        old_to_new: vec![None; old.symbol_count()],
+        new_to_old: vec![None; new.symbol_count()],
+        matches: Vec::new(),
+    };
+
+    for kind in [MatchKind::Name, MatchKind::NameWithoutHash] {
+        let mut old_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for idx in matching.unmatched_old() {
+            old_by_name
+                .entry(name_key(kind, old.symbol_name(idx)))
+                .or_default()
+                .push(idx);
+        }
+
+        let mut new_by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for idx in matching.unmatched_new() {
+            new_by_name
+                .entry(name_key(kind, new.symbol_name(idx)))
+                .or_default()
+                .push(idx);
+        }
+
+        for (name, mut new_group) in new_by_name {
+            let Some(old_group) = old_by_name.get_mut(name) else {
+                continue;
+            };
+
+            // Several functions can share a name (e.g. the same instantiation emitted
+            // in different codegen units), pair them by size.
+            old_group.sort_by_key(|&idx| Reverse(old.symbol_size(idx)));
+            new_group.sort_by_key(|&idx| Reverse(new.symbol_size(idx)));
+            for (&old_idx, &new_idx) in old_group.iter().zip(&new_group) {
+                matching.pair(old_idx, new_idx, kind);
+            }
+        }
+    }
+
+    // Structural matching, only between functions of the same crate.
+    let mut old_by_crate: HashMap<Option<&str>, Vec<usize>> = HashMap::new();
+    for idx in matching.unmatched_old() {
+        old_by_crate
+            .entry(symbol::crate_name(old.symbol_name(idx)))
+            .or_default()
+            .push(idx);
+    }
+
+    let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+    for new_idx in matching.unmatched_new() {
+        let Some(old_group) = old_by_crate.get(&symbol::crate_name(new.symbol_name(new_idx)))
+        else {
+            continue;
+        };
+
+        for &old_idx in old_group {
+            if let Some(score) =
+                structural_similarity(old, new, old_idx, new_idx, &matching.old_to_new)
+            {
+                candidates.push((score, old_idx, new_idx));
+            }
+        }
+    }
+
+    // Best pairs first, each function is matched at most once.
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (_, old_idx, new_idx) in candidates {
+        if matching.old_to_new[old_idx].is_none() && matching.new_to_old[new_idx].is_none() {
+            matching.pair(old_idx, new_idx, MatchKind::Structural);
+        }
+    }
+
+    SymbolMatches {
+        old_only: matching.unmatched_old().collect(),
+        new_only: matching.unmatched_new().collect(),
+        matches: matching.matches,
+    }
+}
+
+struct Matching {
+    old_to_new: Vec<Option<usize>>,
+    new_to_old: Vec<Option<usize>>,
+    matches: Vec<FunctionMatch>,
+}
+
+impl Matching {
+    fn pair(&mut self, old: usize, new: usize, kind: MatchKind) {
+        self.old_to_new[old] = Some(new);
+        self.new_to_old[new] = Some(old);
+        self.matches.push(FunctionMatch { old, new, kind });
+    }
+
+    fn unmatched_old(&self) -> impl Iterator<Item = usize> + use<'_> {
+        (0..self.old_to_new.len()).filter(|&idx| self.old_to_new[idx].is_none())
+    }
+
+    fn unmatched_new(&self) -> impl Iterator<Item = usize> + use<'_> {
+        (0..self.new_to_old.len()).filter(|&idx| self.new_to_old[idx].is_none())
+    }
+}
+
+fn name_key(kind: MatchKind, name: &str) -> &str {
+    match kind {
+        MatchKind::NameWithoutHash => symbol::strip_hash(name),
+        _ => name,
+    }
+}
+
+/// Returns how likely `old_idx` and `new_idx` are the same function, between
+/// `MIN_STRUCTURAL_SCORE` and 1, or `None` if they are too different.
+fn structural_similarity(
+    old: &impl SymbolSet,
+    new: &impl SymbolSet,
+    old_idx: usize,
+    new_idx: usize,
+    old_to_new: &[Option<usize>],
+) -> Option<f32> {
+    let (old_size, new_size) = (old.symbol_size(old_idx), new.symbol_size(new_idx));
+    let size_similarity = old_size.min(new_size) as f32 / old_size.max(new_size).max(1) as f32;
+
+    let old_callees = old.symbol_callees(old_idx);
+    let new_callees = new.symbol_callees(new_idx);
+    if old_callees.is_empty() && new_callees.is_empty() {
+        return (size_similarity >= MIN_LEAF_SIZE_SIMILARITY).then_some(size_similarity);
+    }
+
+    // Callees are compared through the functions matched so far.
+    let shared_callees = old_callees
+        .iter()
+        .filter_map(|&callee| old_to_new[callee as usize])
+        .filter(|&callee| new_callees.contains(&(callee as u32)))
+        .count();
+    let all_callees = old_callees.len() + new_callees.len() - shared_callees;
+    let callee_similarity = shared_callees as f32 / all_callees as f32;
+
+    let score = (size_similarity + callee_similarity) / 2.0;
+    (score >= MIN_STRUCTURAL_SCORE).then_some(score)
+}
+
+/// A function whose size changed between two builds.
+pub struct SizeDiffEntry {
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+    pub old_size_bytes: u32,
+    pub new_size_bytes: u32,
+    /// `None` for added or removed functions.
+    pub match_kind: Option<MatchKind>,
+}
+
+impl SizeDiffEntry {
+    pub fn delta_bytes(&self) -> i64 {
+        self.new_size_bytes as i64 - self.old_size_bytes as i64
+    }
+}
+
+#[derive(Default)]
+pub struct SizeDiffReport {
+    /// Changed, added and removed functions, by decreasing absolute delta.
+    pub entries: Vec<SizeDiffEntry>,
+    pub old_total_bytes: u64,
+    pub new_total_bytes: u64,
+}
+
+impl SizeDiffReport {
+    pub fn new(old: &DataProviderTwiggy, new: &DataProviderTwiggy) -> Self {
+        let symbol_matches = match_symbols(old, new);
+        let mut entries = Vec::new();
+
+        for function_match in symbol_matches.matches {
+            let old_size_bytes = old.symbol_size(function_match.old);
+            let new_size_bytes = new.symbol_size(function_match.new);
+            if old_size_bytes != new_size_bytes {
+                entries.push(SizeDiffEntry {
+                    old: Some(function_match.old),
+                    new: Some(function_match.new),
+                    old_size_bytes,
+                    new_size_bytes,
+                    match_kind: Some(function_match.kind),
+                });
+            }
+        }
+
+        entries.extend(
+            symbol_matches
+                .old_only
+                .into_iter()
+                .map(|idx| SizeDiffEntry {
+                    old: Some(idx),
+                    new: None,
+                    old_size_bytes: old.symbol_size(idx),
+                    new_size_bytes: 0,
+                    match_kind: None,
+                }),
+        );
+        entries.extend(
+            symbol_matches
+                .new_only
+                .into_iter()
+                .map(|idx| SizeDiffEntry {
+                    old: None,
+                    new: Some(idx),
+                    old_size_bytes: 0,
+                    new_size_bytes: new.symbol_size(idx),
+                    match_kind: None,
+                }),
+        );
+
+        entries.sort_by_key(|entry| Reverse(entry.delta_bytes().abs()));
+
+        let total_size = |symbols: &DataProviderTwiggy| {
+            (0..symbols.symbol_count())
+                .map(|idx| symbols.symbol_size(idx) as u64)
+                .sum()
+        };
+
+        Self {
+            entries,
+            old_total_bytes: total_size(old),
+            new_total_bytes: total_size(new),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FunctionMatch, MatchKind, SymbolSet, match_symbols};
+
+    struct TestSymbols {
+        functions: Vec<(&'static str, u32, Vec<u32>)>,
+    }
+
+    impl SymbolSet for TestSymbols {
+        fn symbol_count(&self) -> usize {
+            self.functions.len()
+        }
+
+        fn symbol_name(&self, idx: usize) -> &str {
+            self.functions[idx].0
+        }
+
+        fn symbol_size(&self, idx: usize) -> u32 {
+            self.functions[idx].1
+        }
+
+        fn symbol_callees(&self, idx: usize) -> &[u32] {
+            &self.functions[idx].2
+        }
+    }
+
+    #[test]
+    fn renamed_symbols_are_matched() {
+        let old = TestSymbols {
+            functions: vec![
+                ("app::main::h0123456789abcdef", 100, vec![1, 2]),
+                ("app::helper", 40, vec![]),
+                ("app::parse::{{closure}}", 60, vec![1]),
+                ("app::removed", 10, vec![]),
+            ],
+        };
+        let new = TestSymbols {
+            functions: vec![
+                ("app::helper", 40, vec![]),
+                ("app::parse_args::{{closure}}", 64, vec![0]),
+                ("app::main::hfedcba9876543210", 120, vec![0, 1]),
+                ("other::added", 10, vec![]),
+            ],
+        };
+
+        let mut symbol_matches = match_symbols(&old, &new);
+        symbol_matches
+            .matches
+            .sort_by_key(|function_match| function_match.old);
+
+        assert_eq!(
+            symbol_matches.matches,
+            vec![
+                FunctionMatch {
+                    old: 0,
+                    new: 2,
+                    kind: MatchKind::NameWithoutHash
+                },
+                FunctionMatch {
+                    old: 1,
+                    new: 0,
+                    kind: MatchKind::Name
+                },
+                FunctionMatch {
+                    old: 2,
+                    new: 1,
+                    kind: MatchKind::Structural
+                },
+            ]
+        );
+        assert_eq!(symbol_matches.old_only, vec![3]);
+        assert_eq!(symbol_matches.new_only, vec![3]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::wasm::parser::WasmData;
+
+/// Shorter repeats are mostly small constants and string fragments, not worth
+/// merging.
+const MIN_DUPLICATE_SIZE: usize = 32;
+
+/// Number of bytes kept to preview a blob.
+const PREVIEW_SIZE: usize = 16;
+
+pub struct DataLocation {
+    /// Index of the data segment.
+    pub segment: usize,
+    /// Offset in the data segment.
+    pub offset: u32,
+    /// Address in linear memory, `None` for passive segments.
+    pub address: Option<u32>,
+}
+
+/// Bytes found several times in the data segments.
+pub struct DuplicateBlob {
+    pub size_bytes: u32,
+    /// The first copy, then the others in file order.
+    pub copies: Vec<DataLocation>,
+    /// The first bytes of the blob.
+    pub preview: Vec<u8>,
+}
+
+impl DuplicateBlob {
+    /// Bytes saved by keeping a single copy.
+    pub fn recoverable_bytes(&self) -> u64 {
+        self.size_bytes as u64 * (self.copies.len() as u64 - 1)
+    }
+}
+
+/// Repeated lookup tables, assets embedded twice, etc. in the data segments.
+#[derive(Default)]
+pub struct DuplicateDataReport {
+    pub data_bytes: u64,
+    /// Blobs by decreasing recoverable bytes.
+    pub blobs: Vec<DuplicateBlob>,
+    pub recoverable_bytes: u64,
+}
+
+impl DuplicateDataReport {
+    pub fn new(wasm_data: &WasmData) -> Self {
+        let segments: Vec<&[u8]> = wasm_data
+            .data_segments
+            .iter()
+            .map(|data_segment| data_segment.data)
+            .collect();
+
+        let mut blobs: Vec<DuplicateBlob> = find_duplicates(&segments)
+            .into_iter()
+            .map(|(bytes, copies)| DuplicateBlob {
+                size_bytes: bytes.len() as u32,
+                copies: copies
+                    .into_iter()
+                    .map(|(segment, offset)| DataLocation {
+                        segment,
+                        offset: offset as u32,
+                        address: wasm_data.data_segments[segment]
+                            .offset
+                            .map(|segment_address| segment_address + offset as u32),
+                    })
+                    .collect(),
+                preview: bytes[..bytes.len().min(PREVIEW_SIZE)].to_vec(),
+            })
+            .collect();
+        blobs.sort_by_key(|blob| Reverse(blob.recoverable_bytes()));
+
+        Self {
+            data_bytes: segments.iter().map(|data| data.len() as u64).sum(),
+            recoverable_bytes: blobs.iter().map(DuplicateBlob::recoverable_bytes).sum(),
+            blobs,
+        }
+    }
+}
+
+/// Repeated bytes, with the `(segment, offset)` of their copies.
+type Duplicate<'d> = (&'d [u8], Vec<(usize, usize)>);
+
+/// Finds the bytes repeated in `segments`.
+///
+/// Every window of `MIN_DUPLICATE_SIZE` bytes is looked up in the windows
+/// seen before, and a match is extended as far as the bytes are the same.
+/// Copies don't overlap, and runs of a single byte (zero fill) are ignored.
+fn find_duplicates<'d>(segments: &[&'d [u8]]) -> Vec<Duplicate<'d>> {
+    let mut first_windows: HashMap<&[u8], (usize, usize)> = HashMap::new();
+    let mut blob_lookup: HashMap<&[u8], usize> = HashMap::new();
+    let mut blobs: Vec<Duplicate> = Vec::new();
+
+    for (segment, &data) in segments.iter().enumerate() {
+        let mut offset = 0;
+        while offset + MIN_DUPLICATE_SIZE <= data.len() {
+            let window = &data[offset..offset + MIN_DUPLICATE_SIZE];
+            if window.iter().all(|&byte| byte == window[0]) {
+                offset += 1;
+                continue;
+            }
+
+            let Some(&(first_segment, first_offset)) = first_windows.get(window) else {
+                first_windows.insert(window, (segment, offset));
+                offset += 1;
+                continue;
+            };
+
+            let overlapping =
+                first_segment == segment && first_offset + MIN_DUPLICATE_SIZE > offset;
+            if overlapping {
+                offset += 1;
+                continue;
+            }
+
+            let mut size = segments[first_segment][first_offset..]
+                .iter()
+                .zip(&data[offset..])
+                .take_while(|(first, copy)| first == copy)
+                .count();
+            if first_segment == segment {
+                size = size.min(offset - first_offset);
+            }
+
+            let bytes = &data[offset..offset + size];
+            let blob_idx = *blob_lookup.entry(bytes).or_insert_with(|| {
+                blobs.push((bytes, vec![(first_segment, first_offset)]));
+                blobs.len() - 1
+            });
+            blobs[blob_idx].1.push((segment, offset));
+
+            offset += size;
+        }
+    }
+
+    blobs
+}
+
+#[cfg(test)]
+mod test {
+    use super::find_duplicates;
+
+    #[test]
+    fn finds_the_copies_of_a_table() {
+        let mut seed = 0x2545f491u32;
+        let mut random_bytes = |count: usize| -> Vec<u8> {
+            (0..count)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    seed as u8
+                })
+                .collect()
+        };
+        let table = random_bytes(100);
+
+        // The table twice in a segment, with zero fill between, then in another.
+        let mut first_segment = table.clone();
+        first_segment.extend(random_bytes(50));
+        first_segment.extend([0; 64]);
+        first_segment.extend(&table);
+        let mut second_segment = random_bytes(64);
+        second_segment.extend(&table);
+
+        let duplicates = find_duplicates(&[&first_segment, &second_segment]);
+
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].0, table.as_slice());
+        assert_eq!(duplicates[0].1, [(0, 0), (0, 214), (1, 64)]);
+    }
+}
@@ -0,0 +1,167 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::section_summary::{SectionSize, SectionSummary},
+    data_provider_twiggy::DataProviderTwiggy,
+    dwarf::DwNodeType,
+};
+
+/// Version of the schema of `AnalysisExport`, bumped when a field is removed
+/// or changes meaning. Added fields don't change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The analysis of a file, exported as JSON for other tools:
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "file": "app.wasm",
+///   "file_size_bytes": 48831,
+///   "code_size_bytes": 35245,
+///   "sections": [{ "name": "code", "category": "code", "size_bytes": 35250 }],
+///   "crates": [{ "name": "core", "size_bytes": 9120, "function_count": 64 }],
+///   "functions": [{
+///     "index": 0,
+///     "name": "core::fmt::write::h0123456789abcdef",
+///     "mangled_name": "_ZN4core3fmt5write17h0123456789abcdefE",
+///     "crate": "core",
+///     "monomorphization_of": null,
+///     "shallow_size_bytes": 812,
+///     "retained_size_bytes": 812,
+///     "compressed_size_bytes": 431
+///   }],
+///   "namespaces": [
+///     { "name": "<root>", "kind": "namespace", "size_bytes": 288430, "parent": null },
+///     { "name": "core", "kind": "namespace", "size_bytes": 91205, "parent": 0 }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize)]
+pub struct AnalysisExport {
+    pub schema_version: u32,
+    /// Name of the analyzed file.
+    pub file: String,
+    pub file_size_bytes: u64,
+    /// Size of the bodies of all the functions.
+    pub code_size_bytes: u64,
+    /// Sections by decreasing size.
+    pub sections: Vec<SectionSize>,
+    /// Code size of the crates the functions are attributed to by their
+    /// names, by decreasing size. Functions without a crate are left out.
+    pub crates: Vec<CrateExport>,
+    /// All the functions, in the order of the code section.
+    pub functions: Vec<FunctionExport>,
+    /// Tree of the namespaces, types and functions from the debug info, each
+    /// node after its parent. Empty without debug info.
+    pub namespaces: Vec<NamespaceExport>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CrateExport {
+    pub name: String,
+    pub size_bytes: u64,
+    pub function_count: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FunctionExport {
+    /// Index of the function in the code section, without the imported ones.
+    pub index: usize,
+    /// Demangled name.
+    pub name: String,
+    /// Name of the name section, empty if the function has none.
+    pub mangled_name: String,
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    /// Name of the generic function this one instantiates.
+    pub monomorphization_of: Option<String>,
+    pub shallow_size_bytes: u32,
+    pub retained_size_bytes: u32,
+    /// Estimated contribution to the gzip compressed size of the code.
+    pub compressed_size_bytes: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NamespaceExport {
+    pub name: String,
+    pub kind: DwNodeType,
+    /// Size of the function instances under the node, the inlined ones
+    /// included, so it can exceed the code size.
+    pub size_bytes: u32,
+    /// Index of the parent node, `None` for the root.
+    pub parent: Option<usize>,
+}
+
+impl AnalysisExport {
+    pub fn new(file: &str, data_provider: &DataProviderTwiggy) -> Self {
+        let functions_section = &data_provider.wasm_data.functions_section;
+
+        let functions: Vec<FunctionExport> = data_provider
+            .raw_data
+            .iter()
+            .enumerate()
+            .map(|(index, function_data)| {
+                let property = &function_data.function_property;
+                FunctionExport {
+                    index,
+                    name: property.raw_name.to_string(),
+                    mangled_name: functions_section.function_original_names[index].to_string(),
+                    crate_name: property.crate_name.map(str::to_string),
+                    monomorphization_of: property.monomorphization_of.map(str::to_string),
+                    shallow_size_bytes: property.shallow_size_bytes,
+                    retained_size_bytes: property.retained_size_bytes,
+                    compressed_size_bytes: property.compressed_size_bytes,
+                }
+            })
+            .collect();
+
+        let mut crate_lookup: HashMap<&str, usize> = HashMap::new();
+        let mut crates: Vec<CrateExport> = Vec::new();
+        for function in &functions {
+            let Some(name) = function.crate_name.as_deref() else {
+                continue;
+            };
+            let crate_idx = *crate_lookup.entry(name).or_insert_with(|| {
+                crates.push(CrateExport {
+                    name: name.to_string(),
+                    size_bytes: 0,
+                    function_count: 0,
+                });
+                crates.len() - 1
+            });
+            crates[crate_idx].size_bytes += function.shallow_size_bytes as u64;
+            crates[crate_idx].function_count += 1;
+        }
+        crates.sort_by_key(|crate_export| Reverse(crate_export.size_bytes));
+
+        let tree = &data_provider.dominator_state.tree;
+        let namespaces = if tree.len() > 1 {
+            (0..tree.len())
+                .map(|idx| {
+                    let node = tree.get(idx);
+                    NamespaceExport {
+                        name: node.name.as_str().to_string(),
+                        kind: node.ty,
+                        size_bytes: node.size,
+                        parent: tree.get_parent_index(idx),
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            file: file.to_string(),
+            file_size_bytes: data_provider.wasm_data.bytes.len() as u64,
+            code_size_bytes: functions_section.size_in_bytes as u64,
+            sections: SectionSummary::new(&data_provider.wasm_data).sections,
+            crates,
+            functions,
+            namespaces,
+        }
+    }
+}
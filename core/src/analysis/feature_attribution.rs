@@ -0,0 +1,243 @@
+use std::{cmp::Reverse, collections::HashMap, path::Path};
+
+use crate::{
+    analysis::{diff::SizeDiffReport, symbol},
+    data_provider_twiggy::DataProviderTwiggy,
+};
+
+/// Separates the features in the file name of a build, `app+serde+json.wasm`.
+const FEATURE_SEPARATOR: char = '+';
+
+/// Label of the changes coming with any of several features, like a dependency
+/// they share.
+const SHARED: &str = "<shared by features>";
+
+/// The features of a build, from its file name: `app+serde+json.wasm` has
+/// `serde` and `json`. A name without separator is a single feature.
+pub fn features_of(path: &Path) -> Vec<String> {
+    let file_stem = path
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy())
+        .unwrap_or_default();
+
+    match file_stem.split_once(FEATURE_SEPARATOR) {
+        Some((_, features)) => features
+            .split(FEATURE_SEPARATOR)
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => vec![file_stem.into_owned()],
+    }
+}
+
+pub struct FeatureBuild {
+    pub file_name: String,
+    pub features: Vec<String>,
+    /// Size of the build minus the size of the baseline.
+    pub delta_bytes: i64,
+}
+
+pub struct AttributedFunction {
+    /// Demangled name, without the hash.
+    pub name: String,
+    /// Average change over the builds it changed in.
+    pub delta_bytes: i64,
+    /// Number of builds it changed in.
+    pub build_count: u32,
+}
+
+pub struct FeatureAttribution {
+    /// A feature, the features only contributing together (`a + b`), or the
+    /// code shared by features.
+    pub feature: String,
+    pub delta_bytes: i64,
+    /// Functions by decreasing absolute change.
+    pub functions: Vec<AttributedFunction>,
+}
+
+/// Size of each cargo feature, from builds of the same crate with different
+/// feature sets compared against a baseline without them.
+///
+/// Each build is diffed against the baseline. A function changing in several
+/// builds is attributed to the features all of them have in common, so the
+/// builds enabling several features help separate them.
+#[derive(Default)]
+pub struct FeatureAttributionReport {
+    pub baseline_total_bytes: u64,
+    pub builds: Vec<FeatureBuild>,
+    /// Attributions by decreasing change.
+    pub features: Vec<FeatureAttribution>,
+}
+
+impl FeatureAttributionReport {
+    pub fn new(baseline: &DataProviderTwiggy, builds: &[(&Path, &DataProviderTwiggy)]) -> Self {
+        let mut baseline_total_bytes = 0;
+        let mut feature_builds = Vec::new();
+        let mut function_deltas = Vec::new();
+
+        for &(path, build) in builds {
+            let diff = SizeDiffReport::new(baseline, build);
+            baseline_total_bytes = diff.old_total_bytes;
+
+            let name_of = |data_provider: &DataProviderTwiggy, idx: usize| {
+                symbol::strip_hash(data_provider.raw_data[idx].function_property.raw_name)
+                    .to_string()
+            };
+            function_deltas.push(
+                diff.entries
+                    .iter()
+                    .map(|entry| {
+                        let name = match (entry.new, entry.old) {
+                            (Some(new), _) => name_of(build, new),
+                            (None, Some(old)) => name_of(baseline, old),
+                            (None, None) => String::new(),
+                        };
+                        (name, entry.delta_bytes())
+                    })
+                    .collect(),
+            );
+
+            feature_builds.push(FeatureBuild {
+                file_name: path
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                features: features_of(path),
+                delta_bytes: diff.new_total_bytes as i64 - diff.old_total_bytes as i64,
+            });
+        }
+
+        let build_features: Vec<&[String]> = feature_builds
+            .iter()
+            .map(|build| build.features.as_slice())
+            .collect();
+        let features = attribute(&build_features, &function_deltas);
+
+        Self {
+            baseline_total_bytes,
+            builds: feature_builds,
+            features,
+        }
+    }
+}
+
+/// Attributes the function changes of each build to its features.
+fn attribute(
+    build_features: &[&[String]],
+    function_deltas: &[Vec<(String, i64)>],
+) -> Vec<FeatureAttribution> {
+    // The changes of each function, by build.
+    let mut functions: HashMap<&str, Vec<(usize, i64)>> = HashMap::new();
+    for (build, deltas) in function_deltas.iter().enumerate() {
+        for (name, delta_bytes) in deltas {
+            functions
+                .entry(name.as_str())
+                .or_default()
+                .push((build, *delta_bytes));
+        }
+    }
+
+    let mut attribution_lookup: HashMap<String, usize> = HashMap::new();
+    let mut attributions: Vec<FeatureAttribution> = Vec::new();
+
+    for (name, changes) in functions {
+        let mut common_features: Vec<&String> = build_features[changes[0].0].iter().collect();
+        for &(build, _) in &changes[1..] {
+            common_features.retain(|feature| build_features[build].contains(feature));
+        }
+
+        let feature = if common_features.is_empty() {
+            SHARED.to_string()
+        } else {
+            common_features
+                .iter()
+                .map(|feature| feature.as_str())
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+
+        let build_count = changes.len() as u32;
+        let delta_bytes = changes
+            .iter()
+            .map(|&(_, delta_bytes)| delta_bytes)
+            .sum::<i64>()
+            / build_count as i64;
+
+        let attribution_idx = *attribution_lookup
+            .entry(feature)
+            .or_insert_with_key(|feature| {
+                attributions.push(FeatureAttribution {
+                    feature: feature.clone(),
+                    delta_bytes: 0,
+                    functions: Vec::new(),
+                });
+                attributions.len() - 1
+            });
+
+        let attribution = &mut attributions[attribution_idx];
+        attribution.delta_bytes += delta_bytes;
+        attribution.functions.push(AttributedFunction {
+            name: name.to_string(),
+            delta_bytes,
+            build_count,
+        });
+    }
+
+    for attribution in &mut attributions {
+        attribution
+            .functions
+            .sort_by_key(|function| Reverse(function.delta_bytes.abs()));
+    }
+    attributions.sort_by_key(|attribution| Reverse(attribution.delta_bytes));
+
+    attributions
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{SHARED, attribute, features_of};
+
+    #[test]
+    fn features_come_from_the_file_name() {
+        assert_eq!(
+            features_of(Path::new("out/app+serde+json.wasm")),
+            ["serde", "json"]
+        );
+        assert_eq!(features_of(Path::new("out/serde.wasm")), ["serde"]);
+    }
+
+    #[test]
+    fn changes_go_to_the_common_features() {
+        let serde = [String::from("serde")];
+        let json = [String::from("json")];
+        let serde_json = [String::from("serde"), String::from("json")];
+        let deltas = |changes: &[(&str, i64)]| {
+            changes
+                .iter()
+                .map(|&(name, delta_bytes)| (name.to_string(), delta_bytes))
+                .collect()
+        };
+
+        let attributions = attribute(
+            &[&serde, &json, &serde_json],
+            &[
+                deltas(&[("serialize", 100)]),
+                deltas(&[("parse", 40)]),
+                deltas(&[("serialize", 100), ("parse", 40), ("to_json", 10)]),
+            ],
+        );
+
+        let delta_of = |feature: &str| {
+            attributions
+                .iter()
+                .find(|attribution| attribution.feature == feature)
+                .map(|attribution| attribution.delta_bytes)
+        };
+        assert_eq!(delta_of("serde"), Some(100));
+        assert_eq!(delta_of("json"), Some(40));
+        assert_eq!(delta_of("serde + json"), Some(10));
+        assert_eq!(delta_of(SHARED), None);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{
+    arena::scratch::scratch_arena, data_provider_twiggy::DataProviderTwiggy, path::PathExt,
+};
+
+/// Name used for the bytes without line info or outside of any inlined call.
+const NO_LINE_INFO: &str = "<no line info>";
+const NOT_INLINED: &str = "<not inlined>";
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum BreakdownSortKey {
+    #[default]
+    Size,
+    Name,
+}
+
+pub struct SourceBytes {
+    pub file: String,
+    /// `None` for the per-file totals.
+    pub line: Option<usize>,
+    pub size_bytes: u64,
+    pub instruction_count: u32,
+}
+
+pub struct InlinedFrameBytes {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Number of places the function was inlined at, in the broken down function.
+    pub instance_count: u32,
+}
+
+/// Where the bytes of a single function come from.
+pub struct FunctionBreakdown {
+    pub function: usize,
+    pub size_bytes: u64,
+    pub files: Vec<SourceBytes>,
+    pub lines: Vec<SourceBytes>,
+    /// Calls inlined straight into the function, nested ones are part of their
+    /// enclosing call.
+    pub inlined_frames: Vec<InlinedFrameBytes>,
+}
+
+impl FunctionBreakdown {
+    pub fn new(data_provider: &DataProviderTwiggy, function: usize) -> Self {
+        let functions_section = &data_provider.wasm_data.functions_section;
+        let code_start = functions_section.range.start as u64;
+        let body_range = functions_section.function_bodies[function].range();
+        let body_end = body_range.end as u64;
+
+        let mut files: Vec<SourceBytes> = Vec::new();
+        let mut lines: Vec<SourceBytes> = Vec::new();
+        let mut file_lookup: HashMap<usize, usize> = HashMap::new();
+        let mut line_lookup: HashMap<(usize, usize), usize> = HashMap::new();
+
+        let scratch = scratch_arena(&[]);
+        let ops = &data_provider.raw_data[function].debug_info.function_ops;
+        for (idx, op) in ops.iter().enumerate() {
+            let next_address = ops.get(idx + 1).map_or(body_end, |next| next.address);
+            let size_bytes = next_address.saturating_sub(op.address);
+
+            // The line table row covering the instruction is the last one starting before it.
+            let code_offset = op.address - code_start;
+            let line_info = data_provider
+                .dw_line_infos
+                .floor(&code_offset)
+                .map(|(_, line_info)| line_info);
+
+            let (file_entry_idx, line) = match line_info {
+                Some(line_info) => (line_info.file_entry_idx, line_info.line),
+                None => (usize::MAX, 0),
+            };
+
+            let file_idx = *file_lookup.entry(file_entry_idx).or_insert_with(|| {
+                let file = match data_provider
+                    .dw_file_entries
+                    .get(file_entry_idx.saturating_sub(1))
+                {
+                    Some(file_entry) if line_info.is_some() => PathExt::join_all(
+                        &scratch,
+                        &[
+                            file_entry.base_directory,
+                            file_entry.directory,
+                            file_entry.file,
+                        ],
+                    )
+                    .to_string_lossy()
+                    .into_owned(),
+                    _ => NO_LINE_INFO.to_string(),
+                };
+
+                files.push(SourceBytes {
+                    file,
+                    line: None,
+                    size_bytes: 0,
+                    instruction_count: 0,
+                });
+                files.len() - 1
+            });
+            files[file_idx].size_bytes += size_bytes;
+            files[file_idx].instruction_count += 1;
+
+            let line_idx = *line_lookup
+                .entry((file_entry_idx, line))
+                .or_insert_with(|| {
+                    lines.push(SourceBytes {
+                        file: files[file_idx].file.clone(),
+                        line: Some(line),
+                        size_bytes: 0,
+                        instruction_count: 0,
+                    });
+                    lines.len() - 1
+                });
+            lines[line_idx].size_bytes += size_bytes;
+            lines[line_idx].instruction_count += 1;
+        }
+
+        let mut inlined_frames: Vec<InlinedFrameBytes> = Vec::new();
+        let mut frame_lookup: HashMap<&str, usize> = HashMap::new();
+        let mut inlined_bytes = 0;
+
+        // Inlined instances are sorted by address, the function's are contiguous.
+        let low_pc = body_range.start as u64 - code_start;
+        let high_pc = body_end - code_start;
+        let inlined_instances = &data_provider.dw_inlined_instances;
+        let first = inlined_instances.partition_point(|instance| instance.low_pc < low_pc);
+        for inlined_instance in inlined_instances[first..]
+            .iter()
+            .take_while(|instance| instance.low_pc < high_pc)
+            .filter(|instance| instance.depth == 0)
+        {
+            let frame_idx = *frame_lookup
+                .entry(inlined_instance.name)
+                .or_insert_with(|| {
+                    inlined_frames.push(InlinedFrameBytes {
+                        name: inlined_instance.name.to_string(),
+                        size_bytes: 0,
+                        instance_count: 0,
+                    });
+                    inlined_frames.len() - 1
+                });
+
+            inlined_frames[frame_idx].size_bytes += inlined_instance.size();
+            inlined_frames[frame_idx].instance_count += 1;
+            inlined_bytes += inlined_instance.size();
+        }
+
+        let size_bytes = data_provider.raw_data[function]
+            .function_property
+            .shallow_size_bytes as u64;
+        if !inlined_frames.is_empty() {
+            inlined_frames.push(InlinedFrameBytes {
+                name: NOT_INLINED.to_string(),
+                size_bytes: size_bytes.saturating_sub(inlined_bytes),
+                instance_count: 1,
+            });
+        }
+
+        let mut breakdown = Self {
+            function,
+            size_bytes,
+            files,
+            lines,
+            inlined_frames,
+        };
+        breakdown.sort(BreakdownSortKey::Size);
+        breakdown
+    }
+
+    pub fn has_line_info(&self) -> bool {
+        self.files.iter().any(|file| file.file != NO_LINE_INFO)
+    }
+
+    /// Sorts the tables by decreasing size, or by name (and line).
+    pub fn sort(&mut self, sort_key: BreakdownSortKey) {
+        match sort_key {
+            BreakdownSortKey::Size => {
+                self.files.sort_by_key(|file| Reverse(file.size_bytes));
+                self.lines.sort_by_key(|line| Reverse(line.size_bytes));
+                self.inlined_frames
+                    .sort_by_key(|inlined_frame| Reverse(inlined_frame.size_bytes));
+            }
+            BreakdownSortKey::Name => {
+                self.files.sort_by(|a, b| a.file.cmp(&b.file));
+                self.lines
+                    .sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+                self.inlined_frames.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+        }
+    }
+}
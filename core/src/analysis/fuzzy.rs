@@ -0,0 +1,178 @@
+/// Scores of the fuzzy matching, close to the ones of fzf.
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+/// Matching the start of a path segment or word, `push` in `Vec::push`.
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+/// Matching the start of a word in camel case, `Map` in `HashMap`.
+const BONUS_CAMEL_CASE: i32 = BONUS_BOUNDARY - 1;
+/// Matching right after the previous pattern character.
+const BONUS_CONSECUTIVE: i32 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+/// The first character of the pattern counts more towards the bonuses.
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// Marks the positions the pattern can't be matched at.
+const NO_MATCH: i32 = i32::MIN / 2;
+
+/// Matches names against a pattern whose characters have to appear in order,
+/// but not next to each other, like skim and fzf do: `vecpushstr` matches
+/// `alloc::vec::Vec<T>::push_str`.
+pub struct FuzzyMatcher {
+    pattern: Vec<char>,
+    case_sensitive: bool,
+    candidate: Vec<(char, i32)>,
+    scores: Vec<i32>,
+    previous_scores: Vec<i32>,
+}
+
+impl FuzzyMatcher {
+    pub fn new(pattern: &str, case_sensitive: bool) -> Self {
+        Self {
+            pattern: pattern
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| {
+                    if case_sensitive {
+                        c
+                    } else {
+                        c.to_ascii_lowercase()
+                    }
+                })
+                .collect(),
+            case_sensitive,
+            candidate: Vec::new(),
+            scores: Vec::new(),
+            previous_scores: Vec::new(),
+        }
+    }
+
+    /// Returns the score of the best alignment of the pattern in `name`, the
+    /// higher the better, or `None` if `name` doesn't contain the pattern.
+    pub fn score(&mut self, name: &str) -> Option<i32> {
+        if !self.is_subsequence(name) {
+            return None;
+        }
+        if self.pattern.is_empty() {
+            return Some(0);
+        }
+
+        // Characters of the name with the bonus of matching them.
+        self.candidate.clear();
+        let mut previous = None;
+        for c in name.chars() {
+            let bonus = match previous {
+                None => BONUS_BOUNDARY,
+                Some(previous) if !is_word_char(previous) && is_word_char(c) => BONUS_BOUNDARY,
+                Some(previous) if previous.is_lowercase() && c.is_uppercase() => BONUS_CAMEL_CASE,
+                _ => 0,
+            };
+            self.candidate.push((self.fold_case(c), bonus));
+            previous = Some(c);
+        }
+
+        // `scores[j]` is the best score of the pattern so far with its last
+        // character matched at `j`, computed one pattern character at a time.
+        let len = self.candidate.len();
+        self.scores.clear();
+        self.scores.resize(len, NO_MATCH);
+        self.previous_scores.clear();
+        self.previous_scores.resize(len, NO_MATCH);
+
+        for (pattern_idx, &pattern_char) in self.pattern.iter().enumerate() {
+            std::mem::swap(&mut self.scores, &mut self.previous_scores);
+
+            // Best score of the previous characters ending before a gap.
+            let mut gap_score = NO_MATCH;
+            for j in 0..len {
+                let (c, bonus) = self.candidate[j];
+
+                let score = if c != pattern_char {
+                    NO_MATCH
+                } else if pattern_idx == 0 {
+                    SCORE_MATCH + bonus * BONUS_FIRST_CHAR_MULTIPLIER
+                } else if j == 0 {
+                    NO_MATCH
+                } else {
+                    let consecutive = add(
+                        self.previous_scores[j - 1],
+                        SCORE_MATCH + bonus.max(BONUS_CONSECUTIVE),
+                    );
+                    let after_gap = add(gap_score, SCORE_MATCH + bonus);
+                    consecutive.max(after_gap)
+                };
+
+                if j > 0 {
+                    gap_score = add(gap_score, SCORE_GAP_EXTENSION)
+                        .max(add(self.previous_scores[j - 1], SCORE_GAP_START));
+                }
+                self.scores[j] = score;
+            }
+        }
+
+        self.scores
+            .iter()
+            .copied()
+            .max()
+            .filter(|&score| score != NO_MATCH)
+    }
+
+    /// Cheap check done before scoring, most names don't match.
+    fn is_subsequence(&self, name: &str) -> bool {
+        let mut pattern = self.pattern.iter().peekable();
+        for c in name.chars() {
+            match pattern.peek() {
+                Some(&&pattern_char) if self.fold_case(c) == pattern_char => {
+                    pattern.next();
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        pattern.peek().is_none()
+    }
+
+    fn fold_case(&self, c: char) -> char {
+        if self.case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    }
+}
+
+/// Adds to a score, unless there is no match.
+fn add(score: i32, delta: i32) -> i32 {
+    if score == NO_MATCH {
+        NO_MATCH
+    } else {
+        score + delta
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && c != '_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::FuzzyMatcher;
+
+    #[test]
+    fn boundaries_score_higher() {
+        let mut matcher = FuzzyMatcher::new("vecpushstr", false);
+
+        let push_str = matcher.score("alloc::vec::Vec<T>::push_str").unwrap();
+        let scattered = matcher.score("xvxexcxpxuxsxhxsxtxr").unwrap();
+        assert!(push_str > scattered);
+
+        assert_eq!(matcher.score("alloc::vec::Vec<T>::push"), None);
+        assert_eq!(matcher.score("alloc::string::String::push_str"), None);
+
+        let mut matcher = FuzzyMatcher::new("HashMap", true);
+        assert!(matcher.score("std::collections::HashMap").is_some());
+        assert_eq!(matcher.score("hashmap"), None);
+
+        let mut matcher = FuzzyMatcher::new("HashMap", false);
+        assert!(matcher.score("hashmap").is_some());
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{analysis::symbol, arena::interner::Symbol, data_provider_twiggy::DataProviderTwiggy};
+
+/// Generic functions whose instantiations are bigger than this (on average)
+/// are good candidates to move their type-independent code out.
+const LARGE_INSTANCE_BYTES: u32 = 1024;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum GenericBloatSortKey {
+    #[default]
+    TotalSize,
+    InstanceCount,
+    DistinctTypeArgs,
+}
+
+pub struct GenericBloatEntry {
+    /// Name of the generic function, without generic arguments.
+    pub name: String,
+    pub total_size_bytes: u32,
+    pub instance_count: u32,
+    /// Number of different sets of generic arguments the function was
+    /// instantiated with. Lower than `instance_count` when the same
+    /// instantiation was emitted multiple times (e.g., by different codegen units).
+    pub distinct_type_args: u32,
+    /// Index of the biggest instantiation.
+    pub largest_instance: usize,
+    pub suggestion: Option<&'static str>,
+}
+
+#[derive(Default)]
+pub struct GenericBloatReport {
+    pub entries: Vec<GenericBloatEntry>,
+}
+
+impl GenericBloatReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let mut entry_lookup: HashMap<Symbol, usize> = HashMap::new();
+        let mut instantiations: Vec<HashSet<&str>> = Vec::new();
+        let mut entries: Vec<GenericBloatEntry> = Vec::new();
+
+        for (idx, function_data) in data_provider.raw_data.iter().enumerate() {
+            let property = &function_data.function_property;
+            let Some(generic_name) = property.monomorphization_of else {
+                continue;
+            };
+
+            let entry_idx = *entry_lookup
+                .entry(property.group_symbol)
+                .or_insert_with(|| {
+                    entries.push(GenericBloatEntry {
+                        name: generic_name.to_string(),
+                        total_size_bytes: 0,
+                        instance_count: 0,
+                        distinct_type_args: 0,
+                        largest_instance: idx,
+                        suggestion: None,
+                    });
+                    instantiations.push(HashSet::new());
+                    entries.len() - 1
+                });
+
+            let entry = &mut entries[entry_idx];
+            entry.total_size_bytes += property.shallow_size_bytes;
+            entry.instance_count += 1;
+            if data_provider.raw_data[entry.largest_instance]
+                .function_property
+                .shallow_size_bytes
+                < property.shallow_size_bytes
+            {
+                entry.largest_instance = idx;
+            }
+
+            instantiations[entry_idx].insert(symbol::strip_hash(property.raw_name));
+        }
+
+        for (entry, instantiations) in entries.iter_mut().zip(instantiations) {
+            entry.distinct_type_args = instantiations.len() as u32;
+            entry.suggestion = suggestion(entry);
+        }
+
+        // Functions instantiated only once are not generic bloat.
+        entries.retain(|entry| entry.instance_count > 1);
+
+        let mut report = Self { entries };
+        report.sort(GenericBloatSortKey::TotalSize);
+        report
+    }
+
+    /// Sorts the entries in descending order of the given key.
+    pub fn sort(&mut self, sort_key: GenericBloatSortKey) {
+        match sort_key {
+            GenericBloatSortKey::TotalSize => self
+                .entries
+                .sort_by_key(|entry| Reverse(entry.total_size_bytes)),
+            GenericBloatSortKey::InstanceCount => self
+                .entries
+                .sort_by_key(|entry| Reverse(entry.instance_count)),
+            GenericBloatSortKey::DistinctTypeArgs => self
+                .entries
+                .sort_by_key(|entry| Reverse(entry.distinct_type_args)),
+        }
+    }
+}
+
+fn suggestion(entry: &GenericBloatEntry) -> Option<&'static str> {
+    let average_size = entry.total_size_bytes / entry.instance_count.max(1);
+
+    if entry.name.contains("drop_in_place") {
+        Some("Drop glue: fewer distinct types with Drop impls, or boxing large ones, reduces it")
+    } else if entry.instance_count > entry.distinct_type_args {
+        Some(
+            "Identical instantiations are duplicated, consider `codegen-units = 1` or `-Zshare-generics`",
+        )
+    } else if entry.distinct_type_args >= 4 && average_size >= LARGE_INSTANCE_BYTES {
+        Some("Consider moving the type-independent code into a non-generic inner function")
+    } else if entry.distinct_type_args >= 8 {
+        Some("Consider `dyn Trait` instead of generics so a single instance is shared")
+    } else {
+        None
+    }
+}
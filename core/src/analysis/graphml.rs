@@ -0,0 +1,112 @@
+use std::fmt::Write;
+
+use crate::{analysis::call_graph::exported_functions, data_provider_twiggy::DataProviderTwiggy};
+
+/// Escapes `text` for the content and the attributes of XML elements.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes the calls between the functions in the GraphML format of Gephi
+/// and yEd, only the functions `from` reaches with it, of `min_size` bytes or
+/// more. The functions have their name as label, their crate and sizes.
+pub fn call_graph_graphml(
+    data_provider: &DataProviderTwiggy,
+    from: Option<usize>,
+    min_size: u32,
+) -> String {
+    let exported = exported_functions(data_provider, from, min_size);
+
+    let mut output = String::new();
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (id, ty) in [
+        ("label", "string"),
+        ("crate", "string"),
+        ("shallow_size", "long"),
+        ("retained_size", "long"),
+    ] {
+        writeln!(
+            output,
+            "  <key id=\"{id}\" for=\"node\" attr.name=\"{id}\" attr.type=\"{ty}\"/>"
+        )
+        .unwrap();
+    }
+    output.push_str("  <graph id=\"calls\" edgedefault=\"directed\">\n");
+
+    for (idx, function_data) in data_provider.raw_data.iter().enumerate() {
+        if !exported[idx] {
+            continue;
+        }
+
+        let property = &function_data.function_property;
+        writeln!(output, "    <node id=\"f{}\">", idx).unwrap();
+        writeln!(
+            output,
+            "      <data key=\"label\">{}</data>",
+            escape(data_provider.function_name(idx))
+        )
+        .unwrap();
+        if let Some(crate_name) = property.crate_name {
+            writeln!(
+                output,
+                "      <data key=\"crate\">{}</data>",
+                escape(crate_name)
+            )
+            .unwrap();
+        }
+        writeln!(
+            output,
+            "      <data key=\"shallow_size\">{}</data>",
+            property.shallow_size_bytes
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "      <data key=\"retained_size\">{}</data>",
+            property.retained_size_bytes
+        )
+        .unwrap();
+        output.push_str("    </node>\n");
+    }
+
+    for caller in (0..exported.len()).filter(|&idx| exported[idx]) {
+        for &callee in data_provider.call_graph.callees(caller) {
+            if exported[callee as usize] {
+                writeln!(
+                    output,
+                    "    <edge source=\"f{}\" target=\"f{}\"/>",
+                    caller, callee
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    output.push_str("  </graph>\n</graphml>\n");
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::escape;
+
+    #[test]
+    fn names_are_escaped() {
+        assert_eq!(
+            escape("<Vec<u8> as Drop>::drop & \"'"),
+            "&lt;Vec&lt;u8&gt; as Drop&gt;::drop &amp; &quot;&apos;"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fmt::Write,
+    io::{self, Write as _},
+    process::{Command, Stdio},
+};
+
+use crate::{analysis::call_graph::exported_functions, data_provider_twiggy::DataProviderTwiggy};
+
+/// Crate of the functions without one when there is no debug info.
+const UNATTRIBUTED: &str = "<unattributed>";
+
+/// A graph in the DOT language of Graphviz, the nodes being labeled with
+/// their size.
+struct Dot {
+    output: String,
+    /// Size the percents of the nodes are of.
+    total_size: u32,
+}
+
+impl Dot {
+    fn new(name: &str, total_size: u32) -> Self {
+        let mut output = String::new();
+        writeln!(output, "digraph {} {{", name).unwrap();
+        output.push_str("    rankdir=LR;\n");
+        output.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        Self { output, total_size }
+    }
+
+    fn node(&mut self, id: &str, name: &str, size: u32) {
+        writeln!(
+            self.output,
+            "    {} [label=\"{}\\n{} bytes ({:.2}%)\"];",
+            id,
+            escape(name),
+            size,
+            percent(size, self.total_size),
+        )
+        .unwrap();
+    }
+
+    /// The children of `parent` smaller than the cutoff, in one node.
+    fn others(&mut self, parent: &str, count: usize, size: u32) {
+        writeln!(
+            self.output,
+            "    {}_others [label=\"{} smaller\\n{} bytes ({:.2}%)\", style=dashed];",
+            parent,
+            count,
+            size,
+            percent(size, self.total_size),
+        )
+        .unwrap();
+        self.edge(parent, &format!("{}_others", parent));
+    }
+
+    fn edge(&mut self, from: &str, to: &str) {
+        writeln!(self.output, "    {} -> {};", from, to).unwrap();
+    }
+
+    fn finish(mut self) -> String {
+        self.output.push_str("}\n");
+        self.output
+    }
+}
+
+fn percent(size: u32, total_size: u32) -> f32 {
+    if total_size == 0 {
+        return 0.0;
+    }
+    100.0 * size as f32 / total_size as f32
+}
+
+/// Escapes `text` for a double quoted DOT string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Children of a node kept in the graph, biggest first, and the number and
+/// size of the smaller ones.
+fn split_by_size(
+    children: impl Iterator<Item = (usize, u32)>,
+    min_size: u32,
+) -> (Vec<(usize, u32)>, usize, u32) {
+    let mut kept = Vec::new();
+    let mut other_count = 0;
+    let mut other_size = 0;
+    for (child, size) in children.filter(|(_, size)| *size > 0) {
+        if size >= min_size {
+            kept.push((child, size));
+        } else {
+            other_count += 1;
+            other_size += size;
+        }
+    }
+    kept.sort_by_key(|(_, size)| Reverse(*size));
+    (kept, other_count, other_size)
+}
+
+/// Writes the namespace tree of the dominators view, with its filter, as a
+/// DOT graph of the nodes of `min_size` bytes or more, whose root is named
+/// `root_name`. The smaller children of a node are summed up in one node.
+///
+/// Without debug info the filtered functions are grouped by crate.
+pub fn tree_dot(data_provider: &DataProviderTwiggy, root_name: &str, min_size: u32) -> String {
+    let state = &data_provider.dominator_state;
+    if state.tree.len() <= 1 {
+        return crates_dot(data_provider, root_name, min_size);
+    }
+
+    let mut dot = Dot::new("sizes", state.items_ui_data[0].size);
+    dot.node("n0", root_name, state.items_ui_data[0].size);
+
+    let mut pending = vec![0];
+    while let Some(node) = pending.pop() {
+        let children = state
+            .tree
+            .get_children(node)
+            .filter(|child| state.items_state[*child].visible())
+            .map(|child| (child, state.items_ui_data[child].size));
+        let (kept, other_count, other_size) = split_by_size(children, min_size);
+
+        let id = format!("n{}", node);
+        for (child, size) in kept {
+            let child_id = format!("n{}", child);
+            dot.node(&child_id, state.tree[child].value.name.as_str(), size);
+            dot.edge(&id, &child_id);
+            pending.push(child);
+        }
+        if other_count > 0 {
+            dot.others(&id, other_count, other_size);
+        }
+    }
+
+    dot.finish()
+}
+
+fn crates_dot(data_provider: &DataProviderTwiggy, root_name: &str, min_size: u32) -> String {
+    let mut crates: BTreeMap<&str, (u32, Vec<usize>)> = BTreeMap::new();
+    for &idx in data_provider.top_view_items_filtered.iter() {
+        let property = &data_provider.raw_data[idx].function_property;
+        let (size, functions) = crates
+            .entry(property.crate_name.unwrap_or(UNATTRIBUTED))
+            .or_default();
+        *size += property.shallow_size_bytes;
+        functions.push(idx);
+    }
+
+    let total_size = crates.values().map(|(size, _)| size).sum();
+    let mut dot = Dot::new("sizes", total_size);
+    dot.node("n0", root_name, total_size);
+
+    let crates: Vec<_> = crates.into_iter().collect();
+    let (kept, other_count, other_size) = split_by_size(
+        crates
+            .iter()
+            .enumerate()
+            .map(|(crate_idx, (_, (size, _)))| (crate_idx, *size)),
+        min_size,
+    );
+    for (crate_idx, size) in kept {
+        let (crate_name, (_, functions)) = &crates[crate_idx];
+        let crate_id = format!("c{}", crate_idx);
+        dot.node(&crate_id, crate_name, size);
+        dot.edge("n0", &crate_id);
+
+        let (kept, other_count, other_size) = split_by_size(
+            functions.iter().map(|&idx| {
+                let property = &data_provider.raw_data[idx].function_property;
+                (idx, property.shallow_size_bytes)
+            }),
+            min_size,
+        );
+        for (idx, size) in kept {
+            let function_id = format!("f{}", idx);
+            dot.node(&function_id, data_provider.function_name(idx), size);
+            dot.edge(&crate_id, &function_id);
+        }
+        if other_count > 0 {
+            dot.others(&crate_id, other_count, other_size);
+        }
+    }
+    if other_count > 0 {
+        dot.others("n0", other_count, other_size);
+    }
+
+    dot.finish()
+}
+
+/// Writes the calls between the functions as a DOT graph, only the functions
+/// `from` reaches with it, of `min_size` bytes or more.
+pub fn call_graph_dot(
+    data_provider: &DataProviderTwiggy,
+    from: Option<usize>,
+    min_size: u32,
+) -> String {
+    let exported = exported_functions(data_provider, from, min_size);
+    let code_size = data_provider.wasm_data.functions_section.size_in_bytes as u32;
+    let mut dot = Dot::new("calls", code_size);
+
+    for (idx, function_data) in data_provider.raw_data.iter().enumerate() {
+        if exported[idx] {
+            let size = function_data.function_property.shallow_size_bytes;
+            dot.node(&format!("f{}", idx), data_provider.function_name(idx), size);
+        }
+    }
+    for caller in (0..exported.len()).filter(|&idx| exported[idx]) {
+        for &callee in data_provider.call_graph.callees(caller) {
+            if exported[callee as usize] {
+                dot.edge(&format!("f{}", caller), &format!("f{}", callee));
+            }
+        }
+    }
+
+    dot.finish()
+}
+
+/// Lays `dot` out as an SVG image with the `dot` command of Graphviz, which
+/// must be installed.
+pub fn render_svg(dot: &str) -> io::Result<Vec<u8>> {
+    let mut child = Command::new("dot")
+        .arg("-Tsvg")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::new(err.kind(), format!("Failed to run Graphviz: {}", err)))?;
+
+    // Graphviz reads the whole graph before writing the image.
+    child.stdin.take().unwrap().write_all(dot.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "Graphviz failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{escape, split_by_size};
+
+    #[test]
+    fn smaller_children_are_summed_up() {
+        let children = [(1, 100), (2, 0), (3, 20), (4, 300), (5, 10)];
+        assert_eq!(
+            split_by_size(children.into_iter(), 100),
+            (vec![(4, 300), (1, 100)], 2, 30)
+        );
+    }
+
+    #[test]
+    fn names_are_escaped() {
+        assert_eq!(
+            escape(r#"<&str as core::fmt::Display>::fmt "\""#),
+            r#"<&str as core::fmt::Display>::fmt \"\\\""#
+        );
+    }
+}
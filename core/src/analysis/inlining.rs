@@ -0,0 +1,114 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::data_provider_twiggy::DataProviderTwiggy;
+
+/// Functions copied at least this many times, with copies of at least
+/// `INLINE_NEVER_MIN_COPY_BYTES` on average, are worth an `#[inline(never)]`.
+const INLINE_NEVER_MIN_COPIES: u32 = 4;
+const INLINE_NEVER_MIN_COPY_BYTES: u64 = 64;
+
+pub struct FunctionInlining {
+    pub function: usize,
+    /// Bytes of the function coming from inlined callees.
+    pub inlined_bytes: u64,
+    pub inlined_percent: f32,
+    /// Number of calls inlined straight into the function (not counting nested ones).
+    pub inlined_call_count: u32,
+}
+
+pub struct InlinedCallee {
+    pub name: String,
+    /// Number of places the function was inlined at.
+    pub instance_count: u32,
+    /// Number of distinct functions it was inlined into.
+    pub caller_count: u32,
+    /// Total bytes of all the inlined copies.
+    pub total_bytes: u64,
+}
+
+impl InlinedCallee {
+    pub fn is_inline_never_candidate(&self) -> bool {
+        self.instance_count >= INLINE_NEVER_MIN_COPIES
+            && self.total_bytes / self.instance_count as u64 >= INLINE_NEVER_MIN_COPY_BYTES
+    }
+}
+
+#[derive(Default)]
+pub struct InliningReport {
+    /// Functions by decreasing inlined bytes.
+    pub functions: Vec<FunctionInlining>,
+    /// Inlined functions by decreasing total bytes.
+    pub callees: Vec<InlinedCallee>,
+}
+
+impl InliningReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let mut functions: HashMap<usize, FunctionInlining> = HashMap::new();
+        let mut callee_lookup: HashMap<&str, usize> = HashMap::new();
+        let mut callees: Vec<InlinedCallee> = Vec::new();
+        let mut callers: Vec<HashSet<usize>> = Vec::new();
+
+        for inlined_instance in data_provider.dw_inlined_instances.iter() {
+            let Some(function) = data_provider.function_at_code_offset(inlined_instance.low_pc)
+            else {
+                continue;
+            };
+
+            // Nested instances are part of the bytes of their enclosing instance.
+            if inlined_instance.depth == 0 {
+                let function_inlining =
+                    functions
+                        .entry(function)
+                        .or_insert_with(|| FunctionInlining {
+                            function,
+                            inlined_bytes: 0,
+                            inlined_percent: 0.0,
+                            inlined_call_count: 0,
+                        });
+                function_inlining.inlined_bytes += inlined_instance.size();
+                function_inlining.inlined_call_count += 1;
+            }
+
+            let callee_idx = *callee_lookup
+                .entry(inlined_instance.name)
+                .or_insert_with(|| {
+                    callees.push(InlinedCallee {
+                        name: inlined_instance.name.to_string(),
+                        instance_count: 0,
+                        caller_count: 0,
+                        total_bytes: 0,
+                    });
+                    callers.push(HashSet::new());
+                    callees.len() - 1
+                });
+
+            let callee = &mut callees[callee_idx];
+            callee.instance_count += 1;
+            callee.total_bytes += inlined_instance.size();
+            callers[callee_idx].insert(function);
+        }
+
+        for (callee, callers) in callees.iter_mut().zip(callers) {
+            callee.caller_count = callers.len() as u32;
+        }
+        callees.sort_by_key(|callee| Reverse(callee.total_bytes));
+
+        let mut functions: Vec<FunctionInlining> = functions.into_values().collect();
+        for function_inlining in &mut functions {
+            let size_bytes = data_provider.raw_data[function_inlining.function]
+                .function_property
+                .shallow_size_bytes as u64;
+
+            // Ranges can overlap a bit with the function prologue/epilogue.
+            function_inlining.inlined_bytes = function_inlining.inlined_bytes.min(size_bytes);
+            function_inlining.inlined_percent =
+                function_inlining.inlined_bytes as f32 / size_bytes.max(1) as f32 * 100.0;
+        }
+        functions.sort_by_key(|function_inlining| Reverse(function_inlining.inlined_bytes));
+
+        Self { functions, callees }
+    }
+}
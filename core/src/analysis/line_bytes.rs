@@ -0,0 +1,52 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    arena::scratch::scratch_arena, data_provider_twiggy::DataProviderTwiggy, path::PathExt,
+};
+
+/// Bytes of code generated by each line of a source file, by 1-based line,
+/// summed over all the functions from the line table.
+pub fn bytes_per_line(data_provider: &DataProviderTwiggy, file_path: &Path) -> HashMap<usize, u64> {
+    let mut line_bytes = HashMap::new();
+
+    // A file can have an entry in every compilation unit it is used in.
+    let scratch = scratch_arena(&[]);
+    let file_entries: Vec<bool> = data_provider
+        .dw_file_entries
+        .iter()
+        .map(|file_entry| {
+            PathExt::join_all(
+                &scratch,
+                &[
+                    file_entry.base_directory,
+                    file_entry.directory,
+                    file_entry.file,
+                ],
+            ) == file_path
+        })
+        .collect();
+
+    let functions_section = &data_provider.wasm_data.functions_section;
+    let code_start = functions_section.range.start as u64;
+    for (function, function_body) in functions_section.function_bodies.iter().enumerate() {
+        // A row covers the code up to the next one, without going past the
+        // end of its function, like at the end of a sequence.
+        let function_end = function_body.range().end as u64 - code_start;
+        let line_infos = data_provider.line_infos_of(function);
+        for (idx, (address, line_info)) in line_infos.iter().enumerate() {
+            let in_file = file_entries
+                .get(line_info.file_entry_idx.saturating_sub(1))
+                .is_some_and(|&in_file| in_file);
+            if !in_file || line_info.line == 0 {
+                continue;
+            }
+
+            let next_address = line_infos
+                .get(idx + 1)
+                .map_or(function_end, |(next_address, _)| *next_address);
+            *line_bytes.entry(line_info.line).or_default() += next_address.saturating_sub(*address);
+        }
+    }
+
+    line_bytes
+}
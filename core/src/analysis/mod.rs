@@ -0,0 +1,30 @@
+pub mod budget;
+pub mod call_graph;
+pub mod cargo_metadata;
+pub mod cfg;
+pub mod code_origin;
+pub mod collapsed_stacks;
+pub mod compilation_units;
+pub mod compressed_size;
+pub mod dead_exports;
+pub mod diff;
+pub mod duplicate_data;
+pub mod export;
+pub mod feature_attribution;
+pub mod function_breakdown;
+pub mod fuzzy;
+pub mod generic_bloat;
+pub mod graphml;
+pub mod graphviz;
+pub mod inlining;
+pub mod line_bytes;
+pub mod padding;
+pub mod panic_machinery;
+pub mod query;
+pub mod recursion;
+pub mod section_summary;
+pub mod substring;
+pub mod suggestions;
+pub mod symbol;
+pub mod unwinding;
+pub mod vtables;
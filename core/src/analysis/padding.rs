@@ -0,0 +1,258 @@
+use std::cmp::Reverse;
+
+use crate::data_provider_twiggy::DataProviderTwiggy;
+
+/// Shorter runs of zeroes are mostly zero fields, not padding.
+const MIN_ZERO_RUN: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasteKind {
+    /// Zeroes in a data segment, left by the alignment of the data.
+    ZeroFill,
+    /// Memory between two active data segments, not in the file.
+    SegmentGap,
+}
+
+impl WasteKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WasteKind::ZeroFill => "zero fill",
+            WasteKind::SegmentGap => "gap between segments (memory only)",
+        }
+    }
+}
+
+/// Contiguous wasted bytes.
+pub struct WasteRun {
+    pub kind: WasteKind,
+    /// Address in linear memory, `None` in passive segments.
+    pub address: Option<u32>,
+    /// Index of the data segment the run is in, or ends before.
+    pub segment: usize,
+    pub size_bytes: u32,
+}
+
+pub struct PaddedFunction {
+    pub function: usize,
+    /// Bytes of the LEB128 immediates and body size longer than they need to be.
+    pub padding_bytes: u32,
+}
+
+/// Bytes spent on padding rather than code or data.
+///
+/// Linkers reserve 5 bytes for the LEB128 immediates they relocate (calls,
+/// addresses, globals). Unless relocations are compressed, which `wasm-ld`
+/// doesn't do when keeping debug info, the unused ones stay in the file.
+#[derive(Default)]
+pub struct PaddingReport {
+    pub instruction_padding_bytes: u64,
+    pub body_size_padding_bytes: u64,
+    /// Functions with padding, by decreasing padding.
+    pub padded_functions: Vec<PaddedFunction>,
+
+    pub zero_fill_bytes: u64,
+    pub segment_gap_bytes: u64,
+    /// Runs of wasted bytes, biggest first.
+    pub runs: Vec<WasteRun>,
+}
+
+impl PaddingReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let wasm_data = &data_provider.wasm_data;
+        let bytes = wasm_data.bytes;
+        let function_bodies = &wasm_data.functions_section.function_bodies;
+
+        let mut instruction_padding_bytes = 0;
+        let mut body_size_padding_bytes = 0;
+        let mut padded_functions = Vec::new();
+
+        for (function, function_data) in data_provider.raw_data.iter().enumerate() {
+            let body_range = function_bodies[function].range();
+
+            // The body size comes right before the body, its last byte is the
+            // only one without the continuation bit.
+            let mut body_size_start = body_range.start - 1;
+            while body_range.start - body_size_start < 5 && bytes[body_size_start - 1] & 0x80 != 0 {
+                body_size_start -= 1;
+            }
+            let body_size_padding = leb_lengths(&bytes[body_size_start..], false)
+                .map_or(0, |(length, minimal_length)| length - minimal_length)
+                as u32;
+
+            let ops = &function_data.debug_info.function_ops;
+            let instruction_padding: u32 = ops
+                .iter()
+                .enumerate()
+                .map(|(idx, op)| {
+                    let next_address = ops
+                        .get(idx + 1)
+                        .map_or(body_range.end as u64, |next| next.address);
+                    instruction_padding(&bytes[op.address as usize..next_address as usize])
+                })
+                .sum();
+
+            body_size_padding_bytes += body_size_padding as u64;
+            instruction_padding_bytes += instruction_padding as u64;
+            if body_size_padding + instruction_padding > 0 {
+                padded_functions.push(PaddedFunction {
+                    function,
+                    padding_bytes: body_size_padding + instruction_padding,
+                });
+            }
+        }
+        padded_functions.sort_by_key(|padded_function| Reverse(padded_function.padding_bytes));
+
+        let mut runs = Vec::new();
+        for (segment, data_segment) in wasm_data.data_segments.iter().enumerate() {
+            let mut offset = 0;
+            while offset < data_segment.data.len() {
+                let zero_count = data_segment.data[offset..]
+                    .iter()
+                    .take_while(|&&byte| byte == 0)
+                    .count();
+
+                if zero_count >= MIN_ZERO_RUN {
+                    runs.push(WasteRun {
+                        kind: WasteKind::ZeroFill,
+                        address: data_segment.offset.map(|address| address + offset as u32),
+                        segment,
+                        size_bytes: zero_count as u32,
+                    });
+                }
+                offset += zero_count.max(1);
+            }
+        }
+
+        // Active segments are laid out in increasing addresses, with the
+        // alignment of the next one in between.
+        let mut active_segments: Vec<(usize, u32, u32)> = wasm_data
+            .data_segments
+            .iter()
+            .enumerate()
+            .filter_map(|(segment, data_segment)| {
+                let address = data_segment.offset?;
+                Some((segment, address, address + data_segment.data.len() as u32))
+            })
+            .collect();
+        active_segments.sort_by_key(|&(_, address, _)| address);
+        for pair in active_segments.windows(2) {
+            let (_, _, previous_end) = pair[0];
+            let (segment, address, _) = pair[1];
+            if address > previous_end {
+                runs.push(WasteRun {
+                    kind: WasteKind::SegmentGap,
+                    address: Some(previous_end),
+                    segment,
+                    size_bytes: address - previous_end,
+                });
+            }
+        }
+
+        let bytes_of = |kind: WasteKind| -> u64 {
+            runs.iter()
+                .filter(|run| run.kind == kind)
+                .map(|run| run.size_bytes as u64)
+                .sum()
+        };
+        let zero_fill_bytes = bytes_of(WasteKind::ZeroFill);
+        let segment_gap_bytes = bytes_of(WasteKind::SegmentGap);
+        runs.sort_by_key(|run| Reverse(run.size_bytes));
+
+        Self {
+            instruction_padding_bytes,
+            body_size_padding_bytes,
+            padded_functions,
+            zero_fill_bytes,
+            segment_gap_bytes,
+            runs,
+        }
+    }
+
+    /// Padding bytes in the file, the segment gaps are only in memory.
+    pub fn file_bytes(&self) -> u64 {
+        self.instruction_padding_bytes + self.body_size_padding_bytes + self.zero_fill_bytes
+    }
+}
+
+/// Bytes of the LEB128 immediates of an instruction longer than they need to be.
+///
+/// Only the instructions with relocatable immediates are checked, the others
+/// are always encoded in their shortest form.
+fn instruction_padding(instruction: &[u8]) -> u32 {
+    let Some((&opcode, immediates)) = instruction.split_first() else {
+        return 0;
+    };
+
+    let padding = |immediate: &[u8], signed: bool| {
+        leb_lengths(immediate, signed)
+            .map(|(length, minimal_length)| (length, length - minimal_length))
+    };
+
+    let padding_bytes = match opcode {
+        // call, call_indirect, return_call, global.get, global.set
+        0x10 | 0x11 | 0x12 | 0x23 | 0x24 => padding(immediates, false).map(|(_, padding)| padding),
+        // i32.const, i64.const
+        0x41 | 0x42 => padding(immediates, true).map(|(_, padding)| padding),
+        // Loads and stores, the alignment then the offset.
+        0x28..=0x3e => padding(immediates, false).and_then(|(align_length, align_padding)| {
+            let (_, offset_padding) = padding(&immediates[align_length..], false)?;
+            Some(align_padding + offset_padding)
+        }),
+        _ => None,
+    };
+
+    padding_bytes.unwrap_or(0) as u32
+}
+
+/// Returns the length of the LEB128 at the start of `bytes` and the length of
+/// the shortest encoding of its value.
+fn leb_lengths(bytes: &[u8], signed: bool) -> Option<(usize, usize)> {
+    let length = bytes.iter().take(10).position(|&byte| byte & 0x80 == 0)? + 1;
+
+    let mut value: i128 = 0;
+    for (idx, &byte) in bytes[..length].iter().enumerate() {
+        value |= ((byte & 0x7f) as i128) << (7 * idx);
+    }
+    if signed && bytes[length - 1] & 0x40 != 0 {
+        value -= 1 << (7 * length);
+    }
+
+    let fits = |minimal_length: usize| {
+        if signed {
+            let bound = 1i128 << (7 * minimal_length - 1);
+            (-bound..bound).contains(&value)
+        } else {
+            value < 1 << (7 * minimal_length)
+        }
+    };
+    let minimal_length = (1..=length).find(|&minimal_length| fits(minimal_length))?;
+
+    Some((length, minimal_length))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{instruction_padding, leb_lengths};
+
+    #[test]
+    fn padded_lebs_are_detected() {
+        assert_eq!(leb_lengths(&[0xe5, 0x8e, 0x26], false), Some((3, 3)));
+        assert_eq!(
+            leb_lengths(&[0x80, 0x80, 0x80, 0x80, 0x00], false),
+            Some((5, 1))
+        );
+        assert_eq!(
+            leb_lengths(&[0xff, 0xff, 0xff, 0xff, 0x7f], true),
+            Some((5, 1))
+        );
+        assert_eq!(leb_lengths(&[0xc0, 0xbb, 0x78], true), Some((3, 3)));
+
+        // `call 3` with a relocation padded to 5 bytes.
+        assert_eq!(
+            instruction_padding(&[0x10, 0x83, 0x80, 0x80, 0x80, 0x00]),
+            4
+        );
+        // `i32.load offset=8`, not padded.
+        assert_eq!(instruction_padding(&[0x28, 0x02, 0x08]), 0);
+    }
+}
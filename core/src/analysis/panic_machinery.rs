@@ -0,0 +1,130 @@
+use std::cmp::Reverse;
+
+use crate::data_provider_twiggy::DataProviderTwiggy;
+
+/// Paths of the formatting, panicking and unwinding machinery.
+const MACHINERY_PATHS: &[&str] = &[
+    "core::fmt::",
+    "alloc::fmt::",
+    "std::fmt::",
+    "core::panicking::",
+    "core::panic::",
+    "std::panicking::",
+    "std::panic::",
+    "std::rt::",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::result::unwrap_failed",
+    "core::slice::index::",
+    "panic_unwind::",
+    "panic_abort::",
+    "unwind::",
+];
+
+/// Unmangled symbols of the panic runtime.
+const MACHINERY_SYMBOLS: &[&str] = &[
+    "rust_begin_unwind",
+    "rust_panic",
+    "__rust_start_panic",
+    "__rust_panic_cleanup",
+];
+
+/// Returns `true` if the demangled symbol belongs to the formatting,
+/// panicking or unwinding machinery.
+pub fn is_machinery(demangled_name: &str) -> bool {
+    let name = demangled_name.trim_start_matches('<');
+
+    MACHINERY_PATHS.iter().any(|path| name.starts_with(path))
+        // `Debug`/`Display` implementations are only there to be formatted.
+        || name.contains(" as core::fmt::")
+        || name.starts_with("_Unwind_")
+        || MACHINERY_SYMBOLS.contains(&name)
+}
+
+pub struct MachineryRetainer {
+    pub function: usize,
+    /// Number of distinct machinery functions called directly.
+    pub machinery_callees: u32,
+}
+
+#[derive(Default)]
+pub struct PanicMachineryReport {
+    /// Machinery functions, plus the functions only reachable through them,
+    /// sorted by size.
+    pub machinery: Vec<usize>,
+    pub machinery_size_bytes: u64,
+
+    /// Functions outside of the machinery calling into it, sorted by size.
+    pub retainers: Vec<MachineryRetainer>,
+}
+
+impl PanicMachineryReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let call_graph = &data_provider.call_graph;
+        let function_count = data_provider.raw_data.len();
+
+        let mut in_machinery: Vec<bool> = data_provider
+            .raw_data
+            .iter()
+            .map(|function_data| is_machinery(function_data.function_property.raw_name))
+            .collect();
+
+        // Anything called exclusively from the machinery goes away with it.
+        let mut worklist: Vec<usize> = (0..function_count)
+            .filter(|&idx| in_machinery[idx])
+            .collect();
+        while let Some(idx) = worklist.pop() {
+            for &callee in call_graph.callees(idx) {
+                let callee = callee as usize;
+                if in_machinery[callee] {
+                    continue;
+                }
+
+                if call_graph
+                    .callers(callee)
+                    .iter()
+                    .all(|&caller| in_machinery[caller as usize])
+                {
+                    in_machinery[callee] = true;
+                    worklist.push(callee);
+                }
+            }
+        }
+
+        let size_of = |idx: usize| {
+            data_provider.raw_data[idx]
+                .function_property
+                .shallow_size_bytes
+        };
+
+        let mut machinery: Vec<usize> = (0..function_count)
+            .filter(|&idx| in_machinery[idx])
+            .collect();
+        machinery.sort_by_key(|&idx| Reverse(size_of(idx)));
+
+        let machinery_size_bytes = machinery.iter().map(|&idx| size_of(idx) as u64).sum();
+
+        let mut retainers: Vec<MachineryRetainer> = (0..function_count)
+            .filter(|&idx| !in_machinery[idx])
+            .filter_map(|idx| {
+                let machinery_callees = call_graph
+                    .callees(idx)
+                    .iter()
+                    .filter(|&&callee| in_machinery[callee as usize])
+                    .count() as u32;
+
+                (machinery_callees > 0).then_some(MachineryRetainer {
+                    function: idx,
+                    machinery_callees,
+                })
+            })
+            .collect();
+        retainers.sort_by_key(|retainer| Reverse(size_of(retainer.function)));
+
+        Self {
+            machinery,
+            machinery_size_bytes,
+            retainers,
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use std::{cmp::Ordering, fmt};
+
+use regex::Regex;
+
+use crate::data_provider::{FilterOptions, FunctionProperty};
+
+/// Filter combining conditions on the sizes and names of the functions, like
+/// `size > 10kb && crate == "serde" && !name ~ "test"`.
+///
+/// The conditions compare a field to a value:
+/// - `size` (the retained size), `shallow` and `compressed` to a size in
+///   bytes with an optional `kb`, `mb` or `gb` unit, with `<`, `<=`, `>`,
+///   `>=`, `==` or `!=`;
+/// - `name`, `mangled`, `crate` and `generic` (the generic function of an
+///   instantiation) to a quoted text, with `==` or `!=`, or to a regex with
+///   `~`, which follows the case and whole word options.
+///
+/// They are combined with `!`, `&&`, `||` and parentheses, `&&` binding
+/// tighter than `||`.
+#[derive(Debug)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Size {
+        field: SizeField,
+        op: CompareOp,
+        bytes: u64,
+    },
+    Text {
+        field: TextField,
+        equal: bool,
+        text: String,
+    },
+    Regex {
+        field: TextField,
+        regex: Regex,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeField {
+    Retained,
+    Shallow,
+    Compressed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextField {
+    Name,
+    Mangled,
+    Crate,
+    Generic,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl CompareOp {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Less => ordering.is_lt(),
+            CompareOp::LessOrEqual => ordering.is_le(),
+            CompareOp::Greater => ordering.is_gt(),
+            CompareOp::GreaterOrEqual => ordering.is_ge(),
+            CompareOp::Equal => ordering.is_eq(),
+            CompareOp::NotEqual => ordering.is_ne(),
+        }
+    }
+}
+
+/// What a query is evaluated against, a function of tops or a node of the
+/// dominators.
+pub struct QueryItem<'n> {
+    pub name: &'n str,
+    /// Empty without one.
+    pub mangled_name: &'n str,
+    pub crate_name: Option<&'n str>,
+    pub monomorphization_of: Option<&'n str>,
+    pub retained_size_bytes: u32,
+    pub shallow_size_bytes: u32,
+    pub compressed_size_bytes: u32,
+}
+
+impl<'n> QueryItem<'n> {
+    pub fn function(property: &FunctionProperty<'n>, name: &'n str, mangled_name: &'n str) -> Self {
+        Self {
+            name,
+            mangled_name,
+            crate_name: property.crate_name,
+            monomorphization_of: property.monomorphization_of,
+            retained_size_bytes: property.retained_size_bytes,
+            shallow_size_bytes: property.shallow_size_bytes,
+            compressed_size_bytes: property.compressed_size_bytes,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct QueryError {
+    /// Byte offset in the query.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.position)
+    }
+}
+
+impl Query {
+    pub fn parse(text: &str, options: &FilterOptions) -> Result<Self, QueryError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+            end: text.len(),
+            options,
+        };
+        let query = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            Some((position, token)) => Err(QueryError {
+                position: *position,
+                message: format!("Unexpected {}", token),
+            }),
+            None => Ok(query),
+        }
+    }
+
+    pub fn matches(&self, item: &QueryItem) -> bool {
+        match self {
+            Query::And(a, b) => a.matches(item) && b.matches(item),
+            Query::Or(a, b) => a.matches(item) || b.matches(item),
+            Query::Not(query) => !query.matches(item),
+            Query::Size { field, op, bytes } => {
+                let size = match field {
+                    SizeField::Retained => item.retained_size_bytes,
+                    SizeField::Shallow => item.shallow_size_bytes,
+                    SizeField::Compressed => item.compressed_size_bytes,
+                };
+                op.holds((size as u64).cmp(bytes))
+            }
+            // Missing names are different from any text.
+            Query::Text { field, equal, text } => {
+                (text_field(item, *field) == Some(text.as_str())) == *equal
+            }
+            Query::Regex { field, regex } => {
+                text_field(item, *field).is_some_and(|text| regex.is_match(text))
+            }
+        }
+    }
+}
+
+fn text_field<'n>(item: &QueryItem<'n>, field: TextField) -> Option<&'n str> {
+    match field {
+        TextField::Name => Some(item.name),
+        TextField::Mangled => Some(item.mangled_name).filter(|name| !name.is_empty()),
+        TextField::Crate => item.crate_name,
+        TextField::Generic => item.monomorphization_of,
+    }
+}
+
+/// Parses a size in bytes with an optional `KB`, `MB` or `GB` unit of 1024
+/// bytes, ignoring the case.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let digits_end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(digits_end);
+
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        unit => return Err(format!("unknown size unit '{}'", unit)),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' isn't a size", text))?;
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// Field name or unquoted text.
+    Word(String),
+    Text(String),
+    /// Size with its unit, like `10kb`.
+    Size(String),
+    Compare(CompareOp),
+    Tilde,
+    Not,
+    And,
+    Or,
+    OpenParen,
+    CloseParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) | Token::Size(word) => write!(f, "'{}'", word),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+            Token::Compare(op) => f.write_str(match op {
+                CompareOp::Less => "'<'",
+                CompareOp::LessOrEqual => "'<='",
+                CompareOp::Greater => "'>'",
+                CompareOp::GreaterOrEqual => "'>='",
+                CompareOp::Equal => "'=='",
+                CompareOp::NotEqual => "'!='",
+            }),
+            Token::Tilde => f.write_str("'~'"),
+            Token::Not => f.write_str("'!'"),
+            Token::And => f.write_str("'&&'"),
+            Token::Or => f.write_str("'||'"),
+            Token::OpenParen => f.write_str("'('"),
+            Token::CloseParen => f.write_str("')'"),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '~' => Token::Tilde,
+            '&' if next_is('&') => Token::And,
+            '|' if next_is('|') => Token::Or,
+            '=' if next_is('=') => Token::Compare(CompareOp::Equal),
+            '!' if next_is('=') => Token::Compare(CompareOp::NotEqual),
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Compare(CompareOp::LessOrEqual),
+            '<' => Token::Compare(CompareOp::Less),
+            '>' if next_is('=') => Token::Compare(CompareOp::GreaterOrEqual),
+            '>' => Token::Compare(CompareOp::Greater),
+            '"' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) if let Some((_, escaped)) = chars.next() => {
+                            quoted.push(escaped)
+                        }
+                        Some((_, c)) => quoted.push(c),
+                        None => {
+                            return Err(QueryError {
+                                position,
+                                message: "Unclosed quote".to_string(),
+                            });
+                        }
+                    }
+                }
+                Token::Text(quoted)
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut word = String::from(c);
+                while let Some((_, c)) =
+                    chars.next_if(|&(_, c)| c.is_alphanumeric() || "_.:".contains(c))
+                {
+                    word.push(c);
+                }
+                if c.is_ascii_digit() {
+                    Token::Size(word)
+                } else {
+                    Token::Word(word)
+                }
+            }
+            c => {
+                return Err(QueryError {
+                    position,
+                    message: format!("Unexpected '{}'", c),
+                });
+            }
+        };
+        tokens.push((position, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'o> {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// Length of the query, where the errors past the last token are.
+    end: usize,
+    options: &'o FilterOptions,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(position, _)| *position)
+    }
+
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError {
+            position: self.position(),
+            message: message.into(),
+        }
+    }
+
+    fn take(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        self.next += usize::from(found);
+        found
+    }
+
+    fn or(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.and()?;
+        while self.take(&Token::Or) {
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.unary()?;
+        while self.take(&Token::And) {
+            query = Query::And(Box::new(query), Box::new(self.unary()?));
+        }
+        Ok(query)
+    }
+
+    fn unary(&mut self) -> Result<Query, QueryError> {
+        if self.take(&Token::Not) {
+            return Ok(Query::Not(Box::new(self.unary()?)));
+        }
+        if self.take(&Token::OpenParen) {
+            let query = self.or()?;
+            if !self.take(&Token::CloseParen) {
+                return Err(self.error("Expected ')'"));
+            }
+            return Ok(query);
+        }
+        self.condition()
+    }
+
+    fn condition(&mut self) -> Result<Query, QueryError> {
+        enum Field {
+            Size(SizeField),
+            Text(TextField),
+        }
+
+        let field = match self.peek() {
+            Some(Token::Word(word)) => match word.to_ascii_lowercase().as_str() {
+                "size" | "retained" => Field::Size(SizeField::Retained),
+                "shallow" => Field::Size(SizeField::Shallow),
+                "compressed" => Field::Size(SizeField::Compressed),
+                "name" => Field::Text(TextField::Name),
+                "mangled" => Field::Text(TextField::Mangled),
+                "crate" => Field::Text(TextField::Crate),
+                "generic" => Field::Text(TextField::Generic),
+                _ => return Err(self.error(format!("Unknown field '{}'", word))),
+            },
+            Some(token) => return Err(self.error(format!("Expected a field, found {}", token))),
+            None => return Err(self.error("Expected a field")),
+        };
+        self.next += 1;
+
+        let op = match self.peek() {
+            Some(Token::Compare(op)) => Some(*op),
+            Some(Token::Tilde) => None,
+            _ => return Err(self.error("Expected a comparison")),
+        };
+        self.next += 1;
+
+        let value_position = self.position();
+        let value = match self.tokens.get(self.next) {
+            Some((_, Token::Word(value) | Token::Text(value) | Token::Size(value))) => {
+                value.clone()
+            }
+            _ => return Err(self.error("Expected a value")),
+        };
+        self.next += 1;
+        let value_error = |message: String| QueryError {
+            position: value_position,
+            message,
+        };
+
+        match (field, op) {
+            (Field::Size(field), Some(op)) => Ok(Query::Size {
+                field,
+                op,
+                bytes: parse_size(&value).map_err(value_error)?,
+            }),
+            (Field::Text(field), Some(op @ (CompareOp::Equal | CompareOp::NotEqual))) => {
+                Ok(Query::Text {
+                    field,
+                    equal: op == CompareOp::Equal,
+                    text: value,
+                })
+            }
+            (Field::Text(field), None) => {
+                let pattern = if self.options.whole_word {
+                    format!(r"\b(?:{})\b", value)
+                } else {
+                    value
+                };
+                let regex = regex::RegexBuilder::new(&pattern)
+                    .case_insensitive(!self.options.case_sensitive)
+                    .build()
+                    .map_err(|err| value_error(err.to_string()))?;
+                Ok(Query::Regex { field, regex })
+            }
+            (Field::Size(_), None) => Err(value_error("Sizes can't be matched with '~'".into())),
+            (Field::Text(_), Some(_)) => Err(value_error(
+                "Names are compared with '==', '!=' or '~'".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Query, QueryItem, parse_size};
+    use crate::data_provider::FilterOptions;
+
+    #[test]
+    fn sizes_with_units() {
+        assert_eq!(parse_size("1000"), Ok(1000));
+        assert_eq!(parse_size("512b"), Ok(512));
+        assert_eq!(parse_size("10kb"), Ok(10 * 1024));
+        assert_eq!(parse_size("2MB"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_size("1.5 MiB"), Ok(3 * 512 * 1024));
+        assert!(parse_size("2XB").is_err());
+        assert!(parse_size("MB").is_err());
+    }
+
+    #[test]
+    fn conditions_and_precedence() {
+        let item = QueryItem {
+            name: "serde_json::de::from_str",
+            mangled_name: "_ZN10serde_json2de8from_str17h0123456789abcdefE",
+            crate_name: Some("serde_json"),
+            monomorphization_of: None,
+            retained_size_bytes: 20 * 1024,
+            shallow_size_bytes: 512,
+            compressed_size_bytes: 300,
+        };
+        let matches = |text: &str| {
+            Query::parse(text, &FilterOptions::default())
+                .unwrap()
+                .matches(&item)
+        };
+
+        assert!(matches(
+            r#"size > 10kb && crate == "serde_json" && !name ~ "test""#
+        ));
+        assert!(!matches("shallow >= 1kb"));
+        assert!(matches("shallow >= 1kb || compressed < 400"));
+        assert!(!matches("crate != serde_json"));
+        assert!(matches("name ~ FROM_STR"));
+        // Missing names don't match, and differ from everything.
+        assert!(!matches(r#"generic ~ "" "#));
+        assert!(matches("generic != x"));
+        // `&&` binds tighter than `||`.
+        assert!(matches("size < 1 && shallow < 1 || size > 1"));
+        assert!(!matches("size < 1 && (shallow < 1 || size > 1)"));
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        let error = |text: &str| {
+            let error = Query::parse(text, &FilterOptions::default()).unwrap_err();
+            (error.position, error.message)
+        };
+
+        assert_eq!(error("colour == red").0, 0);
+        assert_eq!(error("size > 10xb").0, 7);
+        assert_eq!(error("size > 1 &&").0, 11);
+        assert_eq!(error("(size > 1").0, 9);
+        assert_eq!(error("name > x").0, 7);
+        assert_eq!(error(r#"name == "x"#).0, 8);
+    }
+}
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+
+use wasmparser::{ExternalKind, Operator};
+
+use crate::{data_provider::FunctionOp, data_provider_twiggy::DataProviderTwiggy};
+
+/// Call chains at least this deep are flagged.
+pub const DEEP_CHAIN_DEPTH: u32 = 32;
+
+/// Number of operators searched for the stack frame allocation at the start
+/// of a function.
+const PROLOGUE_OPS: usize = 16;
+
+pub struct RecursiveCycle {
+    /// Functions calling each other, the largest first. A single function
+    /// calls itself.
+    pub functions: Vec<usize>,
+    pub size_bytes: u64,
+}
+
+pub struct ExportDepth {
+    pub name: String,
+    pub function: usize,
+    /// Functions on the longest chain of direct calls from the export, a
+    /// recursive cycle counting as one.
+    pub depth: u32,
+    /// The longest chain, starting with the export.
+    pub deepest_chain: Vec<usize>,
+    /// Shadow stack bytes used by the frames of the chain using the most,
+    /// a recursive cycle counting as its largest frame.
+    pub stack_bytes: u64,
+    /// Whether a recursive cycle can be reached, the real depth then depends
+    /// on the input.
+    pub reaches_recursion: bool,
+}
+
+impl ExportDepth {
+    pub fn is_deep(&self) -> bool {
+        self.depth >= DEEP_CHAIN_DEPTH
+    }
+}
+
+/// Recursive cycles in the call graph and the static call depth from each
+/// export.
+///
+/// Recursive functions can't be inlined into their callers, and the stack
+/// they use grows with the input. Deep chains of calls are as much at risk
+/// of overflowing the shadow stack, which has no guard page in wasm.
+#[derive(Default)]
+pub struct RecursionReport {
+    /// Cycles by decreasing size.
+    pub cycles: Vec<RecursiveCycle>,
+    pub recursive_function_count: usize,
+    /// Exports by decreasing depth.
+    pub exports: Vec<ExportDepth>,
+}
+
+impl RecursionReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let wasm_data = &data_provider.wasm_data;
+        let call_graph = &data_provider.call_graph;
+        let function_count = data_provider.raw_data.len();
+        let size_of = |function: usize| {
+            data_provider.raw_data[function]
+                .function_property
+                .shallow_size_bytes
+        };
+
+        let (component_of, component_count) =
+            strongly_connected_components(function_count, |function| call_graph.callees(function));
+
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); component_count];
+        for (function, &component) in component_of.iter().enumerate() {
+            members[component as usize].push(function);
+        }
+
+        let mut cycles = Vec::new();
+        let mut is_recursive = vec![false; component_count];
+        for (component, functions) in members.iter().enumerate() {
+            let [function] = functions.as_slice() else {
+                is_recursive[component] = true;
+                continue;
+            };
+            is_recursive[component] = call_graph.callees(*function).contains(&(*function as u32));
+        }
+
+        // The components come callees first, so everything a component calls
+        // is known when it is reached.
+        let mut depth = vec![0u32; component_count];
+        let mut deepest_callee: Vec<Option<usize>> = vec![None; component_count];
+        let mut stack_bytes = vec![0u64; component_count];
+        let mut reaches_recursion = vec![false; component_count];
+        for component in 0..component_count {
+            let mut callee_depth = 0;
+            let mut callee_stack_bytes = 0;
+            let mut frame_bytes = 0;
+            reaches_recursion[component] = is_recursive[component];
+
+            for &function in &members[component] {
+                frame_bytes = frame_bytes.max(frame_size(
+                    data_provider.raw_data[function].debug_info.function_ops,
+                ) as u64);
+
+                for &callee in call_graph.callees(function) {
+                    let callee_component = component_of[callee as usize] as usize;
+                    if callee_component == component {
+                        continue;
+                    }
+                    if depth[callee_component] > callee_depth {
+                        callee_depth = depth[callee_component];
+                        deepest_callee[component] = Some(callee as usize);
+                    }
+                    callee_stack_bytes = callee_stack_bytes.max(stack_bytes[callee_component]);
+                    reaches_recursion[component] |= reaches_recursion[callee_component];
+                }
+            }
+
+            depth[component] = callee_depth + 1;
+            stack_bytes[component] = callee_stack_bytes + frame_bytes;
+
+            if is_recursive[component] {
+                let mut functions = members[component].clone();
+                functions.sort_by_key(|&function| Reverse(size_of(function)));
+                cycles.push(RecursiveCycle {
+                    size_bytes: functions
+                        .iter()
+                        .map(|&function| size_of(function) as u64)
+                        .sum(),
+                    functions,
+                });
+            }
+        }
+        cycles.sort_by_key(|cycle| Reverse((cycle.functions.len(), cycle.size_bytes)));
+
+        let mut exports: Vec<ExportDepth> = wasm_data
+            .exports
+            .iter()
+            .filter(|export| export.kind == ExternalKind::Func)
+            .filter_map(|export| {
+                let function = export
+                    .index
+                    .checked_sub(wasm_data.imported_function_count)
+                    .map(|idx| idx as usize)
+                    .filter(|&idx| idx < function_count)?;
+                let component = component_of[function] as usize;
+
+                let mut deepest_chain = vec![function];
+                let mut next = deepest_callee[component];
+                while let Some(callee) = next {
+                    deepest_chain.push(callee);
+                    next = deepest_callee[component_of[callee] as usize];
+                }
+
+                Some(ExportDepth {
+                    name: export.name.to_string(),
+                    function,
+                    depth: depth[component],
+                    deepest_chain,
+                    stack_bytes: stack_bytes[component],
+                    reaches_recursion: reaches_recursion[component],
+                })
+            })
+            .collect();
+        exports.sort_by_key(|export| Reverse((export.depth, export.stack_bytes)));
+
+        Self {
+            recursive_function_count: cycles.iter().map(|cycle| cycle.functions.len()).sum(),
+            cycles,
+            exports,
+        }
+    }
+
+    pub fn deep_exports(&self) -> impl Iterator<Item = &ExportDepth> {
+        self.exports.iter().filter(|export| export.is_deep())
+    }
+}
+
+/// Bytes the function allocates on the shadow stack, from the
+/// `global.get $__stack_pointer; i32.const N; i32.sub` LLVM emits in the
+/// prologue. Returns 0 for the functions without a frame.
+fn frame_size(ops: &[FunctionOp]) -> u32 {
+    let ops = &ops[..ops.len().min(PROLOGUE_OPS)];
+    let Some(global_get) = ops
+        .iter()
+        .position(|op| matches!(op.op, Operator::GlobalGet { .. }))
+    else {
+        return 0;
+    };
+
+    ops[global_get..]
+        .windows(2)
+        .find_map(|pair| match (&pair[0].op, &pair[1].op) {
+            (Operator::I32Const { value }, Operator::I32Sub) => Some(*value as u32),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+/// Tarjan's algorithm, without recursion since call chains can be deep.
+///
+/// Returns the component of each function and the number of components.
+/// Components are numbered in reverse topological order: the functions a
+/// component calls are in components with a lower number.
+pub(crate) fn strongly_connected_components<'g>(
+    function_count: usize,
+    callees: impl Fn(usize) -> &'g [u32],
+) -> (Vec<u32>, usize) {
+    const UNVISITED: u32 = u32::MAX;
+
+    let mut index = vec![UNVISITED; function_count];
+    let mut low_link = vec![0; function_count];
+    let mut on_stack = vec![false; function_count];
+    let mut stack = Vec::new();
+    let mut component_of = vec![0; function_count];
+    let mut component_count = 0;
+    let mut next_index = 0;
+
+    // Functions being visited, with the next callee to visit.
+    let mut visiting: Vec<(usize, usize)> = Vec::new();
+
+    for root in 0..function_count {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        visiting.push((root, 0));
+
+        while let Some((function, next_callee)) = visiting.last_mut() {
+            let function = *function;
+            if index[function] == UNVISITED {
+                index[function] = next_index;
+                low_link[function] = next_index;
+                next_index += 1;
+                stack.push(function);
+                on_stack[function] = true;
+            }
+
+            if let Some(&callee) = callees(function).get(*next_callee) {
+                *next_callee += 1;
+                let callee = callee as usize;
+                if index[callee] == UNVISITED {
+                    visiting.push((callee, 0));
+                } else if on_stack[callee] {
+                    low_link[function] = low_link[function].min(index[callee]);
+                }
+                continue;
+            }
+
+            visiting.pop();
+            if let Some(&(caller, _)) = visiting.last() {
+                low_link[caller] = low_link[caller].min(low_link[function]);
+            }
+
+            if low_link[function] == index[function] {
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component_of[member] = component_count as u32;
+                    if member == function {
+                        break;
+                    }
+                }
+                component_count += 1;
+            }
+        }
+    }
+
+    (component_of, component_count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::strongly_connected_components;
+
+    #[test]
+    fn cycles_are_found_callees_first() {
+        // 0 -> 1 -> 2 -> 1, 2 -> 3, 3 -> 3, 4 alone.
+        let edges: [&[u32]; 5] = [&[1], &[2], &[1, 3], &[3], &[]];
+
+        let (component_of, component_count) =
+            strongly_connected_components(edges.len(), |function| edges[function]);
+
+        assert_eq!(component_count, 4);
+        assert_eq!(component_of[1], component_of[2]);
+        assert!(component_of[3] < component_of[1]);
+        assert!(component_of[1] < component_of[0]);
+        assert_ne!(component_of[4], component_of[0]);
+    }
+}
@@ -0,0 +1,138 @@
+use std::cmp::Reverse;
+
+use crate::wasm::parser::WasmData;
+
+/// Id of the custom sections, everything else is a known section.
+const CUSTOM_SECTION_ID: u8 = 0;
+const CODE_SECTION_ID: u8 = 10;
+const DATA_SECTION_ID: u8 = 11;
+const DATA_COUNT_SECTION_ID: u8 = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionCategory {
+    Code,
+    Data,
+    CustomAndDebug,
+    /// The module preamble, the section headers and the sections describing the
+    /// module structure (types, imports, exports, etc.).
+    Headers,
+}
+
+impl SectionCategory {
+    pub const ALL: [SectionCategory; 4] = [
+        SectionCategory::Code,
+        SectionCategory::Data,
+        SectionCategory::CustomAndDebug,
+        SectionCategory::Headers,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SectionCategory::Code => "Code",
+            SectionCategory::Data => "Data",
+            SectionCategory::CustomAndDebug => "Custom & debug",
+            SectionCategory::Headers => "Headers & metadata",
+        }
+    }
+
+    fn of_section(id: u8) -> Self {
+        match id {
+            CODE_SECTION_ID => SectionCategory::Code,
+            DATA_SECTION_ID | DATA_COUNT_SECTION_ID => SectionCategory::Data,
+            CUSTOM_SECTION_ID => SectionCategory::CustomAndDebug,
+            _ => SectionCategory::Headers,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SectionSize {
+    pub name: String,
+    pub category: SectionCategory,
+    pub size_bytes: u64,
+}
+
+/// Where the bytes of the file go, at section granularity.
+#[derive(Default)]
+pub struct SectionSummary {
+    pub total_bytes: u64,
+    /// Bytes of each category, in the order of `SectionCategory::ALL`.
+    pub category_bytes: [u64; 4],
+    /// Sections by decreasing size.
+    pub sections: Vec<SectionSize>,
+}
+
+impl SectionSummary {
+    pub fn new(wasm_data: &WasmData) -> Self {
+        let total_bytes = wasm_data.bytes.len() as u64;
+        let mut category_bytes = [0; 4];
+        let mut sections = Vec::new();
+
+        for section in wasm_data.sections.iter() {
+            let category = SectionCategory::of_section(section.id);
+            let size_bytes = section.range.len() as u64;
+
+            category_bytes[category as usize] += size_bytes;
+            sections.push(SectionSize {
+                name: section.name.to_string(),
+                category,
+                size_bytes,
+            });
+        }
+
+        // What isn't in a section's contents is the preamble and the section headers.
+        let section_bytes: u64 = sections.iter().map(|section| section.size_bytes).sum();
+        category_bytes[SectionCategory::Headers as usize] +=
+            total_bytes.saturating_sub(section_bytes);
+
+        sections.sort_by_key(|section| Reverse(section.size_bytes));
+
+        Self {
+            total_bytes,
+            category_bytes,
+            sections,
+        }
+    }
+
+    pub fn bytes_of(&self, category: SectionCategory) -> u64 {
+        self.category_bytes[category as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SectionCategory, SectionSummary};
+    use crate::{
+        arena::{Arena, memory::MB},
+        wasm::parser::WasmData,
+    };
+
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn categories_add_up_to_the_file_size() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            // Type section with a `fn()` type.
+            0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            // Custom section "foo" with a single byte.
+            0x00, 0x05, 0x03, b'f', b'o', b'o', 0x2a,
+        ];
+
+        let arena = Arena::new(MB);
+        let wasm_data =
+            WasmData::from_bytes(&arena, &bytes, &|_| ControlFlow::Continue(())).unwrap();
+        let summary = SectionSummary::new(&wasm_data);
+
+        assert_eq!(summary.total_bytes, bytes.len() as u64);
+        assert_eq!(summary.bytes_of(SectionCategory::CustomAndDebug), 5);
+        assert_eq!(summary.bytes_of(SectionCategory::Headers), 16);
+        assert_eq!(
+            summary.category_bytes.iter().sum::<u64>(),
+            summary.total_bytes
+        );
+        assert_eq!(summary.sections[0].name, "foo");
+    }
+}
@@ -0,0 +1,85 @@
+use memchr::memmem::Finder;
+
+use crate::data_provider::FilterOptions;
+
+/// Matches names containing a text, with a SIMD search of the text built once
+/// for all the names.
+///
+/// Matching is case insensitive unless `case_sensitive` is set, comparing the
+/// ASCII lower case names to the lower case text.
+pub struct SubstringMatcher {
+    finder: Finder<'static>,
+    case_sensitive: bool,
+    whole_word: bool,
+}
+
+impl SubstringMatcher {
+    pub fn new(text: &str, options: &FilterOptions) -> Self {
+        let finder = if options.case_sensitive {
+            Finder::new(text).into_owned()
+        } else {
+            Finder::new(&text.to_ascii_lowercase()).into_owned()
+        };
+
+        Self {
+            finder,
+            case_sensitive: options.case_sensitive,
+            whole_word: options.whole_word,
+        }
+    }
+
+    /// Whether the text is found in `name`, `lower_case_name` being the ASCII
+    /// lower case of it.
+    pub fn matches(&self, name: &str, lower_case_name: &str) -> bool {
+        let name = if self.case_sensitive {
+            name
+        } else {
+            lower_case_name
+        };
+
+        if !self.whole_word {
+            return self.finder.find(name.as_bytes()).is_some();
+        }
+
+        // Both are valid UTF-8, the matches start and end on characters.
+        let is_identifier = |c: char| c.is_alphanumeric() || c == '_';
+        let len = self.finder.needle().len();
+        self.finder.find_iter(name.as_bytes()).any(|start| {
+            !name[..start].ends_with(is_identifier)
+                && !name[start + len..].starts_with(is_identifier)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SubstringMatcher;
+    use crate::data_provider::FilterOptions;
+
+    #[test]
+    fn case_and_whole_word() {
+        let matches = |text: &str, name: &str, case_sensitive, whole_word| {
+            let options = FilterOptions {
+                case_sensitive,
+                whole_word,
+                ..Default::default()
+            };
+            SubstringMatcher::new(text, &options).matches(name, &name.to_ascii_lowercase())
+        };
+
+        assert!(matches("vec", "alloc::vec::Vec<T>::new", false, false));
+        assert!(matches("VEC", "alloc::vec::Vec<T>::new", false, false));
+        assert!(!matches("VEC", "alloc::vec::Vec<T>::new", true, false));
+        assert!(matches("Vec", "alloc::raw::RawVec<T>::new", true, false));
+
+        assert!(!matches("Vec", "alloc::raw::RawVec<T>::new", true, true));
+        assert!(matches(
+            "new",
+            "alloc::vec::Vec<T>::new_in::new",
+            false,
+            true
+        ));
+        assert!(!matches("new", "alloc::vec::Vec<T>::new_in", false, true));
+        assert!(matches("", "main", false, false));
+    }
+}
@@ -0,0 +1,228 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use wasmparser::Operator;
+
+use crate::{analysis::symbol, data_provider_twiggy::DataProviderTwiggy};
+
+/// Typical code size reduction of `wasm-opt -Oz` on a binary rustc already optimized for size.
+const WASM_OPT_SAVINGS_PERCENT: u64 = 10;
+
+pub struct Suggestion {
+    pub title: &'static str,
+    /// What to do about it, with the numbers backing the suggestion.
+    pub action: String,
+    pub estimated_savings_bytes: u64,
+}
+
+#[derive(Default)]
+pub struct SuggestionsReport {
+    /// Sorted by decreasing estimated savings.
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl SuggestionsReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let mut suggestions: Vec<Suggestion> = [
+            debug_sections(data_provider),
+            dead_exports(data_provider),
+            duplicated_monomorphizations(data_provider),
+            missing_wasm_opt(data_provider),
+            overlong_lebs(data_provider),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        suggestions.sort_by_key(|suggestion| Reverse(suggestion.estimated_savings_bytes));
+
+        Self { suggestions }
+    }
+}
+
+fn debug_sections(data_provider: &DataProviderTwiggy) -> Option<Suggestion> {
+    let debug_sections = &data_provider.wasm_data.debug_sections;
+    let size_bytes: u64 = debug_sections
+        .iter()
+        .map(|(_, data)| data.len() as u64)
+        .sum();
+
+    (size_bytes > 0).then(|| Suggestion {
+        title: "Strip debug info",
+        action: format!(
+            "{} DWARF sections are shipped, strip them from release builds \
+             (`strip = \"debuginfo\"` in the cargo profile or `wasm-opt --strip-debug`)",
+            debug_sections.len()
+        ),
+        estimated_savings_bytes: size_bytes,
+    })
+}
+
+fn dead_exports(data_provider: &DataProviderTwiggy) -> Option<Suggestion> {
+    let report = &data_provider.dead_exports;
+    let dead_export_count = report.dead_exports().count();
+
+    (dead_export_count > 0).then(|| Suggestion {
+        title: "Remove unused exports",
+        action: format!(
+            "{} exported functions are not used by the module, wasm-bindgen or the runtime, \
+             remove the `#[no_mangle]`s the host doesn't need",
+            dead_export_count
+        ),
+        estimated_savings_bytes: report.dead_size_bytes,
+    })
+}
+
+/// The same instantiation emitted more than once, usually by different codegen units.
+fn duplicated_monomorphizations(data_provider: &DataProviderTwiggy) -> Option<Suggestion> {
+    // (instance count, total size, largest size) for each instantiation.
+    let mut instances: HashMap<&str, (u32, u64, u32)> = HashMap::new();
+
+    for function_data in data_provider.raw_data.iter() {
+        let property = &function_data.function_property;
+        if property.monomorphization_of.is_none() {
+            continue;
+        }
+
+        let (count, total_size, largest_size) = instances
+            .entry(symbol::strip_hash(property.raw_name))
+            .or_default();
+        *count += 1;
+        *total_size += property.shallow_size_bytes as u64;
+        *largest_size = (*largest_size).max(property.shallow_size_bytes);
+    }
+
+    let mut duplicated_count = 0;
+    let mut size_bytes = 0;
+    for (count, total_size, largest_size) in instances.into_values() {
+        if count > 1 {
+            duplicated_count += 1;
+            size_bytes += total_size - largest_size as u64;
+        }
+    }
+
+    (duplicated_count > 0).then(|| Suggestion {
+        title: "Deduplicate monomorphizations",
+        action: format!(
+            "{} generic instantiations are emitted more than once, \
+             build with `codegen-units = 1` and `lto = true`",
+            duplicated_count
+        ),
+        estimated_savings_bytes: size_bytes,
+    })
+}
+
+fn missing_wasm_opt(data_provider: &DataProviderTwiggy) -> Option<Suggestion> {
+    let processed_by_wasm_opt = data_provider
+        .wasm_data
+        .producers
+        .iter()
+        .any(|&(field, tool)| {
+            field == "processed-by" && (tool.contains("wasm-opt") || tool.contains("binaryen"))
+        });
+    if processed_by_wasm_opt {
+        return None;
+    }
+
+    let code_size_bytes = data_provider.wasm_data.functions_section.size_in_bytes as u64;
+
+    Some(Suggestion {
+        title: "Run wasm-opt",
+        action: String::from(
+            "No wasm-opt signature found in the producers section, run `wasm-opt -Oz` \
+             on the final binary (savings are a typical estimate)",
+        ),
+        estimated_savings_bytes: code_size_bytes * WASM_OPT_SAVINGS_PERCENT / 100,
+    })
+}
+
+/// LEB128 immediates padded to more bytes than needed, as emitted for relocations.
+fn overlong_lebs(data_provider: &DataProviderTwiggy) -> Option<Suggestion> {
+    let mut overlong_count = 0;
+    let mut size_bytes = 0;
+
+    for function_data in data_provider.raw_data.iter() {
+        let ops = &function_data.debug_info.function_ops;
+
+        for (op, next_op) in ops.iter().zip(ops.iter().skip(1)) {
+            let Some(min_size) = min_encoded_size(&op.op) else {
+                continue;
+            };
+
+            let size = (next_op.address - op.address) as usize;
+            if size > min_size {
+                overlong_count += 1;
+                size_bytes += (size - min_size) as u64;
+            }
+        }
+    }
+
+    (overlong_count > 0).then(|| Suggestion {
+        title: "Re-encode padded LEBs",
+        action: format!(
+            "{} instructions use padded LEB128 immediates, link without `--emit-relocs` \
+             or run `wasm-opt` to re-encode them",
+            overlong_count
+        ),
+        estimated_savings_bytes: size_bytes,
+    })
+}
+
+/// Returns the size of the shortest encoding of `op`, for the instructions
+/// whose immediates are commonly padded.
+fn min_encoded_size(op: &Operator<'_>) -> Option<usize> {
+    let size = match *op {
+        Operator::Call { function_index } | Operator::ReturnCall { function_index } => {
+            1 + uleb128_len(function_index as u64)
+        }
+        Operator::LocalGet { local_index }
+        | Operator::LocalSet { local_index }
+        | Operator::LocalTee { local_index } => 1 + uleb128_len(local_index as u64),
+        Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+            1 + uleb128_len(global_index as u64)
+        }
+        Operator::I32Const { value } => 1 + sleb128_len(value as i64),
+        Operator::I64Const { value } => 1 + sleb128_len(value),
+        _ => return None,
+    };
+
+    Some(size)
+}
+
+fn uleb128_len(mut value: u64) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+fn sleb128_len(mut value: i64) -> usize {
+    let mut len = 1;
+    // Done once the remaining bits (including the sign bit of the last byte) are all sign.
+    while !(-64..64).contains(&value) {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sleb128_len, uleb128_len};
+
+    #[test]
+    fn leb128_lengths() {
+        assert_eq!(uleb128_len(0), 1);
+        assert_eq!(uleb128_len(127), 1);
+        assert_eq!(uleb128_len(128), 2);
+        assert_eq!(uleb128_len(u32::MAX as u64), 5);
+
+        assert_eq!(sleb128_len(0), 1);
+        assert_eq!(sleb128_len(63), 1);
+        assert_eq!(sleb128_len(64), 2);
+        assert_eq!(sleb128_len(-64), 1);
+        assert_eq!(sleb128_len(-65), 2);
+        assert_eq!(sleb128_len(i32::MIN as i64), 5);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::arena::{Arena, string::String};
+
+/// Returns the crate a demangled Rust symbol belongs to.
+///
+/// Qualified paths like `<foo::Type as bar::Trait>::method` are attributed
+/// to the crate of the implementing type. Returns `None` for symbols that
+/// are not namespaced (C functions, compiler intrinsics, etc.).
+pub fn crate_name(demangled_name: &str) -> Option<&str> {
+    let name = demangled_name.trim_start_matches(['<', '&', '*', '(']);
+    let name = name
+        .strip_prefix("const ")
+        .or_else(|| name.strip_prefix("mut "))
+        .or_else(|| name.strip_prefix("dyn "))
+        .unwrap_or(name);
+
+    let end = name.find("::")?;
+    let crate_name = &name[..end];
+
+    if !crate_name.is_empty()
+        && crate_name
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'_')
+    {
+        return Some(crate_name);
+    }
+
+    // Types without a crate (slices, primitives, references to generic parameters)
+    // are attributed to the crate of the trait they implement.
+    let (_, trait_path) = name.split_once(" as ")?;
+    crate_name_of_path(trait_path)
+}
+
+/// Removes the legacy mangling hash suffix (e.g. `::h0123456789abcdef`) from
+/// a demangled symbol name.
+pub fn strip_hash(demangled_name: &str) -> &str {
+    const HASH_LEN: usize = "::h".len() + 16;
+
+    if demangled_name.len() > HASH_LEN {
+        let (name, hash) = demangled_name.split_at(demangled_name.len() - HASH_LEN);
+        if hash.starts_with("::h") && hash[3..].bytes().all(|c| c.is_ascii_hexdigit()) {
+            return name;
+        }
+    }
+
+    demangled_name
+}
+
+/// Returns the name of the generic function a demangled symbol is an
+/// instantiation of, i.e. the name with all generic arguments and the hash
+/// removed (`core::ptr::drop_in_place<alloc::string::String>` becomes
+/// `core::ptr::drop_in_place`).
+///
+/// Returns `None` if the symbol doesn't have any generic arguments.
+pub fn monomorphization_of<'a>(arena: &'a Arena, demangled_name: &str) -> Option<&'a str> {
+    let name = strip_hash(demangled_name);
+    if !name.contains('<') {
+        return None;
+    }
+
+    let mut generic_name = String::new(arena, name.len());
+    let mut has_generic_args = false;
+    let mut depth = 0;
+    let mut prev = 0u8;
+
+    for (idx, c) in name.bytes().enumerate() {
+        if depth > 0 {
+            match c {
+                b'<' => depth += 1,
+                // Skip the arrow of function pointer return types
+                b'>' if prev != b'-' => depth -= 1,
+                _ => {}
+            }
+        } else if c == b'<' && (prev.is_ascii_alphanumeric() || prev == b'_') {
+            // A '<' right after an identifier opens the generic arguments,
+            // anything else is the start of a qualified path `<T as Trait>`.
+            depth = 1;
+            has_generic_args = true;
+        } else {
+            generic_name.push_str(&name[idx..idx + 1]);
+        }
+
+        prev = c;
+    }
+
+    if !has_generic_args {
+        return None;
+    }
+
+    generic_name.shrink_to_fit();
+    Some(generic_name.to_str())
+}
+
+/// Returns the trait of a trait method symbol, `core::fmt::Debug` for
+/// `<alloc::string::String as core::fmt::Debug>::fmt`.
+pub fn implemented_trait(demangled_name: &str) -> Option<&str> {
+    let name = demangled_name.strip_prefix('<')?;
+    let mut trait_start = None;
+    let mut depth = 0;
+    let mut prev = 0u8;
+
+    for (idx, c) in name.bytes().enumerate() {
+        match c {
+            b'<' => depth += 1,
+            // Skip the arrow of function pointer return types
+            b'>' if prev != b'-' => {
+                if depth == 0 {
+                    return trait_start.map(|start| &name[start..idx]);
+                }
+                depth -= 1;
+            }
+            b' ' if depth == 0 && name[idx..].starts_with(" as ") => {
+                trait_start = Some(idx + " as ".len());
+            }
+            _ => {}
+        }
+
+        prev = c;
+    }
+
+    None
+}
+
+fn crate_name_of_path(path: &str) -> Option<&str> {
+    let end = path.find("::")?;
+    let crate_name = &path[..end];
+
+    crate_name
+        .bytes()
+        .all(|c| c.is_ascii_alphanumeric() || c == b'_')
+        .then_some(crate_name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crate_name, implemented_trait, monomorphization_of, strip_hash};
+    use crate::arena::{Arena, memory::MB};
+
+    #[test]
+    fn crate_name_works() {
+        assert_eq!(crate_name("core::fmt::write"), Some("core"));
+        assert_eq!(
+            crate_name("<alloc::vec::Vec<T> as core::ops::drop::Drop>::drop"),
+            Some("alloc")
+        );
+        assert_eq!(crate_name("<&T as core::fmt::Debug>::fmt"), Some("core"));
+        assert_eq!(crate_name("<[T] as core::fmt::Debug>::fmt"), Some("core"));
+        assert_eq!(crate_name("memcpy"), None);
+        assert_eq!(crate_name(""), None);
+    }
+
+    #[test]
+    fn monomorphization_of_works() {
+        let arena = Arena::new(MB);
+
+        assert_eq!(
+            monomorphization_of(
+                &arena,
+                "core::ptr::drop_in_place<alloc::vec::Vec<u8>>::h0123456789abcdef"
+            ),
+            Some("core::ptr::drop_in_place")
+        );
+        assert_eq!(
+            monomorphization_of(
+                &arena,
+                "<alloc::vec::Vec<u8> as core::ops::drop::Drop>::drop"
+            ),
+            Some("<alloc::vec::Vec as core::ops::drop::Drop>::drop")
+        );
+        assert_eq!(
+            monomorphization_of(&arena, "foo::call<fn() -> u8>"),
+            Some("foo::call")
+        );
+        assert_eq!(
+            monomorphization_of(&arena, "<foo::Bar as core::fmt::Debug>::fmt"),
+            None
+        );
+        assert_eq!(monomorphization_of(&arena, "memcpy"), None);
+    }
+
+    #[test]
+    fn implemented_trait_works() {
+        assert_eq!(
+            implemented_trait(
+                "<alloc::string::String as core::fmt::Debug>::fmt::h0123456789abcdef"
+            ),
+            Some("core::fmt::Debug")
+        );
+        assert_eq!(
+            implemented_trait("<<T as foo::Bar>::Out as core::iter::Iterator<Item = u8>>::next"),
+            Some("core::iter::Iterator<Item = u8>")
+        );
+        assert_eq!(
+            implemented_trait("<fn() -> u8 as foo::Call>::call"),
+            Some("foo::Call")
+        );
+        assert_eq!(implemented_trait("<[T]>::len"), None);
+        assert_eq!(implemented_trait("core::fmt::write"), None);
+    }
+
+    #[test]
+    fn strip_hash_works() {
+        assert_eq!(strip_hash("foo::bar::h0123456789abcdef"), "foo::bar");
+        assert_eq!(strip_hash("foo::bar"), "foo::bar");
+        assert_eq!(strip_hash("foo::hash"), "foo::hash");
+    }
+}
@@ -0,0 +1,317 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use wasmparser::Operator;
+
+use crate::{data_provider::FunctionOp, data_provider_twiggy::DataProviderTwiggy};
+
+/// Id of the exception handling tag section.
+const TAG_SECTION_ID: u8 = 13;
+
+/// Name used for the functions without a crate.
+const UNATTRIBUTED: &str = "<unattributed>";
+
+/// Paths of the functions only needed to unwind, `panic=abort` replaces them
+/// with `panic_abort`.
+const UNWIND_PATHS: &[&str] = &[
+    "panic_unwind::",
+    "unwind::",
+    "std::panicking::try",
+    "std::panicking::catch_unwind",
+    "std::panic::catch_unwind",
+];
+
+/// Unmangled symbols of the unwinder and the personality routines.
+const UNWIND_SYMBOLS: &[&str] = &[
+    "__rust_try",
+    "__rust_panic_cleanup",
+    "rust_eh_personality",
+    "__gxx_personality_v0",
+    "__gxx_wasm_personality_v0",
+];
+
+/// Returns `true` if the demangled symbol belongs to the unwinder.
+pub fn is_unwind_runtime(demangled_name: &str) -> bool {
+    let name = demangled_name.trim_start_matches('<');
+
+    UNWIND_PATHS.iter().any(|path| name.starts_with(path))
+        || name.starts_with("_Unwind_")
+        || name.starts_with("__cxa_")
+        || UNWIND_SYMBOLS.contains(&name)
+}
+
+pub struct UnwindSection {
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+pub struct LandingPads {
+    pub function: usize,
+    /// Bytes of the `catch`/`catch_all` handlers and of the exception
+    /// handling instructions themselves.
+    pub size_bytes: u32,
+    /// Number of `try` and `try_table` blocks.
+    pub try_count: u32,
+}
+
+pub struct CrateUnwinding {
+    pub crate_name: String,
+    pub landing_pad_bytes: u64,
+    pub runtime_bytes: u64,
+}
+
+impl CrateUnwinding {
+    /// Estimated bytes saved by building with `panic=abort`.
+    pub fn savings_bytes(&self) -> u64 {
+        self.landing_pad_bytes + self.runtime_bytes
+    }
+}
+
+/// What unwinding costs, and what building with `panic=abort` would save.
+///
+/// Without the exception handling proposal, wasm can't unwind and the landing
+/// pads are already gone: only the runtime shows up then.
+#[derive(Default)]
+pub struct UnwindingReport {
+    /// The tag section and the custom sections of the unwinder, by decreasing size.
+    pub sections: Vec<UnwindSection>,
+    pub section_bytes: u64,
+
+    /// Functions with landing pads, by decreasing landing pad size.
+    pub landing_pads: Vec<LandingPads>,
+    pub landing_pad_bytes: u64,
+
+    /// The unwinder, plus the functions only reachable through it, sorted by size.
+    pub runtime: Vec<usize>,
+    pub runtime_bytes: u64,
+
+    /// Crates by decreasing savings.
+    pub crates: Vec<CrateUnwinding>,
+}
+
+impl UnwindingReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let call_graph = &data_provider.call_graph;
+        let function_count = data_provider.raw_data.len();
+        let size_of = |idx: usize| {
+            data_provider.raw_data[idx]
+                .function_property
+                .shallow_size_bytes
+        };
+
+        let mut sections: Vec<UnwindSection> = data_provider
+            .wasm_data
+            .sections
+            .iter()
+            .filter(|section| section.id == TAG_SECTION_ID || is_unwind_section(section.name))
+            .map(|section| UnwindSection {
+                name: section.name.to_string(),
+                size_bytes: section.range.len() as u64,
+            })
+            .collect();
+        sections.sort_by_key(|section| Reverse(section.size_bytes));
+        let section_bytes = sections.iter().map(|section| section.size_bytes).sum();
+
+        let function_bodies = &data_provider.wasm_data.functions_section.function_bodies;
+        let mut landing_pads: Vec<LandingPads> = data_provider
+            .raw_data
+            .iter()
+            .enumerate()
+            .filter_map(|(function, function_data)| {
+                let body_end = function_bodies[function].range().end as u64;
+                let (size_bytes, try_count) =
+                    landing_pad_bytes(function_data.debug_info.function_ops, body_end);
+
+                (try_count > 0 || size_bytes > 0).then_some(LandingPads {
+                    function,
+                    size_bytes,
+                    try_count,
+                })
+            })
+            .collect();
+        landing_pads.sort_by_key(|landing_pads| Reverse(landing_pads.size_bytes));
+        let landing_pad_bytes = landing_pads
+            .iter()
+            .map(|landing_pads| landing_pads.size_bytes as u64)
+            .sum();
+
+        let mut in_runtime: Vec<bool> = data_provider
+            .raw_data
+            .iter()
+            .map(|function_data| is_unwind_runtime(function_data.function_property.raw_name))
+            .collect();
+
+        // Anything called exclusively from the unwinder goes away with it.
+        let mut worklist: Vec<usize> = (0..function_count).filter(|&idx| in_runtime[idx]).collect();
+        while let Some(idx) = worklist.pop() {
+            for &callee in call_graph.callees(idx) {
+                let callee = callee as usize;
+                if in_runtime[callee] {
+                    continue;
+                }
+
+                if call_graph
+                    .callers(callee)
+                    .iter()
+                    .all(|&caller| in_runtime[caller as usize])
+                {
+                    in_runtime[callee] = true;
+                    worklist.push(callee);
+                }
+            }
+        }
+
+        let mut runtime: Vec<usize> = (0..function_count).filter(|&idx| in_runtime[idx]).collect();
+        runtime.sort_by_key(|&idx| Reverse(size_of(idx)));
+        let runtime_bytes = runtime.iter().map(|&idx| size_of(idx) as u64).sum();
+
+        let crate_of = |function: usize| {
+            data_provider.raw_data[function]
+                .function_property
+                .crate_name
+                .unwrap_or(UNATTRIBUTED)
+        };
+
+        // Landing pad and runtime bytes of each crate.
+        let mut crate_bytes: HashMap<&str, (u64, u64)> = HashMap::new();
+        for entry in &landing_pads {
+            // The landing pads of the runtime go away with it, they are only counted once.
+            if !in_runtime[entry.function] {
+                crate_bytes.entry(crate_of(entry.function)).or_default().0 +=
+                    entry.size_bytes as u64;
+            }
+        }
+        for &idx in &runtime {
+            crate_bytes.entry(crate_of(idx)).or_default().1 += size_of(idx) as u64;
+        }
+
+        let mut crates: Vec<CrateUnwinding> = crate_bytes
+            .into_iter()
+            .map(
+                |(crate_name, (landing_pad_bytes, runtime_bytes))| CrateUnwinding {
+                    crate_name: crate_name.to_string(),
+                    landing_pad_bytes,
+                    runtime_bytes,
+                },
+            )
+            .collect();
+        crates.sort_by_key(|crate_unwinding| Reverse(crate_unwinding.savings_bytes()));
+
+        Self {
+            sections,
+            section_bytes,
+            landing_pads,
+            landing_pad_bytes,
+            runtime,
+            runtime_bytes,
+            crates,
+        }
+    }
+
+    /// Estimated bytes saved by building with `panic=abort`.
+    pub fn savings_bytes(&self) -> u64 {
+        self.section_bytes
+            + self
+                .crates
+                .iter()
+                .map(CrateUnwinding::savings_bytes)
+                .sum::<u64>()
+    }
+}
+
+fn is_unwind_section(name: &str) -> bool {
+    name.contains("eh_frame") || name.contains("gcc_except_table") || name.contains("unwind")
+}
+
+/// Returns the bytes of the landing pads of a function and its number of
+/// `try` blocks.
+///
+/// With the legacy exception handling instructions, the landing pads are the
+/// `catch` and `catch_all` handlers of the `try` blocks. `try_table` branches
+/// to its handlers, which can't be told apart from the rest of the code: only
+/// the exception handling instructions are counted for them.
+fn landing_pad_bytes(ops: &[FunctionOp], body_end: u64) -> (u32, u32) {
+    // Open blocks, with whether they are in a handler.
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut handler_depth = 0;
+    let mut size_bytes = 0;
+    let mut try_count = 0;
+
+    for (idx, op) in ops.iter().enumerate() {
+        let next_address = ops.get(idx + 1).map_or(body_end, |next| next.address);
+        let op_size = next_address.saturating_sub(op.address) as u32;
+
+        let is_eh_op = match op.op {
+            Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                blocks.push(false);
+                false
+            }
+            Operator::Try { .. } | Operator::TryTable { .. } => {
+                blocks.push(false);
+                try_count += 1;
+                true
+            }
+            Operator::Catch { .. } | Operator::CatchAll => {
+                if let Some(in_handler) = blocks.last_mut()
+                    && !*in_handler
+                {
+                    *in_handler = true;
+                    handler_depth += 1;
+                }
+                true
+            }
+            Operator::End | Operator::Delegate { .. } => {
+                let closes_handler = blocks.pop().unwrap_or_default();
+                if closes_handler {
+                    handler_depth -= 1;
+                }
+                closes_handler || matches!(op.op, Operator::Delegate { .. })
+            }
+            Operator::Throw { .. } | Operator::ThrowRef | Operator::Rethrow { .. } => true,
+            _ => false,
+        };
+
+        if is_eh_op || handler_depth > 0 {
+            size_bytes += op_size;
+        }
+    }
+
+    (size_bytes, try_count)
+}
+
+#[cfg(test)]
+mod test {
+    use wasmparser::{BlockType, Operator};
+
+    use super::landing_pad_bytes;
+    use crate::data_provider::FunctionOp;
+
+    #[test]
+    fn landing_pads_are_the_catch_handlers() {
+        let ops = [
+            (
+                0,
+                Operator::Try {
+                    blockty: BlockType::Empty,
+                },
+            ),
+            (2, Operator::Call { function_index: 1 }),
+            (4, Operator::Catch { tag_index: 0 }),
+            (
+                6,
+                Operator::Block {
+                    blockty: BlockType::Empty,
+                },
+            ),
+            (8, Operator::Call { function_index: 2 }),
+            (10, Operator::End),
+            (11, Operator::Rethrow { relative_depth: 0 }),
+            (13, Operator::End),
+            (14, Operator::Call { function_index: 3 }),
+            (16, Operator::End),
+        ]
+        .map(|(address, op)| FunctionOp::new(address, op));
+
+        // `try`, then everything from `catch` to the `end` of the `try`.
+        assert_eq!(landing_pad_bytes(&ops, 17), (2 + 10, 1));
+    }
+}
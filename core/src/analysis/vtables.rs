@@ -0,0 +1,267 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use wasmparser::Operator;
+
+use crate::{analysis::symbol, data_provider_twiggy::DataProviderTwiggy};
+
+/// Sizes and alignments past these are not plausible for a vtable header.
+const MAX_TYPE_SIZE: u32 = 1 << 24;
+const MAX_TYPE_ALIGN: u32 = 1 << 12;
+
+/// Name used for the vtables whose trait couldn't be found.
+const UNKNOWN_TRAIT: &str = "<unknown trait>";
+
+/// A vtable-like run of words in a data segment.
+///
+/// On wasm32, Rust vtables are `[drop_in_place, size, align, methods...]`
+/// with the functions stored as table slots.
+pub struct Vtable {
+    /// Address of the vtable in linear memory.
+    pub address: u32,
+    pub size_bytes: u32,
+    /// Type behind the trait object, from its `drop_in_place`.
+    pub type_name: Option<String>,
+    pub trait_name: String,
+    /// Functions of the method slots.
+    pub methods: Vec<usize>,
+}
+
+pub struct TraitObjectEntry {
+    pub trait_name: String,
+    pub vtable_count: u32,
+    pub vtable_bytes: u64,
+    /// Code reachable from the methods of its vtables, each function counted once.
+    pub retained_bytes: u64,
+    /// The largest method, to select when clicked.
+    pub largest_method: usize,
+}
+
+pub struct DynDispatch {
+    pub function: usize,
+    pub call_indirect_count: u32,
+}
+
+#[derive(Default)]
+pub struct VtableReport {
+    pub vtables: Vec<Vtable>,
+    pub vtable_bytes: u64,
+    /// Traits by decreasing retained bytes.
+    pub trait_objects: Vec<TraitObjectEntry>,
+    /// Functions with indirect calls, by decreasing count.
+    pub dyn_dispatches: Vec<DynDispatch>,
+    pub call_indirect_count: u32,
+}
+
+impl VtableReport {
+    pub fn new(data_provider: &DataProviderTwiggy) -> Self {
+        let wasm_data = &data_provider.wasm_data;
+        let function_count = data_provider.raw_data.len();
+        let name_of = |idx: usize| data_provider.raw_data[idx].function_property.raw_name;
+
+        // Functions of the table slots, in the code section index space.
+        let table: HashMap<u32, usize> = wasm_data
+            .table_functions
+            .iter()
+            .filter_map(|&(slot, function_index)| {
+                let idx = function_index.checked_sub(wasm_data.imported_function_count)? as usize;
+                (idx < function_count).then_some((slot, idx))
+            })
+            .collect();
+        let is_drop_glue = |idx: usize| {
+            let name = name_of(idx);
+            name.is_empty() || name.contains("drop_in_place")
+        };
+
+        let mut vtables = Vec::new();
+        for data_segment in wasm_data.data_segments.iter() {
+            let Some(offset) = data_segment.offset else {
+                continue;
+            };
+
+            // Vtables are pointer aligned.
+            let skip = (4 - offset % 4) % 4;
+            let words: Vec<u32> = data_segment
+                .data
+                .get(skip as usize..)
+                .unwrap_or_default()
+                .chunks_exact(4)
+                .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+                .collect();
+
+            let mut idx = 0;
+            while idx + 3 < words.len() {
+                let [drop, size, align] = [words[idx], words[idx + 1], words[idx + 2]];
+                let drop_function = table.get(&drop).copied();
+
+                let is_header = (drop == 0 || drop_function.is_some_and(is_drop_glue))
+                    && size < MAX_TYPE_SIZE
+                    && align.is_power_of_two()
+                    && align <= MAX_TYPE_ALIGN
+                    && size % align == 0;
+                if !is_header {
+                    idx += 1;
+                    continue;
+                }
+
+                // Methods go on until something that isn't a function, or the
+                // `drop_in_place` of the next vtable. Slots of methods that can't
+                // be called on a trait object are null.
+                let mut methods = Vec::new();
+                let mut end = idx + 3;
+                while let Some(&word) = words.get(end) {
+                    match table.get(&word) {
+                        Some(&method) if !is_drop_glue(method) => methods.push(method),
+                        None if word == 0 => {}
+                        _ => break,
+                    }
+                    end += 1;
+                }
+                while end > idx + 3 && words[end - 1] == 0 {
+                    end -= 1;
+                }
+
+                if methods.is_empty() {
+                    idx += 1;
+                    continue;
+                }
+
+                let type_name = drop_function
+                    .map(|drop_function| symbol::strip_hash(name_of(drop_function)))
+                    .and_then(|name| Some(&name[name.find('<')? + 1..name.rfind('>')?]))
+                    .map(str::to_string);
+
+                // Supertrait methods come first, the last ones belong to the trait itself.
+                let trait_name = methods
+                    .iter()
+                    .rev()
+                    .find_map(|&method| trait_of_method(name_of(method)))
+                    .unwrap_or(UNKNOWN_TRAIT)
+                    .to_string();
+
+                vtables.push(Vtable {
+                    address: offset + skip + idx as u32 * 4,
+                    size_bytes: (end - idx) as u32 * 4,
+                    type_name,
+                    trait_name,
+                    methods,
+                });
+                idx = end;
+            }
+        }
+
+        let vtable_bytes = vtables.iter().map(|vtable| vtable.size_bytes as u64).sum();
+        let trait_objects = trait_objects(data_provider, &vtables);
+
+        let mut dyn_dispatches: Vec<DynDispatch> = data_provider
+            .raw_data
+            .iter()
+            .enumerate()
+            .filter_map(|(function, function_data)| {
+                let call_indirect_count = function_data
+                    .debug_info
+                    .function_ops
+                    .iter()
+                    .filter(|op| {
+                        matches!(
+                            op.op,
+                            Operator::CallIndirect { .. } | Operator::ReturnCallIndirect { .. }
+                        )
+                    })
+                    .count() as u32;
+
+                (call_indirect_count > 0).then_some(DynDispatch {
+                    function,
+                    call_indirect_count,
+                })
+            })
+            .collect();
+        dyn_dispatches.sort_by_key(|dyn_dispatch| Reverse(dyn_dispatch.call_indirect_count));
+        let call_indirect_count = dyn_dispatches
+            .iter()
+            .map(|dyn_dispatch| dyn_dispatch.call_indirect_count)
+            .sum();
+
+        Self {
+            vtables,
+            vtable_bytes,
+            trait_objects,
+            dyn_dispatches,
+            call_indirect_count,
+        }
+    }
+}
+
+/// Groups the vtables by trait, with the code their methods retain.
+fn trait_objects(data_provider: &DataProviderTwiggy, vtables: &[Vtable]) -> Vec<TraitObjectEntry> {
+    let size_of = |idx: usize| {
+        data_provider.raw_data[idx]
+            .function_property
+            .shallow_size_bytes
+    };
+
+    let mut trait_lookup: HashMap<&str, usize> = HashMap::new();
+    let mut trait_objects: Vec<TraitObjectEntry> = Vec::new();
+    let mut trait_methods: Vec<Vec<usize>> = Vec::new();
+
+    for vtable in vtables {
+        let trait_idx = *trait_lookup
+            .entry(vtable.trait_name.as_str())
+            .or_insert_with(|| {
+                trait_objects.push(TraitObjectEntry {
+                    trait_name: vtable.trait_name.clone(),
+                    vtable_count: 0,
+                    vtable_bytes: 0,
+                    retained_bytes: 0,
+                    largest_method: vtable.methods[0],
+                });
+                trait_methods.push(Vec::new());
+                trait_objects.len() - 1
+            });
+
+        let trait_object = &mut trait_objects[trait_idx];
+        trait_object.vtable_count += 1;
+        trait_object.vtable_bytes += vtable.size_bytes as u64;
+        for &method in &vtable.methods {
+            if size_of(trait_object.largest_method) < size_of(method) {
+                trait_object.largest_method = method;
+            }
+        }
+        trait_methods[trait_idx].extend(&vtable.methods);
+    }
+
+    // Everything reachable from the methods, through direct calls.
+    let mut visited = vec![usize::MAX; data_provider.raw_data.len()];
+    for (trait_idx, (trait_object, methods)) in
+        trait_objects.iter_mut().zip(trait_methods).enumerate()
+    {
+        let mut worklist = methods;
+        while let Some(idx) = worklist.pop() {
+            if visited[idx] == trait_idx {
+                continue;
+            }
+            visited[idx] = trait_idx;
+
+            trait_object.retained_bytes += size_of(idx) as u64;
+            worklist.extend(
+                data_provider
+                    .call_graph
+                    .callees(idx)
+                    .iter()
+                    .map(|&callee| callee as usize),
+            );
+        }
+    }
+
+    trait_objects.sort_by_key(|trait_object| Reverse(trait_object.retained_bytes));
+    trait_objects
+}
+
+/// The trait a vtable method belongs to: `core::fmt::Debug` for
+/// `<T as core::fmt::Debug>::fmt`, `core::ops::function::FnOnce` for the
+/// `core::ops::function::FnOnce::call_once{{vtable.shim}}` of closures.
+fn trait_of_method(demangled_name: &str) -> Option<&str> {
+    let name = symbol::strip_hash(demangled_name);
+
+    symbol::implemented_trait(name)
+        .or_else(|| Some(name.rsplit_once("::")?.0).filter(|path| !path.contains('<')))
+}
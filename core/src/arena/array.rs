@@ -0,0 +1,971 @@
+use std::{
+    alloc::{Allocator, Layout},
+    hash::Hash,
+    mem::forget,
+    ops::{Deref, DerefMut, Index, IndexMut, Range},
+    ptr::{NonNull, copy, copy_nonoverlapping, slice_from_raw_parts_mut},
+    slice::{self, SliceIndex},
+};
+
+use super::Arena;
+
+/// A contiguous array type.
+pub struct Array<'a, T> {
+    #[allow(unused)]
+    arena: &'a Arena,
+
+    buf: NonNull<T>,
+    len: usize,
+    capacity: usize,
+}
+
+impl<'a, T> Array<'a, T> {
+    #[track_caller]
+    pub fn new(arena: &'a Arena, capacity: usize) -> Self {
+        Self {
+            arena,
+            buf: arena
+                .alloc_raw(
+                    std::mem::size_of::<T>() * capacity,
+                    std::mem::align_of::<T>(),
+                )
+                .cast(),
+            len: 0,
+            capacity,
+        }
+    }
+
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        // The capacity is never less than the length, and there's nothing to do when
+        // they are equal.
+        if self.capacity > self.len {
+            self.arena.shrink(
+                self.buf.cast(),
+                self.capacity * std::mem::size_of::<T>(),
+                self.len * std::mem::size_of::<T>(),
+            );
+            self.capacity = self.len();
+        }
+    }
+
+    /// Appends an element to the back of the array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new length exceeds the array's capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.push(1);
+    /// arr.push(2);
+    /// arr.push(3);
+    /// assert_eq!(arr.as_slice(), [1, 2, 3]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(1) time.
+    #[inline]
+    #[track_caller]
+    pub fn push(&mut self, item: T) {
+        if self.len == self.capacity {
+            panic!("Not enough capacity {}", self.capacity);
+        }
+
+        unsafe { self.buf.add(self.len).write(item) };
+        self.len += 1;
+    }
+
+    /// Removes the last element from a array and returns it, or [`None`] if it
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 2, 3]);
+    /// assert_eq!(arr.pop(), Some(3));
+    /// assert_eq!(arr.as_slice(), [1, 2]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(1) time.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+
+        Some(unsafe { std::ptr::read(self.buf.add(self.len).as_ptr()) })
+    }
+
+    /// Clones and appends all elements in a slice to the `Array`.
+    ///
+    /// This will effectivelly memcopy the arrays from `slice`
+    /// into the next available elements of this `Array`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is not enough capacity
+    /// left in this `Array` to copy the entire slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.push(1);
+    /// arr.extend_from_slice(&[2, 3, 4]);
+    /// assert_eq!(arr.as_slice(), &[1, 2, 3, 4]);
+    /// ```
+    #[track_caller]
+    pub fn extend_from_slice(&mut self, slice: &[T])
+    where
+        T: Copy,
+    {
+        if self.capacity - self.len < slice.len() {
+            panic!(
+                "Not enough capacity {}<{}",
+                self.capacity,
+                self.len + slice.len()
+            );
+        }
+
+        unsafe { &mut *slice_from_raw_parts_mut(self.buf.add(self.len).as_ptr(), slice.len()) }
+            .copy_from_slice(slice);
+
+        self.len += slice.len();
+    }
+
+    #[track_caller]
+    pub unsafe fn extend_from_slice_unchecked(&mut self, slice: &[T]) {
+        if self.capacity - self.len < slice.len() {
+            panic!(
+                "Not enough capacity {}<{}",
+                self.capacity,
+                self.len + slice.len()
+            );
+        }
+
+        unsafe {
+            copy_nonoverlapping(slice.as_ptr(), self.buf.as_ptr(), slice.len());
+        }
+
+        self.len += slice.len();
+    }
+
+    /// Inserts all elements in a slice at `index`, shifting the elements
+    /// after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, or if there is not enough capacity left in
+    /// this `Array` to copy the entire slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 5);
+    /// arr.extend_from_slice(&[1, 4, 5]);
+    /// arr.insert_from_slice(1, &[2, 3]);
+    /// assert_eq!(arr.as_slice(), &[1, 2, 3, 4, 5]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    #[track_caller]
+    pub fn insert_from_slice(&mut self, index: usize, slice: &[T])
+    where
+        T: Copy,
+    {
+        assert!(
+            index <= self.len,
+            "Insertion index {} > {}",
+            index,
+            self.len
+        );
+        if self.capacity - self.len < slice.len() {
+            panic!(
+                "Not enough capacity {}<{}",
+                self.capacity,
+                self.len + slice.len()
+            );
+        }
+
+        unsafe {
+            let start = self.buf.add(index).as_ptr();
+            copy(start, start.add(slice.len()), self.len - index);
+            copy_nonoverlapping(slice.as_ptr(), start, slice.len());
+        }
+
+        self.len += slice.len();
+    }
+
+    /// Removes the elements in `range`, shifting the elements after it to the
+    /// left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 5);
+    /// arr.extend_from_slice(&[1, 2, 3, 4, 5]);
+    /// arr.remove_range(1..3);
+    /// assert_eq!(arr.as_slice(), &[1, 4, 5]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    #[track_caller]
+    pub fn remove_range(&mut self, range: Range<usize>)
+    where
+        T: Copy,
+    {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range {:?} out of bounds {}",
+            range,
+            self.len
+        );
+
+        unsafe {
+            copy(
+                self.buf.add(range.end).as_ptr(),
+                self.buf.add(range.start).as_ptr(),
+                self.len - range.end,
+            );
+        }
+
+        self.len -= range.end - range.start;
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after it
+    /// to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len` or if the array is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 3]);
+    /// arr.insert(1, 2);
+    /// assert_eq!(arr.as_slice(), &[1, 2, 3]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, item: T) {
+        assert!(
+            index <= self.len,
+            "Insertion index {} > {}",
+            index,
+            self.len
+        );
+        if self.len == self.capacity {
+            panic!("Not enough capacity {}", self.capacity);
+        }
+
+        unsafe {
+            let start = self.buf.add(index).as_ptr();
+            copy(start, start.add(1), self.len - index);
+            start.write(item);
+        }
+
+        self.len += 1;
+    }
+
+    /// Removes and returns the element at position `index`, shifting all
+    /// elements after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 2, 3]);
+    /// assert_eq!(arr.remove(1), 2);
+    /// assert_eq!(arr.as_slice(), &[1, 3]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Removal index {} >= {}", index, self.len);
+
+        self.len -= 1;
+        unsafe {
+            let start = self.buf.add(index).as_ptr();
+            let item = start.read();
+            copy(start.add(1), start, self.len - index);
+            item
+        }
+    }
+
+    /// Removes and returns the element at position `index`, replacing it with
+    /// the last element. It doesn't keep the order of the elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 2, 3, 4]);
+    /// assert_eq!(arr.swap_remove(0), 1);
+    /// assert_eq!(arr.as_slice(), &[4, 2, 3]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(1) time.
+    #[track_caller]
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Removal index {} >= {}", index, self.len);
+
+        self.len -= 1;
+        unsafe {
+            let item = self.buf.add(index).read();
+            copy(
+                self.buf.add(self.len).as_ptr(),
+                self.buf.add(index).as_ptr(),
+                1,
+            );
+            item
+        }
+    }
+
+    /// Keeps only the elements for which `keep` returns `true`, in their
+    /// order, dropping the others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 2, 3, 4]);
+    /// arr.retain(|&x| x % 2 == 0);
+    /// assert_eq!(arr.as_slice(), &[2, 4]);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        // The elements are leaked rather than dropped twice if `keep` panics.
+        let len = self.len;
+        self.len = 0;
+
+        let mut kept = 0;
+        for idx in 0..len {
+            unsafe {
+                let item = self.buf.add(idx);
+                if keep(item.as_ref()) {
+                    if kept != idx {
+                        copy_nonoverlapping(item.as_ptr(), self.buf.add(kept).as_ptr(), 1);
+                    }
+                    kept += 1;
+                } else {
+                    item.drop_in_place();
+                }
+            }
+        }
+
+        self.len = kept;
+    }
+
+    /// Removes the elements in `range`, returning them in an iterator. The
+    /// elements after the range are shifted to the left once it is dropped,
+    /// dropping the elements it didn't return.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 5);
+    /// arr.extend_from_slice(&[1, 2, 3, 4, 5]);
+    /// let drained: Vec<i32> = arr.drain(1..3).collect();
+    /// assert_eq!(drained, [2, 3]);
+    /// assert_eq!(arr.as_slice(), &[1, 4, 5]);
+    /// ```
+    ///
+    /// The elements that were not returned are dropped with the iterator:
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    /// use std::rc::Rc;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let value = Rc::new(0);
+    /// let mut arr = Array::new(&arena, 3);
+    /// for _ in 0..3 {
+    ///     arr.push(value.clone());
+    /// }
+    /// arr.drain(0..2);
+    /// assert_eq!(Rc::strong_count(&value), 2);
+    /// arr.retain(|_| false);
+    /// assert_eq!(Rc::strong_count(&value), 1);
+    /// ```
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    #[track_caller]
+    pub fn drain(&mut self, range: Range<usize>) -> Drain<'_, 'a, T> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range {:?} out of bounds {}",
+            range,
+            self.len
+        );
+
+        // The range and the tail are leaked if the iterator is forgotten.
+        let tail_len = self.len - range.end;
+        self.len = range.start;
+
+        Drain {
+            array: self,
+            next: range.start,
+            end: range.end,
+            tail_start: range.end,
+            tail_len,
+        }
+    }
+
+    /// Clears the array, removing all values.
+    ///
+    /// Note that this method has no effect on the allocated capacity
+    /// of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 2, 3]);
+    ///
+    /// arr.clear();
+    ///
+    /// assert!(arr.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the number of elements in the array, also referred to
+    /// as its 'length'.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// arr.extend_from_slice(&[1, 2, 3]);
+    /// assert_eq!(arr.len(), 3);
+    /// ```
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the array contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// let arena = Arena::new(1024);
+    /// let mut arr = Array::new(&arena, 4);
+    /// assert!(arr.is_empty());
+    ///
+    /// arr.push(1);
+    /// assert!(!arr.is_empty());
+    /// ```
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Extracts a slice containing the entire array.
+    ///
+    /// Equivalent to `&s[..]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// use std::io::{self, Write};
+    /// let arena = Arena::new(1024);
+    /// let buffer = Array::new(&arena, 4);
+    /// io::sink().write(buffer.as_slice()).unwrap();
+    /// ```
+    #[inline]
+    pub const fn as_slice(&self) -> &[T] {
+        // SAFETY: `slice::from_raw_parts` requires pointee is a contiguous, aligned buffer of size
+        // `len` containing properly-initialized `T`s. Data must not be mutated for the returned
+        // lifetime. Further, `len * mem::size_of::<T>` <= `ISIZE::MAX`, and allocation does not
+        // "wrap" through overflowing memory addresses.
+        //
+        // * Array API guarantees that self.buf:
+        //      * contains only properly-initialized items within 0..len
+        //      * is aligned, contiguous, and valid for `len` reads
+        //      * obeys size and address-wrapping constraints
+        //
+        // * We only construct `&mut` references to `self.buf` through `&mut self` methods; borrow-
+        //   check ensures that it is not possible to mutably alias `self.buf` within the
+        //   returned lifetime.
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    /// Extracts a mutable slice of the entire array.
+    ///
+    /// Equivalent to `&mut s[..]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// # use binary_size_explorer_core::arena::array::Array;
+    ///
+    /// use std::io::{self, Read};
+    /// let arena = Arena::new(1024);
+    /// let mut buffer = Array::new(&arena, 4);
+    /// io::repeat(0b101).read_exact(buffer.as_mut_slice()).unwrap();
+    /// ```
+    #[inline]
+    pub const fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: `slice::from_raw_parts_mut` requires pointee is a contiguous, aligned buffer of
+        // size `len` containing properly-initialized `T`s. Data must not be accessed through any
+        // other pointer for the returned lifetime. Further, `len * mem::size_of::<T>` <=
+        // `ISIZE::MAX` and allocation does not "wrap" through overflowing memory addresses.
+        //
+        // * Array API guarantees that self.buf:
+        //      * contains only properly-initialized items within 0..len
+        //      * is aligned, contiguous, and valid for `len` reads
+        //      * obeys size and address-wrapping constraints
+        //
+        // * We only construct references to `self.buf` through `&self` and `&mut self` methods;
+        //   borrow-check ensures that it is not possible to construct a reference to `self.buf`
+        //   within the returned lifetime.
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    pub fn to_slice(self) -> &'a [T] {
+        let slice = unsafe { slice::from_raw_parts(self.as_ptr(), self.len) };
+        forget(self);
+        slice
+    }
+
+    /// Returns a raw pointer to the array's buffer.
+    ///
+    /// The caller must ensure that the array outlives the pointer this
+    /// function returns, or else it will end up dangling.
+    ///
+    /// The caller must also ensure that the memory the pointer (non-transitively) points to
+    /// is never written to (except inside an `UnsafeCell`) using this pointer or any pointer
+    /// derived from it. If you need to mutate the contents of the slice, use [`as_mut_ptr`].
+    ///
+    /// This method guarantees that for the purpose of the aliasing model, this method
+    /// does not materialize a reference to the underlying slice, and thus the returned pointer
+    /// will remain valid when mixed with other calls to [`as_ptr`], [`as_mut_ptr`],
+    /// and [`as_non_null`].
+    ///
+    /// [`as_mut_ptr`]: Array::as_mut_ptr
+    /// [`as_ptr`]: Array::as_ptr
+    /// [`as_non_null`]: Array::as_non_null
+    #[inline]
+    pub const fn as_ptr(&self) -> *const T {
+        // We shadow the slice method of the same name to avoid going through
+        // `deref`, which creates an intermediate reference.
+        self.buf.as_ptr()
+    }
+
+    /// Returns a raw mutable pointer to the array's buffer.
+    ///
+    /// The caller must ensure that the array outlives the pointer this
+    /// function returns, or else it will end up dangling.
+    ///
+    /// This method guarantees that for the purpose of the aliasing model, this method
+    /// does not materialize a reference to the underlying slice, and thus the returned pointer
+    /// will remain valid when mixed with other calls to [`as_ptr`], [`as_mut_ptr`],
+    /// and [`as_non_null`].
+    ///
+    /// [`as_mut_ptr`]: Array::as_mut_ptr
+    /// [`as_ptr`]: Array::as_ptr
+    /// [`as_non_null`]: Array::as_non_null
+    #[inline]
+    pub const fn as_mut_ptr(&mut self) -> *mut T {
+        // We shadow the slice method of the same name to avoid going through
+        // `deref_mut`, which creates an intermediate reference.
+        self.buf.as_ptr()
+    }
+
+    /// Returns a `NonNull` pointer to the array's buffer.
+    ///
+    /// The caller must ensure that the array outlives the pointer this
+    /// function returns, or else it will end up dangling.
+    ///
+    /// This method guarantees that for the purpose of the aliasing model, this method
+    /// does not materialize a reference to the underlying slice, and thus the returned pointer
+    /// will remain valid when mixed with other calls to [`as_ptr`], [`as_mut_ptr`],
+    /// and [`as_non_null`].
+    ///
+    /// [`as_mut_ptr`]: Array::as_mut_ptr
+    /// [`as_ptr`]: Array::as_ptr
+    /// [`as_non_null`]: Array::as_non_null
+    #[inline]
+    pub fn as_non_null(&mut self) -> NonNull<T> {
+        self.buf
+    }
+}
+
+/// Iterator over the elements removed from an `Array` by [`Array::drain`].
+pub struct Drain<'d, 'a, T> {
+    array: &'d mut Array<'a, T>,
+    /// Next element to return.
+    next: usize,
+    /// End of the elements left to return.
+    end: usize,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T> Iterator for Drain<'_, '_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+
+        let item = unsafe { self.array.buf.add(self.next).read() };
+        self.next += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, '_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(unsafe { self.array.buf.add(self.end).read() })
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, '_, T> {}
+
+impl<T> Drop for Drain<'_, '_, T> {
+    fn drop(&mut self) {
+        let start = self.array.len;
+        unsafe {
+            for idx in self.next..self.end {
+                self.array.buf.add(idx).drop_in_place();
+            }
+            copy(
+                self.array.buf.add(self.tail_start).as_ptr(),
+                self.array.buf.add(start).as_ptr(),
+                self.tail_len,
+            );
+        }
+        self.array.len = start + self.tail_len;
+    }
+}
+
+impl<T> Drop for Array<'_, T> {
+    fn drop(&mut self) {
+        self.arena
+            .dealloc(self.buf.cast(), self.capacity * std::mem::size_of::<T>());
+    }
+}
+
+impl<'a, Idx, T> Index<Idx> for Array<'a, T>
+where
+    Idx: SliceIndex<[T]>,
+{
+    type Output = Idx::Output;
+
+    fn index(&self, index: Idx) -> &Self::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<'a, Idx, T> IndexMut<Idx> for Array<'a, T>
+where
+    Idx: SliceIndex<[T]>,
+{
+    fn index_mut(&mut self, index: Idx) -> &mut Self::Output {
+        &mut self.as_mut_slice()[index]
+    }
+}
+
+impl<'a, T> Deref for Array<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<'a, T> DerefMut for Array<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<'a, T: PartialEq> PartialEq for Array<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+
+        for i in 0..self.len {
+            if self[i] != other[i] {
+                return false;
+            }
+        }
+        true
+    }
+}
+impl<'a, T: Eq> Eq for Array<'a, T> {}
+
+impl<'a, T: Hash> Hash for Array<'a, T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for index in 0..self.len {
+            self[index].hash(state);
+        }
+    }
+}
+
+impl<'a> std::fmt::Write for Array<'a, u8> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if (self.capacity - self.len) < s.len() {
+            return Err(std::fmt::Error);
+        }
+
+        self.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// An `Array` growing its capacity when it's full, for the arrays whose
+/// length isn't known upfront.
+///
+/// It grows in place when it's the last allocation of the arena, otherwise
+/// it's copied to a new allocation twice as large, and the previous one is
+/// only reclaimed with the arena.
+///
+/// # Examples
+///
+/// ```
+/// # use binary_size_explorer_core::arena::Arena;
+/// # use binary_size_explorer_core::arena::array::GrowableArray;
+///
+/// let arena = Arena::new(1024);
+/// let mut arr = GrowableArray::new(&arena);
+/// for i in 0..10 {
+///     arr.push(i);
+///     // Allocating in between moves it on the next growth.
+///     arena.alloc_slice_zeroed::<u8>(1);
+/// }
+/// arr.extend_from_slice(&[10, 11]);
+/// assert_eq!(arr.len(), 12);
+/// assert_eq!(arr[11], 11);
+///
+/// let arr = arr.into_array();
+/// assert_eq!(arr.as_slice(), (0..12).collect::<Vec<_>>());
+/// ```
+pub struct GrowableArray<'a, T> {
+    inner: Array<'a, T>,
+}
+
+impl<'a, T> GrowableArray<'a, T> {
+    const MIN_CAPACITY: usize = 4;
+
+    pub fn new(arena: &'a Arena) -> Self {
+        Self::with_capacity(arena, 0)
+    }
+
+    pub fn with_capacity(arena: &'a Arena, capacity: usize) -> Self {
+        Self {
+            inner: Array::new(arena, capacity),
+        }
+    }
+
+    /// Makes room for at least `additional` more elements, at least doubling
+    /// the capacity when it grows.
+    pub fn reserve(&mut self, additional: usize) {
+        let inner = &mut self.inner;
+        let required = inner.len + additional;
+        if required <= inner.capacity {
+            return;
+        }
+
+        let new_capacity = required.max(inner.capacity * 2).max(Self::MIN_CAPACITY);
+        let size = std::mem::size_of::<T>();
+        let align = std::mem::align_of::<T>();
+        let old_size = inner.capacity * size;
+        let new_size = new_capacity * size;
+
+        inner.buf = if inner.arena.is_last_allocation(inner.buf.cast(), old_size) {
+            // Copies it to a chained block when the current one is full.
+            unsafe {
+                inner.arena.grow(
+                    inner.buf.cast(),
+                    Layout::from_size_align_unchecked(old_size, align),
+                    Layout::from_size_align_unchecked(new_size, align),
+                )
+            }
+            .unwrap()
+            .cast()
+        } else {
+            let buf = inner.arena.alloc_raw(new_size, align).cast::<T>();
+            unsafe { copy_nonoverlapping(inner.buf.as_ptr(), buf.as_ptr(), inner.len) };
+            buf
+        };
+        inner.capacity = new_capacity;
+    }
+
+    /// Appends an element to the back of the array, growing it if it's full.
+    ///
+    /// # Time complexity
+    ///
+    /// Takes amortized *O*(1) time.
+    pub fn push(&mut self, item: T) {
+        self.reserve(1);
+        self.inner.push(item);
+    }
+
+    /// Appends all the elements of the slice, growing the array if needed.
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*slice.len*) amortized time.
+    pub fn extend_from_slice(&mut self, slice: &[T])
+    where
+        T: Copy,
+    {
+        self.reserve(slice.len());
+        self.inner.extend_from_slice(slice);
+    }
+
+    /// Inserts an element at position `index`, growing the array if it's full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Time complexity
+    ///
+    /// Takes *O*(*len*) time.
+    #[track_caller]
+    pub fn insert(&mut self, index: usize, item: T) {
+        self.reserve(1);
+        self.inner.insert(index, item);
+    }
+
+    /// Returns the elements as a fixed capacity `Array`.
+    pub fn into_array(self) -> Array<'a, T> {
+        self.inner
+    }
+}
+
+impl<'a, T> Deref for GrowableArray<'a, T> {
+    type Target = Array<'a, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a, T> DerefMut for GrowableArray<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'a, T> From<GrowableArray<'a, T>> for Array<'a, T> {
+    fn from(array: GrowableArray<'a, T>) -> Self {
+        array.into_array()
+    }
+}
@@ -0,0 +1,79 @@
+use hashbrown::HashMap;
+
+use super::{Arena, string::String};
+
+/// Id of a string stored once in an `Interner`. Two symbols of the same
+/// interner are equal when their strings are.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// Stores each unique string once in the arena, for the names repeated
+/// across the sections to be compared by id.
+///
+/// ```
+/// use binary_size_explorer_core::arena::{Arena, interner::Interner};
+///
+/// let arena = Arena::new(1024);
+/// let mut names = Interner::new(&arena);
+/// let a = names.intern("core::ptr::drop_in_place");
+/// let b = names.intern(&"core::ptr::drop_in_place".to_owned());
+/// assert_eq!(a, b);
+/// assert_eq!(names.get("core::ptr::drop_in_place"), Some(a));
+/// assert_eq!(names.resolve(a), "core::ptr::drop_in_place");
+/// assert_eq!(names.len(), 1);
+/// ```
+pub struct Interner<'a> {
+    arena: &'a Arena,
+    strings: std::vec::Vec<&'a str>,
+    lookup: HashMap<&'a str, Symbol>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn new(arena: &'a Arena) -> Self {
+        Self {
+            arena,
+            strings: std::vec::Vec::new(),
+            lookup: HashMap::new(),
+        }
+    }
+
+    pub fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    /// Returns the symbol of `text`, copying it in the arena the first time.
+    pub fn intern(&mut self, text: &str) -> Symbol {
+        if let Some(&symbol) = self.lookup.get(text) {
+            return symbol;
+        }
+
+        let text = String::from_str(self.arena, text).to_str();
+        let symbol = Symbol(self.strings.len() as u32);
+        self.strings.push(text);
+        self.lookup.insert(text, symbol);
+        symbol
+    }
+
+    /// Like `intern`, returning the stored string.
+    pub fn intern_str(&mut self, text: &str) -> &'a str {
+        let symbol = self.intern(text);
+        self.resolve(symbol)
+    }
+
+    /// Returns the symbol of `text` if it was interned.
+    pub fn get(&self, text: &str) -> Option<Symbol> {
+        self.lookup.get(text).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &'a str {
+        self.strings[symbol.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
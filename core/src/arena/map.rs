@@ -0,0 +1,72 @@
+use std::{
+    hash::Hash,
+    ops::{Deref, DerefMut},
+};
+
+use hashbrown::DefaultHashBuilder;
+
+use crate::arena::Arena;
+
+/// A `hashbrown::HashMap` allocating its table in the arena.
+///
+/// ```
+/// use binary_size_explorer_core::arena::{Arena, map::HashMap};
+///
+/// let arena = Arena::new(1024);
+/// let mut counts = HashMap::new(&arena, 0);
+/// for name in ["a", "b", "a"] {
+///     *counts.entry(name).or_insert(0) += 1;
+/// }
+/// assert_eq!(counts["a"], 2);
+///
+/// let lengths = HashMap::from_iter_in(&arena, ["ab", "abc"].map(|name| (name, name.len())));
+/// assert_eq!(lengths.get("abc"), Some(&3));
+/// ```
+pub struct HashMap<'a, K, V> {
+    inner: hashbrown::HashMap<K, V, DefaultHashBuilder, &'a Arena>,
+}
+
+impl<'a, K, V> HashMap<'a, K, V> {
+    pub fn new(arena: &'a Arena, capacity: usize) -> Self {
+        Self {
+            inner: hashbrown::HashMap::with_capacity_in(capacity, arena),
+        }
+    }
+
+    /// Collects the key value pairs in a map allocated in the arena.
+    pub fn from_iter_in(arena: &'a Arena, iter: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Eq + Hash,
+    {
+        let iter = iter.into_iter();
+        let mut map = Self::new(arena, iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V> IntoIterator for HashMap<'a, K, V> {
+    type Item = (K, V);
+    type IntoIter = hashbrown::hash_map::IntoIter<K, V, &'a Arena>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, K, V> Deref for HashMap<'a, K, V> {
+    type Target = hashbrown::HashMap<K, V, DefaultHashBuilder, &'a Arena>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a, K, V> DerefMut for HashMap<'a, K, V> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,882 @@
+use std::{
+    alloc::Allocator,
+    cell::Cell,
+    mem::MaybeUninit,
+    ptr::{NonNull, slice_from_raw_parts_mut},
+    slice,
+};
+
+pub mod array;
+pub mod interner;
+pub mod map;
+pub mod scratch;
+pub mod sorted_map;
+pub mod string;
+pub mod sync;
+pub mod tree;
+pub mod vec;
+
+#[cfg(unix)]
+pub mod memory {
+    pub const KB: usize = 1024;
+    pub const MB: usize = 1024 * KB;
+    pub const GB: usize = 1024 * MB;
+    /// Capacity of the arenas reserved for the analysis of a whole file.
+    pub const MAX_RESERVE: usize = 64 * GB;
+
+    use libc;
+    use std::ptr::NonNull;
+
+    pub(super) unsafe fn virtual_reserve(len: usize) -> NonNull<u8> {
+        unsafe {
+            let ptr = libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_NONE,
+                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
+                -1,
+                0,
+            );
+
+            if !ptr.is_null() && !std::ptr::eq(ptr, libc::MAP_FAILED) {
+                NonNull::new_unchecked(ptr.cast())
+            } else {
+                panic!("Failed to reserve virtual memory");
+            }
+        }
+    }
+
+    pub(super) unsafe fn virtual_commit(ptr: NonNull<u8>, len: usize) {
+        unsafe {
+            if libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_READ | libc::PROT_WRITE) != 0 {
+                panic!("Failed to commit virtual memory");
+            }
+        }
+    }
+
+    pub(super) unsafe fn virtual_release(ptr: NonNull<u8>, len: usize) {
+        unsafe {
+            if libc::munmap(ptr.as_ptr().cast(), len) == -1 {
+                panic!("Failed to release virtual memory");
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+pub mod memory {
+    pub const KB: usize = 1024;
+    pub const MB: usize = 1024 * KB;
+    pub const GB: usize = 1024 * MB;
+    /// Capacity of the arenas reserved for the analysis of a whole file.
+    pub const MAX_RESERVE: usize = 64 * GB;
+
+    use std::ptr::NonNull;
+    use windows_sys::Win32::System::Memory::{
+        MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE, VirtualAlloc, VirtualFree,
+    };
+
+    pub(super) unsafe fn virtual_reserve(size: usize) -> NonNull<u8> {
+        unsafe {
+            let ptr = VirtualAlloc(std::ptr::null(), size, MEM_RESERVE, PAGE_READWRITE);
+
+            if !ptr.is_null() {
+                NonNull::new_unchecked(ptr.cast())
+            } else {
+                panic!("Failed to reserve virtual memory");
+            }
+        }
+    }
+
+    pub(super) unsafe fn virtual_commit(ptr: NonNull<u8>, size: usize) {
+        unsafe {
+            VirtualAlloc(ptr.as_ptr().cast(), size, MEM_COMMIT, PAGE_READWRITE);
+        }
+    }
+
+    pub(super) unsafe fn virtual_release(ptr: NonNull<u8>, _: usize) {
+        unsafe {
+            if VirtualFree(ptr.as_ptr().cast(), 0, MEM_RELEASE) == 0 {
+                panic!("Failed to release virtual memory");
+            }
+        }
+    }
+}
+
+/// Browsers have no virtual memory, the blocks are allocated from the heap
+/// when they are reserved, so they start small and double as they are
+/// chained.
+#[cfg(target_arch = "wasm32")]
+pub mod memory {
+    pub const KB: usize = 1024;
+    pub const MB: usize = 1024 * KB;
+    pub const GB: usize = 1024 * MB;
+    /// Capacity of the first block of the arenas, allocated upfront here.
+    pub const MAX_RESERVE: usize = MB;
+
+    use std::{alloc::Layout, ptr::NonNull};
+
+    fn layout(len: usize) -> Layout {
+        Layout::from_size_align(len, super::ALLOCATION_CHUNCK_SIZE).unwrap()
+    }
+
+    pub(super) unsafe fn virtual_reserve(len: usize) -> NonNull<u8> {
+        // Zeroed like the committed pages of the other platforms.
+        match NonNull::new(unsafe { std::alloc::alloc_zeroed(layout(len)) }) {
+            Some(ptr) => ptr,
+            None => panic!("Failed to reserve memory"),
+        }
+    }
+
+    pub(super) unsafe fn virtual_commit(_: NonNull<u8>, _: usize) {}
+
+    pub(super) unsafe fn virtual_release(ptr: NonNull<u8>, len: usize) {
+        unsafe { std::alloc::dealloc(ptr.as_ptr(), layout(len)) };
+    }
+}
+
+const ALLOCATION_CHUNCK_SIZE: usize = 64 * 1024;
+
+macro_rules! assert_pow_of_2 {
+    ($len:expr) => {
+        assert!(($len & ($len - 1)) == 0)
+    };
+}
+
+/// Trait that can be implemented by any type `T` to signal
+/// that given a block of memory with aligment `std::mem::align_of::<T>`
+/// and size `std::mem::align_of::<T>`, that memory represents
+/// a valid object of type `T` regardless of which bytes
+/// are in there.
+pub unsafe trait AnyBits {}
+
+/// Trait that can be implemented by any type `T` to signal
+/// that given a block of zero bytes of memory with aligment
+/// `std::mem::align_of::<T>` and size `std::mem::align_of::<T>`,
+/// that memory represents a valid object of type `T`.
+pub unsafe trait ZeroBits {}
+
+macro_rules! impl_zero_bits_to_types {
+    ($type:ty) => {
+        unsafe impl ZeroBits for $type {}
+    };
+
+    ($type:ty, $($types:ty),+) => {
+        impl_zero_bits_to_types!($type);
+        impl_zero_bits_to_types!($($types),+);
+    };
+}
+
+impl_zero_bits_to_types!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+/// An arena allocator.
+///
+/// The capacity is reserved up front and committed as it is used. Once it is
+/// full, another block as big is reserved and chained to it, so the capacity
+/// is only a hint.
+///
+/// ```
+/// # use binary_size_explorer_core::arena::Arena;
+/// let arena = Arena::new(64 * 1024);
+/// let first = arena.alloc_slice_zeroed::<u8>(48 * 1024);
+/// // Doesn't fit in the first block, goes in a chained one.
+/// let second = arena.alloc_slice_zeroed::<u8>(48 * 1024);
+/// first[0] = 1;
+/// second[0] = 2;
+/// assert_eq!((first[0], second[0]), (1, 2));
+/// ```
+pub struct Arena {
+    buffer: Cell<NonNull<u8>>,
+    capacity: Cell<usize>,
+    offset: Cell<usize>,
+    commited: Cell<usize>,
+    /// Capacity of the blocks before the current one, the offsets of the
+    /// arena counting from the start of the first block.
+    base: Cell<usize>,
+    /// Header of the current block when it was chained to a full one.
+    previous_block: Cell<Option<NonNull<BlockHeader>>>,
+    allocation_count: Cell<usize>,
+    /// The last value to drop with the arena, the others linked from it.
+    finalizers: Cell<Option<NonNull<Finalizer>>>,
+    /// Whether the allocations are checked, see `new_guarded`.
+    guarded: bool,
+    /// The canary before the last allocation, when guarded.
+    last_canary: Cell<Option<NonNull<Canary>>>,
+    /// End of the poisoned memory of the current block, when guarded.
+    poisoned_end: Cell<usize>,
+}
+
+/// Memory taken by an arena, in all its blocks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArenaUsage {
+    pub used_bytes: usize,
+    pub commited_bytes: usize,
+    pub reserved_bytes: usize,
+    pub block_count: usize,
+    /// Allocations since the arena was created, the reset ones included.
+    pub allocation_count: usize,
+}
+
+/// Start of a chained block, with the state of the block it follows to go
+/// back to it once the allocations made after it are reset.
+struct BlockHeader {
+    buffer: NonNull<u8>,
+    capacity: usize,
+    offset: usize,
+    commited: usize,
+    base: usize,
+    previous_block: Option<NonNull<BlockHeader>>,
+}
+
+/// A value of the arena to drop when it's reset before it or dropped, stored
+/// in the arena after the value.
+struct Finalizer {
+    value: NonNull<u8>,
+    drop: unsafe fn(NonNull<u8>),
+    /// Offset of the arena before the value was allocated.
+    offset: usize,
+    next: Option<NonNull<Finalizer>>,
+}
+
+/// Written before each allocation of a guarded arena, to detect the writes
+/// past the end of the allocation before it.
+struct Canary {
+    magic: u64,
+    /// Offset of the arena where the canary is.
+    offset: usize,
+    previous: Option<NonNull<Canary>>,
+}
+
+const CANARY_MAGIC: u64 = 0x5CA7_C4A2_1E5C_A7C4;
+
+/// Written over the memory of a guarded arena when it's released.
+const POISON: u8 = 0xDD;
+
+unsafe fn drop_value<T>(value: NonNull<u8>) {
+    unsafe { value.cast::<T>().drop_in_place() };
+}
+
+impl Arena {
+    pub const fn empty() -> Self {
+        Self {
+            buffer: Cell::new(NonNull::dangling()),
+            capacity: Cell::new(0),
+            offset: Cell::new(0),
+            commited: Cell::new(0),
+            base: Cell::new(0),
+            previous_block: Cell::new(None),
+            allocation_count: Cell::new(0),
+            finalizers: Cell::new(None),
+            guarded: false,
+            last_canary: Cell::new(None),
+            poisoned_end: Cell::new(0),
+        }
+    }
+
+    /// Reserves `capacity` bytes, at most `memory::MAX_RESERVE`, the
+    /// allocations past them going in chained blocks.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = usize::clamp(capacity, 1, memory::MAX_RESERVE);
+        let capacity = (capacity + ALLOCATION_CHUNCK_SIZE - 1) & !(ALLOCATION_CHUNCK_SIZE - 1);
+
+        let buffer = unsafe { memory::virtual_reserve(capacity) };
+
+        Self {
+            buffer: Cell::new(buffer),
+            capacity: Cell::new(capacity),
+            commited: Cell::new(0),
+            offset: Cell::new(0),
+            base: Cell::new(0),
+            previous_block: Cell::new(None),
+            allocation_count: Cell::new(0),
+            finalizers: Cell::new(None),
+            guarded: false,
+            last_canary: Cell::new(None),
+            poisoned_end: Cell::new(0),
+        }
+    }
+
+    /// Like `new`, checking the allocations in debug builds to catch the
+    /// memory used after the arena was reset or written out of bounds.
+    ///
+    /// The released memory is poisoned and checked when it's allocated again,
+    /// and a canary word before each allocation is checked when it's reset.
+    ///
+    /// The scratch arenas are guarded, catching the allocations used after
+    /// the scratch arena was dropped:
+    ///
+    /// ```should_panic
+    /// # use binary_size_explorer_core::arena::scratch::scratch_arena;
+    /// let released = {
+    ///     let scratch = scratch_arena(&[]);
+    ///     scratch.alloc_slice_zeroed::<u8>(16).as_mut_ptr()
+    /// };
+    /// unsafe { *released = 1 };
+    /// // Panics, the memory was written after it was released.
+    /// scratch_arena(&[]).alloc_slice_zeroed::<u8>(16);
+    /// ```
+    ///
+    /// Or written past their end:
+    ///
+    /// ```should_panic
+    /// # use binary_size_explorer_core::arena::scratch::scratch_arena;
+    /// let scratch = scratch_arena(&[]);
+    /// let first = scratch.alloc_slice_zeroed::<u8>(8).as_mut_ptr();
+    /// scratch.alloc_slice_zeroed::<u8>(8);
+    /// unsafe { first.add(8).write_bytes(0, 8) };
+    /// // Panics, the canary of the second allocation was overwritten.
+    /// drop(scratch);
+    /// ```
+    pub fn new_guarded(capacity: usize) -> Self {
+        let mut arena = Self::new(capacity);
+        arena.guarded = cfg!(debug_assertions);
+        arena
+    }
+
+    pub fn usage(&self) -> ArenaUsage {
+        let mut usage = ArenaUsage {
+            used_bytes: self.offset.get(),
+            commited_bytes: self.commited.get(),
+            reserved_bytes: self.capacity.get(),
+            block_count: usize::from(self.is_reserved()),
+            allocation_count: self.allocation_count.get(),
+        };
+
+        let mut previous_block = self.previous_block.get();
+        while let Some(header_ptr) = previous_block {
+            let header = unsafe { header_ptr.as_ref() };
+            usage.used_bytes += header.offset;
+            usage.commited_bytes += header.commited;
+            usage.reserved_bytes += header.capacity;
+            usage.block_count += 1;
+            previous_block = header.previous_block;
+        }
+
+        usage
+    }
+
+    pub(super) fn is_reserved(&self) -> bool {
+        self.buffer.get() != NonNull::dangling()
+    }
+
+    /// End of the allocations of the current block.
+    fn end(&self) -> NonNull<u8> {
+        unsafe { self.buffer.get().add(self.offset.get()) }
+    }
+
+    /// Whether the `size` bytes at `ptr` are the last allocation, the only one
+    /// that can grow in place.
+    pub(super) fn is_last_allocation(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        unsafe { ptr.add(size) == self.end() }
+    }
+
+    /// Commits the current block up to `end`, `false` if it's past its
+    /// capacity.
+    fn commit(&self, end: usize) -> bool {
+        if end <= self.commited.get() {
+            return true;
+        }
+
+        let new_commited = (end + ALLOCATION_CHUNCK_SIZE - 1) & !(ALLOCATION_CHUNCK_SIZE - 1);
+        if new_commited > self.capacity.get() {
+            return false;
+        }
+
+        unsafe {
+            memory::virtual_commit(
+                self.buffer.get().add(self.commited.get()),
+                new_commited - self.commited.get(),
+            );
+        }
+        self.commited.set(new_commited);
+        true
+    }
+
+    /// Reserves a new block with room for `size` bytes, as big as the
+    /// current one, twice as big in browsers, to allocate in it.
+    fn chain_block(&self, size: usize) {
+        let header_size = std::mem::size_of::<BlockHeader>();
+        let capacity = if cfg!(target_arch = "wasm32") {
+            2 * self.capacity.get()
+        } else {
+            self.capacity.get()
+        };
+        let capacity = usize::max(capacity, header_size + size);
+        let capacity = (capacity + ALLOCATION_CHUNCK_SIZE - 1) & !(ALLOCATION_CHUNCK_SIZE - 1);
+        let buffer = unsafe { memory::virtual_reserve(capacity) };
+
+        // An empty arena has nothing to go back to.
+        if !self.is_reserved() {
+            self.buffer.set(buffer);
+            self.capacity.set(capacity);
+            return;
+        }
+
+        let header = BlockHeader {
+            buffer: self.buffer.get(),
+            capacity: self.capacity.get(),
+            offset: self.offset.get(),
+            commited: self.commited.get(),
+            base: self.base.get(),
+            previous_block: self.previous_block.get(),
+        };
+
+        self.base.set(self.base.get() + self.capacity.get());
+        self.buffer.set(buffer);
+        self.capacity.set(capacity);
+        self.commited.set(0);
+        self.offset.set(header_size);
+        self.poisoned_end.set(0);
+        assert!(self.commit(header_size));
+
+        let header_ptr = buffer.cast::<BlockHeader>();
+        unsafe { header_ptr.write(header) };
+        self.previous_block.set(Some(header_ptr));
+    }
+
+    /// Releases the current block, going back to the one it was chained to.
+    ///
+    /// It's the caller responsability to ensure that no allocations of the
+    /// current block are still used.
+    unsafe fn pop_block(&self) {
+        let Some(header_ptr) = self.previous_block.get() else {
+            return;
+        };
+
+        unsafe {
+            let header = header_ptr.read();
+            memory::virtual_release(self.buffer.get(), self.capacity.get());
+
+            self.buffer.set(header.buffer);
+            self.capacity.set(header.capacity);
+            self.offset.set(header.offset);
+            self.commited.set(header.commited);
+            // What was poisoned in the block before isn't tracked anymore.
+            self.poisoned_end.set(0);
+            self.base.set(header.base);
+            self.previous_block.set(header.previous_block);
+        }
+    }
+
+    /// Allocates a region of memory with the alignment and size
+    /// of type `T`.
+    ///
+    /// The memory is not initialised and it's up
+    /// to the caller to initialise the returned block of memory
+    /// before using it.
+    pub fn alloc_unint<T>(&self) -> &mut MaybeUninit<T> {
+        let size = std::mem::size_of::<MaybeUninit<T>>();
+        let align = std::mem::align_of::<MaybeUninit<T>>();
+
+        let ptr = self.alloc_raw(size, align).as_ptr();
+
+        unsafe { &mut *ptr.cast() }
+    }
+
+    /// Allocates a region of memory with the alignment and size
+    /// of type `T`.
+    ///
+    /// The memory is not initialised and it's up
+    /// to the caller to initialise the returned block of memory
+    /// before using it.
+    pub fn alloc_new<T: AnyBits>(&self) -> &mut T {
+        let size = std::mem::size_of::<T>();
+        let align = std::mem::align_of::<T>();
+
+        let ptr = self.alloc_raw(size, align).as_ptr();
+
+        unsafe { &mut *ptr.cast() }
+    }
+
+    /// Allocates a region of memory with the alignment and size
+    /// of type `T`.
+    ///
+    /// The memory is guaranteed to contain only zero bytes.
+    pub fn alloc_zeroed<T: ZeroBits>(&self) -> &mut T {
+        let size = std::mem::size_of::<T>();
+        let align = std::mem::align_of::<T>();
+
+        let ptr = self.alloc_raw(size, align).cast::<u8>().as_ptr();
+        unsafe {
+            std::ptr::write_bytes(ptr, 0, size);
+        }
+
+        unsafe { &mut *ptr.cast() }
+    }
+
+    /// Moves `value` in the arena, to be dropped when the arena is dropped or
+    /// reset before it. It's for the values owning resources outside of the
+    /// arena, like files, the others don't need it.
+    ///
+    /// ```
+    /// # use binary_size_explorer_core::arena::Arena;
+    /// use std::rc::Rc;
+    ///
+    /// let value = Rc::new(0);
+    /// let arena = Arena::new(1024);
+    /// let copy = arena.alloc_with_drop(value.clone());
+    /// assert_eq!(**copy, 0);
+    /// assert_eq!(Rc::strong_count(&value), 2);
+    /// drop(arena);
+    /// assert_eq!(Rc::strong_count(&value), 1);
+    ///
+    /// // Resetting a scratch arena drops its values too.
+    /// # use binary_size_explorer_core::arena::scratch::scratch_arena;
+    /// {
+    ///     let scratch = scratch_arena(&[]);
+    ///     scratch.alloc_with_drop(value.clone());
+    ///     assert_eq!(Rc::strong_count(&value), 2);
+    /// }
+    /// assert_eq!(Rc::strong_count(&value), 1);
+    /// ```
+    pub fn alloc_with_drop<T>(&self, value: T) -> &mut T {
+        let offset = self.offset();
+        let value_ptr = self.alloc_unint::<T>().write(value);
+
+        if std::mem::needs_drop::<T>() {
+            let finalizer = self.alloc_unint::<Finalizer>().write(Finalizer {
+                value: NonNull::from(&mut *value_ptr).cast(),
+                drop: drop_value::<T>,
+                offset,
+                next: self.finalizers.get(),
+            });
+            self.finalizers.set(Some(NonNull::from(finalizer)));
+        }
+
+        value_ptr
+    }
+
+    /// Drops the values allocated at or after `offset` with `alloc_with_drop`,
+    /// the last allocated first.
+    unsafe fn drop_values(&self, offset: usize) {
+        while let Some(finalizer_ptr) = self.finalizers.get() {
+            let finalizer = unsafe { finalizer_ptr.read() };
+            if finalizer.offset < offset {
+                break;
+            }
+
+            self.finalizers.set(finalizer.next);
+            unsafe { (finalizer.drop)(finalizer.value) };
+        }
+    }
+
+    pub fn alloc_slice_zeroed<T: ZeroBits>(&self, len: usize) -> &mut [T] {
+        let size = std::mem::size_of::<T>();
+        let align = std::mem::align_of::<T>();
+
+        let ptr = self.alloc_raw(size * len, align).cast::<u8>().as_ptr();
+        unsafe {
+            std::ptr::write_bytes(ptr, 0, size * len);
+        }
+
+        unsafe { &mut *slice_from_raw_parts_mut::<T>(ptr.cast(), len) }
+    }
+
+    #[track_caller]
+    pub fn alloc_raw(&self, size: usize, align: usize) -> NonNull<[u8]> {
+        assert_pow_of_2!(align);
+
+        let canary = self.guarded.then(|| {
+            self.offset
+                .get()
+                .next_multiple_of(std::mem::align_of::<Canary>())
+        });
+        let mut start = canary.map_or(self.offset.get(), |canary| {
+            canary + std::mem::size_of::<Canary>()
+        });
+        let module = start & (align - 1);
+        if module > 0 {
+            start += align - module;
+        }
+        let end = start + size;
+
+        if !self.commit(end) {
+            self.chain_block(size + align + 2 * std::mem::size_of::<Canary>());
+            return self.alloc_raw(size, align);
+        }
+
+        if let Some(canary) = canary {
+            self.guard_allocation(canary, end);
+        }
+        self.offset.set(end);
+        self.allocation_count.set(self.allocation_count.get() + 1);
+        unsafe { NonNull::slice_from_raw_parts(self.buffer.get().add(start), end - start) }
+    }
+
+    pub fn dealloc(&self, ptr: NonNull<u8>, size: usize) {
+        // It only makes sense to deallocate the last allocation.
+        // The arena doesn't really handle deallocation and it will
+        // not re-allocate any released memory in between two allocations
+        unsafe {
+            if ptr.add(size) == self.end() {
+                self.truncate(self.offset.get().unchecked_sub(size));
+            }
+        }
+    }
+
+    pub fn shrink(&self, ptr: NonNull<u8>, old_size: usize, new_size: usize) {
+        debug_assert!(old_size >= new_size);
+
+        // It only makes sense to shrink the last allocation.
+        // The arena doesn't handle deallocation and it will
+        // not re-allocate any released memory in between two allocations
+        if unsafe { ptr.add(old_size) == self.end() } {
+            self.truncate(self.offset.get() - (old_size - new_size));
+        } else {
+            debug_assert!(
+                false,
+                "Attempting to shrink memory that was not the last allocated"
+            );
+        }
+    }
+
+    /// The offset of the arena represents the number of bytes
+    /// reserved by allocations so far, in all its blocks.
+    pub(super) fn offset(&self) -> usize {
+        self.base.get() + self.offset.get()
+    }
+
+    /// Resets the `offset` of the arena, effectivelly "freeing"
+    /// all previous allocations.
+    ///
+    /// It's the callee responsability to ensure that no allocations
+    /// (i.e., mutable references) returned by this arena still exists.
+    ///
+    /// This call will not actually free memory. So dangling pointers
+    /// can still point to accessible memory, so be careful!!.
+    pub(super) unsafe fn reset(&self, offset: usize) {
+        unsafe { self.drop_values(offset) };
+        if self.guarded {
+            self.check_canaries(offset);
+        }
+
+        // The blocks chained after the offset are released.
+        while offset < self.base.get() {
+            unsafe { self.pop_block() };
+        }
+        self.truncate(offset - self.base.get());
+    }
+
+    /// Moves the offset of the current block back to `offset`, poisoning the
+    /// released memory when guarded.
+    fn truncate(&self, offset: usize) {
+        let end = self.offset.get();
+        self.offset.set(offset);
+
+        if self.guarded && offset < end {
+            unsafe {
+                self.buffer
+                    .get()
+                    .add(offset)
+                    .write_bytes(POISON, end - offset);
+            }
+            self.poisoned_end
+                .set(usize::max(self.poisoned_end.get(), end));
+        }
+    }
+
+    /// Checks the memory from the offset to `end` wasn't written since it was
+    /// poisoned, then writes the canary of the allocation at `canary`.
+    #[track_caller]
+    fn guard_allocation(&self, canary: usize, end: usize) {
+        let checked = self.offset.get()..usize::min(end, self.poisoned_end.get());
+        if !checked.is_empty() {
+            let bytes = unsafe {
+                slice::from_raw_parts(self.buffer.get().add(checked.start).as_ptr(), checked.len())
+            };
+            if let Some(idx) = bytes.iter().position(|&byte| byte != POISON) {
+                panic!(
+                    "Arena memory at offset {} was written after it was released",
+                    self.base.get() + checked.start + idx
+                );
+            }
+        }
+
+        let canary_ptr = unsafe { self.buffer.get().add(canary).cast::<Canary>() };
+        unsafe {
+            canary_ptr.write(Canary {
+                magic: CANARY_MAGIC,
+                offset: self.base.get() + canary,
+                previous: self.last_canary.get(),
+            });
+        }
+        self.last_canary.set(Some(canary_ptr));
+    }
+
+    /// Checks the canaries of the allocations at or after `offset`, which
+    /// are about to be released.
+    #[track_caller]
+    fn check_canaries(&self, offset: usize) {
+        while let Some(canary_ptr) = self.last_canary.get() {
+            let canary = unsafe { canary_ptr.read() };
+            assert!(
+                canary.magic == CANARY_MAGIC,
+                "Arena allocation written past its end, over the canary of the next one"
+            );
+            if canary.offset < offset {
+                break;
+            }
+
+            self.last_canary.set(canary.previous);
+        }
+    }
+}
+
+unsafe impl Allocator for Arena {
+    fn allocate(
+        &self,
+        layout: std::alloc::Layout,
+    ) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        Ok(self.alloc_raw(layout.size(), layout.align()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: std::alloc::Layout) {
+        // We only really deallocate if this is the last allocation in the arena.
+        if unsafe { ptr.add(layout.size()) == self.end() } {
+            unsafe { self.truncate(self.offset.get().unchecked_sub(layout.size())) };
+        }
+    }
+
+    fn allocate_zeroed(
+        &self,
+        layout: std::alloc::Layout,
+    ) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        let ptr = self.allocate(layout)?;
+        // SAFETY: `alloc` returns a valid memory block
+        unsafe { ptr.cast::<u8>().as_ptr().write_bytes(0, layout.size()) }
+        Ok(ptr)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: std::alloc::Layout,
+        new_layout: std::alloc::Layout,
+    ) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        std::debug_assert!(
+            new_layout.size() >= old_layout.size(),
+            "`new_layout.size()` must be greater than or equal to `old_layout.size()`"
+        );
+
+        // Handle the case where we are growing the last allocation.
+        if unsafe { ptr.add(old_layout.size()) == self.end() } {
+            let end = self.offset.get() + new_layout.size() - old_layout.size();
+
+            // Past the end of the block, it's moved to a chained one.
+            if self.commit(end) {
+                self.offset.set(end);
+                return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+            }
+        } else {
+            // In debug, we want to let the developers know that their
+            // program is wasting memory
+            debug_assert!(
+                false,
+                "Attempting to grow memory that was not the last allocated"
+            );
+        }
+
+        let new_ptr = self.allocate(new_layout)?.cast::<u8>();
+
+        // SAFETY: because `new_layout.size()` must be greater than or equal to
+        // `old_layout.size()`, both the old and new memory allocation are valid for reads and
+        // writes for `old_layout.size()` bytes. Also, because the old allocation wasn't yet
+        // deallocated, it cannot overlap `new_ptr`. Thus, the call to `copy_nonoverlapping` is
+        // safe. The safety contract for `dealloc` must be upheld by the caller.
+        unsafe {
+            std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+
+        Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size()))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: std::alloc::Layout,
+        new_layout: std::alloc::Layout,
+    ) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        std::debug_assert!(
+            new_layout.size() >= old_layout.size(),
+            "`new_layout.size()` must be greater than or equal to `old_layout.size()`"
+        );
+
+        // Handle the case where we are growing the last allocation.
+        if unsafe { ptr.add(old_layout.size()) == self.end() } {
+            let end = self.offset.get() + new_layout.size() - old_layout.size();
+
+            // Past the end of the block, it's moved to a chained one.
+            if self.commit(end) {
+                self.offset.set(end);
+
+                unsafe {
+                    ptr.add(old_layout.size())
+                        .write_bytes(0, new_layout.size() - old_layout.size());
+                }
+
+                return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+            }
+        } else {
+            // In debug, we want to let the developers know that their
+            // program is wasting memory
+            debug_assert!(
+                false,
+                "Attempting to grow memory that was not the last allocated"
+            );
+        }
+
+        let new_ptr = self.allocate_zeroed(new_layout)?.cast::<u8>();
+
+        // SAFETY: because `new_layout.size()` must be greater than or equal to
+        // `old_layout.size()`, both the old and new memory allocation are valid for reads and
+        // writes for `old_layout.size()` bytes. Also, because the old allocation wasn't yet
+        // deallocated, it cannot overlap `new_ptr`. Thus, the call to `copy_nonoverlapping` is
+        // safe. The safety contract for `dealloc` must be upheld by the caller.
+        unsafe {
+            std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+
+        Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size()))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: std::alloc::Layout,
+        new_layout: std::alloc::Layout,
+    ) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        std::debug_assert!(
+            new_layout.size() <= old_layout.size(),
+            "`new_layout.size()` must be smaller than or equal to `old_layout.size()`"
+        );
+
+        self.shrink(ptr, old_layout.size(), new_layout.size());
+
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+
+    fn by_ref(&self) -> &Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        unsafe {
+            self.drop_values(0);
+            while self.previous_block.get().is_some() {
+                self.pop_block();
+            }
+            if self.is_reserved() {
+                memory::virtual_release(self.buffer.get(), self.capacity.get());
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    cell::{Cell, UnsafeCell},
+    ops::Deref,
+};
+
+use crate::arena::memory::MB;
+
+use super::{Arena, ArenaUsage};
+
+/// Scratch arenas of a thread, enough for a function to get one whatever
+/// the arenas holding the data it works on and the one it returns.
+pub const SCRATCH_ARENA_COUNT: usize = 4;
+
+struct ScratchSlot {
+    arena: UnsafeCell<Arena>,
+    /// Scratch arenas taken from this one and not dropped yet.
+    live_count: Cell<usize>,
+}
+
+impl ScratchSlot {
+    const fn new() -> Self {
+        Self {
+            arena: UnsafeCell::new(Arena::empty()),
+            live_count: Cell::new(0),
+        }
+    }
+}
+
+thread_local! {
+    // Per thread for the files analyzed in the background, released when the
+    // thread exits.
+    static SCRATCH_ARENAS: [ScratchSlot; SCRATCH_ARENA_COUNT] =
+        const { [const { ScratchSlot::new() }; SCRATCH_ARENA_COUNT] };
+}
+
+/// Allocations of a scope, released when it is dropped. The scratch arenas
+/// taken from the same arena are dropped in the reverse order.
+///
+/// It borrows a thread local, so it can't be sent to another thread.
+pub struct ScratchArena<'s> {
+    arena: &'s Arena,
+    offset: usize,
+    live_count: &'s Cell<usize>,
+    /// Number of live scratch arenas of the same arena, this one included,
+    /// when it was taken.
+    depth: usize,
+}
+
+impl Drop for ScratchArena<'_> {
+    fn drop(&mut self) {
+        debug_assert_eq!(
+            self.live_count.get(),
+            self.depth,
+            "Scratch arena dropped before the ones taken after it"
+        );
+        self.live_count.set(self.depth - 1);
+
+        unsafe {
+            self.arena.reset(self.offset);
+        }
+    }
+}
+
+impl Deref for ScratchArena<'_> {
+    type Target = Arena;
+
+    fn deref(&self) -> &Self::Target {
+        &self.arena
+    }
+}
+
+/// Returns a scratch arena of the calling thread that isn't one of `arenas`,
+/// those holding the allocations that must outlive it.
+///
+/// ```
+/// # use binary_size_explorer_core::arena::{Arena, array::Array, scratch::scratch_arena};
+/// let arena = Arena::new(1024);
+/// let outer = scratch_arena(&[&arena]);
+/// let mut kept = Array::new(&outer, 4);
+/// {
+///     // Not the outer one, whose allocations stay valid.
+///     let inner = scratch_arena(&[&arena, &outer]);
+///     let mut temporary = Array::new(&inner, 4);
+///     temporary.push(1);
+///     kept.push(temporary[0] + 1);
+/// }
+/// assert_eq!(kept.as_slice(), &[2]);
+/// ```
+pub fn scratch_arena<'a>(arenas: &[&'a Arena]) -> ScratchArena<'a> {
+    SCRATCH_ARENAS.with(|slots| unsafe {
+        let slot = slots
+            .iter()
+            .find(|slot| {
+                let sa = slot.arena.get().cast_const();
+                !arenas.iter().any(|&arena| std::ptr::eq(arena, sa))
+            })
+            .expect("All the scratch arenas are used by the caller");
+
+        let sa = slot.arena.get();
+        if !(*sa).is_reserved() {
+            debug_assert_eq!(slot.live_count.get(), 0);
+            *sa = Arena::new_guarded(128 * MB);
+        }
+
+        let depth = slot.live_count.get() + 1;
+        slot.live_count.set(depth);
+
+        ScratchArena {
+            arena: &*sa,
+            offset: (*sa).offset(),
+            live_count: &*(&slot.live_count as *const Cell<usize>),
+            depth,
+        }
+    })
+}
+
+/// Usage of the scratch arenas of the calling thread, which are empty
+/// between two uses unless a scratch arena outlives its use.
+pub fn scratch_arenas_usage() -> [ArenaUsage; SCRATCH_ARENA_COUNT] {
+    SCRATCH_ARENAS.with(|slots| {
+        slots
+            .each_ref()
+            .map(|slot| unsafe { (*slot.arena.get()).usage() })
+    })
+}
@@ -0,0 +1,132 @@
+use std::ops::{Deref, Range};
+
+use super::{
+    Arena,
+    array::{Array, GrowableArray},
+};
+
+/// Entries sorted by key, looked up by binary search.
+///
+/// ```
+/// use binary_size_explorer_core::arena::{Arena, sorted_map::SortedMap};
+///
+/// let arena = Arena::new(1024);
+/// let rows = SortedMap::from_iter_in(&arena, [(30, "c"), (10, "a"), (20, "b")]);
+/// assert_eq!(rows.get(&20), Some(&"b"));
+/// assert_eq!(rows.get(&25), None);
+/// // The last entry at or before the key.
+/// assert_eq!(rows.floor(&25), Some(&(20, "b")));
+/// assert_eq!(rows.floor(&5), None);
+/// ```
+pub struct SortedMap<'a, K, V> {
+    entries: Array<'a, (K, V)>,
+}
+
+impl<'a, K: Ord, V> SortedMap<'a, K, V> {
+    /// Sorts the entries by key, keeping the order of equal keys.
+    pub fn from_array(mut entries: Array<'a, (K, V)>) -> Self {
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self { entries }
+    }
+
+    pub fn from_iter_in(arena: &'a Arena, iter: impl IntoIterator<Item = (K, V)>) -> Self {
+        let mut entries = GrowableArray::new(arena);
+        for entry in iter {
+            entries.push(entry);
+        }
+        Self::from_array(entries.into_array())
+    }
+
+    /// Returns the value of the first entry with `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let idx = self
+            .entries
+            .partition_point(|(entry_key, _)| entry_key < key);
+        self.entries
+            .get(idx)
+            .filter(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Index of the last entry whose key is at most `key`.
+    pub fn floor_index(&self, key: &K) -> Option<usize> {
+        self.entries
+            .partition_point(|(entry_key, _)| entry_key <= key)
+            .checked_sub(1)
+    }
+
+    /// Returns the last entry whose key is at most `key`.
+    pub fn floor(&self, key: &K) -> Option<&(K, V)> {
+        self.floor_index(key).map(|idx| &self.entries[idx])
+    }
+}
+
+impl<K, V> Deref for SortedMap<'_, K, V> {
+    type Target = [(K, V)];
+
+    fn deref(&self) -> &Self::Target {
+        self.entries.as_slice()
+    }
+}
+
+/// Values of non-overlapping ranges sorted by their start, looked up by any
+/// key in a range.
+///
+/// ```
+/// use binary_size_explorer_core::arena::{Arena, sorted_map::IntervalMap};
+///
+/// let arena = Arena::new(1024);
+/// let functions = IntervalMap::from_iter_in(&arena, [(10..20, "b"), (0..8, "a")]);
+/// assert_eq!(functions.get(&4), Some(&"a"));
+/// assert_eq!(functions.get(&8), None);
+/// assert_eq!(functions.get(&19), Some(&"b"));
+/// assert_eq!(functions.get(&20), None);
+/// ```
+pub struct IntervalMap<'a, K, V> {
+    entries: Array<'a, (Range<K>, V)>,
+}
+
+impl<'a, K: Ord, V> IntervalMap<'a, K, V> {
+    /// Sorts the entries by the start of their range.
+    pub fn from_array(mut entries: Array<'a, (Range<K>, V)>) -> Self {
+        entries.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start));
+        debug_assert!(
+            entries
+                .windows(2)
+                .all(|pair| pair[0].0.end <= pair[1].0.start),
+            "Overlapping ranges"
+        );
+        Self { entries }
+    }
+
+    pub fn from_iter_in(arena: &'a Arena, iter: impl IntoIterator<Item = (Range<K>, V)>) -> Self {
+        let mut entries = GrowableArray::new(arena);
+        for entry in iter {
+            entries.push(entry);
+        }
+        Self::from_array(entries.into_array())
+    }
+
+    /// Returns the entry whose range contains `key`.
+    pub fn get_entry(&self, key: &K) -> Option<&(Range<K>, V)> {
+        let idx = self
+            .entries
+            .partition_point(|(range, _)| range.start <= *key)
+            .checked_sub(1)?;
+        let entry = &self.entries[idx];
+        (*key < entry.0.end).then_some(entry)
+    }
+
+    /// Returns the value of the range containing `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_entry(key).map(|(_, value)| value)
+    }
+}
+
+impl<K, V> Deref for IntervalMap<'_, K, V> {
+    type Target = [(Range<K>, V)];
+
+    fn deref(&self) -> &Self::Target {
+        self.entries.as_slice()
+    }
+}
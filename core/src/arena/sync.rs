@@ -0,0 +1,347 @@
+use std::{
+    alloc::Allocator,
+    ptr::{NonNull, copy_nonoverlapping},
+    slice,
+    sync::{
+        Mutex,
+        atomic::{AtomicPtr, AtomicUsize, Ordering},
+    },
+};
+
+use super::{
+    ALLOCATION_CHUNCK_SIZE, Arena,
+    array::GrowableArray,
+    memory::{self, MB},
+};
+
+/// Size of the blocks the threads take from a `SyncArena`, bigger for the
+/// slices that don't fit.
+pub const BLOCK_SIZE: usize = MB;
+
+/// An arena allocator shared by threads, bumping its offset atomically.
+///
+/// Like `Arena` it chains a new block once the reserved one is full, twice as
+/// big so the chain stays short, the capacity is only the one of the first
+/// block. The threads only take the lock to chain one.
+///
+/// ```
+/// # #![feature(allocator_api)]
+/// # use binary_size_explorer_core::arena::sync::SyncArena;
+/// let arena = SyncArena::new(64 * 1024);
+/// let slices: Vec<&[u32]> = std::thread::scope(|scope| {
+///     let workers: Vec<_> = (0..4u32)
+///         .map(|idx| {
+///             let arena = &arena;
+///             scope.spawn(move || {
+///                 // Doesn't fit in the first block, the threads chain others.
+///                 let mut values = Vec::with_capacity_in(16 * 1024, arena);
+///                 values.extend((0..16 * 1024).map(|value| value * idx));
+///                 values.leak() as &[u32]
+///             })
+///         })
+///         .collect();
+///     workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+/// });
+/// assert_eq!(slices[3][2], 6);
+/// ```
+pub struct SyncArena {
+    /// The block allocated in, owning the full ones before it.
+    current: AtomicPtr<SyncBlock>,
+    /// Held while chaining, for a single thread to chain each block.
+    chain_lock: Mutex<()>,
+}
+
+/// A block of memory of a `SyncArena`, reserved up front and committed as
+/// it is used.
+struct SyncBlock {
+    buffer: NonNull<u8>,
+    capacity: usize,
+    offset: AtomicUsize,
+    commited: AtomicUsize,
+    /// Held while committing, for a single thread to commit each chunk.
+    commit_lock: Mutex<()>,
+    /// The full block it was chained to, kept for its allocations.
+    previous: Option<Box<SyncBlock>>,
+}
+
+// SAFETY: The offsets are only bumped atomically, each thread getting its own
+// part of the blocks, which are only committed and chained under the locks
+// and released with the arena.
+unsafe impl Send for SyncArena {}
+unsafe impl Sync for SyncArena {}
+
+impl SyncArena {
+    /// Reserves a first block of `capacity` bytes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            current: AtomicPtr::new(Box::into_raw(Box::new(SyncBlock::new(capacity, None)))),
+            chain_lock: Mutex::new(()),
+        }
+    }
+
+    #[track_caller]
+    pub fn alloc_raw(&self, size: usize, align: usize) -> NonNull<[u8]> {
+        assert!(align.is_power_of_two());
+
+        loop {
+            // The blocks live as long as the arena.
+            let block = unsafe { &*self.current.load(Ordering::Acquire) };
+            if let Some(allocation) = block.alloc_raw(size, align) {
+                return allocation;
+            }
+
+            self.chain_block(block, size);
+        }
+    }
+
+    /// Chains a block with room for `size` bytes to `full_block`, unless
+    /// another thread already did.
+    fn chain_block(&self, full_block: &SyncBlock, size: usize) {
+        let _guard = self.chain_lock.lock().unwrap();
+        let current = self.current.load(Ordering::Acquire);
+        if !std::ptr::eq(current, full_block) {
+            return;
+        }
+
+        let capacity = usize::max(2 * full_block.capacity, size);
+        let previous = unsafe { Box::from_raw(current) };
+        let block = Box::new(SyncBlock::new(capacity, Some(previous)));
+        self.current.store(Box::into_raw(block), Ordering::Release);
+    }
+
+    /// Bytes reserved by the allocations so far, in all the blocks.
+    pub fn offset(&self) -> usize {
+        let _guard = self.chain_lock.lock().unwrap();
+        let mut offset = 0;
+        let mut block = Some(unsafe { &*self.current.load(Ordering::Acquire) });
+        while let Some(current) = block {
+            offset += current.offset.load(Ordering::Relaxed);
+            block = current.previous.as_deref();
+        }
+        offset
+    }
+
+    /// Returns a block of the arena for the calling thread to allocate in.
+    pub fn block(&self) -> SyncArenaBlock<'_> {
+        SyncArenaBlock {
+            arena: self,
+            buffer: NonNull::dangling(),
+            offset: 0,
+            end: 0,
+        }
+    }
+}
+
+impl SyncBlock {
+    fn new(capacity: usize, previous: Option<Box<SyncBlock>>) -> Self {
+        let capacity =
+            (usize::max(capacity, 1) + ALLOCATION_CHUNCK_SIZE - 1) & !(ALLOCATION_CHUNCK_SIZE - 1);
+
+        Self {
+            buffer: unsafe { memory::virtual_reserve(capacity) },
+            capacity,
+            offset: AtomicUsize::new(0),
+            commited: AtomicUsize::new(0),
+            commit_lock: Mutex::new(()),
+            previous,
+        }
+    }
+
+    /// Bumps the offset atomically, `None` once the block is full.
+    fn alloc_raw(&self, size: usize, align: usize) -> Option<NonNull<[u8]>> {
+        let mut offset = self.offset.load(Ordering::Relaxed);
+        let (start, end) = loop {
+            let start = (offset + align - 1) & !(align - 1);
+            let end = start + size;
+            if end > self.capacity {
+                return None;
+            }
+
+            match self.offset.compare_exchange_weak(
+                offset,
+                end,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break (start, end),
+                Err(current) => offset = current,
+            }
+        };
+
+        self.commit(end);
+        Some(unsafe { NonNull::slice_from_raw_parts(self.buffer.add(start), end - start) })
+    }
+
+    /// Commits the buffer up to `end`, if another thread didn't already.
+    fn commit(&self, end: usize) {
+        if end <= self.commited.load(Ordering::Acquire) {
+            return;
+        }
+
+        let _guard = self.commit_lock.lock().unwrap();
+        let commited = self.commited.load(Ordering::Acquire);
+        if end <= commited {
+            return;
+        }
+
+        let new_commited = usize::min(
+            (end + ALLOCATION_CHUNCK_SIZE - 1) & !(ALLOCATION_CHUNCK_SIZE - 1),
+            self.capacity,
+        );
+        unsafe {
+            memory::virtual_commit(self.buffer.add(commited), new_commited - commited);
+        }
+        self.commited.store(new_commited, Ordering::Release);
+    }
+}
+
+impl Drop for SyncArena {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(*self.current.get_mut()) });
+    }
+}
+
+impl Drop for SyncBlock {
+    fn drop(&mut self) {
+        unsafe {
+            memory::virtual_release(self.buffer, self.capacity);
+        }
+    }
+}
+
+/// The allocations of a thread in a `SyncArena`, bumped in the blocks it
+/// takes from it so the threads only contend when they take one.
+///
+/// ```
+/// # use binary_size_explorer_core::arena::sync::{SliceArena, SyncArena, BLOCK_SIZE};
+/// let arena = SyncArena::new(4 * BLOCK_SIZE);
+/// let slices: Vec<&[u32]> = std::thread::scope(|scope| {
+///     let workers: Vec<_> = (0..4u32)
+///         .map(|idx| {
+///             let mut block = arena.block();
+///             scope.spawn(move || block.alloc_from_iter((0..256).map(|value| value * idx)))
+///         })
+///         .collect();
+///     workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+/// });
+/// assert_eq!(slices[3][2], 6);
+/// ```
+pub struct SyncArenaBlock<'s> {
+    arena: &'s SyncArena,
+    /// Start of the memory taken from the arena, and the offsets in it of
+    /// the end of the allocations and of the memory.
+    buffer: NonNull<u8>,
+    offset: usize,
+    end: usize,
+}
+
+// SAFETY: The memory taken from the arena is only used by this block.
+unsafe impl Send for SyncArenaBlock<'_> {}
+
+impl SyncArenaBlock<'_> {
+    /// Takes a new block of at least `size` bytes and moves the `len` bytes
+    /// at `start` to it, returns where they are.
+    fn take_block(&mut self, size: usize, align: usize, start: usize, len: usize) -> usize {
+        let block = self.arena.alloc_raw(usize::max(size, BLOCK_SIZE), align);
+        if len > 0 {
+            unsafe {
+                copy_nonoverlapping(
+                    self.buffer.add(start).as_ptr(),
+                    block.cast::<u8>().as_ptr(),
+                    len,
+                );
+            }
+        }
+        self.buffer = block.cast();
+        self.end = block.len();
+        0
+    }
+}
+
+/// An arena a thread fills slices in, its `Arena` or a block of a
+/// `SyncArena` shared with other threads.
+pub trait SliceArena<'a> {
+    /// Allocates the values in a slice, as long as `values`, which must not
+    /// allocate in the arena. They aren't dropped, like the other
+    /// allocations of the arenas.
+    fn alloc_from_iter<T>(&mut self, values: impl IntoIterator<Item = T>) -> &'a [T];
+}
+
+impl<'a> SliceArena<'a> for &'a Arena {
+    fn alloc_from_iter<T>(&mut self, values: impl IntoIterator<Item = T>) -> &'a [T] {
+        let mut array = GrowableArray::new(self);
+        for value in values {
+            array.push(value);
+        }
+        let mut array = array.into_array();
+        array.shrink_to_fit();
+        array.to_slice()
+    }
+}
+
+impl<'s> SliceArena<'s> for SyncArenaBlock<'s> {
+    fn alloc_from_iter<T>(&mut self, values: impl IntoIterator<Item = T>) -> &'s [T] {
+        let size = std::mem::size_of::<T>();
+        let align = std::mem::align_of::<T>();
+        assert_ne!(size, 0, "Zero sized values aren't allocated in a SyncArena");
+
+        let mut start = self.offset + unsafe { self.buffer.add(self.offset) }.align_offset(align);
+        let mut len = 0;
+        for value in values {
+            if start + (len + 1) * size > self.end {
+                start = self.take_block(2 * (len + 1) * size, align, start, len * size);
+            }
+            unsafe {
+                self.buffer.add(start).cast::<T>().add(len).write(value);
+            }
+            len += 1;
+        }
+
+        if len == 0 {
+            return &[];
+        }
+
+        // The block is only filled by this thread, the slice isn't shared.
+        self.offset = start + len * size;
+        unsafe { slice::from_raw_parts(self.buffer.add(start).cast::<T>().as_ptr(), len) }
+    }
+}
+
+unsafe impl Allocator for SyncArena {
+    fn allocate(
+        &self,
+        layout: std::alloc::Layout,
+    ) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        Ok(self.alloc_raw(layout.size(), layout.align()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: std::alloc::Layout) {
+        // Another thread may have allocated after it, the memory is only
+        // released with the arena.
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BLOCK_SIZE, SliceArena, SyncArena};
+
+    #[test]
+    fn slices_outgrowing_a_block_are_moved() {
+        // Too small for the big slice, which goes in a chained block.
+        let arena = SyncArena::new(2 * BLOCK_SIZE);
+        let mut block = arena.block();
+        let small = block.alloc_from_iter(0..10u64);
+        let len = 3 * BLOCK_SIZE / size_of::<u64>();
+        let big = block.alloc_from_iter(0..len as u64);
+        let after = block.alloc_from_iter([7u8]);
+
+        assert_eq!(small, (0..10).collect::<Vec<_>>());
+        assert_eq!(big.len(), len);
+        assert!(
+            big.iter()
+                .enumerate()
+                .all(|(idx, value)| *value == idx as u64)
+        );
+        assert_eq!(after, [7]);
+    }
+}
@@ -0,0 +1,213 @@
+use hashbrown::HashSet;
+use wasmparser::{Operator, ValType};
+
+use crate::{
+    analysis::{query::Query, symbol},
+    arena::interner::Symbol,
+    data_provider_twiggy::DataProviderTwiggy,
+    dwarf::DwLineInfo,
+};
+
+#[derive(Clone, Copy)]
+pub struct FunctionProperty<'a> {
+    pub raw_name: &'a str,
+    pub monomorphization_of: Option<&'a str>,
+    /// Interned mangled name, the one of the name section.
+    pub linkage_symbol: Symbol,
+    /// Interned `monomorphization_of`, or `raw_name` for the functions that
+    /// aren't generic.
+    pub group_symbol: Symbol,
+    pub crate_name: Option<&'a str>,
+    pub shallow_size_bytes: u32,
+    pub shallow_size_percent: f32,
+    /// Estimated contribution to the gzip compressed size of the code.
+    pub compressed_size_bytes: u32,
+    pub retained_size_bytes: u32,
+    pub retained_size_percent: f32,
+}
+
+/// All the instantiations of a generic function.
+#[derive(Clone, Copy)]
+pub struct MonomorphizationGroup<'a> {
+    pub name: &'a str,
+    pub instance_count: u32,
+    pub size_bytes: u32,
+    pub size_percent: f32,
+    /// Index of the biggest instantiation of the group.
+    pub largest_instance: usize,
+}
+
+pub struct FunctionPropertyDebugInfo<'a> {
+    pub locals: &'a [(u32, ValType)],
+    pub function_ops: &'a [FunctionOp<'a>],
+}
+
+pub struct FunctionOp<'a> {
+    pub address: u64,
+    pub op: Operator<'a>,
+}
+
+impl<'a> FunctionOp<'a> {
+    pub fn new(addr: u64, op: Operator<'a>) -> FunctionOp<'a> {
+        FunctionOp { address: addr, op }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum ViewMode {
+    #[default]
+    Tops,
+    Dominators,
+}
+
+/// The functions of an analyzed file, as shown by the functions explorer.
+///
+/// The formats of `provider_registry` load their files into a provider
+/// implementing it, the functions being indexed like in the code section.
+pub trait FunctionsView {
+    fn set_view_mode(&mut self, view_mode: ViewMode);
+    /// Filters the tops and the dominators, updating the totals.
+    ///
+    /// `functions` restricts the tops to the functions with these mangled
+    /// names, the dominators don't have them.
+    fn set_filter(
+        &mut self,
+        filter: Filter,
+        options: FilterOptions,
+        functions: Option<&HashSet<&str>>,
+    );
+    /// Size of the functions matching the filter, in bytes, the one of the
+    /// root of the filtered dominators in that view.
+    fn get_total_size(&self) -> u32;
+    /// Share of the code of the functions matching the filter.
+    fn get_total_percent(&self) -> f32;
+
+    /// Locals of a function, empty for the formats without code.
+    fn get_locals_at(&self, idx: usize) -> &[(u32, ValType)];
+    /// Instructions of a function, empty for the formats without code.
+    fn get_ops_at(&self, idx: usize) -> &[FunctionOp<'_>];
+}
+
+#[derive(Debug)]
+pub enum Filter<'a> {
+    All,
+    Name {
+        name: &'a str,
+    },
+    /// Matches the demangled names in tops, and the paths of the nodes
+    /// (`crate::module::Type`) in dominators.
+    Regex {
+        regex: &'a regex::Regex,
+    },
+    /// Matches like the regex filter, ordering the tops by match score.
+    Fuzzy {
+        pattern: &'a str,
+    },
+    /// Matches the functions in tops, and the nodes in dominators by their
+    /// paths and sizes.
+    Query {
+        query: &'a Query,
+    },
+}
+
+impl<'a> Filter<'a> {
+    pub fn name_filter(name: &'a str) -> Self {
+        Filter::Name { name }
+    }
+
+    pub fn regex(regex: &'a regex::Regex) -> Self {
+        Filter::Regex { regex }
+    }
+
+    pub fn fuzzy(pattern: &'a str) -> Self {
+        Filter::Fuzzy { pattern }
+    }
+
+    pub fn query(query: &'a Query) -> Self {
+        Filter::Query { query }
+    }
+}
+
+/// Names of the functions the filter is matched against in tops.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FilterNames {
+    #[default]
+    Demangled,
+    Mangled,
+    Both,
+}
+
+impl FilterNames {
+    pub fn select<'n>(self, demangled: &'n str, mangled: &'n str) -> impl Iterator<Item = &'n str> {
+        let (demangled, mangled) = match self {
+            FilterNames::Demangled => (Some(demangled), None),
+            FilterNames::Mangled => (None, Some(mangled)),
+            FilterNames::Both => (Some(demangled), Some(mangled)),
+        };
+        demangled.into_iter().chain(mangled)
+    }
+}
+
+/// How the names of the functions are shown.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NameStyle {
+    Mangled,
+    #[default]
+    Demangled,
+    /// Demangled without the `::h0123456789abcdef` hash suffix.
+    DemangledWithoutHash,
+}
+
+impl NameStyle {
+    pub const ALL: [NameStyle; 3] = [
+        NameStyle::Mangled,
+        NameStyle::Demangled,
+        NameStyle::DemangledWithoutHash,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            NameStyle::Mangled => "Mangled",
+            NameStyle::Demangled => "Demangled",
+            NameStyle::DemangledWithoutHash => "Demangled without hash",
+        }
+    }
+
+    /// The functions without a mangled name keep their demangled one.
+    pub fn apply<'n>(self, demangled: &'n str, mangled: &'n str) -> &'n str {
+        match self {
+            NameStyle::Mangled if !mangled.is_empty() => mangled,
+            NameStyle::Mangled | NameStyle::Demangled => demangled,
+            NameStyle::DemangledWithoutHash => symbol::strip_hash(demangled),
+        }
+    }
+}
+
+/// How the filter text is matched, the regex filter being built with these
+/// options already.
+#[derive(Clone, Copy, Default, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FilterOptions {
+    pub case_sensitive: bool,
+    /// Only matches whole identifiers, `new` not matching `new_in`.
+    pub whole_word: bool,
+    /// The dominators only have the demangled paths from the debug info.
+    pub names: FilterNames,
+}
+
+/// The source lines of the code of an analyzed file, from its debug info.
+pub trait SourceCodeView {
+    /// Line the instruction at `virtual_addr` in the code section was
+    /// compiled from, `None` without debug info for it.
+    fn get_line_info_for_addr(&self, virtual_addr: u64) -> Option<&DwLineInfo>;
+}
+
+/// An analyzed file, what the formats of `provider_registry` load.
+///
+/// The analyses of the code, like the dominators or the call graph, are the
+/// ones of a WebAssembly module, they are only shown for the providers
+/// having one.
+pub trait DataProvider<'a>: FunctionsView + SourceCodeView {
+    /// The analyzed module, `None` for the files that aren't turned into one.
+    fn module(&self) -> Option<&DataProviderTwiggy<'a>>;
+    fn module_mut(&mut self) -> Option<&mut DataProviderTwiggy<'a>>;
+}
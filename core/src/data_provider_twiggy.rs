@@ -0,0 +1,1049 @@
+use crate::{
+    analysis::{
+        call_graph::CallGraph, compressed_size, dead_exports::DeadExportsReport,
+        fuzzy::FuzzyMatcher, query::QueryItem, substring::SubstringMatcher, symbol,
+    },
+    arena::{
+        Arena,
+        array::Array,
+        interner::{Interner, Symbol},
+        map::HashMap,
+        scratch::scratch_arena,
+        sorted_map::{IntervalMap, SortedMap},
+        string::String,
+        sync::{BLOCK_SIZE, SliceArena, SyncArena},
+        tree::Tree,
+        vec::Vec,
+    },
+    data_provider::{
+        DataProvider, Filter, FilterNames, FilterOptions, FunctionOp, FunctionProperty,
+        FunctionPropertyDebugInfo, FunctionsView, MonomorphizationGroup, NameStyle, SourceCodeView,
+        ViewMode,
+    },
+    dwarf::{
+        DwCompilationUnit, DwData, DwFileEntry, DwInlinedInstance, DwLineInfo, DwNode, DwNodeType,
+    },
+    progress::Phase,
+    timings::Timings,
+    tree_state::{TreeItemStateFlags, TreeState},
+    wasm::parser::{Section, WasmData},
+};
+use hashbrown::HashSet;
+use std::{
+    cmp::Reverse,
+    ops::{ControlFlow, Range},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+use wasmparser::{BinaryReader, ValType};
+use web_time::Instant;
+
+pub struct FunctionItemState {
+    /// Size of the node and of the nodes under it.
+    pub size: u32,
+    /// Size of the code of the function itself, 0 for the other nodes.
+    pub shallow_size: u32,
+}
+
+pub struct FunctionData<'a> {
+    pub function_property: FunctionProperty<'a>,
+    pub debug_info: FunctionPropertyDebugInfo<'a>,
+}
+
+pub struct DataProviderTwiggy<'a> {
+    pub wasm_data: WasmData<'a>,
+
+    pub dw_line_infos: SortedMap<'a, u64, DwLineInfo>,
+    pub dw_inlined_instances: Array<'a, DwInlinedInstance<'a>>,
+    pub dw_compilation_units: Array<'a, DwCompilationUnit<'a>>,
+    pub dw_file_entries: Array<'a, DwFileEntry<'a>>,
+    /// Range of the line infos of each function in `dw_line_infos`.
+    pub function_line_infos: Array<'a, Range<usize>>,
+
+    /// The index of the functions by the range of their body in the file.
+    pub function_ranges: IntervalMap<'a, usize, usize>,
+    /// The index of the sections by their range in the file.
+    pub section_ranges: IntervalMap<'a, usize, usize>,
+
+    /// The names of the functions, each stored once.
+    pub names: Interner<'a>,
+    /// ASCII lower case `function_names` and `function_original_names` of the
+    /// functions section, for the case insensitive filters.
+    pub lower_case_names: Array<'a, &'a str>,
+    pub lower_case_original_names: Array<'a, &'a str>,
+
+    pub view_mode: ViewMode,
+    /// How the views show the names of the functions, from the settings.
+    pub name_style: NameStyle,
+    pub raw_data: Array<'a, FunctionData<'a>>,
+    pub call_graph: CallGraph<'a>,
+
+    /// Size of the filtered dominators, the one of their root.
+    pub total_size: u32,
+    /// Code size of the filtered tops items, and their share of the code.
+    pub tops_total_size: u32,
+    pub total_percent: f32,
+
+    pub top_view_items_filtered: Vec<'a, usize>,
+    /// Code size of the filtered tops items, `None` while none is filtered
+    /// out.
+    pub filtered_code_bytes: Option<u64>,
+    /// The filtered tops items grouped by the generic function they instantiate.
+    pub top_view_groups: Vec<'a, MonomorphizationGroup<'a>>,
+    pub dominator_state: TreeState<'a, DwNode<'a>, FunctionItemState>,
+    /// Exported functions and what removing them would save.
+    pub dead_exports: DeadExportsReport,
+
+    /// Time taken by the analysis and the last filtering.
+    pub timings: Timings,
+}
+
+impl<'a> DataProviderTwiggy<'a> {
+    /// `progress` is called with the current phase of the analysis and its
+    /// done part, from 0 to 1. The analysis fails if it breaks, to cancel it.
+    #[profiling::function]
+    pub fn from_bytes(
+        arena: &'a Arena,
+        file_bytes: &'a [u8],
+        progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+    ) -> Result<Self, ()> {
+        let mut timings = Timings::default();
+
+        let wasm_data = timings
+            .measure(Phase::ParsingWasm.title(), || {
+                WasmData::from_bytes(arena, file_bytes, &|fraction| {
+                    progress(Phase::ParsingWasm, fraction)
+                })
+            })
+            .ok_or(())?;
+        let mut names = Interner::new(arena);
+        let dw_data = timings
+            .measure(Phase::ParsingDwarf.title(), || {
+                DwData::from_raw_sections(&mut names, &wasm_data.debug_sections, &|fraction| {
+                    progress(Phase::ParsingDwarf, fraction)
+                })
+            })
+            .ok_or(())?;
+
+        let mut item_count = 0;
+        let mut total_size = 0;
+        for idx in 0..wasm_data.functions_section.function_count {
+            item_count += 1;
+            total_size += wasm_data.functions_section.function_bodies[idx]
+                .as_bytes()
+                .len();
+        }
+
+        let function_ranges: std::vec::Vec<Range<usize>> = wasm_data
+            .functions_section
+            .function_bodies
+            .iter()
+            .map(|function_body| function_body.range())
+            .collect();
+        let decoded_functions = timings
+            .measure(Phase::DecodingFunctions.title(), || {
+                let thread_count = std::thread::available_parallelism().map_or(1, usize::from);
+                decode_functions(
+                    arena,
+                    wasm_data.bytes,
+                    &function_ranges,
+                    thread_count,
+                    &|fraction| progress(Phase::DecodingFunctions, fraction),
+                )
+            })
+            .ok_or(())?;
+
+        let mut raw_data = Array::new(arena, item_count);
+
+        for (idx, decoded_function) in decoded_functions.into_iter().enumerate() {
+            let name = wasm_data.functions_section.function_names[idx];
+
+            let shallow_size_bytes = wasm_data.functions_section.function_bodies[idx]
+                .as_bytes()
+                .len() as u32;
+            let shallow_size_percent = (shallow_size_bytes as f32 / total_size as f32) * 100.0;
+
+            let retained_size_bytes = wasm_data.functions_section.function_bodies[idx]
+                .as_bytes()
+                .len() as u32;
+            let retained_size_percent = (retained_size_bytes as f32 / total_size as f32) * 100.0;
+
+            let symbol = names.intern(name);
+            let raw_name = names.resolve(symbol);
+            let linkage_symbol =
+                names.intern(wasm_data.functions_section.function_original_names[idx]);
+            // Instances of a generic function share its name.
+            let generic_symbol = symbol::monomorphization_of(&scratch_arena(&[arena]), name)
+                .map(|generic_name| names.intern(generic_name));
+            let monomorphization_of = generic_symbol.map(|generic| names.resolve(generic));
+            let group_symbol = generic_symbol.unwrap_or(symbol);
+
+            raw_data.push(FunctionData {
+                function_property: FunctionProperty {
+                    raw_name,
+                    monomorphization_of,
+                    linkage_symbol,
+                    group_symbol,
+                    crate_name: symbol::crate_name(raw_name),
+                    shallow_size_bytes,
+                    shallow_size_percent,
+                    compressed_size_bytes: 0, // Estimated below, for all functions at once.
+                    retained_size_bytes,
+                    retained_size_percent,
+                },
+                debug_info: FunctionPropertyDebugInfo {
+                    locals: decoded_function.locals,
+                    function_ops: decoded_function.ops,
+                },
+            });
+        }
+
+        if progress(Phase::ComputingSizes, 0.0).is_break() {
+            return Err(());
+        }
+        let sizes_start = Instant::now();
+        let compressed_sizes =
+            compressed_size::compressed_function_sizes(wasm_data.bytes, &function_ranges);
+        for (function_data, compressed_size_bytes) in raw_data.iter_mut().zip(compressed_sizes) {
+            function_data.function_property.compressed_size_bytes = compressed_size_bytes;
+        }
+
+        let call_graph = CallGraph::new(
+            arena,
+            raw_data.len(),
+            wasm_data.imported_function_count,
+            |idx| raw_data[idx].debug_info.function_ops,
+        );
+
+        let top_view_items_filtered = Vec::new(arena, raw_data.len());
+        let top_view_groups = Vec::new(arena, raw_data.len());
+        let dominator_state: TreeState<'a, DwNode<'a>, FunctionItemState> = TreeState::from_tree(
+            arena,
+            dw_data.nodes,
+            1,
+            |item, _| FunctionItemState {
+                size: item.size,
+                shallow_size: 0,
+            },
+            |(_, a), (_, b)| b.size.cmp(&a.size),
+            |item| item.name.as_str(),
+        );
+
+        // The rows are sorted by address, each function's are contiguous.
+        let code_start = wasm_data.functions_section.range.start as u64;
+        let mut function_line_infos = Array::new(arena, function_ranges.len());
+        for range in &function_ranges {
+            let rows_before = |offset: usize| {
+                let code_offset = offset as u64 - code_start;
+                dw_data
+                    .line_infos
+                    .partition_point(|(address, _)| *address < code_offset)
+            };
+            function_line_infos.push(rows_before(range.start)..rows_before(range.end));
+        }
+
+        let function_ranges = IntervalMap::from_iter_in(
+            arena,
+            function_ranges
+                .into_iter()
+                .enumerate()
+                .map(|(idx, range)| (range, idx)),
+        );
+        let section_ranges = IntervalMap::from_iter_in(
+            arena,
+            wasm_data
+                .sections
+                .iter()
+                .enumerate()
+                .map(|(idx, section)| (section.range.clone(), idx)),
+        );
+
+        timings.record(Phase::ComputingSizes.title(), sizes_start.elapsed());
+
+        let lower_case_all = |names: &[&'a str]| {
+            let mut lower_case_names = Array::new(arena, names.len());
+            for name in names {
+                lower_case_names.push(lower_case(arena, name));
+            }
+            lower_case_names
+        };
+        let lower_case_names = lower_case_all(&wasm_data.functions_section.function_names);
+        let lower_case_original_names =
+            lower_case_all(&wasm_data.functions_section.function_original_names);
+
+        let mut provider = DataProviderTwiggy {
+            wasm_data: wasm_data,
+            dw_line_infos: dw_data.line_infos,
+            dw_inlined_instances: dw_data.inlined_instances,
+            dw_compilation_units: dw_data.compilation_units,
+            dw_file_entries: dw_data.file_entries,
+            function_line_infos,
+            function_ranges,
+            section_ranges,
+            names,
+            lower_case_names,
+            lower_case_original_names,
+            view_mode: ViewMode::Tops,
+            name_style: NameStyle::default(),
+            raw_data,
+            call_graph,
+            total_size: 0,
+            tops_total_size: 0,
+            total_percent: 0.0,
+            top_view_items_filtered,
+            filtered_code_bytes: None,
+            top_view_groups,
+            dominator_state,
+            dead_exports: DeadExportsReport::default(),
+            timings,
+        };
+        provider.dead_exports = DeadExportsReport::new(&provider);
+        provider.recompute_index_map(Filter::All, FilterOptions::default(), None);
+
+        Ok(provider)
+    }
+}
+
+/// Functions decoded by a thread taking a chunk of them at a time.
+const FUNCTIONS_PER_CHUNK: usize = 256;
+/// Functions matched by the filter on a thread at least, fewer not being
+/// worth starting one.
+const FUNCTIONS_PER_MATCHING_THREAD: usize = 4096;
+
+/// Locals and instructions of a function body, decoded in the arena of the
+/// thread decoding it.
+struct DecodedFunction<'a> {
+    locals: &'a [(u32, ValType)],
+    ops: &'a [FunctionOp<'a>],
+}
+
+impl<'a> DecodedFunction<'a> {
+    fn new(arena: &mut impl SliceArena<'a>, data: &'a [u8], range: &Range<usize>) -> Self {
+        // The function body is what we save in the range.
+        // In WASM the Code section is layed out as:
+        // CodeStart (0x0a) | CodeSectionSize(bytes) | FunctionCount | FunctionBodySize(Bytes) | LocalsSize | Locals | Operators
+        // We assume that range.start is starting with LocalsSize
+        //   and that range.end-range.start is equal FunctionBodySize(Bytes)
+        // We set the reader offset to 0 since range is an absolute offset in the wasm file.
+        // Decent reference here: https://blog.ttulka.com/learning-webassembly-2-wasm-binary-format/
+        let function_body =
+            wasmparser::FunctionBody::new(BinaryReader::new(&data[range.start..range.end], 0));
+
+        let locals = match function_body.get_locals_reader() {
+            // We must check the locals count since this reader will just read even if we have 0 locals.
+            Ok(mut locals_reader) => arena.alloc_from_iter(
+                (0..locals_reader.get_count()).filter_map(|_| locals_reader.read().ok()),
+            ),
+            Err(_) => &[],
+        };
+
+        let mut body = function_body.get_operators_reader().unwrap();
+        let ops = arena.alloc_from_iter(std::iter::from_fn(|| {
+            let (op, offset) = body.read_with_offset().ok()?;
+            Some(FunctionOp::new((range.start + offset) as u64, op))
+        }));
+
+        Self { locals, ops }
+    }
+}
+
+/// Decodes the function bodies on `thread_count` threads, each taking the
+/// next chunk of functions until none are left, and puts them back in order.
+///
+/// The workers decode them in blocks of a `SyncArena` of `arena`, the calling
+/// thread straight in `arena` when it's the only one. `progress` is called
+/// on the calling thread as the chunks are done, `None` once it breaks.
+fn decode_functions<'a>(
+    arena: &'a Arena,
+    data: &'a [u8],
+    function_ranges: &[Range<usize>],
+    thread_count: usize,
+    progress: &dyn Fn(f32) -> ControlFlow<()>,
+) -> Option<std::vec::Vec<DecodedFunction<'a>>> {
+    let chunk_count = function_ranges.len().div_ceil(FUNCTIONS_PER_CHUNK);
+    let thread_count = thread_count.min(chunk_count);
+
+    if thread_count <= 1 {
+        // Threads can't be spawned in browsers, the chunks are decoded in turn.
+        let mut arena = arena;
+        let mut functions = std::vec::Vec::with_capacity(function_ranges.len());
+        for ranges in function_ranges.chunks(FUNCTIONS_PER_CHUNK) {
+            if progress(functions.len() as f32 / function_ranges.len() as f32).is_break() {
+                return None;
+            }
+            functions.extend(
+                ranges
+                    .iter()
+                    .map(|range| DecodedFunction::new(&mut arena, data, range)),
+            );
+        }
+        return Some(functions);
+    }
+
+    // The arena chains more blocks as the threads need them.
+    let sync_arena = &*arena.alloc_with_drop(SyncArena::new(thread_count * BLOCK_SIZE));
+
+    let next_chunk = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let (decoded_sender, decoded_receiver) = mpsc::channel();
+
+    let mut chunks = std::thread::scope(|scope| {
+        let workers: std::vec::Vec<_> = (0..thread_count)
+            .map(|_| {
+                let decoded_sender = decoded_sender.clone();
+                let (next_chunk, cancelled) = (&next_chunk, &cancelled);
+                scope.spawn(move || {
+                    let mut block = sync_arena.block();
+                    let mut chunks = std::vec::Vec::new();
+                    while !cancelled.load(Ordering::Relaxed) {
+                        let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        let Some(ranges) = function_ranges.chunks(FUNCTIONS_PER_CHUNK).nth(chunk)
+                        else {
+                            break;
+                        };
+
+                        let functions: std::vec::Vec<DecodedFunction> = ranges
+                            .iter()
+                            .map(|range| DecodedFunction::new(&mut block, data, range))
+                            .collect();
+                        // Only fails once the calling thread stopped waiting.
+                        let _ = decoded_sender.send(functions.len());
+                        chunks.push((chunk, functions));
+                    }
+                    chunks
+                })
+            })
+            .collect();
+        // The workers hold the other senders, the channel closes once they
+        // are all done.
+        drop(decoded_sender);
+
+        let mut decoded_count = 0;
+        for count in decoded_receiver {
+            decoded_count += count;
+            let fraction = decoded_count as f32 / function_ranges.len() as f32;
+            if progress(fraction).is_break() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Failed to decode the functions"))
+            .collect::<std::vec::Vec<_>>()
+    });
+
+    if cancelled.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    chunks.sort_unstable_by_key(|(chunk, _)| *chunk);
+    Some(
+        chunks
+            .into_iter()
+            .flat_map(|(_, functions)| functions)
+            .collect(),
+    )
+}
+
+impl DataProviderTwiggy<'_> {
+    /// Returns the function whose body contains `code_offset`, an offset in
+    /// the code section like the addresses used by DWARF.
+    pub fn function_at_code_offset(&self, code_offset: u64) -> Option<usize> {
+        let address = self.wasm_data.functions_section.range.start + code_offset as usize;
+        self.function_at(address)
+    }
+
+    /// Returns the function whose body contains the file offset `address`.
+    pub fn function_at(&self, address: usize) -> Option<usize> {
+        self.function_ranges.get(&address).copied()
+    }
+
+    /// Returns the section containing the file offset `address`.
+    pub fn section_at(&self, address: usize) -> Option<&Section<'_>> {
+        let idx = *self.section_ranges.get(&address)?;
+        Some(&self.wasm_data.sections[idx])
+    }
+
+    /// Returns the line infos of the function's body, by address.
+    pub fn line_infos_of(&self, function: usize) -> &[(u64, DwLineInfo)] {
+        &self.dw_line_infos[self.function_line_infos[function].clone()]
+    }
+
+    /// Returns the function of mangled name `name`.
+    pub fn function_with_linkage_name(&self, name: &str) -> Option<usize> {
+        let symbol = self.names.get(name)?;
+        self.raw_data
+            .iter()
+            .position(|function_data| function_data.function_property.linkage_symbol == symbol)
+    }
+
+    /// Name of the function in the style picked in the settings.
+    pub fn function_name(&self, function: usize) -> &str {
+        self.function_name_in(self.name_style, function)
+    }
+
+    pub fn function_name_in(&self, name_style: NameStyle, function: usize) -> &str {
+        name_style.apply(
+            self.raw_data[function].function_property.raw_name,
+            self.wasm_data.functions_section.function_original_names[function],
+        )
+    }
+
+    /// Returns the names of the functions inlined at `code_offset`, from the
+    /// one inlined into the concrete function to the innermost one.
+    pub fn inlined_stack_at(&self, code_offset: u64) -> std::vec::Vec<&str> {
+        let Some(function) = self.function_at_code_offset(code_offset) else {
+            return std::vec::Vec::new();
+        };
+        let functions_section = &self.wasm_data.functions_section;
+        let low_pc = (functions_section.function_bodies[function].range().start
+            - functions_section.range.start) as u64;
+
+        // Inlined instances are sorted by address, the function's are contiguous.
+        let inlined_instances = &self.dw_inlined_instances;
+        let first = inlined_instances.partition_point(|instance| instance.low_pc < low_pc);
+        let mut stack: std::vec::Vec<&DwInlinedInstance> = inlined_instances[first..]
+            .iter()
+            .take_while(|instance| instance.low_pc <= code_offset)
+            .filter(|instance| code_offset < instance.high_pc)
+            .collect();
+        stack.sort_by_key(|instance| instance.depth);
+
+        stack.into_iter().map(|instance| instance.name).collect()
+    }
+
+    /// Returns the DWARF compilation unit the function was compiled in, `None`
+    /// without debug info.
+    pub fn compilation_unit_of(&self, function: usize) -> Option<&DwCompilationUnit<'_>> {
+        let functions_section = &self.wasm_data.functions_section;
+        let code_offset = (functions_section.function_bodies[function].range().start
+            - functions_section.range.start) as u64;
+
+        // The unit range covering the function is the last one starting before it.
+        let idx = self
+            .dw_compilation_units
+            .partition_point(|compilation_unit| compilation_unit.low_pc <= code_offset)
+            .checked_sub(1)?;
+        let compilation_unit = &self.dw_compilation_units[idx];
+
+        (code_offset < compilation_unit.high_pc).then_some(compilation_unit)
+    }
+
+    /// Whether the `names` of each function contain the text of `matcher`,
+    /// the functions being split between all the cores.
+    fn functions_matching(
+        &self,
+        matcher: &SubstringMatcher,
+        names: FilterNames,
+    ) -> std::vec::Vec<bool> {
+        let function_section = &self.wasm_data.functions_section;
+        let function_names: &[&str] = &function_section.function_names;
+        let original_names: &[&str] = &function_section.function_original_names;
+        let lower_case_names: &[&str] = &self.lower_case_names;
+        let lower_case_original_names: &[&str] = &self.lower_case_original_names;
+
+        let function_count = function_section.function_count;
+        let thread_count = std::thread::available_parallelism().map_or(1, usize::from);
+        let chunk_size = function_count
+            .div_ceil(thread_count)
+            .max(FUNCTIONS_PER_MATCHING_THREAD);
+
+        let match_chunk = move |chunk_idx: usize, chunk: &mut [bool]| {
+            for (idx, is_match) in (chunk_idx * chunk_size..).zip(chunk) {
+                let mut names = names
+                    .select(function_names[idx], original_names[idx])
+                    .zip(names.select(lower_case_names[idx], lower_case_original_names[idx]));
+                *is_match =
+                    names.any(|(name, lower_case_name)| matcher.matches(name, lower_case_name));
+            }
+        };
+
+        let mut matches = std::vec::Vec::new();
+        matches.resize(function_count, false);
+        if function_count <= chunk_size {
+            // A single chunk, matched here rather than on a thread, which
+            // browsers can't spawn.
+            match_chunk(0, &mut matches);
+        } else {
+            std::thread::scope(|scope| {
+                for (chunk_idx, chunk) in matches.chunks_mut(chunk_size).enumerate() {
+                    scope.spawn(move || match_chunk(chunk_idx, chunk));
+                }
+            });
+        }
+        matches
+    }
+
+    /// This functions recomputes the index map used to return
+    /// the correct item/size information to the active view.
+    ///
+    /// Whenever the view mode or filter changes, this function
+    /// should be called to update the internal state shared
+    /// between tops and dominators view modes.
+    #[profiling::function]
+    fn recompute_index_map<'a>(
+        &mut self,
+        filter: Filter<'a>,
+        options: FilterOptions,
+        functions: Option<&HashSet<&str>>,
+    ) {
+        let start = Instant::now();
+        let function_section = &self.wasm_data.functions_section;
+
+        // Update tops
+        {
+            self.top_view_items_filtered.clear();
+            self.tops_total_size = 0;
+            self.total_percent = 0.0;
+
+            let mut fuzzy_matcher = match &filter {
+                Filter::Fuzzy { pattern } => {
+                    Some(FuzzyMatcher::new(pattern, options.case_sensitive))
+                }
+                _ => None,
+            };
+            // The functions are compared by the ids of their names.
+            let functions: Option<HashSet<Symbol>> = functions.map(|functions| {
+                functions
+                    .iter()
+                    .filter_map(|name| self.names.get(name))
+                    .collect()
+            });
+
+            // The substring filter is matched on all the cores beforehand.
+            let matched_names = match &filter {
+                Filter::Name { name } => {
+                    self.functions_matching(&SubstringMatcher::new(name, &options), options.names)
+                }
+                _ => std::vec::Vec::new(),
+            };
+
+            let scores_scratch = scratch_arena(&[]);
+            let mut match_scores = Vec::new(&scores_scratch, function_section.function_count);
+            match_scores.resize(function_section.function_count, 0);
+
+            for idx in 0..function_section.function_count {
+                if let Some(functions) = &functions
+                    && !functions.contains(&self.raw_data[idx].function_property.linkage_symbol)
+                {
+                    continue;
+                }
+
+                let function_size = function_section.function_sizes[idx];
+                let function_names = options.names.select(
+                    function_section.function_names[idx],
+                    function_section.function_original_names[idx],
+                );
+
+                // The best score of the names, 0 for the filters without one.
+                let mut score = None;
+                for function_name in function_names {
+                    let name_score = match &filter {
+                        Filter::Name { .. } => matched_names[idx].then_some(0),
+                        Filter::Regex { regex } => regex.is_match(function_name).then_some(0),
+                        Filter::Fuzzy { .. } => fuzzy_matcher
+                            .as_mut()
+                            .and_then(|matcher| matcher.score(function_name)),
+                        // The query names its fields, whatever names are selected.
+                        Filter::Query { query } => query
+                            .matches(&QueryItem::function(
+                                &self.raw_data[idx].function_property,
+                                function_section.function_names[idx],
+                                function_section.function_original_names[idx],
+                            ))
+                            .then_some(0),
+                        Filter::All => Some(0),
+                    };
+                    score = score.max(name_score);
+                }
+
+                if let Some(score) = score {
+                    match_scores[idx] = score;
+                    self.top_view_items_filtered.push(idx);
+                    self.tops_total_size += function_size;
+                }
+            }
+
+            self.filtered_code_bytes = (self.top_view_items_filtered.len()
+                < function_section.function_count)
+                .then_some(self.tops_total_size as u64);
+
+            let Self {
+                raw_data,
+                top_view_items_filtered: items_filtered,
+                ..
+            } = self;
+
+            if fuzzy_matcher.is_some() {
+                // Best matches first, then the biggest.
+                items_filtered.sort_by_key(|&idx| {
+                    Reverse((
+                        match_scores[idx],
+                        raw_data[idx].function_property.retained_size_bytes,
+                    ))
+                });
+            } else {
+                items_filtered.sort_by(|a, b| {
+                    raw_data[*a]
+                        .function_property
+                        .retained_size_bytes
+                        .cmp(&raw_data[*b].function_property.retained_size_bytes)
+                });
+            }
+        }
+
+        // Update monomorphization groups
+        {
+            let Self {
+                raw_data,
+                top_view_items_filtered,
+                top_view_groups,
+                ..
+            } = self;
+
+            let scratch = scratch_arena(&[]);
+            let mut group_lookup =
+                HashMap::<Symbol, usize>::new(&scratch, top_view_items_filtered.len());
+
+            top_view_groups.clear();
+            for &idx in top_view_items_filtered.iter() {
+                let property = &raw_data[idx].function_property;
+                let name = property.monomorphization_of.unwrap_or(property.raw_name);
+
+                match group_lookup.get(&property.group_symbol).copied() {
+                    Some(group_idx) => {
+                        let group = &mut top_view_groups[group_idx];
+                        group.instance_count += 1;
+                        group.size_bytes += property.retained_size_bytes;
+                        group.size_percent += property.retained_size_percent;
+
+                        let largest = &raw_data[group.largest_instance].function_property;
+                        if property.retained_size_bytes >= largest.retained_size_bytes {
+                            group.largest_instance = idx;
+                        }
+                    }
+                    None => {
+                        group_lookup.insert(property.group_symbol, top_view_groups.len());
+                        top_view_groups.push(MonomorphizationGroup {
+                            name,
+                            instance_count: 1,
+                            size_bytes: property.retained_size_bytes,
+                            size_percent: property.retained_size_percent,
+                            largest_instance: idx,
+                        });
+                    }
+                }
+            }
+
+            // The groups of a fuzzy search stay ordered by their best match.
+            if !matches!(filter, Filter::Fuzzy { .. }) {
+                top_view_groups.sort_by_key(|group| group.size_bytes);
+            }
+        }
+
+        self.total_percent =
+            100.0 * self.tops_total_size as f32 / function_section.size_in_bytes as f32;
+
+        // Update dominators
+        {
+            fill_tree_view_state(
+                &self.wasm_data,
+                &mut self.dominator_state,
+                &filter,
+                &options,
+            );
+
+            if !self.dominator_state.row_indices.is_empty() {
+                self.total_size = self.dominator_state.items_ui_data[0].size;
+            } else {
+                self.total_size = 0;
+            }
+        }
+
+        self.timings
+            .record("Filtering the functions", start.elapsed());
+    }
+}
+
+fn fill_tree_view_state<'a>(
+    wams_data: &WasmData<'a>,
+    state: &mut TreeState<'a, DwNode<'a>, FunctionItemState>,
+    filter: &Filter,
+    options: &FilterOptions,
+) {
+    profiling::scope!("fill_tree_view_state");
+
+    match filter {
+        Filter::All => {
+            for idx in 0..state.items_state.len() {
+                state.items_state[idx]
+                    .flags
+                    .insert(TreeItemStateFlags::VISIBLE);
+                state.items_state[idx]
+                    .flags
+                    .remove(TreeItemStateFlags::FORCE_OPENED);
+            }
+        }
+        Filter::Name { .. }
+        | Filter::Regex { .. }
+        | Filter::Fuzzy { .. }
+        | Filter::Query { .. } => {
+            let mut path = std::string::String::new();
+            let mut fuzzy_matcher = match filter {
+                Filter::Fuzzy { pattern } => {
+                    Some(FuzzyMatcher::new(pattern, options.case_sensitive))
+                }
+                _ => None,
+            };
+            let substring_matcher = match filter {
+                Filter::Name { name } => Some(SubstringMatcher::new(name, options)),
+                _ => None,
+            };
+
+            for idx in 0..state.items_state.len() {
+                let scratch = scratch_arena(&[]);
+                let visible = match filter {
+                    Filter::Name { .. } => {
+                        // let fn_index = state.tree[idx].value.fn_index;
+                        let fn_index = u32::MAX;
+                        let node_name = if fn_index != u32::MAX {
+                            wams_data.functions_section.function_names[fn_index as usize]
+                        } else {
+                            state.tree[idx].value.name.as_str()
+                        };
+                        substring_matcher.as_ref().is_some_and(|matcher| {
+                            matcher.matches(node_name, lower_case(&scratch, node_name))
+                        })
+                    }
+                    Filter::Regex { regex } => {
+                        node_path(&state.tree, idx, &mut path);
+                        regex.is_match(&path)
+                    }
+                    Filter::Fuzzy { .. } => {
+                        node_path(&state.tree, idx, &mut path);
+                        fuzzy_matcher
+                            .as_mut()
+                            .and_then(|matcher| matcher.score(&path))
+                            .is_some()
+                    }
+                    Filter::Query { query } => {
+                        node_path(&state.tree, idx, &mut path);
+                        let size = state.tree[idx].value.size;
+                        query.matches(&QueryItem {
+                            name: &path,
+                            mangled_name: "",
+                            crate_name: path.split("::").next(),
+                            monomorphization_of: None,
+                            retained_size_bytes: size,
+                            shallow_size_bytes: size,
+                            compressed_size_bytes: 0,
+                        })
+                    }
+                    Filter::All => true,
+                };
+
+                state.items_state[idx]
+                    .flags
+                    .set(TreeItemStateFlags::FORCE_OPENED, false);
+                state.items_state[idx]
+                    .flags
+                    .set(TreeItemStateFlags::VISIBLE, visible);
+
+                if visible {
+                    // Force parents to be visible
+                    let mut cur_idx = state.tree[idx].parent.unwrap_or(0);
+                    while cur_idx > 0 {
+                        let cur_node = &mut state.items_state[cur_idx];
+                        cur_node.flags.set(TreeItemStateFlags::FORCE_OPENED, true);
+                        cur_node.flags.set(TreeItemStateFlags::VISIBLE, true);
+                        cur_idx = state.tree[cur_idx].parent.unwrap_or(0);
+                    }
+                }
+            }
+        }
+    };
+
+    // Reset size and then recompute it by just taking visible nodes into account
+    for idx in 0..state.items_ui_data.len() {
+        state.items_ui_data[idx].size = 0;
+        state.items_ui_data[idx].shallow_size = 0;
+    }
+
+    for idx in (0..state.tree.len()).rev() {
+        if !state.items_state[idx].visible() {
+            continue;
+        }
+
+        let item_ui_data = &mut state.items_ui_data[idx];
+
+        let dw_node = &state.tree[idx].value;
+
+        if matches!(
+            dw_node.ty,
+            DwNodeType::FunctionInstance | DwNodeType::FunctionInlinedInstance
+        ) {
+            item_ui_data.size = dw_node.size;
+            item_ui_data.shallow_size = dw_node.size;
+        }
+
+        if let Some(parent_idx) = state.tree[idx].parent {
+            state.items_ui_data[parent_idx].size += state.items_ui_data[idx].size;
+        }
+    }
+
+    state.recompute_indices();
+}
+
+/// ASCII lower case `name`, only copied to the arena if it has upper case
+/// letters.
+fn lower_case<'s>(arena: &'s Arena, name: &'s str) -> &'s str {
+    if !name.bytes().any(|byte| byte.is_ascii_uppercase()) {
+        return name;
+    }
+
+    let mut lower_case = String::new(arena, name.len());
+    lower_case.push_str(name);
+    lower_case.make_ascii_lowercase();
+    lower_case.to_str()
+}
+
+/// Writes the path of a node from the top level namespace, like
+/// `crate::module::Type::function`, to `path`.
+pub fn node_path(tree: &Tree<DwNode>, idx: usize, path: &mut std::string::String) {
+    path.clear();
+
+    let mut names = std::vec::Vec::new();
+    let mut cur_idx = idx;
+    while let Some(parent_idx) = tree.get_parent_index(cur_idx) {
+        names.push(tree[cur_idx].value.name.as_str());
+        cur_idx = parent_idx;
+    }
+
+    for (name_idx, name) in names.iter().rev().enumerate() {
+        if name_idx > 0 {
+            path.push_str("::");
+        }
+        path.push_str(name);
+    }
+}
+
+impl<'a> FunctionsView for DataProviderTwiggy<'a> {
+    fn set_view_mode(&mut self, view_mode: ViewMode) {
+        if self.view_mode == view_mode {
+            return;
+        }
+
+        self.view_mode = view_mode;
+    }
+
+    fn set_filter<'b>(
+        &mut self,
+        filter: Filter<'b>,
+        options: FilterOptions,
+        functions: Option<&HashSet<&str>>,
+    ) {
+        self.recompute_index_map(filter, options, functions);
+    }
+
+    fn get_total_size(&self) -> u32 {
+        match self.view_mode {
+            ViewMode::Tops => self.tops_total_size,
+            ViewMode::Dominators => self.total_size,
+        }
+    }
+
+    fn get_total_percent(&self) -> f32 {
+        self.total_percent
+    }
+
+    fn get_locals_at(&self, idx: usize) -> &[(u32, ValType)] {
+        self.raw_data[idx].debug_info.locals
+    }
+
+    fn get_ops_at(&self, idx: usize) -> &[FunctionOp<'a>] {
+        self.raw_data[idx].debug_info.function_ops
+    }
+}
+
+impl<'a> DataProvider<'a> for DataProviderTwiggy<'a> {
+    fn module(&self) -> Option<&DataProviderTwiggy<'a>> {
+        Some(self)
+    }
+
+    fn module_mut(&mut self) -> Option<&mut DataProviderTwiggy<'a>> {
+        Some(self)
+    }
+}
+
+impl<'a> SourceCodeView for DataProviderTwiggy<'a> {
+    fn get_line_info_for_addr(&self, virtual_addr: u64) -> Option<&DwLineInfo> {
+        let code_section_start = self.wasm_data.functions_section.range.start as u64;
+        let adjusted_addr = virtual_addr - code_section_start;
+        let function = self.function_at_code_offset(adjusted_addr)?;
+
+        // The row covering the address is the last one starting before it,
+        // the first one of the function for the code before its first row.
+        let line_infos = self.line_infos_of(function);
+        let idx = line_infos
+            .partition_point(|(address, _)| *address <= adjusted_addr)
+            .saturating_sub(1);
+        line_infos.get(idx).map(|(_, line_info)| line_info)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arena::memory::MB;
+
+    use super::*;
+
+    #[test]
+    fn test_a_simple_wasm_function_that_returns_42() {
+        let function_bytes = [0, 65, 42, 15, 11];
+
+        let arena = Arena::new(2 * MB);
+        let DecodedFunction { locals, ops } =
+            DecodedFunction::new(&mut &arena, &function_bytes, &(0..5));
+        assert_eq!(locals.len(), 0);
+        assert_eq!(ops.len(), 3);
+
+        let ref_ops = ["I32Const { value: 42 }", "Return", "End"];
+
+        for idx in 0..3 {
+            let ops = format!("{:?}", ops[idx].op);
+            assert_eq!(ops, ref_ops[idx]);
+        }
+    }
+
+    #[test]
+    fn functions_decoded_on_threads_are_in_order() {
+        let function_bytes = [0, 65, 42, 15, 11].repeat(1000);
+        let function_ranges: std::vec::Vec<_> = (0..1000).map(|idx| idx * 5..idx * 5 + 5).collect();
+
+        let arena = Arena::new(2 * MB);
+        let functions = decode_functions(&arena, &function_bytes, &function_ranges, 4, &|_| {
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(functions.len(), 1000);
+        for (function, range) in functions.iter().zip(&function_ranges) {
+            let addresses: std::vec::Vec<_> = function.ops.iter().map(|op| op.address).collect();
+            assert_eq!(
+                addresses,
+                [
+                    range.start as u64 + 1,
+                    range.start as u64 + 3,
+                    range.start as u64 + 4
+                ]
+            );
+        }
+
+        let cancelled = decode_functions(&arena, &function_bytes, &function_ranges, 4, &|_| {
+            ControlFlow::Break(())
+        });
+        assert!(cancelled.is_none());
+    }
+}
@@ -1,18 +1,32 @@
 use std::{
     ffi::OsStr,
     hash::{DefaultHasher, Hash, Hasher},
+    ops::ControlFlow,
     path::Path,
-    time::Instant,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
 };
 
 use gimli::{
-    AttributeValue, DW_AT_high_pc, DW_AT_inline, DW_AT_linkage_name, DW_AT_low_pc, DW_AT_name,
-    DW_AT_specification, DW_INL_inlined, DW_TAG_namespace, DW_TAG_structure_type,
-    DW_TAG_subprogram, EndianSlice, LittleEndian, UnitType,
+    AttributeValue, DW_AT_abstract_origin, DW_AT_high_pc, DW_AT_inline, DW_AT_linkage_name,
+    DW_AT_low_pc, DW_AT_name, DW_AT_specification, DW_INL_inlined, DW_TAG_inlined_subroutine,
+    DW_TAG_namespace, DW_TAG_structure_type, DW_TAG_subprogram, EndianSlice, LittleEndian,
+    UnitHeader, UnitOffset, UnitRef, UnitType,
 };
-use hashbrown::{DefaultHashBuilder, HashMap};
 
-use crate::arena::{Arena, array::Array, scratch::scratch_arena, string::String, tree::Tree};
+use crate::arena::{
+    Arena,
+    array::Array,
+    interner::Interner,
+    map::HashMap,
+    scratch::scratch_arena,
+    sorted_map::SortedMap,
+    string::String,
+    sync::{BLOCK_SIZE, SliceArena, SyncArena},
+    tree::Tree,
+};
 
 #[derive(Clone, Copy)]
 pub struct DwNode<'a> {
@@ -21,7 +35,8 @@ pub struct DwNode<'a> {
     pub size: u32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DwNodeType {
     Namespace,
     Struct,
@@ -43,16 +58,39 @@ pub struct DwFileEntry<'a> {
 
     /// The path to the file relative to the directory.
     pub file: &'a Path,
+
+    /// Text of the file embedded in the debug info, which DWARF 5 allows.
+    pub source: Option<&'a str>,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct DwLineInfo {
-    pub address: u64,
     pub file_entry_idx: usize,
     pub line: usize,
     pub col: usize,
 }
 
+/// A function inlined into another one (a `DW_TAG_inlined_subroutine`).
+///
+/// Inlined instances with non-contiguous address ranges get one entry per range.
+#[derive(Clone, Copy, Debug)]
+pub struct DwInlinedInstance<'a> {
+    /// Demangled name of the inlined function.
+    pub name: &'a str,
+    /// Address range of the inlined code, relative to the code section like `DwLineInfo::address`.
+    pub low_pc: u64,
+    pub high_pc: u64,
+    /// Number of inlined instances this one is nested in, 0 when inlined
+    /// straight into a concrete function.
+    pub depth: u32,
+}
+
+impl DwInlinedInstance<'_> {
+    pub fn size(&self) -> u64 {
+        self.high_pc - self.low_pc
+    }
+}
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct SymbolName<'a> {
     parent_hash: u64,
@@ -82,18 +120,42 @@ impl<'a> SymbolName<'a> {
     }
 }
 
+/// An address range of a compilation unit (a `DW_TAG_compile_unit`), which
+/// is an object file for the linker.
+///
+/// Compilation units with non-contiguous address ranges get one entry per range.
+#[derive(Clone, Copy, Debug)]
+pub struct DwCompilationUnit<'a> {
+    /// Name of the main source file, `src/lib.rs/@/crate.hash-cgu.0` for Rust.
+    pub name: &'a str,
+    pub comp_dir: &'a Path,
+    /// Address range, relative to the code section like the line infos.
+    pub low_pc: u64,
+    pub high_pc: u64,
+}
+
 pub struct DwData<'a> {
     pub nodes: Tree<'a, DwNode<'a>>,
-    pub line_infos: Array<'a, DwLineInfo>,
+    /// By address, relative to the code section.
+    pub line_infos: SortedMap<'a, u64, DwLineInfo>,
     pub file_entries: Array<'a, DwFileEntry<'a>>,
+    /// Sorted by address.
+    pub inlined_instances: Array<'a, DwInlinedInstance<'a>>,
+    /// Sorted by address.
+    pub compilation_units: Array<'a, DwCompilationUnit<'a>>,
 }
 
 impl<'a> DwData<'a> {
+    /// `progress` is called with the parsed part of the compilation units,
+    /// from 0 to 1, at the start of the units. Returns `None` if it breaks, to
+    /// cancel the parsing.
+    /// The names of the inlined functions are interned in `names`.
     pub fn from_raw_sections(
-        arena: &'a Arena,
+        names: &mut Interner<'a>,
         debug_sections: &Vec<(&'a str, &'a [u8]), &'a Arena>,
-    ) -> Self {
-        let start = Instant::now();
+        progress: &dyn Fn(f32) -> ControlFlow<()>,
+    ) -> Option<Self> {
+        let arena = names.arena();
         let dwarf = gimli::Dwarf::load::<_, ()>(|section_id| {
             let section = debug_sections
                 .iter()
@@ -106,36 +168,72 @@ impl<'a> DwData<'a> {
 
         let root_symbol_name = SymbolName::root();
 
-        let scratch = scratch_arena(&[arena]);
-        let mut dw_node_stack = Array::new(&scratch, 128);
-        let mut dw_node_name_lookup =
-            HashMap::<SymbolName<'a>, usize, DefaultHashBuilder, &Arena>::with_capacity_in(
-                0, &scratch,
-            );
-
-        let mut line_info_count = 0;
-        let mut file_entry_count = 0;
-
-        // First pass: compute number of file entries and line infos
+        // The line programs are executed first, on all the cores, which is
+        // about a third of the time, then the DIEs are walked in order.
+        let debug_info_len = debug_sections
+            .iter()
+            .find(|section| section.0 == ".debug_info")
+            .map_or(0, |section| section.1.len())
+            .max(1);
+        let unit_progress = |unit_header: &UnitHeader<_>| {
+            let offset = unit_header
+                .offset()
+                .as_debug_info_offset()
+                .map_or(0, |offset| offset.0);
+            let fraction = offset as f32 / debug_info_len as f32;
+            progress(1.0 / 3.0 + 2.0 * fraction / 3.0)
+        };
+
+        let mut unit_headers = std::vec::Vec::new();
         let mut units = dwarf.units();
         while let Ok(Some(unit_header)) = units.next() {
-            if unit_header.type_() != UnitType::Compilation {
-                continue;
+            if unit_header.type_() == UnitType::Compilation {
+                unit_headers.push(unit_header);
+            } else {
+                log::warn!("Unity type '{:?}' not supported!", unit_header.type_());
             }
+        }
 
-            let unit = dwarf.unit(unit_header).unwrap();
-            let Some(program) = unit.line_program.clone() else {
-                continue;
-            };
-            file_entry_count += program.header().file_names().len();
+        let scratch = scratch_arena(&[arena]);
+        let line_rows_progress = |fraction: f32| progress(fraction / 3.0);
+        let thread_count = std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(unit_headers.len());
+        // The rows are copied with the files of all the units indexed, the
+        // arena they are executed in is released once they are parsed.
+        let sync_arena;
+        let unit_line_rows = if thread_count <= 1 {
+            let mut scratch = &*scratch;
+            let mut unit_line_rows = std::vec::Vec::with_capacity(unit_headers.len());
+            for (idx, unit_header) in unit_headers.iter().enumerate() {
+                if line_rows_progress(idx as f32 / unit_headers.len() as f32).is_break() {
+                    return None;
+                }
+                unit_line_rows.push(line_rows(&mut scratch, &dwarf, *unit_header));
+            }
+            unit_line_rows
+        } else {
+            // The arena chains more blocks as the threads need them.
+            sync_arena = SyncArena::new(thread_count * BLOCK_SIZE);
+            line_rows_on_threads(
+                &sync_arena,
+                &dwarf,
+                &unit_headers,
+                thread_count,
+                &line_rows_progress,
+            )?
+        };
 
-            let (com_program, sequences) = program.clone().sequences().unwrap();
-            for sequence in &sequences {
-                let mut resumed_rows = com_program.resume_from(sequence);
+        let mut dw_node_stack = Array::new(&scratch, 128);
+        let mut dw_node_name_lookup = HashMap::<SymbolName<'a>, usize>::new(&scratch, 0);
 
-                while let Some(_) = resumed_rows.next_row().unwrap() {
-                    line_info_count += 1;
-                }
+        let line_info_count = unit_line_rows.iter().map(|rows| rows.len()).sum();
+        let mut file_entry_count = 0;
+        for unit_header in &unit_headers {
+            if let Ok(unit) = dwarf.unit(*unit_header)
+                && let Some(program) = &unit.line_program
+            {
+                file_entry_count += program.header().file_names().len();
             }
         }
 
@@ -152,19 +250,17 @@ impl<'a> DwData<'a> {
             },
         );
 
-        // Second pass: actually process line info, file entries and DIEs.
-        let mut units = dwarf.units();
-        while let Ok(Some(unit_header)) = units.next() {
-            if unit_header.type_() != UnitType::Compilation {
-                println!("Unity type '{:?}' not supported!", unit_header.type_());
-                continue;
+        // Process the file entries, line infos and DIEs.
+        for (unit_header, unit_line_rows) in unit_headers.iter().zip(unit_line_rows) {
+            if unit_progress(unit_header).is_break() {
+                return None;
             }
 
-            let unit = dwarf.unit(unit_header).unwrap();
+            let unit = dwarf.unit(*unit_header).unwrap();
             let unit_ref = unit.unit_ref(&dwarf);
 
             let Some(program) = unit_ref.line_program.clone() else {
-                println!(
+                log::warn!(
                     "Skipping unit '{}': missing line program!",
                     unit.name.map(dw_slice_to_str).unwrap_or("")
                 );
@@ -198,35 +294,30 @@ impl<'a> DwData<'a> {
                     Path::new("")
                 };
 
+                // Files without embedded text have an empty source.
+                let source = file_name
+                    .source()
+                    .and_then(|source| unit_ref.attr_string(source).ok())
+                    .map(dw_slice_to_str)
+                    .filter(|source| !source.is_empty());
+
                 file_entries.push(DwFileEntry {
                     base_directory,
                     directory,
                     file,
+                    source,
                 });
             }
 
-            // Execute the line program.
-            let (com_program, sequences) = program.clone().sequences().unwrap();
-            for sequence in &sequences {
-                let mut resumed_rows = com_program.resume_from(sequence);
-
-                while let Some((_, row)) = resumed_rows.next_row().unwrap() {
-                    let column = match row.column() {
-                        gimli::ColumnType::LeftEdge => 0,
-                        gimli::ColumnType::Column(non_zero) => non_zero.get(),
-                    };
-
-                    let address = row.address();
-                    let file_entry_idx = row.file_index() as usize;
-                    let line = row.line().map(|line| line.get()).unwrap_or(0) as usize;
-
-                    line_infos.push(DwLineInfo {
-                        address,
-                        file_entry_idx: file_base_idx + file_entry_idx,
-                        line,
-                        col: column as usize,
-                    });
-                }
+            // The rows index the files of their unit.
+            for (address, line_info) in unit_line_rows {
+                line_infos.push((
+                    *address,
+                    DwLineInfo {
+                        file_entry_idx: file_base_idx + line_info.file_entry_idx,
+                        ..*line_info
+                    },
+                ));
             }
 
             dw_node_stack.clear();
@@ -316,9 +407,9 @@ impl<'a> DwData<'a> {
                         let new_symbol_name =
                             SymbolName::new_with_parent(parent_symbol_name, name_str);
 
-                        let dw_node_idx = match dw_node_name_lookup.get(&new_symbol_name).copied() {
-                            Some(dw_node_idx) => dw_node_idx,
-                            None => {
+                        let dw_node_idx = *dw_node_name_lookup
+                            .entry(new_symbol_name)
+                            .or_insert_with(|| {
                                 let ty = match abbreviation.tag() {
                                     DW_TAG_structure_type => DwNodeType::Struct,
                                     DW_TAG_namespace if name_str.starts_with("{impl#") => {
@@ -335,13 +426,8 @@ impl<'a> DwData<'a> {
                                         size: 0,
                                     },
                                 );
-                                let new_dw_node_idx = dw_node_tree.len() - 1;
-
-                                dw_node_name_lookup.insert(new_symbol_name, new_dw_node_idx);
-
-                                new_dw_node_idx
-                            }
-                        };
+                                dw_node_tree.len() - 1
+                            });
 
                         dw_node_stack.push((0, dw_node_idx, new_symbol_name));
                     }
@@ -363,9 +449,9 @@ impl<'a> DwData<'a> {
                         let new_symbol_name =
                             SymbolName::new_with_parent(parent_symbol_name, name_str);
 
-                        let dw_node_idx = match dw_node_name_lookup.get(&new_symbol_name).copied() {
-                            Some(dw_node_idx) => dw_node_idx,
-                            None => {
+                        let dw_node_idx = *dw_node_name_lookup
+                            .entry(new_symbol_name)
+                            .or_insert_with(|| {
                                 dw_node_tree.add_child(
                                     parent_dw_node_idx,
                                     DwNode {
@@ -374,13 +460,8 @@ impl<'a> DwData<'a> {
                                         size: 0,
                                     },
                                 );
-                                let new_dw_node_idx = dw_node_tree.len() - 1;
-
-                                dw_node_name_lookup.insert(new_symbol_name, new_dw_node_idx);
-
-                                new_dw_node_idx
-                            }
-                        };
+                                dw_node_tree.len() - 1
+                            });
 
                         dw_node_stack.push((0, dw_node_idx, new_symbol_name));
                     }
@@ -561,21 +642,264 @@ impl<'a> DwData<'a> {
             }
         }
 
-        println!("Dwarf parsing: {}s", (Instant::now() - start).as_secs_f32());
-        println!("Dwarf total rows: {}", line_info_count);
-        println!(
+        log::debug!("Dwarf total rows: {}", line_info_count);
+        log::debug!(
             "Dwarf sizes line_infos:'{}', file_entries:'{}'",
-            std::mem::size_of::<DwLineInfo>() * line_infos.len(),
+            std::mem::size_of::<(u64, DwLineInfo)>() * line_infos.len(),
             std::mem::size_of::<DwFileEntry<'_>>() * file_entries.len()
         );
 
-        line_infos.sort_by(|a, b| a.address.cmp(&b.address));
+        let inlined_instances = collect_inlined_instances(names, &dwarf);
+        let compilation_units = collect_compilation_units(arena, &dwarf);
 
-        Self {
+        Some(Self {
             nodes: dw_node_tree,
-            line_infos,
+            line_infos: SortedMap::from_array(line_infos),
             file_entries,
+            inlined_instances,
+            compilation_units,
+        })
+    }
+}
+
+/// Rows of the line program of a unit, by address, their files indexed in
+/// the ones of the unit.
+fn line_rows<'s>(
+    arena: &mut impl SliceArena<'s>,
+    dwarf: &gimli::Dwarf<EndianSlice<'_, LittleEndian>>,
+    unit_header: UnitHeader<EndianSlice<'_, LittleEndian>>,
+) -> &'s [(u64, DwLineInfo)] {
+    let Ok(unit) = dwarf.unit(unit_header) else {
+        return &[];
+    };
+    let Some(program) = unit.line_program else {
+        return &[];
+    };
+
+    let (com_program, sequences) = program.sequences().unwrap();
+    arena.alloc_from_iter(sequences.iter().flat_map(|sequence| {
+        let mut resumed_rows = com_program.resume_from(sequence);
+        std::iter::from_fn(move || {
+            let (_, row) = resumed_rows.next_row().unwrap()?;
+            let column = match row.column() {
+                gimli::ColumnType::LeftEdge => 0,
+                gimli::ColumnType::Column(non_zero) => non_zero.get(),
+            };
+
+            Some((
+                row.address(),
+                DwLineInfo {
+                    file_entry_idx: row.file_index() as usize,
+                    line: row.line().map(|line| line.get()).unwrap_or(0) as usize,
+                    col: column as usize,
+                },
+            ))
+        })
+    }))
+}
+
+/// Executes the line programs of the units on `thread_count` threads, each
+/// taking the next unit until none are left, in blocks of `sync_arena`.
+/// `progress` is called on the calling thread as the units are done, `None`
+/// once it breaks.
+fn line_rows_on_threads<'s>(
+    sync_arena: &'s SyncArena,
+    dwarf: &gimli::Dwarf<EndianSlice<'_, LittleEndian>>,
+    unit_headers: &[UnitHeader<EndianSlice<'_, LittleEndian>>],
+    thread_count: usize,
+    progress: &dyn Fn(f32) -> ControlFlow<()>,
+) -> Option<std::vec::Vec<&'s [(u64, DwLineInfo)]>> {
+    let next_unit = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let (done_sender, done_receiver) = mpsc::channel();
+
+    let mut unit_line_rows = std::thread::scope(|scope| {
+        let workers: std::vec::Vec<_> = (0..thread_count)
+            .map(|_| {
+                let done_sender = done_sender.clone();
+                let (next_unit, cancelled) = (&next_unit, &cancelled);
+                scope.spawn(move || {
+                    let mut block = sync_arena.block();
+                    let mut unit_line_rows = std::vec::Vec::new();
+                    while !cancelled.load(Ordering::Relaxed) {
+                        let idx = next_unit.fetch_add(1, Ordering::Relaxed);
+                        let Some(unit_header) = unit_headers.get(idx) else {
+                            break;
+                        };
+
+                        unit_line_rows.push((idx, line_rows(&mut block, dwarf, *unit_header)));
+                        // Only fails once the calling thread stopped waiting.
+                        let _ = done_sender.send(());
+                    }
+                    unit_line_rows
+                })
+            })
+            .collect();
+        // The workers hold the other senders, the channel closes once they
+        // are all done.
+        drop(done_sender);
+
+        for (done_count, ()) in done_receiver.into_iter().enumerate() {
+            let fraction = (done_count + 1) as f32 / unit_headers.len() as f32;
+            if progress(fraction).is_break() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Failed to execute the line programs"))
+            .collect::<std::vec::Vec<_>>()
+    });
+
+    if cancelled.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    unit_line_rows.sort_unstable_by_key(|(idx, _)| *idx);
+    Some(unit_line_rows.into_iter().map(|(_, rows)| rows).collect())
+}
+
+/// Walks the DIEs of all compilation units collecting the inlined subroutines.
+fn collect_inlined_instances<'a>(
+    names: &mut Interner<'a>,
+    dwarf: &gimli::Dwarf<EndianSlice<'a, LittleEndian>>,
+) -> Array<'a, DwInlinedInstance<'a>> {
+    let arena = names.arena();
+    let scratch = scratch_arena(&[arena]);
+    let mut inlined_instances = crate::arena::vec::Vec::new(&scratch, 0);
+
+    // Tree depth of the inlined subroutines enclosing the current entry. Kept out of
+    // the scratch arena, `inlined_instances` is the one growing there.
+    let mut inlined_depths: std::vec::Vec<isize> = std::vec::Vec::new();
+
+    let mut units = dwarf.units();
+    while let Ok(Some(unit_header)) = units.next() {
+        if unit_header.type_() != UnitType::Compilation {
+            continue;
+        }
+
+        let Ok(unit) = dwarf.unit(unit_header) else {
+            continue;
+        };
+        let unit_ref = unit.unit_ref(dwarf);
+
+        inlined_depths.clear();
+        let mut depth: isize = 0;
+
+        let mut entries = unit_ref.entries();
+        while let Ok(Some((delta_depth, entry))) = entries.next_dfs() {
+            depth += delta_depth;
+            while inlined_depths
+                .last()
+                .is_some_and(|&inlined_depth| inlined_depth >= depth)
+            {
+                inlined_depths.pop();
+            }
+
+            if entry.tag() != DW_TAG_inlined_subroutine {
+                continue;
+            }
+
+            let name = match entry.attr_value(DW_AT_abstract_origin) {
+                Ok(Some(AttributeValue::UnitRef(origin))) => {
+                    origin_name(names, unit_ref, origin).unwrap_or("<unknown>")
+                }
+                _ => "<unknown>",
+            };
+            if let Ok(mut ranges) = unit_ref.die_ranges(entry) {
+                while let Ok(Some(range)) = ranges.next() {
+                    if range.begin < range.end {
+                        inlined_instances.push(DwInlinedInstance {
+                            name,
+                            low_pc: range.begin,
+                            high_pc: range.end,
+                            depth: inlined_depths.len() as u32,
+                        });
+                    }
+                }
+            }
+
+            inlined_depths.push(depth);
+        }
+    }
+
+    inlined_instances.sort_by_key(|inlined_instance| inlined_instance.low_pc);
+
+    let mut result = Array::new(arena, inlined_instances.len());
+    for inlined_instance in inlined_instances.iter() {
+        result.push(*inlined_instance);
+    }
+    result
+}
+
+/// Collects the address ranges of all compilation units.
+fn collect_compilation_units<'a>(
+    arena: &'a Arena,
+    dwarf: &gimli::Dwarf<EndianSlice<'a, LittleEndian>>,
+) -> Array<'a, DwCompilationUnit<'a>> {
+    let scratch = scratch_arena(&[arena]);
+    let mut compilation_units = crate::arena::vec::Vec::new(&scratch, 0);
+
+    let mut units = dwarf.units();
+    while let Ok(Some(unit_header)) = units.next() {
+        if unit_header.type_() != UnitType::Compilation {
+            continue;
+        }
+
+        let Ok(unit) = dwarf.unit(unit_header) else {
+            continue;
+        };
+        let name = unit.name.map(dw_slice_to_str).unwrap_or("<unknown>");
+        let comp_dir = dw_option_slice_to_path(unit.comp_dir);
+
+        if let Ok(mut ranges) = dwarf.unit_ranges(&unit) {
+            while let Ok(Some(range)) = ranges.next() {
+                if range.begin < range.end {
+                    compilation_units.push(DwCompilationUnit {
+                        name,
+                        comp_dir,
+                        low_pc: range.begin,
+                        high_pc: range.end,
+                    });
+                }
+            }
+        }
+    }
+
+    compilation_units.sort_by_key(|compilation_unit| compilation_unit.low_pc);
+
+    let mut result = Array::new(arena, compilation_units.len());
+    for compilation_unit in compilation_units.iter() {
+        result.push(*compilation_unit);
+    }
+    result
+}
+
+/// Returns the demangled name of the subprogram at `offset`, following its
+/// specification if the name lives there. Functions are inlined in many
+/// places, their names are interned.
+fn origin_name<'a>(
+    names: &mut Interner<'a>,
+    unit_ref: UnitRef<'_, EndianSlice<'a, LittleEndian>>,
+    offset: UnitOffset,
+) -> Option<&'a str> {
+    let entry = unit_ref.entry(offset).ok()?;
+
+    for attr_name in [DW_AT_linkage_name, DW_AT_name] {
+        if let Ok(Some(attr_value)) = entry.attr_value(attr_name)
+            && let Ok(name) = unit_ref.attr_string(attr_value)
+        {
+            let scratch = scratch_arena(&[names.arena()]);
+            return Some(names.intern_str(demangled_name(&scratch, dw_slice_to_str(name))));
+        }
+    }
+
+    match entry.attr_value(DW_AT_specification) {
+        Ok(Some(AttributeValue::UnitRef(specification))) => {
+            origin_name(names, unit_ref, specification)
         }
+        _ => None,
     }
 }
 
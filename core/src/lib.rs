@@ -0,0 +1,16 @@
+//! Analysis of the size of WebAssembly binaries, shared by the explorer and
+//! its commands without depending on the GUI.
+#![feature(allocator_api)]
+
+pub mod analysis;
+pub mod arena;
+pub mod data_provider;
+pub mod data_provider_twiggy;
+pub mod dwarf;
+pub mod path;
+pub mod progress;
+pub mod provider_registry;
+pub mod reports;
+pub mod timings;
+pub mod tree_state;
+pub mod wasm;
@@ -1,4 +1,10 @@
-use std::{ffi::OsStr, io::Write, path::Path};
+use std::{
+    ffi::OsStr,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use crate::arena::Arena;
 
@@ -31,4 +37,27 @@ impl PathExt {
 
         Path::new(unsafe { OsStr::from_encoded_bytes_unchecked(&buff[0..idx]) })
     }
+
+    /// Finds a source file from the debug info: the path itself when it
+    /// exists, or a relative path in the first search folder having it.
+    pub fn find_source_file(path: &Path, search_folders: &[PathBuf]) -> Option<PathBuf> {
+        if path.exists() {
+            return Some(path.to_path_buf());
+        }
+        if path.is_absolute() {
+            return None;
+        }
+
+        search_folders
+            .iter()
+            .map(|folder| folder.join(path))
+            .find(|path| path.exists())
+    }
+
+    /// When the file was last modified, `None` if it can't be read.
+    pub fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
 }
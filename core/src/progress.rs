@@ -0,0 +1,71 @@
+use std::{
+    ops::ControlFlow,
+    sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering},
+};
+
+/// Steps of the analysis of a file, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    OptimizingWithWasmOpt,
+    ParsingWasm,
+    ParsingDwarf,
+    DecodingFunctions,
+    ComputingSizes,
+}
+
+impl Phase {
+    const ALL: [Phase; 5] = [
+        Phase::OptimizingWithWasmOpt,
+        Phase::ParsingWasm,
+        Phase::ParsingDwarf,
+        Phase::DecodingFunctions,
+        Phase::ComputingSizes,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Phase::OptimizingWithWasmOpt => "Running wasm-opt -Oz",
+            Phase::ParsingWasm => "Parsing the sections",
+            Phase::ParsingDwarf => "Parsing the debug info",
+            Phase::DecodingFunctions => "Decoding the functions",
+            Phase::ComputingSizes => "Computing the sizes",
+        }
+    }
+}
+
+/// Progress of an analysis, shown by the UI.
+#[derive(Debug, Default)]
+pub struct Progress {
+    phase: AtomicU8,
+    /// Done part of the phase, from 0 to 1, as the bits of a `f32`.
+    fraction: AtomicU32,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Breaks once the analysis is cancelled, for it to stop.
+    pub fn set(&self, phase: Phase, fraction: f32) -> ControlFlow<()> {
+        self.phase.store(phase as u8, Ordering::Relaxed);
+        self.fraction
+            .store(fraction.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+        if self.is_cancelled() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self) -> (Phase, f32) {
+        let phase = Phase::ALL[self.phase.load(Ordering::Relaxed) as usize];
+        let fraction = f32::from_bits(self.fraction.load(Ordering::Relaxed));
+        (phase, fraction)
+    }
+}
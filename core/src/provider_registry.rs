@@ -0,0 +1,239 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read},
+    ops::ControlFlow,
+    path::Path,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::{
+    arena::Arena,
+    data_provider::DataProvider,
+    data_provider_twiggy::DataProviderTwiggy,
+    progress::Phase,
+    reports::{BLOATY_FORMAT, TWIGGY_FORMAT},
+};
+
+/// Number of bytes at the start of the files the formats are detected from.
+const HEADER_SIZE: usize = 1024;
+
+/// Format of an analyzed file, the key of its `FileFormat` in the registry.
+///
+/// It is saved like the variant of an enum, `Wasm`, the formats being an
+/// enum before they could be registered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileType(&'static str);
+
+impl FileType {
+    pub const WASM: FileType = FileType("Wasm");
+    pub const TWIGGY: FileType = FileType("Twiggy");
+    pub const BLOATY: FileType = FileType("Bloaty");
+    /// Read for the saved names of the formats that aren't registered, it
+    /// has no format so their files are closed like the missing ones.
+    pub const UNKNOWN: FileType = FileType("Unknown");
+
+    pub const fn new(name: &'static str) -> Self {
+        Self(name)
+    }
+
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl serde::Serialize for FileType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_variant("FileType", 0, self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for FileType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FileTypeVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FileTypeVisitor {
+            type Value = FileType;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a file type")
+            }
+
+            fn visit_enum<A: serde::de::EnumAccess<'de>>(
+                self,
+                data: A,
+            ) -> Result<FileType, A::Error> {
+                let (name, variant): (std::string::String, _) = data.variant()?;
+                serde::de::VariantAccess::unit_variant(variant)?;
+                Ok(formats()
+                    .iter()
+                    .map(|format| format.file_type)
+                    .find(|file_type| file_type.0 == name)
+                    .unwrap_or(FileType::UNKNOWN))
+            }
+        }
+
+        deserializer.deserialize_enum("FileType", &[], FileTypeVisitor)
+    }
+}
+
+/// Analyzes the bytes of a file of a format into a `DataProvider`, whose
+/// views (`FunctionsView`, `SourceCodeView`) the explorer and the commands
+/// show. The bytes are in the arena, or are borrowed as long.
+pub type LoadFn = for<'a> fn(
+    arena: &'a Arena,
+    bytes: &'a [u8],
+    progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+) -> Result<Box<dyn DataProvider<'a> + 'a>, ()>;
+
+/// A format of the files that can be analyzed.
+#[derive(Clone, Copy)]
+pub struct FileFormat {
+    pub file_type: FileType,
+    /// Shown to pick the files, like `WebAssembly`.
+    pub description: &'static str,
+    /// Extensions of the files, without the dot.
+    pub extensions: &'static [&'static str],
+    /// Whether a file is of this format, from its path and its first bytes.
+    pub detect: fn(path: &Path, header: &[u8]) -> bool,
+    pub load: LoadFn,
+}
+
+const WASM_FORMAT: FileFormat = FileFormat {
+    file_type: FileType::WASM,
+    description: "WebAssembly",
+    extensions: &["wasm"],
+    detect: |_, header| header.starts_with(b"\0asm"),
+    load: |arena, bytes, progress| {
+        DataProviderTwiggy::from_bytes(arena, bytes, progress)
+            .map(|data_provider| Box::new(data_provider) as _)
+    },
+};
+
+/// Formats registered besides the built-in ones.
+static REGISTERED_FORMATS: RwLock<FormatRegistry> = RwLock::new(FormatRegistry::new());
+
+/// Formats registered besides the built-in ones, in their order.
+struct FormatRegistry {
+    formats: std::vec::Vec<FileFormat>,
+}
+
+impl FormatRegistry {
+    const fn new() -> Self {
+        Self {
+            formats: std::vec::Vec::new(),
+        }
+    }
+
+    fn register(&mut self, format: FileFormat) {
+        self.formats
+            .retain(|registered| registered.file_type != format.file_type);
+        self.formats.push(format);
+    }
+
+    /// The registered formats, then the built-in ones they didn't replace.
+    fn formats(&self) -> std::vec::Vec<FileFormat> {
+        let mut formats = self.formats.clone();
+        for builtin in [WASM_FORMAT, TWIGGY_FORMAT, BLOATY_FORMAT] {
+            if !formats
+                .iter()
+                .any(|format| format.file_type == builtin.file_type)
+            {
+                formats.push(builtin);
+            }
+        }
+        formats
+    }
+}
+
+/// Adds a format, replacing the one of the same `FileType`. Formats are
+/// registered before opening the window, so that the saved files of the
+/// format open again.
+pub fn register_format(format: FileFormat) {
+    REGISTERED_FORMATS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(format);
+}
+
+/// The registered formats, then the built-in ones they didn't replace.
+pub fn formats() -> std::vec::Vec<FileFormat> {
+    REGISTERED_FORMATS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .formats()
+}
+
+pub fn format(file_type: FileType) -> Option<FileFormat> {
+    formats()
+        .into_iter()
+        .find(|format| format.file_type == file_type)
+}
+
+/// The first format detecting the file named `path`, from its bytes, `None`
+/// if no format knows it. Uploaded files only have their name as path.
+pub fn detect_format(path: &Path, bytes: &[u8]) -> Option<FileFormat> {
+    let header = &bytes[..bytes.len().min(HEADER_SIZE)];
+    formats()
+        .into_iter()
+        .find(|format| (format.detect)(path, header))
+}
+
+/// Reads the whole file at `path` in the arena, to load it.
+pub fn read_file<'a>(arena: &'a Arena, path: &Path) -> io::Result<&'a [u8]> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len() as usize;
+    let bytes = arena.alloc_slice_zeroed(size);
+    file.read_exact(bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FileFormat, FileType, FormatRegistry, WASM_FORMAT, format};
+
+    #[test]
+    fn file_types_are_saved_like_enum_variants() {
+        let json = serde_json::to_string(&FileType::WASM).unwrap();
+        assert_eq!(json, r#""Wasm""#);
+        assert_eq!(
+            serde_json::from_str::<FileType>(&json).unwrap(),
+            FileType::WASM
+        );
+        assert_eq!(
+            serde_json::from_str::<FileType>(r#""Elf""#).unwrap(),
+            FileType::UNKNOWN
+        );
+    }
+
+    #[test]
+    fn registered_formats_come_first() {
+        const TEXT: FileType = FileType::new("Text");
+        let mut registry = FormatRegistry::new();
+        registry.register(FileFormat {
+            file_type: TEXT,
+            description: "Text",
+            extensions: &["txt"],
+            detect: |path, _| path.extension().is_some_and(|extension| extension == "txt"),
+            load: |_, _, _| Err(()),
+        });
+        registry.register(WASM_FORMAT);
+
+        let file_types: std::vec::Vec<_> = registry
+            .formats()
+            .iter()
+            .map(|format| format.file_type)
+            .collect();
+        assert_eq!(
+            file_types,
+            [TEXT, FileType::WASM, FileType::TWIGGY, FileType::BLOATY]
+        );
+        assert!(format(FileType::UNKNOWN).is_none());
+    }
+}
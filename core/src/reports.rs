@@ -0,0 +1,313 @@
+//! Reports of other size profilers, opened like the binaries to compare them
+//! with this analysis.
+//!
+//! A report is turned into a WebAssembly module with a function for each of
+//! its items, named like it and whose body has its size, only `nop`s. The
+//! module is then analyzed like any other, the items being rows of the
+//! functions explorer. The items don't call each other, their retained size
+//! is their shallow size.
+
+use std::{ops::ControlFlow, path::Path};
+
+use crate::{
+    arena::Arena,
+    data_provider::DataProvider,
+    data_provider_twiggy::DataProviderTwiggy,
+    progress::Phase,
+    provider_registry::{FileFormat, FileType},
+};
+
+/// Reports of `twiggy top --format json`, or `twiggy dominators`, whose
+/// items are nested.
+pub const TWIGGY_FORMAT: FileFormat = FileFormat {
+    file_type: FileType::TWIGGY,
+    description: "twiggy JSON report",
+    extensions: &["json"],
+    detect: |path, header| {
+        has_extension(path, "json") && memchr::memmem::find(header, b"\"shallow_size\"").is_some()
+    },
+    load: |arena, bytes, progress| load(arena, bytes, parse_twiggy_json, progress),
+};
+
+/// Reports of `bloaty --csv`, the items being the rows of the last data
+/// source, like the symbols of `-d compileunits,symbols`.
+pub const BLOATY_FORMAT: FileFormat = FileFormat {
+    file_type: FileType::BLOATY,
+    description: "bloaty CSV report",
+    extensions: &["csv"],
+    detect: |path, header| {
+        let first_line = header.split(|byte| *byte == b'\n').next().unwrap_or(&[]);
+        has_extension(path, "csv")
+            && (memchr::memmem::find(first_line, b"filesize").is_some()
+                || memchr::memmem::find(first_line, b"vmsize").is_some())
+    },
+    load: |arena, bytes, progress| load(arena, bytes, parse_bloaty_csv, progress),
+};
+
+/// An item of a report, the size of its file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReportItem {
+    pub name: String,
+    pub size: u32,
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|ext| ext == extension)
+}
+
+fn load<'a>(
+    arena: &'a Arena,
+    bytes: &'a [u8],
+    parse: fn(&str) -> Result<Vec<ReportItem>, String>,
+    progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+) -> Result<Box<dyn DataProvider<'a> + 'a>, ()> {
+    let items = std::str::from_utf8(bytes)
+        .map_err(|err| err.to_string())
+        .and_then(parse)
+        .map_err(|err| log::error!("Failed to parse the report: {}", err))?;
+
+    let module = encode_module(&items);
+    let module_bytes = arena.alloc_slice_zeroed(module.len());
+    module_bytes.copy_from_slice(&module);
+    let data_provider = DataProviderTwiggy::from_bytes(arena, module_bytes, progress)?;
+    Ok(Box::new(data_provider))
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum TwiggyReport {
+    Items(Vec<TwiggyItem>),
+    Item(TwiggyItem),
+}
+
+#[derive(serde::Deserialize)]
+struct TwiggyItem {
+    name: String,
+    shallow_size: u32,
+    #[serde(default)]
+    children: Vec<TwiggyItem>,
+}
+
+/// The items of a twiggy report, those of a tree in depth first order.
+pub fn parse_twiggy_json(text: &str) -> Result<Vec<ReportItem>, String> {
+    fn push_items(items: Vec<TwiggyItem>, report_items: &mut Vec<ReportItem>) {
+        for item in items {
+            // The rows summing the others up.
+            if !item.name.starts_with('Σ') && !item.name.starts_with("...") {
+                report_items.push(ReportItem {
+                    name: item.name,
+                    size: item.shallow_size,
+                });
+            }
+            push_items(item.children, report_items);
+        }
+    }
+
+    let items = match serde_json::from_str(text).map_err(|err| err.to_string())? {
+        TwiggyReport::Items(items) => items,
+        TwiggyReport::Item(item) => vec![item],
+    };
+    let mut report_items = Vec::new();
+    push_items(items, &mut report_items);
+    Ok(report_items)
+}
+
+/// The rows of a bloaty report, sized by the file, or by the memory without
+/// the file size. Rows without a size, only taking memory, are left out.
+pub fn parse_bloaty_csv(text: &str) -> Result<Vec<ReportItem>, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header = csv_fields(lines.next().ok_or("Empty report")?);
+
+    let column = |name: &str| header.iter().position(|field| field == name);
+    let size_column = column("filesize")
+        .or_else(|| column("vmsize"))
+        .ok_or("No `filesize` or `vmsize` column")?;
+    // The data sources come first, then the sizes.
+    let name_column = column("vmsize")
+        .unwrap_or(size_column)
+        .min(size_column)
+        .checked_sub(1)
+        .ok_or("No data source column")?;
+
+    let mut items = Vec::new();
+    for (line_idx, line) in lines.enumerate() {
+        let mut fields = csv_fields(line);
+        let size = fields
+            .get(size_column)
+            .and_then(|size| size.parse::<u32>().ok())
+            .ok_or_else(|| format!("Invalid size on row {}", line_idx + 1))?;
+        if size > 0 && name_column < fields.len() {
+            items.push(ReportItem {
+                name: fields.swap_remove(name_column),
+                size,
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// The fields of a CSV line, unquoted.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn write_uleb128(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_section(bytes: &mut Vec<u8>, id: u8, content: &[u8]) {
+    bytes.push(id);
+    write_uleb128(bytes, content.len() as u64);
+    bytes.extend_from_slice(content);
+}
+
+/// A module with a function of type `[] -> []` for each item, the bodies
+/// taking the size of the items, at least the 2 bytes of an empty one.
+fn encode_module(items: &[ReportItem]) -> Vec<u8> {
+    const NOP: u8 = 0x01;
+    const END: u8 = 0x0b;
+
+    let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+    // One type, a function without parameters nor results.
+    write_section(&mut bytes, 1, &[1, 0x60, 0, 0]);
+
+    let mut functions = Vec::new();
+    write_uleb128(&mut functions, items.len() as u64);
+    functions.resize(functions.len() + items.len(), 0);
+    write_section(&mut bytes, 3, &functions);
+
+    let mut code = Vec::new();
+    write_uleb128(&mut code, items.len() as u64);
+    for item in items {
+        let size = item.size.max(2) as usize;
+        write_uleb128(&mut code, size as u64);
+        // No locals.
+        code.push(0);
+        code.resize(code.len() + size - 2, NOP);
+        code.push(END);
+    }
+    write_section(&mut bytes, 10, &code);
+
+    let mut function_names = Vec::new();
+    write_uleb128(&mut function_names, items.len() as u64);
+    for (idx, item) in items.iter().enumerate() {
+        write_uleb128(&mut function_names, idx as u64);
+        write_uleb128(&mut function_names, item.name.len() as u64);
+        function_names.extend_from_slice(item.name.as_bytes());
+    }
+    let mut names = Vec::new();
+    write_uleb128(&mut names, 4);
+    names.extend_from_slice(b"name");
+    write_section(&mut names, 1, &function_names);
+    write_section(&mut bytes, 0, &names);
+
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::ControlFlow;
+
+    use super::{ReportItem, encode_module, parse_bloaty_csv, parse_twiggy_json};
+    use crate::{
+        arena::{Arena, memory::MB},
+        data_provider_twiggy::DataProviderTwiggy,
+    };
+
+    fn item(name: &str, size: u32) -> ReportItem {
+        ReportItem {
+            name: name.to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn parses_twiggy_reports() {
+        let top = r#"[
+            {"name": "dlmalloc", "shallow_size": 5368, "shallow_size_percent": 10.99},
+            {"name": "\"function names\" subsection", "shallow_size": 830, "shallow_size_percent": 1.7}
+        ]"#;
+        assert_eq!(
+            parse_twiggy_json(top).unwrap(),
+            [
+                item("dlmalloc", 5368),
+                item("\"function names\" subsection", 830)
+            ]
+        );
+
+        let dominators = r#"{"name": "[1] \"items\"", "shallow_size": 0, "retained_size": 40, "children": [
+            {"name": "main", "shallow_size": 30, "retained_size": 40, "children": [
+                {"name": "helper", "shallow_size": 10, "retained_size": 10}
+            ]}
+        ]}"#;
+        assert_eq!(
+            parse_twiggy_json(dominators).unwrap(),
+            [
+                item("[1] \"items\"", 0),
+                item("main", 30),
+                item("helper", 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_bloaty_reports() {
+        let csv = "compileunits,symbols,vmsize,filesize\n\
+                   src/lib.rs,\"<T as core::fmt::Debug>::fmt, the \"\"impl\"\"\",0,120\n\
+                   src/lib.rs,main,0,64\n\
+                   [section .bss],[section .bss],512,0\n";
+        assert_eq!(
+            parse_bloaty_csv(csv).unwrap(),
+            [
+                item("<T as core::fmt::Debug>::fmt, the \"impl\"", 120),
+                item("main", 64)
+            ]
+        );
+        assert!(parse_bloaty_csv("symbols,vmsize,filesize\nmain,0,big\n").is_err());
+    }
+
+    #[test]
+    fn items_are_functions_of_their_size() {
+        let module = encode_module(&[item("main", 300), item("tiny", 1)]);
+        let arena = Arena::new(MB);
+        let data_provider =
+            DataProviderTwiggy::from_bytes(&arena, &module, &|_, _| ControlFlow::Continue(()))
+                .unwrap();
+
+        let functions: Vec<_> = data_provider
+            .raw_data
+            .iter()
+            .map(|function| {
+                let property = &function.function_property;
+                (
+                    property.raw_name,
+                    property.shallow_size_bytes,
+                    property.retained_size_bytes,
+                )
+            })
+            .collect();
+        assert_eq!(functions, [("main", 300, 300), ("tiny", 2, 2)]);
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use web_time::Instant;
+
+/// Durations of the steps of the analysis of a file, in the order they first
+/// ran, shown by the profiler window.
+#[derive(Clone, Debug, Default)]
+pub struct Timings {
+    steps: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    /// Runs `f` as the step `name`, also a profiling scope.
+    pub fn measure<R>(&mut self, name: &'static str, f: impl FnOnce() -> R) -> R {
+        profiling::scope!(name);
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    /// Sets the duration of the step `name`, replacing the one of its last
+    /// run.
+    pub fn record(&mut self, name: &'static str, duration: Duration) {
+        match self.steps.iter_mut().find(|(step, _)| *step == name) {
+            Some((_, step_duration)) => *step_duration = duration,
+            None => self.steps.push((name, duration)),
+        }
+    }
+
+    pub fn steps(&self) -> &[(&'static str, Duration)] {
+        &self.steps
+    }
+
+    pub fn total(&self) -> Duration {
+        self.steps.iter().map(|(_, duration)| *duration).sum()
+    }
+}
@@ -0,0 +1,395 @@
+use std::{cmp::Ordering, ops::Range};
+
+use crate::arena::{Arena, array::Array, scratch::scratch_arena, tree::Tree, vec::Vec};
+
+bitflags::bitflags! {
+    pub struct TreeItemStateFlags: u8 {
+        const OPENED = 0b00000001;
+        const FORCE_OPENED = 0b00000010;
+        const VISIBLE = 0b00000100;
+    }
+}
+
+pub struct TreeItemState {
+    // pub descendants_count: u32,
+    pub flags: TreeItemStateFlags,
+    pub depth: u8,
+}
+
+impl TreeItemState {
+    #[inline(always)]
+    pub fn visible(&self) -> bool {
+        self.flags.contains(TreeItemStateFlags::VISIBLE)
+    }
+}
+
+/// Moves of the selection of a tree, like with the arrow keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeNavigation {
+    Down,
+    Up,
+    First,
+    Last,
+    /// Expands the selected item, or moves to its first child.
+    Children,
+    /// Collapses the selected item, or moves to its parent.
+    Parent,
+}
+
+pub struct TreeState<'a, T, D> {
+    /// The input tree used to create this tree state.
+    pub tree: Tree<'a, T>,
+
+    /// This state of the
+    pub items_state: Array<'a, TreeItemState>,
+
+    /// Associated UI data for each item in the tree.
+    pub items_ui_data: Array<'a, D>,
+
+    /// The indices to display the visible items (in order)
+    /// This allows us to compute the order of indices
+    /// to use when displaying each item one after the other.
+    pub row_indices: Array<'a, usize>,
+
+    /// The minimum depth in the tree to start displaying items.
+    /// This is useful in case where the first few levels of
+    /// the tree are not meant to be displayed in the UI.
+    pub min_depth_to_display: u8,
+
+    pub sort_fn: fn((&T, &D), (&T, &D)) -> Ordering,
+
+    /// Children of the items in display order, the ones of `idx` being in
+    /// `children_ranges[idx]`. Sorting them is the slow part of computing the
+    /// rows, so it's only done when the sizes or the order change.
+    sorted_children: Array<'a, usize>,
+    children_ranges: Array<'a, Range<u32>>,
+
+    /// Name of the items searched by typing it.
+    pub name_fn: fn(&T) -> &str,
+
+    pub hovered_index: usize,
+    pub selected_index: usize,
+    /// Item to scroll to on the next frame, when it's selected from outside
+    /// the tree.
+    pub scroll_to_index: Option<usize>,
+}
+
+impl<'a, T, D> TreeState<'a, T, D> {
+    pub fn from_tree(
+        arena: &'a Arena,
+        tree: Tree<'a, T>,
+        min_depth_to_display: u8,
+        state: fn(&T, usize) -> D,
+        sort: fn((&T, &D), (&T, &D)) -> Ordering,
+        name: fn(&T) -> &str,
+    ) -> Self {
+        let mut items_state: Array<'_, TreeItemState> = Array::new(arena, tree.len());
+        let mut items_ui_data: Array<'_, D> = Array::new(arena, tree.len());
+
+        for idx in 0..tree.len() {
+            items_state.push(TreeItemState {
+                flags: TreeItemStateFlags::VISIBLE,
+                depth: 0,
+            });
+            items_ui_data.push(state(tree.get(idx), idx));
+        }
+
+        fn add_tree_item<T, D>(
+            items_state: &mut Array<'_, TreeItemState>,
+            items_ui_data: &mut Array<'_, D>,
+            tree: &Tree<'_, T>,
+            state: fn(&T, usize) -> D,
+            sort: fn((&T, &D), (&T, &D)) -> Ordering,
+            item_idx: usize,
+            depth: u8,
+        ) {
+            items_state[item_idx].depth = depth;
+
+            let scratch = scratch_arena(&[]);
+            let mut children_idx = Vec::new(&scratch, 1024);
+
+            for child_idx in tree.get_children(item_idx) {
+                children_idx.push(child_idx);
+            }
+
+            children_idx.sort_by(|a, b| {
+                sort(
+                    (&tree[*a].value, &items_ui_data[*a]),
+                    (&tree[*b].value, &items_ui_data[*b]),
+                )
+            });
+
+            for child_idx in children_idx {
+                add_tree_item(
+                    items_state,
+                    items_ui_data,
+                    tree,
+                    state,
+                    sort,
+                    child_idx,
+                    depth + 1,
+                );
+            }
+        }
+
+        add_tree_item(
+            &mut items_state,
+            &mut items_ui_data,
+            &tree,
+            state,
+            sort,
+            0,
+            0,
+        );
+
+        if !items_state.is_empty() {
+            items_state[0].flags.set(TreeItemStateFlags::OPENED, true);
+        }
+
+        let row_indices = Array::new(arena, items_state.len());
+        let sorted_children = Array::new(arena, items_state.len());
+        let children_ranges = Array::new(arena, items_state.len());
+
+        let mut result = Self {
+            tree,
+            items_state,
+            items_ui_data,
+            row_indices,
+            min_depth_to_display,
+            sort_fn: sort,
+            sorted_children,
+            children_ranges,
+            name_fn: name,
+            hovered_index: usize::MAX,
+            selected_index: usize::MAX,
+            scroll_to_index: None,
+        };
+
+        result.recompute_indices();
+        result
+    }
+
+    /// Selects an item and scrolls to it, if it's shown.
+    pub fn select(&mut self, index: usize) {
+        self.selected_index = index;
+        self.scroll_to_index = Some(index);
+    }
+
+    fn row_of(&self, index: usize) -> Option<usize> {
+        self.row_indices.iter().position(|&item| item == index)
+    }
+
+    pub fn is_opened(&self, index: usize) -> bool {
+        self.items_state[index]
+            .flags
+            .intersects(TreeItemStateFlags::OPENED | TreeItemStateFlags::FORCE_OPENED)
+    }
+
+    /// Moves the selection, collapsing or expanding the selected item first
+    /// when moving to its parent or its children.
+    pub fn navigate(&mut self, navigation: TreeNavigation) {
+        if self.row_indices.is_empty() {
+            return;
+        }
+
+        let last_row = self.row_indices.len() - 1;
+        let selected = self.selected_index;
+        let row = self.row_of(selected);
+        let new_row = match (navigation, row) {
+            (TreeNavigation::Down, Some(row)) => (row + 1).min(last_row),
+            (TreeNavigation::Up, Some(row)) => row.saturating_sub(1),
+            (TreeNavigation::Down | TreeNavigation::Up | TreeNavigation::First, _) => 0,
+            (TreeNavigation::Last, _) => last_row,
+            (TreeNavigation::Children, Some(row)) => {
+                if !self.tree.has_children(selected) {
+                    return;
+                }
+                if !self.is_opened(selected) {
+                    self.set_opened(selected, true);
+                    return;
+                }
+                (row + 1).min(last_row)
+            }
+            (TreeNavigation::Parent, Some(_)) => {
+                if self.tree.has_children(selected) && self.is_opened(selected) {
+                    self.set_opened(selected, false);
+                    return;
+                }
+                match self
+                    .tree
+                    .get_parent_index(selected)
+                    .and_then(|parent| self.row_of(parent))
+                {
+                    Some(parent_row) => parent_row,
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        self.select(self.row_indices[new_row]);
+    }
+
+    /// Selects the next shown item whose name starts with `prefix`, ignoring
+    /// the case. The selected item matches too when `include_selected`.
+    pub fn select_by_prefix(&mut self, prefix: &str, include_selected: bool) {
+        let rows_count = self.row_indices.len();
+        let start = match self.row_of(self.selected_index) {
+            Some(row) if include_selected => row,
+            Some(row) => row + 1,
+            None => 0,
+        };
+
+        for offset in 0..rows_count {
+            let index = self.row_indices[(start + offset) % rows_count];
+            let name = (self.name_fn)(&self.tree[index].value);
+            if name
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+            {
+                self.select(index);
+                return;
+            }
+        }
+    }
+
+    /// Opens the items up to `levels` levels under the first displayed one,
+    /// closing the deeper ones, or opens all of them without a level.
+    pub fn open_levels(&mut self, levels: Option<u8>) {
+        for item_state in self.items_state.iter_mut() {
+            let open = match levels {
+                Some(levels) => {
+                    (item_state.depth as u32) < self.min_depth_to_display as u32 + levels as u32
+                }
+                None => true,
+            };
+            if open {
+                item_state.flags.insert(TreeItemStateFlags::OPENED);
+            } else {
+                item_state
+                    .flags
+                    .remove(TreeItemStateFlags::OPENED | TreeItemStateFlags::FORCE_OPENED);
+            }
+        }
+
+        self.rebuild_rows();
+    }
+
+    /// Sorts the children again and rebuilds all the rows, when the sizes or
+    /// the order of the items changed.
+    #[profiling::function]
+    pub fn recompute_indices(&mut self) {
+        self.sort_children();
+        self.rebuild_rows();
+    }
+
+    fn sort_children(&mut self) {
+        let Self {
+            tree,
+            items_ui_data,
+            sorted_children,
+            children_ranges,
+            sort_fn,
+            ..
+        } = self;
+
+        sorted_children.clear();
+        children_ranges.clear();
+        for idx in 0..tree.len() {
+            let start = sorted_children.len();
+            for child_idx in tree.get_children(idx) {
+                sorted_children.push(child_idx);
+            }
+            let end = sorted_children.len();
+
+            sorted_children[start..end].sort_by(|a, b| {
+                sort_fn(
+                    (&tree[*a].value, &items_ui_data[*a]),
+                    (&tree[*b].value, &items_ui_data[*b]),
+                )
+            });
+            children_ranges.push(start as u32..end as u32);
+        }
+    }
+
+    /// Rebuilds the rows from the sorted children, after opening or closing
+    /// many items.
+    fn rebuild_rows(&mut self) {
+        self.row_indices.clear();
+        if self.tree.is_empty() {
+            return;
+        }
+
+        let scratch = scratch_arena(&[]);
+        let mut rows = Vec::new(&scratch, self.tree.len());
+        if self.items_state[0].depth >= self.min_depth_to_display {
+            rows.push(0);
+        }
+        self.push_rows_under(0, &mut rows);
+        self.row_indices.extend_from_slice(&rows);
+    }
+
+    /// Appends the rows shown under an item, in order.
+    fn push_rows_under(&self, idx: usize, rows: &mut std::vec::Vec<usize, &Arena>) {
+        if !self.is_opened(idx) {
+            return;
+        }
+
+        let scratch = scratch_arena(&[rows.allocator()]);
+        let mut node_stack = Vec::new(&scratch, 1024);
+        node_stack.extend(self.visible_children(idx).rev());
+
+        while let Some(idx) = node_stack.pop() {
+            if self.items_state[idx].depth >= self.min_depth_to_display {
+                rows.push(idx);
+            }
+
+            if self.is_opened(idx) {
+                node_stack.extend(self.visible_children(idx).rev());
+            }
+        }
+    }
+
+    /// Children shown under an opened item, in order.
+    fn visible_children(&self, idx: usize) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        let range = &self.children_ranges[idx];
+        self.sorted_children[range.start as usize..range.end as usize]
+            .iter()
+            .copied()
+            .filter(|&child_idx| self.items_state[child_idx].visible())
+    }
+
+    /// Opens or closes an item, only updating the rows under it.
+    pub fn set_opened(&mut self, idx: usize, opened: bool) {
+        let flags = &mut self.items_state[idx].flags;
+        if opened {
+            flags.insert(TreeItemStateFlags::OPENED);
+        } else {
+            flags.remove(TreeItemStateFlags::OPENED | TreeItemStateFlags::FORCE_OPENED);
+        }
+
+        let Some(row) = self.row_of(idx) else {
+            // The rows of the items that aren't displayed are computed when
+            // their parents open, except for the levels that are never
+            // displayed.
+            if self.items_state[idx].depth < self.min_depth_to_display {
+                self.rebuild_rows();
+            }
+            return;
+        };
+
+        let depth = self.items_state[idx].depth;
+        let rows_under = self.row_indices[row + 1..]
+            .iter()
+            .position(|&row_idx| self.items_state[row_idx].depth <= depth)
+            .unwrap_or(self.row_indices.len() - row - 1);
+        self.row_indices.remove_range(row + 1..row + 1 + rows_under);
+
+        if opened {
+            let scratch = scratch_arena(&[]);
+            let mut rows = Vec::new(&scratch, 1024);
+            self.push_rows_under(idx, &mut rows);
+            self.row_indices.insert_from_slice(row + 1, &rows);
+        }
+    }
+}
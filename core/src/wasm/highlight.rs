@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+/// Kinds of the tokens of the wasm instructions, as shown by the assembly
+/// viewer in the `Debug` format of `wasmparser::Operator`, like
+/// `LocalGet { local_index: 0 }`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmToken {
+    Comment,
+    /// The name of the instruction, like `LocalGet`.
+    Opcode,
+    /// A local, function, type, label, table or memory index.
+    Index,
+    /// Any other number, like the value of a constant or a memory offset.
+    Immediate,
+    /// The name of an immediate, like `local_index`.
+    Field,
+    /// The names of the types of the immediates, like `MemArg` or `I32`.
+    Type,
+    Punctuation,
+    Whitespace,
+}
+
+/// Whether the numbers of the field are indices.
+fn is_index_field(field: &str) -> bool {
+    field.ends_with("index")
+        || field.ends_with("idx")
+        || matches!(
+            field,
+            "relative_depth" | "targets" | "default" | "memory" | "table" | "mem"
+        )
+}
+
+/// Splits an instruction into tokens, covering all the text.
+pub fn tokenize(text: &str) -> Vec<(Range<usize>, WasmToken)> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut field: Option<&str> = None;
+    let mut start = 0;
+
+    let take_while = |from: usize, predicate: &dyn Fn(u8) -> bool| {
+        from + bytes[from..]
+            .iter()
+            .position(|&byte| !predicate(byte))
+            .unwrap_or(bytes.len() - from)
+    };
+
+    while start < bytes.len() {
+        let byte = bytes[start];
+        let next_is_digit = bytes.get(start + 1).is_some_and(u8::is_ascii_digit);
+
+        let (end, token) = if text[start..].starts_with(";;") {
+            (bytes.len(), WasmToken::Comment)
+        } else if byte.is_ascii_whitespace() {
+            (
+                take_while(start, &|byte| byte.is_ascii_whitespace()),
+                WasmToken::Whitespace,
+            )
+        } else if byte.is_ascii_digit() || (byte == b'-' && next_is_digit) {
+            let end = take_while(start + 1, &|byte| {
+                byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'_'
+            });
+            let token = if field.is_some_and(is_index_field) {
+                WasmToken::Index
+            } else {
+                WasmToken::Immediate
+            };
+            (end, token)
+        } else if byte.is_ascii_alphabetic() || byte == b'_' {
+            let end = take_while(start, &|byte| byte.is_ascii_alphanumeric() || byte == b'_');
+            let followed_by_colon = text[end..].trim_start().starts_with(':');
+            let token = if followed_by_colon {
+                field = Some(&text[start..end]);
+                WasmToken::Field
+            } else if tokens
+                .iter()
+                .all(|(_, token)| *token == WasmToken::Whitespace)
+            {
+                WasmToken::Opcode
+            } else {
+                WasmToken::Type
+            };
+            (end, token)
+        } else {
+            let len = text[start..].chars().next().map_or(1, char::len_utf8);
+            (start + len, WasmToken::Punctuation)
+        };
+
+        tokens.push((start..end, token));
+        start = end;
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::{WasmToken, tokenize};
+
+    fn kinds(text: &str) -> Vec<(&str, WasmToken)> {
+        tokenize(text)
+            .into_iter()
+            .filter(|(_, token)| *token != WasmToken::Whitespace)
+            .map(|(range, token)| (&text[range], token))
+            .collect()
+    }
+
+    #[test]
+    fn instructions_are_split_into_tokens() {
+        assert_eq!(
+            kinds("LocalGet { local_index: 3 }"),
+            [
+                ("LocalGet", WasmToken::Opcode),
+                ("{", WasmToken::Punctuation),
+                ("local_index", WasmToken::Field),
+                (":", WasmToken::Punctuation),
+                ("3", WasmToken::Index),
+                ("}", WasmToken::Punctuation),
+            ]
+        );
+
+        let tokens = kinds("I32Load { memarg: MemArg { align: 2, offset: -8 } } ;; load");
+        assert!(tokens.contains(&("MemArg", WasmToken::Type)));
+        assert!(tokens.contains(&("-8", WasmToken::Immediate)));
+        assert_eq!(tokens.last(), Some(&(";; load", WasmToken::Comment)));
+
+        assert_eq!(kinds("0x002a"), [("0x002a", WasmToken::Immediate)]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod highlight;
+pub mod parser;
+pub mod wasm_opt;
@@ -0,0 +1,482 @@
+use std::ops::{ControlFlow, Range};
+use wasmparser::{Encoding, Export, FuncType, FunctionBody};
+
+use crate::arena::{
+    Arena,
+    array::{Array, GrowableArray},
+    string::String,
+    vec::Vec,
+};
+
+pub struct WasmData<'a> {
+    pub bytes: &'a [u8],
+
+    /// The wasm version of the processed data.
+    pub version: u16,
+
+    /// Types section
+    pub types_section: TypeSection<'a>,
+
+    /// Functions section
+    pub functions_section: FunctionSection<'a>,
+
+    /// Number of imported functions. Imported functions come first in the
+    /// function index space, so function `idx` of the functions section
+    /// has the wasm function index `idx + imported_function_count`.
+    pub imported_function_count: u32,
+
+    /// Export section
+    pub exports: Array<'a, Export<'a>>,
+
+    /// Function index of the start function, if any.
+    pub start_function: Option<u32>,
+
+    /// Function indices referenced by element segments, i.e. callable through tables.
+    pub element_functions: Vec<'a, u32>,
+
+    /// `(slot, function index)` pairs of the function table, from the active
+    /// element segments with a constant offset.
+    pub table_functions: Array<'a, (u32, u32)>,
+
+    /// Data segments
+    pub data_segments: Array<'a, DataSegment<'a>>,
+
+    /// `(field, tool)` pairs of the `producers` section, e.g. `("processed-by", "rustc")`.
+    pub producers: Vec<'a, (&'a str, &'a str)>,
+
+    /// All the `debug_*` sections in the bundle.
+    pub debug_sections: Vec<'a, (&'a str, &'a [u8])>,
+
+    /// All the sections of the module, in file order.
+    pub sections: Array<'a, Section<'a>>,
+}
+
+impl<'a> WasmData<'a> {
+    /// `progress` is called with the parsed part of the module, from 0 to 1,
+    /// at the start of the sections and of the functions. Returns `None` if
+    /// it breaks, to cancel the parsing.
+    #[profiling::function]
+    pub fn from_bytes(
+        arena: &'a Arena,
+        bytes: &'a [u8],
+        progress: &dyn Fn(f32) -> ControlFlow<()>,
+    ) -> Option<Self> {
+        let mut version = 0;
+        let mut types_section = TypeSection {
+            types: Array::new(arena, 0),
+        };
+        let mut imported_function_count = 0;
+        let mut functions_section = FunctionSection {
+            range: Default::default(),
+            function_types: Array::new(arena, 0),
+            function_names: Array::new(arena, 0),
+            function_original_names: Array::new(arena, 0),
+            function_sizes: Array::new(arena, 0),
+            function_bodies: Array::new(arena, 0),
+            function_called: Array::new(arena, 0),
+            function_count: 0,
+            size_in_bytes: 0,
+        };
+        let mut exports = Array::new(arena, 0);
+        let mut start_function = None;
+        let mut element_functions = Vec::new(arena, 0);
+        let mut table_functions = Array::new(arena, 0);
+        let mut data_segments = Array::new(arena, 0);
+        let mut producers = Vec::new(arena, 0);
+        let mut debug_sections = Vec::new(arena, 0);
+        let mut sections = GrowableArray::new(arena);
+
+        for section in wasmparser::Parser::new(0).parse_all(bytes) {
+            let payload = match section {
+                Ok(section) => section,
+                Err(err) => panic!("Failed to parse section with error {}", err),
+            };
+
+            if let Some((id, range)) = payload.as_section() {
+                if progress(range.start as f32 / bytes.len() as f32).is_break() {
+                    return None;
+                }
+
+                let name = match &payload {
+                    wasmparser::Payload::CustomSection(custom_section_reader) => {
+                        custom_section_reader.name()
+                    }
+                    _ => section_name(id),
+                };
+
+                sections.push(Section { id, name, range });
+            }
+
+            match payload {
+                wasmparser::Payload::Version { num, encoding, .. } => {
+                    assert!(
+                        encoding == Encoding::Module,
+                        "Encoding '{:?}' not supported",
+                        encoding
+                    );
+
+                    version = num;
+                }
+                wasmparser::Payload::TypeSection(type_section_reader) => {
+                    types_section.types = Array::new(arena, type_section_reader.count() as usize);
+
+                    for rec_group in type_section_reader.into_iter() {
+                        let rec_group = match rec_group {
+                            Ok(rec_group) => rec_group,
+                            Err(err) => panic!("Failed to parse type with error {}", err),
+                        };
+
+                        for ty in rec_group.into_types() {
+                            assert!(ty.is_final, "Unexepcted sub-type in type section");
+                            assert!(
+                                !ty.composite_type.shared,
+                                "Unexpected shared type in type section"
+                            );
+
+                            match ty.composite_type.inner {
+                                wasmparser::CompositeInnerType::Func(func_type) => {
+                                    types_section.types.push(func_type)
+                                }
+                                _ => panic!("Unexpected non-function-type in type section"),
+                            }
+                        }
+                    }
+                }
+                wasmparser::Payload::ImportSection(import_section_reader) => {
+                    for import in import_section_reader.into_iter() {
+                        let import = match import {
+                            Ok(import) => import,
+                            Err(err) => panic!("Failed to parse import with error {}", err),
+                        };
+
+                        // Only imported functions take indices in the function index space.
+                        if matches!(import.ty, wasmparser::TypeRef::Func(_)) {
+                            imported_function_count += 1;
+                        }
+                    }
+                }
+                wasmparser::Payload::ExportSection(export_section_reader) => {
+                    exports = Array::new(arena, export_section_reader.count() as usize);
+
+                    for export in export_section_reader.into_iter() {
+                        match export {
+                            Ok(export) => exports.push(export),
+                            Err(err) => panic!("Failed to parse export with error {}", err),
+                        }
+                    }
+                }
+                wasmparser::Payload::StartSection { func, .. } => {
+                    start_function = Some(func);
+                }
+                wasmparser::Payload::ElementSection(element_section_reader) => {
+                    let item_count = element_section_reader
+                        .clone()
+                        .into_iter()
+                        .flatten()
+                        .map(|element| match element.items {
+                            wasmparser::ElementItems::Functions(functions) => functions.count(),
+                            wasmparser::ElementItems::Expressions(_, exprs) => exprs.count(),
+                        })
+                        .sum::<u32>();
+                    table_functions = Array::new(arena, item_count as usize);
+
+                    for element in element_section_reader.into_iter() {
+                        let element = match element {
+                            Ok(element) => element,
+                            Err(err) => panic!("Failed to parse element with error {}", err),
+                        };
+
+                        // Only the slots of the function table are tracked.
+                        let table_offset = match &element.kind {
+                            wasmparser::ElementKind::Active {
+                                table_index: None | Some(0),
+                                offset_expr,
+                            } => const_offset(offset_expr),
+                            _ => None,
+                        };
+
+                        match element.items {
+                            wasmparser::ElementItems::Functions(functions) => {
+                                for (slot, function_index) in
+                                    functions.into_iter().flatten().enumerate()
+                                {
+                                    element_functions.push(function_index);
+                                    if let Some(table_offset) = table_offset {
+                                        table_functions
+                                            .push((table_offset + slot as u32, function_index));
+                                    }
+                                }
+                            }
+                            wasmparser::ElementItems::Expressions(_, exprs) => {
+                                for (slot, expr) in exprs.into_iter().flatten().enumerate() {
+                                    for op in expr.get_operators_reader().into_iter().flatten() {
+                                        if let wasmparser::Operator::RefFunc { function_index } = op
+                                        {
+                                            element_functions.push(function_index);
+                                            if let Some(table_offset) = table_offset {
+                                                table_functions.push((
+                                                    table_offset + slot as u32,
+                                                    function_index,
+                                                ));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                wasmparser::Payload::DataSection(data_section_reader) => {
+                    data_segments = Array::new(arena, data_section_reader.count() as usize);
+
+                    for data in data_section_reader.into_iter() {
+                        let data = match data {
+                            Ok(data) => data,
+                            Err(err) => panic!("Failed to parse data segment with error {}", err),
+                        };
+
+                        let offset = match &data.kind {
+                            wasmparser::DataKind::Active {
+                                memory_index: 0,
+                                offset_expr,
+                            } => const_offset(offset_expr),
+                            _ => None,
+                        };
+
+                        data_segments.push(DataSegment {
+                            offset,
+                            data: data.data,
+                        });
+                    }
+                }
+                wasmparser::Payload::FunctionSection(function_section_reader) => {
+                    functions_section.function_types =
+                        Array::new(arena, function_section_reader.count() as usize);
+
+                    for fn_type_idx in function_section_reader.into_iter() {
+                        let fn_type_idx = match fn_type_idx {
+                            Ok(fn_type_id) => fn_type_id,
+                            Err(err) => panic!("Failed to parse function type with error {}", err),
+                        };
+
+                        functions_section.function_types.push(fn_type_idx as usize);
+                    }
+                }
+                wasmparser::Payload::CodeSectionStart { count, range, .. } => {
+                    functions_section.range = range;
+                    functions_section.function_bodies = Array::new(arena, count as usize);
+                    functions_section.function_sizes = Array::new(arena, count as usize);
+
+                    functions_section.function_names = Array::new(arena, count as usize);
+                    functions_section.function_original_names = Array::new(arena, count as usize);
+                    functions_section.function_called = Array::new(arena, count as usize);
+                    for _ in 0..count {
+                        functions_section.function_names.push("");
+                        functions_section.function_original_names.push("");
+                    }
+
+                    functions_section.function_count = count as usize;
+                }
+                wasmparser::Payload::CodeSectionEntry(function_body) => {
+                    // The code section is most of the module.
+                    if progress(function_body.range().start as f32 / bytes.len() as f32).is_break()
+                    {
+                        return None;
+                    }
+
+                    functions_section
+                        .function_sizes
+                        .push(function_body.as_bytes().len() as u32);
+                    functions_section.size_in_bytes += function_body.as_bytes().len();
+
+                    functions_section.function_bodies.push(function_body);
+                }
+                wasmparser::Payload::CustomSection(custom_section_reader) => {
+                    match custom_section_reader.as_known() {
+                        wasmparser::KnownCustom::Name(name_section_reader) => {
+                            for name in name_section_reader.into_iter() {
+                                let name = match name {
+                                    Ok(name) => name,
+                                    Err(err) => panic!("Failed to parse name with error {}", err),
+                                };
+
+                                match name {
+                                    wasmparser::Name::Function(name_map) => {
+                                        for naming in name_map
+                                            .into_iter()
+                                            .skip(imported_function_count as usize)
+                                        {
+                                            let naming = match naming {
+                                                Ok(naming) => naming,
+                                                Err(err) => panic!(
+                                                    "Failed to parse function name with error {}",
+                                                    err
+                                                ),
+                                            };
+
+                                            let linkage_name = naming.name;
+                                            let demangled_name =
+                                                demangled_name(arena, linkage_name);
+
+                                            let fn_index =
+                                                (naming.index - imported_function_count) as usize;
+
+                                            functions_section.function_names[fn_index] =
+                                                demangled_name;
+                                            functions_section.function_original_names[fn_index] =
+                                                linkage_name;
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        wasmparser::KnownCustom::Producers(producers_section_reader) => {
+                            // The producers section is informative only, skip it if malformed.
+                            for field in producers_section_reader.into_iter().flatten() {
+                                for value in field.values.into_iter().flatten() {
+                                    producers.push((field.name, value.name));
+                                }
+                            }
+                        }
+                        wasmparser::KnownCustom::Unknown => {
+                            if custom_section_reader.name().starts_with(".debug") {
+                                debug_sections.push((
+                                    custom_section_reader.name(),
+                                    custom_section_reader.data(),
+                                ));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // // Extract symbol dependencies
+        // for idx in 0..functions_section.function_bodies.len() {
+        //     let function_body = &functions_section.function_bodies[idx];
+
+        //     // TODO: (bruno) what is the minimum instruction size here? surely it's not 1 byte
+        //     let mut dependants = Array::new(arena, function_body.as_bytes().len());
+
+        //     let mut operators_reader = match function_body.get_operators_reader() {
+        //         Ok(operators_reader) => operators_reader,
+        //         Err(err) => {
+        //             panic!("Failed to parse function operators with error {}", err)
+        //         }
+        //     };
+
+        //     while !operators_reader.eof() {
+        //         let operator = match operators_reader.read() {
+        //             Ok(operator) => operator,
+        //             Err(err) => {
+        //                 panic!("Failed to parse function operator with error {}", err)
+        //             }
+        //         };
+
+        //         match operator {
+        //             Operator::Call { function_index } => {
+        //                 if function_index >= imports_count {
+        //                     dependants.push(function_index - imports_count);
+        //                 }
+        //             }
+        //             // Operator::CallIndirect { type_index, table_index } => todo!(),
+        //             _ => {}
+        //         }
+        //     }
+
+        //     dependants.shrink_to_fit();
+        //     functions_section.function_called.push(dependants);
+        // }
+
+        Some(Self {
+            bytes,
+            version,
+            types_section,
+            functions_section,
+            imported_function_count,
+            exports,
+            start_function,
+            element_functions,
+            table_functions,
+            data_segments,
+            producers,
+            debug_sections,
+            sections: sections.into_array(),
+        })
+    }
+}
+
+pub struct Section<'a> {
+    pub id: u8,
+    /// Name of the custom section, or of the section kind for the others.
+    pub name: &'a str,
+    /// Contents of the section, without the id and size header.
+    pub range: Range<usize>,
+}
+
+pub struct DataSegment<'a> {
+    /// Address of the segment in linear memory, `None` for passive segments
+    /// or if it isn't a constant.
+    pub offset: Option<u32>,
+    pub data: &'a [u8],
+}
+
+/// Returns the offset of an active segment, if it's a constant.
+fn const_offset(offset_expr: &wasmparser::ConstExpr<'_>) -> Option<u32> {
+    match offset_expr.get_operators_reader().read().ok()? {
+        wasmparser::Operator::I32Const { value } => Some(value as u32),
+        _ => None,
+    }
+}
+
+/// Name of a non-custom section, from its id.
+fn section_name(id: u8) -> &'static str {
+    match id {
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        11 => "data",
+        12 => "datacount",
+        13 => "tag",
+        _ => "unknown",
+    }
+}
+
+pub struct TypeSection<'a> {
+    pub types: Array<'a, FuncType>,
+}
+
+pub struct FunctionSection<'a> {
+    pub range: Range<usize>,
+    pub function_types: Array<'a, usize>,
+    pub function_original_names: Array<'a, &'a str>,
+    pub function_names: Array<'a, &'a str>,
+    pub function_bodies: Array<'a, FunctionBody<'a>>,
+    pub function_sizes: Array<'a, u32>,
+    pub function_called: Array<'a, Array<'a, u32>>,
+    pub function_count: usize,
+    pub size_in_bytes: usize,
+}
+
+fn demangled_name<'a>(arena: &'a Arena, name: &'a str) -> &'a str {
+    use std::fmt::Write;
+    let demangled_symbol = rustc_demangle::demangle(name);
+
+    // Demangled names should be shorter, generally, but adding buffer here just in case
+    let mut demangled_name = String::new(arena, name.len() * 2);
+
+    _ = write!(&mut demangled_name, "{}", demangled_symbol);
+
+    demangled_name.shrink_to_fit();
+    demangled_name.to_str()
+}
@@ -0,0 +1,68 @@
+use std::{
+    io::Read,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+
+use crate::progress::Phase;
+
+/// How often `wasm-opt` is checked for completion or cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs `wasm-opt -Oz` on the module, writing the result to the temp directory.
+///
+/// Names and debug info are kept so the functions of both builds can be matched.
+/// `wasm-opt` is killed once `progress` breaks.
+pub fn optimize(
+    path: &Path,
+    progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+) -> anyhow::Result<PathBuf> {
+    let file_stem = path
+        .file_stem()
+        .context("The module path has no file name")?;
+    let mut output_name = file_stem.to_os_string();
+    output_name.push(".wasm-opt.wasm");
+    let output_path = std::env::temp_dir().join(output_name);
+
+    let mut child = Command::new("wasm-opt")
+        .arg("-Oz")
+        .arg("--debuginfo")
+        .arg(path)
+        .arg("-o")
+        .arg(&output_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run `wasm-opt`, is binaryen installed and in the PATH?")?;
+
+    // Read on another thread, `wasm-opt` blocking once the pipe is full.
+    let mut stderr_pipe = child.stderr.take().context("No stderr for `wasm-opt`")?;
+    let stderr = std::thread::spawn(move || {
+        let mut stderr = String::new();
+        let _ = stderr_pipe.read_to_string(&mut stderr);
+        stderr
+    });
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if progress(Phase::OptimizingWithWasmOpt, 0.0).is_break() {
+            child.kill()?;
+            child.wait()?;
+            bail!("`wasm-opt` was cancelled");
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        bail!("`wasm-opt` failed: {}", stderr.trim());
+    }
+
+    Ok(output_path)
+}
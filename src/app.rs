@@ -1,60 +1,716 @@
-use crate::arena::{Arena, memory::GB, scratch::scratch_arena, string};
-use crate::code_viewer::{CodeViewer, RowData};
-use crate::data_provider::{FunctionsView, SourceCodeView};
+use crate::analysis::budget::{BudgetViolation, SizeBudget, SizeBudgets};
+use crate::analysis::call_graph::call_target;
+use crate::analysis::cfg::{ControlFlowGraph, branches};
+use crate::analysis::collapsed_stacks::collapsed_stacks;
+use crate::analysis::compilation_units::{CompilationUnitReport, CompilationUnitSortKey};
+use crate::analysis::diff::SizeDiffReport;
+use crate::analysis::duplicate_data::DuplicateDataReport;
+use crate::analysis::export::AnalysisExport;
+use crate::analysis::feature_attribution::FeatureAttributionReport;
+use crate::analysis::function_breakdown::{BreakdownSortKey, FunctionBreakdown};
+use crate::analysis::generic_bloat::{GenericBloatReport, GenericBloatSortKey};
+use crate::analysis::graphml::call_graph_graphml;
+use crate::analysis::graphviz::{call_graph_dot, render_svg, tree_dot};
+use crate::analysis::inlining::InliningReport;
+use crate::analysis::line_bytes::bytes_per_line;
+use crate::analysis::padding::PaddingReport;
+use crate::analysis::panic_machinery::PanicMachineryReport;
+use crate::analysis::recursion::RecursionReport;
+use crate::analysis::section_summary::SectionSummary;
+use crate::analysis::suggestions::SuggestionsReport;
+use crate::analysis::unwinding::UnwindingReport;
+use crate::analysis::vtables::VtableReport;
+use crate::arena::{
+    Arena, ArenaUsage, memory,
+    scratch::{scratch_arena, scratch_arenas_usage},
+    string,
+};
+use crate::bookmarks::Bookmarks;
+use crate::code_viewer::{CodeViewer, RowData, RowLink, SyntaxTheme};
+use crate::data_provider::{DataProvider, FunctionsView, NameStyle, SourceCodeView};
 use crate::data_provider_twiggy::DataProviderTwiggy;
+use crate::file_watcher::FileWatcher;
 use crate::functions_explorer::FunctionsExplorer;
-use crate::memory_viewer::MemoryViewer;
+use crate::gui::appearance::{Appearance, RowHeight};
+use crate::gui::arena_usage_viewer::ArenaUsageViewer;
+use crate::gui::bookmarks_viewer::BookmarksViewer;
+use crate::gui::byte_map::{self, ByteKind, byte_kinds};
+use crate::gui::cfg_viewer::CfgViewer;
+use crate::gui::code_origin_viewer::CodeOriginViewer;
+use crate::gui::compilation_units_viewer::CompilationUnitsViewer;
+use crate::gui::crate_treemap_viewer::CrateTreemapViewer;
+use crate::gui::dead_exports_viewer::DeadExportsViewer;
+use crate::gui::dominators_viewer::{DominatorsViewer, ExpandedPaths};
+use crate::gui::duplicate_data_viewer::DuplicateDataViewer;
+use crate::gui::feature_attribution_viewer::FeatureAttributionViewer;
+use crate::gui::function_breakdown_viewer::FunctionBreakdownViewer;
+use crate::gui::generic_bloat_viewer::GenericBloatViewer;
+use crate::gui::icicle_viewer::IcicleViewer;
+use crate::gui::inlining_viewer::InliningViewer;
+use crate::gui::navigation_history::NavigationHistory;
+use crate::gui::padding_viewer::PaddingViewer;
+use crate::gui::panic_machinery_viewer::PanicMachineryViewer;
+use crate::gui::profiler_viewer::ProfilerViewer;
+use crate::gui::recursion_viewer::RecursionViewer;
+use crate::gui::section_summary_viewer::{SectionSummaryViewer, show_size_bar};
+use crate::gui::size_diff_viewer::SizeDiffViewer;
+use crate::gui::suggestions_viewer::SuggestionsViewer;
+use crate::gui::unwinding_viewer::UnwindingViewer;
+use crate::gui::vtables_viewer::VtablesViewer;
+use crate::memory_viewer::{AddressOwner, MemoryViewer};
+use crate::notes::Notes;
 use crate::path::PathExt;
+use crate::progress::{Phase, Progress};
+use crate::provider_registry::{self, FileType};
+use crate::source_cache::{SourceCache, source_rows};
+use crate::tags::Tags;
+use crate::timings::Timings;
+use crate::wasm::wasm_opt;
 use egui::{ComboBox, ScrollArea, Vec2b};
 use egui_file_dialog::FileDialog;
 use serde::ser::SerializeStruct;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Write;
 use std::fs;
+use std::ops::{ControlFlow, Range};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+use web_time::Instant;
+
+/// Opens the window, loading `file` in it once it shows.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_native(file: Option<PathBuf>) -> eframe::Result {
+    let native_options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([600.0, 400.0]),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "Binary Size Explorer",
+        native_options,
+        Box::new(|cc| {
+            let mut app = TemplateApp::new(cc);
+            if let Some(file) = file {
+                app.load_file(file);
+            }
+            Ok(Box::new(app))
+        }),
+    )
+}
+
+/// File analyzed before getting a file entry, maybe on another thread.
+struct LoadedFile {
+    data_provider: Box<dyn DataProvider<'static>>,
+    arena: Arena,
+    ty: FileType,
+}
+
+// SAFETY: The data provider only points to its arena, which moves along with
+// it, and the thread analyzing the file keeps nothing from it.
+unsafe impl Send for LoadedFile {}
+
+impl LoadedFile {
+    /// Loads the file with the format of `ty`, or with the detected one
+    /// without it.
+    fn load(
+        path: &Path,
+        ty: Option<FileType>,
+        progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+    ) -> Option<Self> {
+        let arena = Arena::new(memory::MAX_RESERVE);
+        let bytes = provider_registry::read_file(
+            unsafe { std::mem::transmute::<&Arena, &'static Arena>(&arena) },
+            path,
+        )
+        .ok()?;
+        Self::analyze(path, ty, arena, bytes, progress)
+    }
+
+    /// Loads a file uploaded to the page, named `path`, from its bytes.
+    fn upload(
+        path: &Path,
+        bytes: &[u8],
+        progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+    ) -> Option<Self> {
+        let arena = Arena::new(memory::MAX_RESERVE);
+        let arena_bytes = unsafe { std::mem::transmute::<&Arena, &'static Arena>(&arena) }
+            .alloc_slice_zeroed(bytes.len());
+        arena_bytes.copy_from_slice(bytes);
+        Self::analyze(path, None, arena, arena_bytes, progress)
+    }
 
-#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
-pub enum FileType {
-    Wasm,
+    /// `bytes` are in `arena`.
+    fn analyze(
+        path: &Path,
+        ty: Option<FileType>,
+        arena: Arena,
+        bytes: &'static [u8],
+        progress: &dyn Fn(Phase, f32) -> ControlFlow<()>,
+    ) -> Option<Self> {
+        let format = match ty {
+            Some(ty) => provider_registry::format(ty),
+            None => provider_registry::detect_format(path, bytes),
+        }?;
+        let data_provider = (format.load)(
+            unsafe { std::mem::transmute::<&Arena, &'static Arena>(&arena) },
+            bytes,
+            progress,
+        )
+        .ok()?;
+
+        Some(Self {
+            data_provider,
+            arena,
+            ty: format.file_type,
+        })
+    }
 }
 
+/// How often the files are checked for changes, to reload them.
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shown by the tabs of a file that isn't open anymore.
+const FILE_CLOSED: &str = "The file was closed";
+
+/// Frames whose time is shown by the profiler window.
+const FRAME_TIME_COUNT: usize = 240;
+
 pub struct FileEntry {
     pub path: PathBuf,
     pub ty: FileType,
-
-    pub data_provider: Option<DataProviderTwiggy<'static>>,
+    /// Modification time of the file when it was analyzed, it is analyzed
+    /// again once the file changes.
+    pub modified: Option<SystemTime>,
+
+    pub data_provider: Option<Box<dyn DataProvider<'static>>>,
+    pub budget_violations: Vec<BudgetViolation>,
+    /// Section sizes, shown in the bottom panel.
+    pub section_summary: SectionSummary,
+    /// Kinds of the bytes of the file, colored by the raw binary viewer.
+    pub byte_kinds: Vec<(Range<usize>, ByteKind)>,
+    /// Shown in the right panel while the file is the active one.
+    pub functions_explorer: FunctionsExplorer,
     // TODO: (bruno) We need a better way to have both the arena
     // and the object allocated with it as part of a struct
-    #[allow(unused)]
     pub arena: Arena,
 }
 
+impl FileEntry {
+    /// The analyzed module, which the tabs and the functions explorer show,
+    /// `None` while loading or for the files without one.
+    pub fn module(&self) -> Option<&DataProviderTwiggy<'static>> {
+        self.data_provider.as_ref()?.module()
+    }
+
+    pub fn module_mut(&mut self) -> Option<&mut DataProviderTwiggy<'static>> {
+        self.data_provider.as_mut()?.module_mut()
+    }
+}
+
 struct TabViewer<'a> {
     /// All the file entries currently loaded.
     file_entries: &'a Vec<FileEntry>,
+    /// File shown in the functions explorer.
+    active_file: usize,
+
+    /// Function selected from one of the tabs with the index of its file, to
+    /// be shown in the functions explorer.
+    selected_function: Option<(usize, usize)>,
+
+    bookmarks: &'a mut Bookmarks,
+    /// Namespaces opened in the linked dominators tabs.
+    linked_dominator_paths: &'a mut ExpandedPaths,
+
+    syntax_theme: SyntaxTheme,
 }
 
 impl egui_dock::TabViewer for TabViewer<'_> {
     type Tab = DockTab;
 
     fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
-        tab.title.clone().into()
+        // The tabs of the different files are told apart by the file name.
+        match tab
+            .contents
+            .file_index()
+            .filter(|_| self.file_entries.len() > 1)
+            .and_then(|file_index| self.file_entries.get(file_index))
+        {
+            Some(file_entry) => format!("{} ({})", tab.title, file_name(&file_entry.path)).into(),
+            None => tab.title.clone().into(),
+        }
+    }
+
+    fn context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        tab: &mut Self::Tab,
+        _surface: egui_dock::SurfaceIndex,
+        _node: egui_dock::NodeIndex,
+    ) {
+        if ui
+            .button("Pop out")
+            .on_hover_text("Moves the tab to its own window, closing it docks the tab back")
+            .clicked()
+        {
+            tab.pop_out = true;
+            ui.close_menu();
+        }
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
+        if self.file_entries.len() > 1
+            && let Some(file_index) = tab.contents.file_index()
+            && file_index < self.file_entries.len()
+        {
+            let mut selected_file = file_index;
+            ComboBox::from_label("File")
+                .selected_text(file_name(&self.file_entries[file_index].path))
+                .show_ui(ui, |ui| {
+                    for (idx, file_entry) in self.file_entries.iter().enumerate() {
+                        ui.selectable_value(&mut selected_file, idx, file_name(&file_entry.path))
+                            .on_hover_text(file_entry.path.to_string_lossy());
+                    }
+                });
+            if selected_file != file_index {
+                tab.contents.set_file(selected_file);
+            }
+        }
+
         match &mut tab.contents {
             TabContent::SourceCodeViewer { code_viewer, .. } => {
-                code_viewer.show_code_as_table(ui);
+                code_viewer.show_code_as_table(ui, self.syntax_theme);
             }
 
             TabContent::AssemblyViewer { asm, .. } => {
-                asm.show_code_as_table(ui);
+                // The assembly shows the functions of the active file.
+                if let Some(function) = asm.show_code_as_table(ui, self.syntax_theme) {
+                    self.selected_function = Some((self.active_file, function));
+                }
+            }
+
+            TabContent::CompilationUnitReport {
+                file_index,
+                sort_key,
+                report,
+            } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| {
+                    let mut report = CompilationUnitReport::new(data_provider);
+                    report.sort(*sort_key);
+                    report
+                });
+
+                if let Some(function_idx) = CompilationUnitsViewer::show(ui, report, sort_key) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::GenericBloatReport {
+                file_index,
+                sort_key,
+                report,
+            } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| {
+                    let mut report = GenericBloatReport::new(data_provider);
+                    report.sort(*sort_key);
+                    report
+                });
+
+                if let Some(function_idx) = GenericBloatViewer::show(ui, report, sort_key) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::FunctionBreakdown {
+                file_index,
+                function,
+                sort_key,
+                breakdown,
+            } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+                let Some(function) = *function else {
+                    ui.label("Select a function to break its size down.");
+                    return;
+                };
+
+                let breakdown = breakdown.get_or_insert_with(|| {
+                    let mut breakdown = FunctionBreakdown::new(data_provider, function);
+                    breakdown.sort(*sort_key);
+                    breakdown
+                });
+
+                FunctionBreakdownViewer::show(ui, breakdown, sort_key, data_provider);
+            }
+
+            TabContent::ControlFlowGraph {
+                file_index,
+                function,
+                graph,
+            } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+                let Some(function) = *function else {
+                    ui.label("Select a function to show its control-flow graph.");
+                    return;
+                };
+
+                let graph =
+                    graph.get_or_insert_with(|| ControlFlowGraph::new(data_provider, function));
+
+                CfgViewer::show(ui, graph, data_provider);
+            }
+
+            TabContent::InliningReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| InliningReport::new(data_provider));
+
+                if let Some(function_idx) = InliningViewer::show(ui, report, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::PanicMachineryReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| PanicMachineryReport::new(data_provider));
+
+                if let Some(function_idx) = PanicMachineryViewer::show(ui, report, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::UnwindingReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| UnwindingReport::new(data_provider));
+
+                if let Some(function_idx) = UnwindingViewer::show(ui, report, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::CrateTreemap { file_index, viewer } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                if let Some(function_idx) = viewer.show(ui, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::Bookmarks { file_index, viewer } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                if let Some(function_idx) = viewer.show(ui, self.bookmarks, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::Icicle { file_index, viewer } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                viewer.show(ui, data_provider);
+            }
+
+            TabContent::Dominators { file_index, viewer } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                viewer.show(ui, data_provider, self.linked_dominator_paths);
+            }
+
+            TabContent::CodeOrigin { file_index, viewer } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                if let Some(function_idx) = viewer.show(ui, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::DeadExportsReport { file_index } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = &data_provider.dead_exports;
+                if let Some(function_idx) = DeadExportsViewer::show(ui, report, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::DuplicateDataReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report
+                    .get_or_insert_with(|| DuplicateDataReport::new(&data_provider.wasm_data));
+                DuplicateDataViewer::show(ui, report);
+            }
+
+            TabContent::SectionSummary {
+                file_index,
+                summary,
+            } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let summary =
+                    summary.get_or_insert_with(|| SectionSummary::new(&data_provider.wasm_data));
+                SectionSummaryViewer::show(ui, summary);
+            }
+
+            TabContent::SuggestionsReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| SuggestionsReport::new(data_provider));
+                SuggestionsViewer::show(ui, report);
+            }
+
+            TabContent::VtableReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| VtableReport::new(data_provider));
+
+                if let Some(function_idx) = VtablesViewer::show(ui, report, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::FeatureAttribution {
+                baseline_file_index,
+                build_file_indices,
+                report,
+            } => {
+                let data_provider_at = |file_index: usize| {
+                    self.file_entries
+                        .get(file_index)
+                        .and_then(FileEntry::module)
+                };
+                let Some(baseline) = data_provider_at(*baseline_file_index) else {
+                    return;
+                };
+                let Some(builds) = build_file_indices
+                    .iter()
+                    .map(|&file_index| {
+                        let data_provider = data_provider_at(file_index)?;
+                        Some((self.file_entries[file_index].path.as_path(), data_provider))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return;
+                };
+
+                let report =
+                    report.get_or_insert_with(|| FeatureAttributionReport::new(baseline, &builds));
+
+                FeatureAttributionViewer::show(ui, report);
+            }
+
+            TabContent::PaddingReport { file_index, report } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| PaddingReport::new(data_provider));
+
+                if let Some(function_idx) = PaddingViewer::show(ui, report, data_provider) {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::RecursionReport {
+                file_index,
+                selected_export,
+                report,
+            } => {
+                let Some(data_provider) = self
+                    .file_entries
+                    .get(*file_index)
+                    .and_then(FileEntry::module)
+                else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| RecursionReport::new(data_provider));
+
+                if let Some(function_idx) =
+                    RecursionViewer::show(ui, report, selected_export, data_provider)
+                {
+                    self.selected_function = Some((*file_index, function_idx));
+                }
+            }
+
+            TabContent::SizeDiffReport {
+                old_file_index,
+                new_file_index,
+                report,
+            } => {
+                let data_provider_at = |file_index: usize| {
+                    self.file_entries
+                        .get(file_index)
+                        .and_then(FileEntry::module)
+                };
+                let (Some(old), Some(new)) = (
+                    data_provider_at(*old_file_index),
+                    data_provider_at(*new_file_index),
+                ) else {
+                    return;
+                };
+
+                let report = report.get_or_insert_with(|| SizeDiffReport::new(old, new));
+
+                if let Some(entry) = SizeDiffViewer::show(ui, report, old, new) {
+                    // Stays on the active file when the function is in it,
+                    // the new build being preferred otherwise.
+                    let selected_function =
+                        [(*new_file_index, entry.new), (*old_file_index, entry.old)]
+                            .into_iter()
+                            .filter_map(|(file_index, function_idx)| {
+                                Some((file_index, function_idx?))
+                            })
+                            .min_by_key(|&(file_index, _)| file_index != self.active_file);
+                    if selected_function.is_some() {
+                        self.selected_function = selected_function;
+                    }
+                }
             }
 
             TabContent::RawBinaryViewer { file_index } => {
-                if let Some(data_provider) = &self.file_entries[*file_index].data_provider {
-                    MemoryViewer::show(ui, &data_provider.wasm_data.bytes);
+                let Some(file_entry) = self.file_entries.get(*file_index) else {
+                    ui.weak(FILE_CLOSED);
+                    return;
+                };
+                if let Some(data_provider) = file_entry.module() {
+                    let wasm_data = &data_provider.wasm_data;
+                    // The bytes are colored by the kind of the section they are in.
+                    let dark_mode = ui.visuals().dark_mode;
+                    let regions: Vec<_> = file_entry
+                        .byte_kinds
+                        .iter()
+                        .map(|(range, kind)| (range.clone(), kind.color(dark_mode)))
+                        .collect();
+                    byte_map::show_legend(ui);
+
+                    // The bytes of the function selected in the functions explorer.
+                    let highlighted = file_entry.functions_explorer.selected_row.map(|function| {
+                        wasm_data.functions_section.function_bodies[function].range()
+                    });
+                    let clicked_function = MemoryViewer::show(
+                        ui,
+                        wasm_data.bytes,
+                        0,
+                        highlighted,
+                        &regions,
+                        &|address| address_owner(data_provider, address),
+                    );
+                    if let Some(function) = clicked_function {
+                        self.selected_function = Some((*file_index, function));
+                    }
                 }
             }
 
@@ -62,10 +718,12 @@ impl egui_dock::TabViewer for TabViewer<'_> {
                 file_index,
                 fn_index,
             } => {
-                if self.file_entries.len() <= *file_index {
+                let Some(file_entry) = self.file_entries.get(*file_index) else {
+                    ui.weak(FILE_CLOSED);
                     return;
                 };
-                if let Some(data_provider) = &self.file_entries[*file_index].data_provider {
+                if let Some(data_provider) = file_entry.module() {
+                    let mut clicked_function = None;
                     ScrollArea::both().auto_shrink(Vec2b::FALSE).show(ui, |ui| {
                         let wasm_data = &data_provider.wasm_data;
 
@@ -180,12 +838,26 @@ impl egui_dock::TabViewer for TabViewer<'_> {
                                         )
                                 });
 
-                            MemoryViewer::show(
+                            let function_body =
+                                &wasm_data.functions_section.function_bodies[*fn_index];
+                            let highlighted = (self.file_entries[*file_index]
+                                .functions_explorer
+                                .selected_row
+                                == Some(*fn_index))
+                            .then(|| function_body.range());
+                            clicked_function = MemoryViewer::show(
                                 ui,
-                                wasm_data.functions_section.function_bodies[*fn_index].as_bytes(),
+                                function_body.as_bytes(),
+                                function_body.range().start,
+                                highlighted,
+                                &[],
+                                &|address| address_owner(data_provider, address),
                             );
                         });
                     });
+                    if let Some(function) = clicked_function {
+                        self.selected_function = Some((*file_index, function));
+                    }
                 }
             }
         }
@@ -196,6 +868,9 @@ impl egui_dock::TabViewer for TabViewer<'_> {
 struct DockTab {
     contents: TabContent,
     title: String,
+    /// Set from the context menu of the tab, to move it to its own window.
+    #[serde(skip)]
+    pop_out: bool,
 }
 
 impl DockTab {
@@ -203,8 +878,33 @@ impl DockTab {
         DockTab {
             contents,
             title: title.into(),
+            pop_out: false,
+        }
+    }
+}
+
+/// Tab popped out of the dock into its own native window, docked back when
+/// the window is closed.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DetachedTab {
+    tab: DockTab,
+    #[serde(skip, default = "DetachedTab::new_viewport_id")]
+    viewport_id: egui::ViewportId,
+}
+
+impl DetachedTab {
+    fn new(tab: DockTab) -> Self {
+        Self {
+            tab,
+            viewport_id: Self::new_viewport_id(),
         }
     }
+
+    /// The window of a tab keeps its viewport while it's opened.
+    fn new_viewport_id() -> egui::ViewportId {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        egui::ViewportId::from_hash_of(("detached_tab", NEXT_ID.fetch_add(1, Ordering::Relaxed)))
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -225,24 +925,556 @@ enum TabContent {
         file_index: usize,
         fn_index: usize,
     },
+    GenericBloatReport {
+        file_index: usize,
+        sort_key: GenericBloatSortKey,
+        #[serde(skip)]
+        report: Option<GenericBloatReport>,
+    },
+    CompilationUnitReport {
+        file_index: usize,
+        sort_key: CompilationUnitSortKey,
+        #[serde(skip)]
+        report: Option<CompilationUnitReport>,
+    },
+    FunctionBreakdown {
+        file_index: usize,
+        /// Follows the function selected in the functions explorer.
+        function: Option<usize>,
+        sort_key: BreakdownSortKey,
+        #[serde(skip)]
+        breakdown: Option<FunctionBreakdown>,
+    },
+    ControlFlowGraph {
+        file_index: usize,
+        /// Follows the function selected in the functions explorer.
+        function: Option<usize>,
+        #[serde(skip)]
+        graph: Option<ControlFlowGraph>,
+    },
+    InliningReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<InliningReport>,
+    },
+    PanicMachineryReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<PanicMachineryReport>,
+    },
+    UnwindingReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<UnwindingReport>,
+    },
+    CrateTreemap {
+        file_index: usize,
+        viewer: CrateTreemapViewer,
+    },
+    CodeOrigin {
+        file_index: usize,
+        viewer: CodeOriginViewer,
+    },
+    Dominators {
+        file_index: usize,
+        viewer: DominatorsViewer,
+    },
+    Icicle {
+        file_index: usize,
+        viewer: IcicleViewer,
+    },
+    Bookmarks {
+        file_index: usize,
+        viewer: BookmarksViewer,
+    },
+    DeadExportsReport {
+        file_index: usize,
+    },
+    DuplicateDataReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<DuplicateDataReport>,
+    },
+    SectionSummary {
+        file_index: usize,
+        #[serde(skip)]
+        summary: Option<SectionSummary>,
+    },
+    SuggestionsReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<SuggestionsReport>,
+    },
+    VtableReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<VtableReport>,
+    },
+    PaddingReport {
+        file_index: usize,
+        #[serde(skip)]
+        report: Option<PaddingReport>,
+    },
+    RecursionReport {
+        file_index: usize,
+        #[serde(skip)]
+        selected_export: Option<usize>,
+        #[serde(skip)]
+        report: Option<RecursionReport>,
+    },
+    SizeDiffReport {
+        old_file_index: usize,
+        new_file_index: usize,
+        #[serde(skip)]
+        report: Option<SizeDiffReport>,
+    },
+    /// Builds with other feature sets, compared with the baseline built
+    /// without them.
+    FeatureAttribution {
+        #[serde(default)]
+        baseline_file_index: usize,
+        build_file_indices: Vec<usize>,
+        #[serde(skip)]
+        report: Option<FeatureAttributionReport>,
+    },
 }
 
-pub struct TemplateApp {
-    file_dialog: FileDialog,
-
-    last_path_picked: PathBuf,
-
-    analyzer_state: Option<AnalyzerState>,
-
-    functions_explorer: FunctionsExplorer,
-
-    file_entries: Vec<FileEntry>,
-
-    // TODO: (bruno) remove this with the function id once you re-write
-    // the parser
-    selected_row: Option<usize>,
+impl TabContent {
+    /// File of the tabs showing a single file, which can be switched to
+    /// another one.
+    fn file_index(&self) -> Option<usize> {
+        match self {
+            Self::RawBinaryViewer { file_index }
+            | Self::SectionsBinaryViewer { file_index, .. }
+            | Self::GenericBloatReport { file_index, .. }
+            | Self::CompilationUnitReport { file_index, .. }
+            | Self::FunctionBreakdown { file_index, .. }
+            | Self::ControlFlowGraph { file_index, .. }
+            | Self::InliningReport { file_index, .. }
+            | Self::PanicMachineryReport { file_index, .. }
+            | Self::UnwindingReport { file_index, .. }
+            | Self::CrateTreemap { file_index, .. }
+            | Self::CodeOrigin { file_index, .. }
+            | Self::Dominators { file_index, .. }
+            | Self::Icicle { file_index, .. }
+            | Self::Bookmarks { file_index, .. }
+            | Self::DeadExportsReport { file_index, .. }
+            | Self::DuplicateDataReport { file_index, .. }
+            | Self::SectionSummary { file_index, .. }
+            | Self::SuggestionsReport { file_index, .. }
+            | Self::VtableReport { file_index, .. }
+            | Self::PaddingReport { file_index, .. }
+            | Self::RecursionReport { file_index, .. } => Some(*file_index),
+            Self::SourceCodeViewer { .. }
+            | Self::AssemblyViewer { .. }
+            | Self::SizeDiffReport { .. }
+            | Self::FeatureAttribution { .. } => None,
+        }
+    }
+
+    /// Shows another file, dropping what was computed for the previous one.
+    fn set_file(&mut self, new_file_index: usize) {
+        match self {
+            Self::RawBinaryViewer { file_index } | Self::DeadExportsReport { file_index } => {
+                *file_index = new_file_index
+            }
+            Self::SectionsBinaryViewer {
+                file_index,
+                fn_index,
+            } => {
+                *file_index = new_file_index;
+                *fn_index = 0;
+            }
+            Self::GenericBloatReport {
+                file_index, report, ..
+            } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::CompilationUnitReport {
+                file_index, report, ..
+            } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::FunctionBreakdown {
+                file_index,
+                function,
+                breakdown,
+                ..
+            } => {
+                *file_index = new_file_index;
+                *function = None;
+                *breakdown = None;
+            }
+            Self::ControlFlowGraph {
+                file_index,
+                function,
+                graph,
+            } => {
+                *file_index = new_file_index;
+                *function = None;
+                *graph = None;
+            }
+            Self::InliningReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::PanicMachineryReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::UnwindingReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::CrateTreemap { file_index, viewer } => {
+                *file_index = new_file_index;
+                *viewer = CrateTreemapViewer::default();
+            }
+            Self::CodeOrigin { file_index, viewer } => {
+                *file_index = new_file_index;
+                *viewer = CodeOriginViewer::default();
+            }
+            Self::Dominators { file_index, viewer } => {
+                *file_index = new_file_index;
+                // The expanded paths apply to the other file too.
+                viewer.clear_rows();
+            }
+            Self::Icicle { file_index, viewer } => {
+                *file_index = new_file_index;
+                *viewer = IcicleViewer::default();
+            }
+            Self::Bookmarks { file_index, viewer } => {
+                *file_index = new_file_index;
+                *viewer = BookmarksViewer::default();
+            }
+            Self::DuplicateDataReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::SectionSummary {
+                file_index,
+                summary,
+            } => {
+                *file_index = new_file_index;
+                *summary = None;
+            }
+            Self::SuggestionsReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::VtableReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::PaddingReport { file_index, report } => {
+                *file_index = new_file_index;
+                *report = None;
+            }
+            Self::RecursionReport {
+                file_index,
+                selected_export,
+                report,
+            } => {
+                *file_index = new_file_index;
+                *selected_export = None;
+                *report = None;
+            }
+            Self::SourceCodeViewer { .. }
+            | Self::AssemblyViewer { .. }
+            | Self::SizeDiffReport { .. }
+            | Self::FeatureAttribution { .. } => {}
+        }
+    }
+
+    /// Drops what was computed from a file analyzed again.
+    fn file_reloaded(&mut self, reloaded_file_index: usize) {
+        if !self
+            .file_indices_mut()
+            .into_iter()
+            .any(|file_index| *file_index == reloaded_file_index)
+        {
+            return;
+        }
+
+        match self {
+            Self::SizeDiffReport { report, .. } => *report = None,
+            Self::FeatureAttribution { report, .. } => *report = None,
+            _ => self.set_file(reloaded_file_index),
+        }
+    }
+
+    /// All the files the tab shows, the comparisons having several.
+    fn file_indices_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Self::SizeDiffReport {
+                old_file_index,
+                new_file_index,
+                ..
+            } => vec![old_file_index, new_file_index],
+            Self::FeatureAttribution {
+                baseline_file_index,
+                build_file_indices,
+                ..
+            } => std::iter::once(baseline_file_index)
+                .chain(build_file_indices.iter_mut())
+                .collect(),
+            Self::RawBinaryViewer { file_index }
+            | Self::SectionsBinaryViewer { file_index, .. }
+            | Self::GenericBloatReport { file_index, .. }
+            | Self::CompilationUnitReport { file_index, .. }
+            | Self::FunctionBreakdown { file_index, .. }
+            | Self::ControlFlowGraph { file_index, .. }
+            | Self::InliningReport { file_index, .. }
+            | Self::PanicMachineryReport { file_index, .. }
+            | Self::UnwindingReport { file_index, .. }
+            | Self::CrateTreemap { file_index, .. }
+            | Self::CodeOrigin { file_index, .. }
+            | Self::Dominators { file_index, .. }
+            | Self::Icicle { file_index, .. }
+            | Self::Bookmarks { file_index, .. }
+            | Self::DeadExportsReport { file_index, .. }
+            | Self::DuplicateDataReport { file_index, .. }
+            | Self::SectionSummary { file_index, .. }
+            | Self::SuggestionsReport { file_index, .. }
+            | Self::VtableReport { file_index, .. }
+            | Self::PaddingReport { file_index, .. }
+            | Self::RecursionReport { file_index, .. } => vec![file_index],
+            Self::SourceCodeViewer { .. } | Self::AssemblyViewer { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Closes the tabs showing the removed file, and shifts the indices of the
+/// files after it in the other tabs.
+fn remove_file_tabs(
+    tree: &mut egui_dock::DockState<DockTab>,
+    detached_tabs: &mut Vec<DetachedTab>,
+    removed_file_index: usize,
+) {
+    let shows_removed_file = |tab: &mut DockTab| {
+        tab.contents
+            .file_indices_mut()
+            .into_iter()
+            .any(|file_index| *file_index == removed_file_index)
+    };
+    tree.retain_tabs(|tab| !shows_removed_file(tab));
+    detached_tabs.retain_mut(|detached_tab| !shows_removed_file(&mut detached_tab.tab));
+
+    for tab in all_tabs_mut(tree, detached_tabs) {
+        for file_index in tab.contents.file_indices_mut() {
+            if *file_index > removed_file_index {
+                *file_index -= 1;
+            }
+        }
+    }
+}
+
+/// Tabs of the dock and of the windows.
+fn all_tabs_mut<'t>(
+    tree: &'t mut egui_dock::DockState<DockTab>,
+    detached_tabs: &'t mut [DetachedTab],
+) -> impl Iterator<Item = &'t mut DockTab> {
+    tree.iter_all_tabs_mut().map(|(_, tab)| tab).chain(
+        detached_tabs
+            .iter_mut()
+            .map(|detached_tab| &mut detached_tab.tab),
+    )
+}
+
+/// Splits the dock to show the dominators of two files next to each other,
+/// opening the same namespaces.
+fn add_side_by_side_dominators(
+    tree: &mut egui_dock::DockState<DockTab>,
+    left_file_index: usize,
+    right_file_index: usize,
+) {
+    let [left, right] = [left_file_index, right_file_index].map(|file_index| {
+        DockTab::new(
+            "Dominators",
+            TabContent::Dominators {
+                file_index,
+                viewer: DominatorsViewer::linked(),
+            },
+        )
+    });
+
+    if tree.iter_all_tabs().next().is_none() {
+        *tree = egui_dock::DockState::new(vec![left]);
+        tree.main_surface_mut()
+            .split_right(egui_dock::NodeIndex::root(), 0.5, vec![right]);
+        return;
+    }
+
+    // Below the other tabs, to keep them.
+    let surface = tree.main_surface_mut();
+    let [_, bottom] = surface.split_below(egui_dock::NodeIndex::root(), 0.5, vec![left]);
+    surface.split_right(bottom, 0.5, vec![right]);
+}
+
+/// Extension of the project files, which save the whole exploration.
+const PROJECT_EXTENSION: &str = "bse";
+
+fn project_file_dialog(title: &str) -> FileDialog {
+    FileDialog::new()
+        .title(title)
+        .add_file_filter(
+            "Projects",
+            Arc::new(|path: &Path| path.extension().is_some_and(|ext| ext == PROJECT_EXTENSION)),
+        )
+        .default_file_filter("Projects")
+        .add_save_extension("Project", PROJECT_EXTENSION)
+        .default_save_extension("Project")
+}
+
+/// Dialog picking the files to open, with a filter for each registered
+/// format.
+fn open_file_dialog() -> FileDialog {
+    let mut dialog = FileDialog::new();
+    for format in provider_registry::formats() {
+        let extensions = format.extensions;
+        dialog = dialog.add_file_filter(
+            format.description,
+            Arc::new(move |path: &Path| {
+                path.extension()
+                    .is_some_and(|ext| extensions.iter().any(|extension| ext == *extension))
+            }),
+        );
+    }
+    dialog
+}
+
+/// Files uploaded to the page, with their name as path and their bytes.
+type Uploads = Arc<Mutex<Vec<(PathBuf, Vec<u8>)>>>;
+
+/// Asks the browser for a file to upload, added to `uploads` once read while
+/// the UI keeps running.
+#[cfg(target_arch = "wasm32")]
+fn pick_upload(ctx: &egui::Context, uploads: &Uploads) {
+    let ctx = ctx.clone();
+    let uploads = Arc::clone(uploads);
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut dialog = rfd::AsyncFileDialog::new();
+        for format in provider_registry::formats() {
+            dialog = dialog.add_filter(format.description, format.extensions);
+        }
+        let Some(file) = dialog.pick_file().await else {
+            return;
+        };
+
+        let bytes = file.read().await;
+        uploads
+            .lock()
+            .unwrap()
+            .push((PathBuf::from(file.file_name()), bytes));
+        ctx.request_repaint();
+    });
+}
+
+/// Section sizes of the module of a file, none without one.
+fn section_summary(data_provider: &dyn DataProvider<'_>) -> SectionSummary {
+    data_provider
+        .module()
+        .map_or_else(SectionSummary::default, |module| {
+            SectionSummary::new(&module.wasm_data)
+        })
+}
+
+fn file_byte_kinds(data_provider: &dyn DataProvider<'_>) -> Vec<(Range<usize>, ByteKind)> {
+    data_provider
+        .module()
+        .map_or_else(Vec::new, |module| byte_kinds(&module.wasm_data))
+}
+
+/// Function or section the address of a binary viewer links to.
+fn address_owner<'a>(
+    data_provider: &'a DataProviderTwiggy<'_>,
+    address: usize,
+) -> Option<AddressOwner<'a>> {
+    if let Some(index) = data_provider.function_at(address) {
+        return Some(AddressOwner::Function {
+            index,
+            name: data_provider.wasm_data.functions_section.function_names[index],
+        });
+    }
+
+    data_provider
+        .section_at(address)
+        .map(|section| AddressOwner::Section {
+            name: section.name,
+            start: section.range.start,
+        })
+}
+
+/// Writes a DOT graph to `path`, laid out as SVG for the `.svg` files.
+fn write_graph(path: &Path, dot: &str) -> std::io::Result<()> {
+    if path.extension().is_some_and(|ext| ext == "svg") {
+        fs::write(path, render_svg(dot)?)
+    } else {
+        fs::write(path, dot)
+    }
+}
+
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => path.to_string_lossy(),
+    }
+}
+
+pub struct TemplateApp {
+    file_dialog: FileDialog,
+
+    last_path_picked: PathBuf,
+
+    /// Picks the baseline build the active file is compared with.
+    baseline_file_dialog: FileDialog,
+
+    /// Picks builds of the active crate with other cargo features.
+    feature_builds_file_dialog: FileDialog,
+
+    /// Picks the file the size tree is exported to as collapsed stacks.
+    collapsed_stacks_file_dialog: FileDialog,
+    export_analysis_file_dialog: FileDialog,
+    /// Picks the file the dominator tree is exported to as a graph.
+    tree_graph_file_dialog: FileDialog,
+    /// Picks the file the call graph is exported to.
+    call_graph_file_dialog: FileDialog,
+
+    open_project_dialog: FileDialog,
+    save_project_dialog: FileDialog,
+    /// Project file the exploration was last opened from or saved to.
+    project_path: Option<PathBuf>,
+
+    analyzer_state: Option<AnalyzerState>,
+
+    /// Error of the last analysis, shown until dismissed.
+    analyzer_error: Option<String>,
+    /// Checks the files for changes while they are reloaded automatically.
+    file_watcher: Option<FileWatcher>,
+    /// Files read by the browser, opened on the next frame.
+    uploads: Uploads,
+    /// Time spent in the last frames, in seconds, for the profiler window.
+    frame_times: VecDeque<f32>,
+    source_cache: SourceCache,
+
+    file_entries: Vec<FileEntry>,
+    /// File shown in the functions explorer and by the new tabs.
+    active_file: usize,
+
+    // TODO: (bruno) remove this with the function id once you re-write
+    // the parser
+    selected_row: Option<usize>,
+    navigation_history: NavigationHistory,
+
+    bookmarks: Bookmarks,
+    notes: Notes,
+    tags: Tags,
+    linked_dominator_paths: ExpandedPaths,
 
     tree: egui_dock::DockState<DockTab>,
+    detached_tabs: Vec<DetachedTab>,
 
     settings: AppSettings,
 }
@@ -251,10 +1483,160 @@ pub struct TemplateApp {
 struct AppSettings {
     source_code_search_folders: Vec<PathBuf>,
     source_file_dialog: FileDialog,
+    size_budgets: SizeBudgets,
+    show_budgets_window: bool,
+    syntax_theme: SyntaxTheme,
+    name_style: NameStyle,
+    /// Analyzes the files again when they change on disk.
+    auto_reload: bool,
+    appearance: Appearance,
+    /// Appearance applied to the context, to apply the changes.
+    applied_appearance: Option<Appearance>,
+    font_file_dialog: FileDialog,
+    show_settings_window: bool,
+    show_arena_window: bool,
+    show_profiler_window: bool,
+    /// Nodes smaller than it are left out of the exported graphs.
+    graph_min_size: u32,
+    /// Only exports the functions the selected one calls in the call graph.
+    call_graph_from_selected: bool,
 }
 
 enum AnalyzerState {
-    AnalyzeWasm { path: PathBuf },
+    AnalyzeWasm {
+        path: PathBuf,
+    },
+    /// The files are analyzed on other threads, to show the progress.
+    LoadingWasm {
+        loads: Vec<BackgroundLoad>,
+        purpose: LoadPurpose,
+    },
+    AnalyzeBaseline {
+        path: PathBuf,
+    },
+    OptimizeWithWasmOpt,
+    AttributeFeatures {
+        paths: Vec<PathBuf>,
+    },
+}
+
+impl AnalyzerState {
+    /// Starts analyzing the file on another thread.
+    fn load_in_background(path: PathBuf, purpose: LoadPurpose) -> Self {
+        Self::LoadingWasm {
+            loads: vec![BackgroundLoad::start(path, None)],
+            purpose,
+        }
+    }
+
+    /// Starts analyzing the files of a project again, each on its own thread.
+    fn restore_in_background(project: RestoredProject) -> Self {
+        Self::LoadingWasm {
+            loads: project
+                .files
+                .iter()
+                .map(|(path, ty)| BackgroundLoad::start(path.clone(), Some(*ty)))
+                .collect(),
+            purpose: LoadPurpose::Restore(Box::new(project)),
+        }
+    }
+}
+
+/// File analyzed on another thread.
+struct BackgroundLoad {
+    /// File shown while it is analyzed.
+    path: PathBuf,
+    progress: Arc<Progress>,
+    /// Gives the analyzed file with its path, the one of the output for
+    /// wasm-opt.
+    analysis: JoinHandle<Result<(PathBuf, LoadedFile), String>>,
+}
+
+impl BackgroundLoad {
+    fn start(path: PathBuf, ty: Option<FileType>) -> Self {
+        let thread_path = path.clone();
+        Self::spawn(path, move |progress| {
+            let loaded_file = LoadedFile::load(&thread_path, ty, progress)
+                .ok_or_else(|| format!("Failed to load {}", thread_path.display()))?;
+            Ok((thread_path, loaded_file))
+        })
+    }
+
+    /// Optimizes the module with wasm-opt, then analyzes the optimized copy.
+    fn optimize(path: PathBuf) -> Self {
+        let thread_path = path.clone();
+        Self::spawn(path, move |progress| {
+            let optimized_path =
+                wasm_opt::optimize(&thread_path, progress).map_err(|err| format!("{:#}", err))?;
+            let loaded_file = LoadedFile::load(&optimized_path, None, progress)
+                .ok_or_else(|| format!("Failed to load {}", optimized_path.display()))?;
+            Ok((optimized_path, loaded_file))
+        })
+    }
+
+    /// Runs `analyze` on another thread, with the progress it reports.
+    fn spawn(
+        path: PathBuf,
+        analyze: impl FnOnce(
+            &dyn Fn(Phase, f32) -> ControlFlow<()>,
+        ) -> Result<(PathBuf, LoadedFile), String>
+        + Send
+        + 'static,
+    ) -> Self {
+        let progress = Arc::new(Progress::default());
+        let thread_progress = Arc::clone(&progress);
+        let analysis = std::thread::spawn(move || {
+            analyze(&|phase, fraction| thread_progress.set(phase, fraction))
+        });
+
+        Self {
+            path,
+            progress,
+            analysis,
+        }
+    }
+
+    /// Waits for the analysis, returns the analyzed file with its path or
+    /// the error to show.
+    fn finish(self) -> Result<(PathBuf, LoadedFile), String> {
+        self.analysis
+            .join()
+            .unwrap_or_else(|_| Err(format!("Failed to analyze {}", self.path.display())))
+    }
+}
+
+/// What the files analyzed in the background are opened for.
+enum LoadPurpose {
+    /// Shown in the functions explorer and the tabs.
+    Open,
+    /// Replaces the entry of a file changed on disk.
+    Reload { file_index: usize },
+    /// Baseline the build at `new_path` is compared with.
+    Baseline { new_path: PathBuf },
+    /// Output of wasm-opt, compared with the build at `original_path`.
+    Optimized { original_path: PathBuf },
+    /// Builds of the crate with other features than the one at
+    /// `baseline_path`, the ones already open not being analyzed again.
+    FeatureBuilds {
+        baseline_path: PathBuf,
+        build_paths: Vec<PathBuf>,
+    },
+    /// Files of a project, shown in the tabs saved with it.
+    Restore(Box<RestoredProject>),
+}
+
+/// Exploration of a project, put back once its files are analyzed again.
+///
+/// The tabs only show once their files are there.
+struct RestoredProject {
+    files: Vec<(PathBuf, FileType)>,
+    functions_explorers: Vec<FunctionsExplorer>,
+    active_file: usize,
+    tree: egui_dock::DockState<DockTab>,
+    detached_tabs: Vec<DetachedTab>,
+    /// File opened after the ones of the project, like the one given on the
+    /// command line.
+    then_open: Option<PathBuf>,
 }
 
 impl Default for TemplateApp {
@@ -262,18 +1644,39 @@ impl Default for TemplateApp {
         let tree = egui_dock::DockState::new(vec![]);
 
         Self {
-            file_dialog: FileDialog::new(),
+            file_dialog: open_file_dialog(),
             last_path_picked: "".into(),
+            baseline_file_dialog: FileDialog::new(),
+            feature_builds_file_dialog: FileDialog::new(),
+            collapsed_stacks_file_dialog: FileDialog::new().default_file_name("sizes.folded"),
+            export_analysis_file_dialog: FileDialog::new().default_file_name("analysis.json"),
+            tree_graph_file_dialog: FileDialog::new().default_file_name("tree.dot"),
+            call_graph_file_dialog: FileDialog::new().default_file_name("calls.dot"),
 
-            analyzer_state: None,
+            open_project_dialog: project_file_dialog("Open project"),
+            save_project_dialog: project_file_dialog("Save project"),
+            project_path: None,
 
-            functions_explorer: FunctionsExplorer::default(),
+            analyzer_state: None,
+            analyzer_error: None,
+            file_watcher: None,
+            uploads: Uploads::default(),
+            frame_times: VecDeque::new(),
+            source_cache: SourceCache::default(),
 
             file_entries: Vec::new(),
+            active_file: 0,
 
             tree,
+            detached_tabs: Vec::new(),
 
             selected_row: None,
+            navigation_history: NavigationHistory::default(),
+
+            bookmarks: Bookmarks::default(),
+            notes: Notes::default(),
+            tags: Tags::default(),
+            linked_dominator_paths: ExpandedPaths::default(),
 
             settings: AppSettings::default(),
         }
@@ -295,11 +1698,372 @@ impl TemplateApp {
         Default::default()
     }
 
-    fn show_src_folder_pick_window(&mut self, ctx: &egui::Context) {
-        egui::Window::new("Source code folders").show(ctx, |_| {
-            self.file_dialog.pick_directory();
-        });
+    /// Analyzes the file in the background, replacing its entry if it's
+    /// already open, like when it was rebuilt.
+    pub fn load_file(&mut self, path: PathBuf) {
+        if let Some(AnalyzerState::LoadingWasm {
+            purpose: LoadPurpose::Restore(project),
+            ..
+        }) = &mut self.analyzer_state
+        {
+            // The files of the last session are restored first.
+            project.then_open = Some(path);
+            return;
+        }
+
+        let purpose = match self.file_index_of(&path) {
+            Some(file_index) => LoadPurpose::Reload { file_index },
+            None => LoadPurpose::Open,
+        };
+        self.analyzer_state = Some(AnalyzerState::load_in_background(path, purpose));
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings.show_settings_window;
+        let settings = &mut self.settings;
+
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong("Source code folders");
+                ui.weak("Searched in order for the source files with relative paths");
+                let folders = &mut settings.source_code_search_folders;
+                let folder_count = folders.len();
+                let mut removed = None;
+                let mut swapped = None;
+                for (idx, folder) in folders.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(idx > 0, egui::Button::new("⬆")).clicked() {
+                            swapped = Some(idx - 1);
+                        }
+                        if ui
+                            .add_enabled(idx + 1 < folder_count, egui::Button::new("⬇"))
+                            .clicked()
+                        {
+                            swapped = Some(idx);
+                        }
+                        if ui.button("Remove").clicked() {
+                            removed = Some(idx);
+                        }
+                        ui.label(folder.to_string_lossy());
+                    });
+                }
+                if let Some(idx) = swapped {
+                    folders.swap(idx, idx + 1);
+                }
+                if let Some(idx) = removed {
+                    folders.remove(idx);
+                }
+                if ui.button("Add folder...").clicked() {
+                    settings.source_file_dialog.pick_directory();
+                }
+                if ui.button("Size budgets...").clicked() {
+                    settings.show_budgets_window = true;
+                }
+                if ui
+                    .button("Arena usage...")
+                    .on_hover_text("Memory of the files and of the scratch arenas, to tune them")
+                    .clicked()
+                {
+                    settings.show_arena_window = true;
+                }
+                if ui
+                    .button("Profiler...")
+                    .on_hover_text("Time taken by the frames and by the analysis of the files")
+                    .clicked()
+                {
+                    settings.show_profiler_window = true;
+                }
+                ui.checkbox(
+                    &mut settings.auto_reload,
+                    "Reload the files when they change",
+                )
+                .on_hover_text(
+                    "Analyzes the files again once rebuilt, keeping the filters, \
+                         the selected function and the tabs",
+                );
+
+                ui.separator();
+
+                let appearance = &mut settings.appearance;
+                egui::Grid::new("appearance").num_columns(2).show(ui, |ui| {
+                    ui.label("UI scale");
+                    ui.add(
+                        egui::Slider::new(&mut appearance.ui_scale, 0.5..=3.0)
+                            .step_by(0.05)
+                            .suffix("x"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Monospace font");
+                    ui.horizontal(|ui| {
+                        match &appearance.monospace_font {
+                            Some(path) => ui
+                                .label(file_name(path))
+                                .on_hover_text(path.to_string_lossy()),
+                            None => ui.label("Default"),
+                        };
+                        if ui.button("Browse...").clicked() {
+                            settings.font_file_dialog.pick_file();
+                        }
+                        if appearance.monospace_font.is_some() && ui.button("Reset").clicked() {
+                            appearance.monospace_font = None;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Monospace size");
+                    ui.add(
+                        egui::DragValue::new(&mut appearance.monospace_size)
+                            .range(6.0..=32.0)
+                            .speed(0.5)
+                            .suffix(" pt"),
+                    )
+                    .on_hover_text("Size of the text of the code and memory viewers");
+                    ui.end_row();
+
+                    ui.label("Table rows");
+                    ui.horizontal(|ui| {
+                        for row_height in RowHeight::ALL {
+                            ui.radio_value(
+                                &mut appearance.row_height,
+                                row_height,
+                                row_height.name(),
+                            );
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Function names");
+                    egui::ComboBox::from_id_salt("name_style")
+                        .selected_text(settings.name_style.title())
+                        .show_ui(ui, |ui| {
+                            for name_style in NameStyle::ALL {
+                                ui.selectable_value(
+                                    &mut settings.name_style,
+                                    name_style,
+                                    name_style.title(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Syntax theme");
+                    egui::ComboBox::from_id_salt("syntax_theme")
+                        .selected_text(settings.syntax_theme.name())
+                        .show_ui(ui, |ui| {
+                            for theme in SyntaxTheme::ALL {
+                                ui.selectable_value(
+                                    &mut settings.syntax_theme,
+                                    theme,
+                                    theme.name(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                if ui.button("Reset appearance").clicked() {
+                    settings.appearance = Appearance::default();
+                    settings.syntax_theme = SyntaxTheme::default();
+                }
+            });
+
+        self.settings.show_settings_window = open;
+    }
+
+    /// Applies the appearance to the context when it changed.
+    fn apply_appearance(&mut self, ctx: &egui::Context) {
+        let settings = &mut self.settings;
+        if settings.applied_appearance.as_ref() == Some(&settings.appearance) {
+            return;
+        }
+
+        if let Err(err) = settings
+            .appearance
+            .apply(ctx, settings.applied_appearance.as_ref())
+        {
+            self.analyzer_error = Some(err);
+        }
+        settings.applied_appearance = Some(settings.appearance.clone());
+    }
+
+    fn show_arena_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings.show_arena_window;
+
+        // The scratch arenas of the UI thread, the other threads' being gone.
+        let arenas: Vec<(String, ArenaUsage)> = self
+            .file_entries
+            .iter()
+            .map(|file_entry| {
+                (
+                    file_name(&file_entry.path).into_owned(),
+                    file_entry.arena.usage(),
+                )
+            })
+            .chain(
+                scratch_arenas_usage()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, usage)| (format!("Scratch {}", idx + 1), usage)),
+            )
+            .collect();
+
+        egui::Window::new("Arena usage")
+            .open(&mut open)
+            .show(ctx, |ui| ArenaUsageViewer::show(ui, &arenas));
+
+        self.settings.show_arena_window = open;
+        // The usage changes without input.
+        ctx.request_repaint_after(Duration::from_secs(1));
+    }
+
+    fn show_profiler_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings.show_profiler_window;
+
+        let files: Vec<(String, &Timings)> = self
+            .file_entries
+            .iter()
+            .filter_map(|file_entry| {
+                let data_provider = file_entry.module()?;
+                Some((
+                    file_name(&file_entry.path).into_owned(),
+                    &data_provider.timings,
+                ))
+            })
+            .collect();
+        let frame_times: Vec<f32> = self.frame_times.iter().copied().collect();
+
+        egui::Window::new("Profiler")
+            .open(&mut open)
+            .show(ctx, |ui| ProfilerViewer::show(ui, &frame_times, &files));
+
+        self.settings.show_profiler_window = open;
+    }
+
+    fn show_budgets_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings.show_budgets_window;
+        let mut changed = false;
+
+        egui::Window::new("Size budgets")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong("Files");
+                changed |= budget_list_ui(ui, "files", &mut self.settings.size_budgets.files);
+
+                ui.separator();
+
+                ui.strong("Crates");
+                changed |= budget_list_ui(ui, "crates", &mut self.settings.size_budgets.crates);
+            });
+
+        self.settings.show_budgets_window = open;
+        if changed {
+            self.check_budgets();
+        }
+    }
+
+    /// Saves the exploration, i.e. the open files, the layout, the filters,
+    /// the bookmarks and the notes, to a project file.
+    fn save_project(&mut self, ctx: &egui::Context, path: PathBuf) {
+        let saved = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|err| err.to_string()));
+        match saved {
+            Ok(()) => self.set_project_path(ctx, path),
+            Err(err) => {
+                self.analyzer_error = Some(format!("Failed to save {}: {}", path.display(), err));
+            }
+        }
     }
+
+    /// Replaces the exploration with the one of a project file. The settings
+    /// stay as they are.
+    fn open_project(&mut self, ctx: &egui::Context, path: PathBuf) {
+        let project = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<TemplateApp>(&json).map_err(|err| err.to_string())
+            });
+        let project = match project {
+            Ok(project) => project,
+            Err(err) => {
+                self.analyzer_error = Some(format!("Failed to open {}: {}", path.display(), err));
+                return;
+            }
+        };
+
+        // The files of the project show once they are analyzed again.
+        self.analyzer_state = project.analyzer_state;
+        self.file_entries = project.file_entries;
+        self.active_file = project.active_file;
+        self.selected_row = None;
+        self.navigation_history = NavigationHistory::default();
+        self.bookmarks = project.bookmarks;
+        self.notes = project.notes;
+        self.tags = project.tags;
+        self.linked_dominator_paths = ExpandedPaths::default();
+        self.tree = project.tree;
+        self.detached_tabs = project.detached_tabs;
+        self.set_project_path(ctx, path);
+    }
+
+    /// Shows the name of the project in the title of the window.
+    fn set_project_path(&mut self, ctx: &egui::Context, path: PathBuf) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!(
+            "{} - Binary Size Explorer",
+            file_name(&path)
+        )));
+        self.project_path = Some(path);
+    }
+
+    /// Re-evaluates the size budgets for every loaded file.
+    fn check_budgets(&mut self) {
+        for file_entry in &mut self.file_entries {
+            file_entry.budget_violations = match file_entry.module() {
+                Some(data_provider) => self
+                    .settings
+                    .size_budgets
+                    .check(&file_entry.path, data_provider),
+                None => Vec::new(),
+            };
+        }
+    }
+}
+
+/// Shows an editable list of budgets, returning `true` if any of them changed.
+fn budget_list_ui(ui: &mut egui::Ui, id_salt: &str, budgets: &mut Vec<SizeBudget>) -> bool {
+    let mut changed = false;
+    let mut removed = None;
+
+    egui::Grid::new(id_salt).num_columns(3).show(ui, |ui| {
+        for (idx, budget) in budgets.iter_mut().enumerate() {
+            changed |= ui.text_edit_singleline(&mut budget.name).changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut budget.max_bytes)
+                        .speed(1024)
+                        .suffix(" bytes"),
+                )
+                .changed();
+            if ui.button("Remove").clicked() {
+                removed = Some(idx);
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some(idx) = removed {
+        budgets.remove(idx);
+        changed = true;
+    }
+
+    if ui.button("Add").clicked() {
+        budgets.push(SizeBudget::default());
+        changed = true;
+    }
+
+    changed
 }
 
 impl eframe::App for TemplateApp {
@@ -310,6 +2074,8 @@ impl eframe::App for TemplateApp {
 
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_start = Instant::now();
+
         // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
 
@@ -317,52 +2083,450 @@ impl eframe::App for TemplateApp {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
                     if ui.button("Open").clicked() {
+                        #[cfg(not(target_arch = "wasm32"))]
                         self.file_dialog.pick_file();
+                        #[cfg(target_arch = "wasm32")]
+                        pick_upload(ctx, &self.uploads);
+                    }
+                    // The projects and the comparisons read the files from their
+                    // paths, the web build only has the uploaded bytes.
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button("Open project...").clicked() {
+                        self.open_project_dialog.pick_file();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Save project"),
+                        )
+                        .on_hover_text("Saves the open files, the layout, the filters, the bookmarks and the notes")
+                        .clicked()
+                    {
+                        match self.project_path.clone() {
+                            Some(path) => self.save_project(ctx, path),
+                            None => self.save_project_dialog.save_file(),
+                        }
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Save project as..."),
+                        )
+                        .clicked()
+                    {
+                        self.save_project_dialog.save_file();
+                    }
+                    ui.separator();
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Compare with baseline..."),
+                        )
+                        .clicked()
+                    {
+                        self.baseline_file_dialog.pick_file();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Compare with wasm-opt -Oz"),
+                        )
+                        .clicked()
+                    {
+                        self.analyzer_state = Some(AnalyzerState::OptimizeWithWasmOpt);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Attribute size to features..."),
+                        )
+                        .on_hover_text(
+                            "Pick builds with other cargo features, named like `app+feature1+feature2.wasm`",
+                        )
+                        .clicked()
+                    {
+                        self.feature_builds_file_dialog.pick_multiple();
                     }
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Export collapsed stacks..."),
+                        )
+                        .on_hover_text("Sizes in the `a;b;c bytes` format of inferno and speedscope")
+                        .clicked()
+                    {
+                        self.collapsed_stacks_file_dialog.save_file();
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Export analysis (JSON)..."),
+                        )
+                        .on_hover_text(
+                            "The functions, crates, namespaces and sections, for other tools",
+                        )
+                        .clicked()
+                    {
+                        self.export_analysis_file_dialog.save_file();
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Export dominator tree (DOT)..."),
+                        )
+                        .on_hover_text(
+                            "The nodes of the dominators view above the size cutoff, laid out \
+                             by Graphviz when the file name ends with `.svg`",
+                        )
+                        .clicked()
+                    {
+                        self.tree_graph_file_dialog.save_file();
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.file_entries.is_empty(),
+                            egui::Button::new("Export call graph..."),
+                        )
+                        .on_hover_text(
+                            "The calls between the functions above the size cutoff, as GraphML \
+                             for Gephi when the file name ends with `.graphml`, as DOT otherwise",
+                        )
+                        .clicked()
+                    {
+                        self.call_graph_file_dialog.save_file();
+                    }
+                    ui.checkbox(
+                        &mut self.settings.call_graph_from_selected,
+                        "Call graph of the selected function",
+                    )
+                    .on_hover_text("Only the functions it calls, directly or not");
+                    ui.horizontal(|ui| {
+                        ui.label("Graph size cutoff");
+                        ui.add(
+                            egui::DragValue::new(&mut self.settings.graph_min_size)
+                                .speed(64)
+                                .suffix(" bytes"),
+                        );
+                    });
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                 });
 
                 ui.menu_button("Views", |ui| {
+                    if ui.button("Summary").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Summary", TabContent::SectionSummary {
+                                file_index: self.active_file,
+                                summary: None,
+                            }));
+                    }
+
                     if ui.button("Raw Binary").clicked() {
-                        self.tree.main_surface_mut().push_to_first_leaf(DockTab {
-                            title: String::from("Raw Binary"),
-                            contents: TabContent::RawBinaryViewer { file_index: 0 },
-                        });
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Raw Binary", TabContent::RawBinaryViewer { file_index: self.active_file }));
                     }
 
                     if ui.button("Sections Binary").clicked() {
-                        self.tree.main_surface_mut().push_to_first_leaf(DockTab {
-                            title: String::from("Sections Binary"),
-                            contents: TabContent::SectionsBinaryViewer {
-                                file_index: 0,
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Sections Binary", TabContent::SectionsBinaryViewer {
+                                file_index: self.active_file,
                                 fn_index: 0,
-                            },
-                        });
+                            }));
+                    }
+
+                    if ui.button("Generic Bloat").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Generic Bloat", TabContent::GenericBloatReport {
+                                file_index: self.active_file,
+                                sort_key: GenericBloatSortKey::default(),
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Compilation Units").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Compilation Units", TabContent::CompilationUnitReport {
+                                file_index: self.active_file,
+                                sort_key: CompilationUnitSortKey::default(),
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Function Breakdown").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Function Breakdown", TabContent::FunctionBreakdown {
+                                file_index: self.active_file,
+                                function: self.selected_row,
+                                sort_key: BreakdownSortKey::default(),
+                                breakdown: None,
+                            }));
+                    }
+
+                    if ui.button("Control-Flow Graph").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Control-Flow Graph", TabContent::ControlFlowGraph {
+                                file_index: self.active_file,
+                                function: self.selected_row,
+                                graph: None,
+                            }));
+                    }
+
+                    if ui.button("Inlining").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Inlining", TabContent::InliningReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Panic & Fmt").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Panic & Fmt", TabContent::PanicMachineryReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Unwinding").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Unwinding", TabContent::UnwindingReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
                     }
-                });
 
-                ui.menu_button("Settings", |ui| {
-                    if ui.button("Set source code folders").clicked() {
-                        self.show_src_folder_pick_window(ctx);
+                    if ui.button("Crate Treemap").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Crate Treemap", TabContent::CrateTreemap {
+                                file_index: self.active_file,
+                                viewer: CrateTreemapViewer::default(),
+                            }));
                     }
+
+                    if ui.button("Icicle Chart").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Icicle Chart", TabContent::Icicle {
+                                file_index: self.active_file,
+                                viewer: IcicleViewer::default(),
+                            }));
+                    }
+
+                    if ui.button("Bookmarks").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Bookmarks", TabContent::Bookmarks {
+                                file_index: self.active_file,
+                                viewer: BookmarksViewer::default(),
+                            }));
+                    }
+
+                    if ui.button("Std vs User Code").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Std vs User Code", TabContent::CodeOrigin {
+                                file_index: self.active_file,
+                                viewer: CodeOriginViewer::default(),
+                            }));
+                    }
+
+                    if ui.button("Dominators").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Dominators", TabContent::Dominators {
+                                file_index: self.active_file,
+                                viewer: DominatorsViewer::default(),
+                            }));
+                    }
+
+                    if ui.button("Dead Exports").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Dead Exports", TabContent::DeadExportsReport {
+                                file_index: self.active_file,
+                            }));
+                    }
+
+                    if ui.button("Duplicate Data").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Duplicate Data", TabContent::DuplicateDataReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Padding").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Padding", TabContent::PaddingReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Recursion & Call Depth").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Recursion & Call Depth", TabContent::RecursionReport {
+                                file_index: self.active_file,
+                                selected_export: None,
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Vtables & dyn Trait").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Vtables & dyn Trait", TabContent::VtableReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
+                    }
+
+                    if ui.button("Suggestions").clicked() {
+                        self.tree.main_surface_mut().push_to_first_leaf(DockTab::new("Suggestions", TabContent::SuggestionsReport {
+                                file_index: self.active_file,
+                                report: None,
+                            }));
+                    }
+
+                    ui.separator();
+
+                    ui.add_enabled_ui(self.file_entries.len() > 1, |ui| {
+                        ui.menu_button("Side by side dominators", |ui| {
+                            for (file_index, file_entry) in self.file_entries.iter().enumerate() {
+                                if file_index != self.active_file
+                                    && ui.button(file_name(&file_entry.path)).clicked()
+                                {
+                                    add_side_by_side_dominators(
+                                        &mut self.tree,
+                                        file_index,
+                                        self.active_file,
+                                    );
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Dominators of another file next to the active one");
+                    });
+                });
+
+                if ui.button("Settings").clicked() {
+                    self.settings.show_settings_window = true;
+                }
+
+                ui.separator();
+
+                // Mouse buttons 4 and 5 go back and forward like in a browser.
+                let (mouse_back, mouse_forward) = ctx.input(|input| {
+                    (
+                        input.pointer.button_pressed(egui::PointerButton::Extra1),
+                        input.pointer.button_pressed(egui::PointerButton::Extra2),
+                    )
                 });
+                let history = &mut self.navigation_history;
+                let back = ui
+                    .add_enabled(history.can_go_back(), egui::Button::new("⬅"))
+                    .on_hover_text("Previously selected function (mouse button 4)")
+                    .clicked();
+                let forward = ui
+                    .add_enabled(history.can_go_forward(), egui::Button::new("➡"))
+                    .on_hover_text("Next selected function (mouse button 5)")
+                    .clicked();
+
+                let function = if back || mouse_back {
+                    history.back()
+                } else if forward || mouse_forward {
+                    history.forward()
+                } else {
+                    None
+                };
+                if function.is_some()
+                    && let Some(file_entry) = self.file_entries.get_mut(self.active_file)
+                {
+                    file_entry.functions_explorer.selected_row = function;
+                }
 
                 self.file_dialog.update(ctx);
-                if let Some(path) = self.file_dialog.picked() {
-                    if path != self.last_path_picked {
-                        self.analyzer_state = Some(AnalyzerState::AnalyzeWasm {
-                            path: path.to_path_buf(),
-                        });
-                        self.last_path_picked = path.into();
-                        self.functions_explorer = FunctionsExplorer::default();
+                if let Some(path) = self.file_dialog.take_picked() {
+                    self.last_path_picked = path.clone();
+                    self.analyzer_state = Some(AnalyzerState::AnalyzeWasm { path });
+                }
+
+                self.open_project_dialog.update(ctx);
+                if let Some(path) = self.open_project_dialog.take_picked() {
+                    self.open_project(ctx, path);
+                }
+
+                self.save_project_dialog.update(ctx);
+                if let Some(path) = self.save_project_dialog.take_picked() {
+                    self.save_project(ctx, path);
+                }
+
+                self.baseline_file_dialog.update(ctx);
+                if let Some(path) = self.baseline_file_dialog.take_picked() {
+                    self.analyzer_state = Some(AnalyzerState::AnalyzeBaseline { path });
+                }
+
+                self.feature_builds_file_dialog.update(ctx);
+                if let Some(paths) = self.feature_builds_file_dialog.take_picked_multiple() {
+                    self.analyzer_state = Some(AnalyzerState::AttributeFeatures { paths });
+                }
+
+                self.collapsed_stacks_file_dialog.update(ctx);
+                if let Some(path) = self.collapsed_stacks_file_dialog.take_picked()
+                    && let Some(data_provider) = self
+                        .file_entries
+                        .get(self.active_file)
+                        .and_then(FileEntry::module)
+                {
+                    if let Err(err) = std::fs::write(&path, collapsed_stacks(data_provider)) {
+                        self.analyzer_error =
+                            Some(format!("Failed to write {}: {}", path.display(), err));
+                    }
+                }
+
+                self.export_analysis_file_dialog.update(ctx);
+                if let Some(path) = self.export_analysis_file_dialog.take_picked()
+                    && let Some(file_entry) = self.file_entries.get(self.active_file)
+                    && let Some(data_provider) = file_entry.module()
+                {
+                    let export = AnalysisExport::new(&file_name(&file_entry.path), data_provider);
+                    let written = serde_json::to_string_pretty(&export)
+                        .map_err(|err| err.to_string())
+                        .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+                    if let Err(err) = written {
+                        self.analyzer_error =
+                            Some(format!("Failed to write {}: {}", path.display(), err));
+                    }
+                }
+
+                self.tree_graph_file_dialog.update(ctx);
+                if let Some(path) = self.tree_graph_file_dialog.take_picked()
+                    && let Some(file_entry) = self.file_entries.get(self.active_file)
+                    && let Some(data_provider) = file_entry.module()
+                {
+                    let dot = tree_dot(
+                        data_provider,
+                        &file_name(&file_entry.path),
+                        self.settings.graph_min_size,
+                    );
+                    if let Err(err) = write_graph(&path, &dot) {
+                        self.analyzer_error =
+                            Some(format!("Failed to write {}: {}", path.display(), err));
+                    }
+                }
+
+                self.call_graph_file_dialog.update(ctx);
+                if let Some(path) = self.call_graph_file_dialog.take_picked()
+                    && let Some(file_entry) = self.file_entries.get(self.active_file)
+                    && let Some(data_provider) = file_entry.module()
+                {
+                    let from = file_entry
+                        .functions_explorer
+                        .selected_row
+                        .filter(|_| self.settings.call_graph_from_selected);
+                    let min_size = self.settings.graph_min_size;
+                    let written = if path.extension().is_some_and(|ext| ext == "graphml") {
+                        fs::write(&path, call_graph_graphml(data_provider, from, min_size))
+                    } else {
+                        write_graph(&path, &call_graph_dot(data_provider, from, min_size))
+                    };
+                    if let Err(err) = written {
+                        self.analyzer_error =
+                            Some(format!("Failed to write {}: {}", path.display(), err));
                     }
                 }
 
                 self.settings.source_file_dialog.update(ctx);
-                if let Some(folder) = self.settings.source_file_dialog.picked() {
-                    self.settings.source_code_search_folders.push(folder.into());
+                if let Some(folder) = self.settings.source_file_dialog.take_picked()
+                    && !self.settings.source_code_search_folders.contains(&folder)
+                {
+                    self.settings.source_code_search_folders.push(folder);
+                }
+
+                self.settings.font_file_dialog.update(ctx);
+                if let Some(path) = self.settings.font_file_dialog.take_picked() {
+                    self.settings.appearance.monospace_font = Some(path);
                 }
 
                 ui.add_space(16.0);
@@ -373,12 +2537,98 @@ impl eframe::App for TemplateApp {
             });
         });
 
+        if self.settings.show_settings_window {
+            self.show_settings_window(ctx);
+        }
+        if self.settings.show_budgets_window {
+            self.show_budgets_window(ctx);
+        }
+        if self.settings.show_arena_window {
+            self.show_arena_window(ctx);
+        }
+        if self.settings.show_profiler_window {
+            self.show_profiler_window(ctx);
+        }
+        self.apply_appearance(ctx);
+        for file_entry in &mut self.file_entries {
+            if let Some(data_provider) = file_entry.module_mut() {
+                data_provider.name_style = self.settings.name_style;
+            }
+        }
+
+        if let Some(error) = &self.analyzer_error {
+            let mut dismissed = false;
+            egui::TopBottomPanel::top("AnalyzerErrorPanel").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                    dismissed = ui.small_button("Dismiss").clicked();
+                });
+            });
+
+            if dismissed {
+                self.analyzer_error = None;
+            }
+        }
+
+        if self
+            .file_entries
+            .iter()
+            .any(|file_entry| !file_entry.budget_violations.is_empty())
+        {
+            egui::TopBottomPanel::top("BudgetWarningPanel").show(ctx, |ui| {
+                for file_entry in &self.file_entries {
+                    for violation in &file_entry.budget_violations {
+                        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", violation));
+                    }
+                }
+            });
+        }
+
         egui::TopBottomPanel::bottom("BottomPanel")
             .resizable(false)
             .show(ctx, |ui| {
+                if let Some(AnalyzerState::LoadingWasm { loads, .. }) = &self.analyzer_state {
+                    let cancelled = loads.iter().any(|load| load.progress.is_cancelled());
+                    for (idx, load) in loads.iter().enumerate() {
+                        let (phase, fraction) = load.progress.get();
+                        ui.horizontal(|ui| {
+                            if load.analysis.is_finished() {
+                                ui.label("✔");
+                            } else {
+                                ui.spinner();
+                            }
+                            ui.label(format!("Loading {}", file_name(&load.path)));
+                            ui.add(
+                                egui::ProgressBar::new(fraction)
+                                    .desired_width(240.0)
+                                    .show_percentage(),
+                            );
+                            if cancelled {
+                                ui.label("Cancelling…");
+                            } else {
+                                ui.label(phase.title());
+                                // Cancels the files loaded together.
+                                if idx == 0 && ui.button("Cancel").clicked() {
+                                    loads.iter().for_each(|load| load.progress.cancel());
+                                }
+                            }
+                        });
+                    }
+                    ui.ctx().request_repaint();
+                }
+
                 if !self.file_entries.is_empty() {
-                    if let Some(file_entry) = self.file_entries.first() {
-                        ui.label(file_entry.path.to_string_lossy());
+                    if let Some(file_entry) = self.file_entries.get(self.active_file) {
+                        ui.horizontal(|ui| {
+                            ui.label(file_entry.path.to_string_lossy());
+                            ui.separator();
+
+                            // Only the code matching a filter is highlighted.
+                            let filtered_code_bytes = file_entry
+                                .module()
+                                .and_then(|data_provider| data_provider.filtered_code_bytes);
+                            show_size_bar(ui, &file_entry.section_summary, filtered_code_bytes);
+                        });
                     } else {
                         ui.label("Not file loaded yet.");
                     }
@@ -388,172 +2638,267 @@ impl eframe::App for TemplateApp {
         egui::SidePanel::right("RightPanel")
             .resizable(true)
             .show(ctx, |ui| {
-                if !self.file_entries.is_empty() {
-                    if let Some(data_provider) = &mut self.file_entries[0].data_provider {
-                        self.functions_explorer
-                            .show_functions_table(ui, data_provider);
-
-                        if self.selected_row != self.functions_explorer.selected_row {
-                            let start = Instant::now();
-
-                            self.selected_row = self.functions_explorer.selected_row;
-                            if let Some(idx) = self.functions_explorer.selected_row {
-                                let first_selected_address =
-                                    data_provider.wasm_data.functions_section.function_bodies[idx]
-                                        .range()
-                                        .start as u64;
-
-                                let (mut asm_row_data, op_start_idx, ops_addresses): (
-                                    Vec<RowData>,
-                                    usize,
-                                    Vec<u64>,
-                                ) = {
-                                    let mut row_data = Vec::new();
-                                    let mut ops_addresses = Vec::new();
-                                    for (index, &local) in
-                                        data_provider.get_locals_at(idx).iter().enumerate()
-                                    {
-                                        row_data.push(RowData {
-                                            cells: vec![
-                                                format!("{:?}", index),
-                                                format!("{:?}", local),
-                                            ],
-                                            bg_color: None,
-                                            tooltip: None,
-                                        });
-                                    }
-
-                                    for op in data_provider.get_ops_at(idx).iter() {
-                                        row_data.push(RowData {
-                                            cells: vec![
-                                                format!("0x{:04x}", op.address),
-                                                format!("{:?}", op.op),
-                                            ],
-                                            bg_color: None,
-                                            tooltip: None,
-                                        });
-                                        ops_addresses.push(op.address);
-                                    }
+                if let Some(FileEntry {
+                    data_provider: Some(data_provider),
+                    budget_violations,
+                    functions_explorer,
+                    ..
+                }) = self.file_entries.get_mut(self.active_file)
+                    && let Some(data_provider) = data_provider.module_mut()
+                {
+                    functions_explorer.show_functions_table(
+                        ui,
+                        data_provider,
+                        budget_violations,
+                        &mut self.bookmarks,
+                        &mut self.notes,
+                        &mut self.tags,
+                    );
+
+                    if self.selected_row != functions_explorer.selected_row {
+                        profiling::scope!("select_row");
+
+                        self.selected_row = functions_explorer.selected_row;
+                        if let Some(function) = self.selected_row {
+                            self.navigation_history.visit(function);
+                        }
+                        let active_file = self.active_file;
+                        all_tabs_mut(&mut self.tree, &mut self.detached_tabs).for_each(|tab| {
+                            match &mut tab.contents {
+                                TabContent::FunctionBreakdown {
+                                    file_index,
+                                    function,
+                                    breakdown,
+                                    ..
+                                } if *file_index == active_file => {
+                                    *function = self.selected_row;
+                                    *breakdown = None;
+                                }
+                                TabContent::ControlFlowGraph {
+                                    file_index,
+                                    function,
+                                    graph,
+                                } if *file_index == active_file => {
+                                    *function = self.selected_row;
+                                    *graph = None;
+                                }
+                                _ => {}
+                            }
+                        });
 
-                                    (
-                                        row_data,
-                                        data_provider.get_locals_at(idx).len(),
-                                        ops_addresses,
-                                    )
-                                };
-
-                                let mut code_rows = Vec::new();
-                                let mut current_color_idx = 0;
-                                let mut colors_for_source: HashMap<u32, egui::Color32> =
-                                    HashMap::default();
-                                const COLORS: [egui::Color32; 4] = [
-                                    egui::Color32::LIGHT_RED,
-                                    egui::Color32::LIGHT_GREEN,
-                                    egui::Color32::LIGHT_BLUE,
-                                    egui::Color32::LIGHT_GRAY,
-                                ];
-
-                                let scratch = scratch_arena(&[]);
-                                let mut selected_file_path = Path::new("");
-                                if let Some(line_info) =
-                                    data_provider.get_line_info_for_addr(first_selected_address)
+                        if let Some(idx) = functions_explorer.selected_row {
+                            let first_selected_address =
+                                data_provider.wasm_data.functions_section.function_bodies[idx]
+                                    .range()
+                                    .start as u64;
+
+                            let (mut asm_row_data, op_start_idx, ops_addresses): (
+                                Vec<RowData>,
+                                usize,
+                                Vec<u64>,
+                            ) = {
+                                let mut row_data = Vec::new();
+                                let mut ops_addresses = Vec::new();
+                                for (index, &local) in
+                                    data_provider.get_locals_at(idx).iter().enumerate()
                                 {
-                                    let file_entry = &data_provider.dw_file_entries
-                                        [line_info.file_entry_idx.saturating_sub(1)];
-
-                                    selected_file_path = PathExt::join_all(
-                                        &scratch,
-                                        &[
-                                            file_entry.base_directory,
-                                            file_entry.directory,
-                                            file_entry.file,
+                                    row_data.push(RowData {
+                                        cells: vec![format!("{:?}", index), format!("{:?}", local)],
+                                        bg_color: None,
+                                        tooltip: None,
+                                    });
+                                }
+
+                                for op in data_provider.get_ops_at(idx).iter() {
+                                    row_data.push(RowData {
+                                        cells: vec![
+                                            format!("0x{:04x}", op.address),
+                                            format!("{:?}", op.op),
                                         ],
-                                    );
+                                        bg_color: None,
+                                        tooltip: None,
+                                    });
+                                    ops_addresses.push(op.address);
+                                }
 
-                                    if let Ok(source_code) = fs::read_to_string(selected_file_path)
-                                    {
-                                        for (idx, line) in source_code.lines().enumerate() {
-                                            code_rows.push(RowData {
-                                                cells: vec![format!("{:?}", idx), line.to_string()],
-                                                bg_color: None,
-                                                tooltip: None,
-                                            });
-                                        }
+                                (
+                                    row_data,
+                                    data_provider.get_locals_at(idx).len(),
+                                    ops_addresses,
+                                )
+                            };
+                            let asm_branches: Vec<(usize, usize)> =
+                                branches(data_provider.get_ops_at(idx))
+                                    .into_iter()
+                                    .map(|(from, to)| (op_start_idx + from, op_start_idx + to))
+                                    .collect();
+                            let asm_row_links: Vec<Option<RowLink>> =
+                                std::iter::repeat_n(None, op_start_idx)
+                                    .chain(data_provider.get_ops_at(idx).iter().map(|op| {
+                                        let callee = call_target(
+                                            &op.op,
+                                            data_provider.wasm_data.imported_function_count,
+                                            data_provider.raw_data.len(),
+                                        )?
+                                            as usize;
+                                        Some(RowLink {
+                                            function: callee,
+                                            name: data_provider.function_name(callee).to_string(),
+                                        })
+                                    }))
+                                    .collect();
+
+                            let mut code_rows = Vec::new();
+                            let mut code_row_bytes = Vec::new();
+                            let mut current_color_idx = 0;
+                            let mut colors_for_source: HashMap<u32, egui::Color32> =
+                                HashMap::default();
+                            const COLORS: [egui::Color32; 4] = [
+                                egui::Color32::LIGHT_RED,
+                                egui::Color32::LIGHT_GREEN,
+                                egui::Color32::LIGHT_BLUE,
+                                egui::Color32::LIGHT_GRAY,
+                            ];
+
+                            let scratch = scratch_arena(&[]);
+                            let mut selected_file_path = Path::new("");
+                            let mut source_placeholder = Some(
+                                "No line info for this function, the binary needs DWARF debug info."
+                                    .to_owned(),
+                            );
+                            if let Some(line_info) =
+                                data_provider.get_line_info_for_addr(first_selected_address)
+                            {
+                                let file_entry = &data_provider.dw_file_entries
+                                    [line_info.file_entry_idx.saturating_sub(1)];
+
+                                selected_file_path = PathExt::join_all(
+                                    &scratch,
+                                    &[
+                                        file_entry.base_directory,
+                                        file_entry.directory,
+                                        file_entry.file,
+                                    ],
+                                );
+
+                                // The debug info can have the source when the file is missing.
+                                let source_rows = PathExt::find_source_file(
+                                    selected_file_path,
+                                    &self.settings.source_code_search_folders,
+                                )
+                                .and_then(|path| self.source_cache.rows(&path).map(<[_]>::to_vec))
+                                .or_else(|| file_entry.source.map(source_rows));
+                                source_placeholder = source_rows.is_none().then(|| {
+                                    format!(
+                                        "Source unavailable (path: {})",
+                                        selected_file_path.display()
+                                    )
+                                });
 
-                                        for (idx, address) in ops_addresses.iter().enumerate() {
-                                            if let Some(line_info) =
-                                                data_provider.get_line_info_for_addr(*address)
-                                            {
-                                                let color = colors_for_source
-                                                    .entry(line_info.line as u32)
-                                                    .or_insert_with(|| {
-                                                        current_color_idx += 1;
-                                                        COLORS[current_color_idx % COLORS.len()]
-                                                    });
-
-                                                let file_entry = &data_provider.dw_file_entries
-                                                    [line_info.file_entry_idx.saturating_sub(1)];
-
-                                                let line_file_path = PathExt::join_all(
-                                                    &scratch,
-                                                    &[
-                                                        file_entry.base_directory,
-                                                        file_entry.directory,
-                                                        file_entry.file,
-                                                    ],
-                                                );
-
-                                                // code_viewer.highlight_line(location.line as usize, *color);
-                                                if selected_file_path == line_file_path {
-                                                    // Line '0' is not attributed to any source line
-                                                    // Lines are 1-based indexed
-                                                    if line_info.line != 0 {
-                                                        code_rows[line_info.line as usize - 1]
-                                                            .bg_color = Some(*color);
-                                                    }
+                                if let Some(source_rows) = source_rows {
+                                    code_rows = source_rows;
+
+                                    let line_bytes =
+                                        bytes_per_line(data_provider, selected_file_path);
+                                    code_row_bytes = (1..=code_rows.len())
+                                        .map(|line| line_bytes.get(&line).copied().unwrap_or(0))
+                                        .collect();
+
+                                    for (idx, address) in ops_addresses.iter().enumerate() {
+                                        if let Some(line_info) =
+                                            data_provider.get_line_info_for_addr(*address)
+                                        {
+                                            let color = colors_for_source
+                                                .entry(line_info.line as u32)
+                                                .or_insert_with(|| {
+                                                    current_color_idx += 1;
+                                                    COLORS[current_color_idx % COLORS.len()]
+                                                });
+
+                                            let file_entry = &data_provider.dw_file_entries
+                                                [line_info.file_entry_idx.saturating_sub(1)];
+
+                                            let line_file_path = PathExt::join_all(
+                                                &scratch,
+                                                &[
+                                                    file_entry.base_directory,
+                                                    file_entry.directory,
+                                                    file_entry.file,
+                                                ],
+                                            );
+
+                                            // code_viewer.highlight_line(location.line as usize, *color);
+                                            if selected_file_path == line_file_path {
+                                                // Line '0' is not attributed to any source line
+                                                // Lines are 1-based indexed
+                                                if line_info.line != 0 {
+                                                    code_rows[line_info.line as usize - 1]
+                                                        .bg_color = Some(*color);
                                                 }
-
-                                                let asm_row_data =
-                                                    &mut asm_row_data[op_start_idx + idx];
-                                                asm_row_data.bg_color = Some(*color);
-                                                asm_row_data.tooltip = Some(format!(
-                                                    "File: {:?}\nLine: {}\nColumn: {}",
-                                                    line_file_path, line_info.line, line_info.col
-                                                ));
                                             }
+
+                                            let asm_row_data =
+                                                &mut asm_row_data[op_start_idx + idx];
+                                            asm_row_data.bg_color = Some(*color);
+                                            let location = format!(
+                                                "{}:{}:{}",
+                                                line_file_path.display(),
+                                                line_info.line,
+                                                line_info.col
+                                            );
+                                            let code_offset = *address
+                                                - data_provider
+                                                    .wasm_data
+                                                    .functions_section
+                                                    .range
+                                                    .start
+                                                    as u64;
+                                            let inlined_stack =
+                                                data_provider.inlined_stack_at(code_offset);
+                                            asm_row_data.tooltip =
+                                                Some(if inlined_stack.is_empty() {
+                                                    location
+                                                } else {
+                                                    format!(
+                                                        "Inlined from {} at {}",
+                                                        inlined_stack.join(" → "),
+                                                        location
+                                                    )
+                                                });
                                         }
                                     }
                                 }
+                            }
 
-                                self.tree.iter_all_tabs_mut().for_each(|(_, tab)| {
-                                    match &mut tab.contents {
-                                        TabContent::SourceCodeViewer {
-                                            code_viewer,
-                                            file_path,
-                                            first_address,
-                                        } => {
-                                            if *first_address != first_selected_address {
-                                                *first_address = first_selected_address;
-                                                *file_path = selected_file_path.to_path_buf();
-
-                                                code_viewer.set_row_data(code_rows.clone());
+                            all_tabs_mut(&mut self.tree, &mut self.detached_tabs).for_each(|tab| {
+                                match &mut tab.contents {
+                                    TabContent::SourceCodeViewer {
+                                        code_viewer,
+                                        file_path,
+                                        first_address,
+                                    } => {
+                                        if *first_address != first_selected_address {
+                                            *first_address = first_selected_address;
+                                            *file_path = selected_file_path.to_path_buf();
+
+                                            code_viewer.set_row_data(code_rows.clone());
+                                            code_viewer.set_row_bytes(code_row_bytes.clone());
+                                            if let Some(placeholder) = &source_placeholder {
+                                                code_viewer.set_placeholder(placeholder.clone());
                                             }
                                         }
-                                        TabContent::AssemblyViewer { asm, first_address } => {
-                                            if *first_address != first_selected_address {
-                                                *first_address = first_selected_address;
-                                                asm.set_row_data(asm_row_data.clone());
-                                            }
+                                    }
+                                    TabContent::AssemblyViewer { asm, first_address } => {
+                                        if *first_address != first_selected_address {
+                                            *first_address = first_selected_address;
+                                            asm.set_row_data(asm_row_data.clone());
+                                            asm.set_branches(asm_branches.clone());
+                                            asm.set_row_links(asm_row_links.clone());
                                         }
-                                        _ => {}
                                     }
-                                });
-                            }
-
-                            println!(
-                                "Select Row time: {}",
-                                (Instant::now() - start).as_secs_f32()
-                            );
+                                    _ => {}
+                                }
+                            });
                         }
                     }
                 }
@@ -563,30 +2908,154 @@ impl eframe::App for TemplateApp {
             .resizable(true)
             .width_range(100.0..=400.0)
             .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    egui::CollapsingHeader::new("OpenFiles")
-                        .default_open(true)
-                        .show(ui, |ui| {
-                            ui.label("TreeViewEntry");
-                        })
-                })
+                let mut activated_file = None;
+                let mut closed_file = None;
+
+                egui::CollapsingHeader::new("Open files")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        if self.file_entries.is_empty() {
+                            ui.weak("No file loaded yet.");
+                        }
+
+                        for (idx, file_entry) in self.file_entries.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").on_hover_text("Close").clicked() {
+                                    closed_file = Some(idx);
+                                }
+                                if ui
+                                    .selectable_label(
+                                        idx == self.active_file,
+                                        file_name(&file_entry.path),
+                                    )
+                                    .on_hover_text(file_entry.path.to_string_lossy())
+                                    .clicked()
+                                {
+                                    activated_file = Some(idx);
+                                }
+                            });
+                        }
+                    });
+
+                if let Some(file_index) = activated_file {
+                    self.set_active_file(file_index);
+                }
+                if let Some(file_index) = closed_file {
+                    self.close_file(file_index);
+                }
             });
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            // The first file, or the ones of a project, have no tabs to show
+            // while they are analyzed.
+            if let Some(AnalyzerState::LoadingWasm { .. }) = &self.analyzer_state
+                && self.tree.iter_all_tabs().next().is_none()
+            {
+                ui.centered_and_justified(|ui| ui.spinner());
+            }
+
             let Self {
-                tree, file_entries, ..
+                tree,
+                detached_tabs,
+                file_entries,
+                bookmarks,
+                linked_dominator_paths,
+                ..
             } = self;
 
+            let mut tab_viewer = TabViewer {
+                file_entries,
+                active_file: self.active_file,
+                selected_function: None,
+                bookmarks,
+                linked_dominator_paths,
+                syntax_theme: self.settings.syntax_theme,
+            };
+
             egui_dock::DockArea::new(tree)
                 .style(egui_dock::Style::from_egui(ctx.style().as_ref()))
-                .show(ctx, &mut TabViewer { file_entries });
+                .show(ctx, &mut tab_viewer);
+
+            if let Some(tab_index) = tree.find_tab_from(|tab| tab.pop_out)
+                && let Some(mut tab) = tree.remove_tab(tab_index)
+            {
+                tab.pop_out = false;
+                detached_tabs.push(DetachedTab::new(tab));
+            }
+
+            let mut docked_tab = None;
+            for (idx, detached_tab) in detached_tabs.iter_mut().enumerate() {
+                let title = egui_dock::TabViewer::title(&mut tab_viewer, &mut detached_tab.tab)
+                    .text()
+                    .to_owned();
+                let viewport = egui::ViewportBuilder::default()
+                    .with_title(title.as_str())
+                    .with_inner_size([800.0, 600.0]);
+                ctx.show_viewport_immediate(detached_tab.viewport_id, viewport, |ctx, class| {
+                    if class == egui::ViewportClass::Embedded {
+                        // Without native windows, like on the web.
+                        let mut open = true;
+                        egui::Window::new(title.as_str())
+                            .id(detached_tab.viewport_id.0)
+                            .open(&mut open)
+                            .show(ctx, |ui| {
+                                egui_dock::TabViewer::ui(&mut tab_viewer, ui, &mut detached_tab.tab)
+                            });
+                        if !open {
+                            docked_tab = Some(idx);
+                        }
+                        return;
+                    }
+
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        egui_dock::TabViewer::ui(&mut tab_viewer, ui, &mut detached_tab.tab)
+                    });
+                    if ctx.input(|input| input.viewport().close_requested()) {
+                        docked_tab = Some(idx);
+                    }
+                });
+            }
+            if let Some(idx) = docked_tab {
+                let detached_tab = detached_tabs.remove(idx);
+                tree.main_surface_mut().push_to_first_leaf(detached_tab.tab);
+            }
+
+            if let Some((file_index, function_idx)) = tab_viewer.selected_function {
+                self.set_active_file(file_index);
+                self.file_entries[file_index]
+                    .functions_explorer
+                    .selected_row = Some(function_idx);
+                ctx.request_repaint();
+            }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 egui::warn_if_debug_build(ui);
             });
         });
 
+        // Uploaded files aren't on a disk to watch, nor can threads watch it.
+        if self.settings.auto_reload && cfg!(not(target_arch = "wasm32")) {
+            let file_watcher = self
+                .file_watcher
+                .get_or_insert_with(|| FileWatcher::new(ctx.clone(), RELOAD_CHECK_INTERVAL));
+            file_watcher.watch(
+                self.file_entries
+                    .iter()
+                    .map(|file_entry| file_entry.path.as_path()),
+            );
+            self.reload_changed_file();
+        } else {
+            self.file_watcher = None;
+        }
+        self.open_uploads();
         self.update_state();
+
+        if self.frame_times.len() == FRAME_TIME_COUNT {
+            self.frame_times.pop_front();
+        }
+        self.frame_times
+            .push_back(frame_start.elapsed().as_secs_f32());
+        profiling::finish_frame!();
     }
 }
 
@@ -597,72 +3066,498 @@ impl TemplateApp {
         if let Some(state) = self.analyzer_state.take() {
             match state {
                 AnalyzerState::AnalyzeWasm { path, .. } => {
-                    self.file_entries.clear(); // Not supporting multiple for now.
+                    if let Some(file_index) = self.file_index_of(&path) {
+                        self.set_active_file(file_index);
+                        return;
+                    }
+
+                    next_state = Some(AnalyzerState::load_in_background(path, LoadPurpose::Open));
+                }
+                AnalyzerState::LoadingWasm { loads, purpose } => {
+                    if !loads.iter().all(|load| load.analysis.is_finished()) {
+                        self.analyzer_state = Some(AnalyzerState::LoadingWasm { loads, purpose });
+                        return;
+                    }
 
-                    let arena = Arena::new(64 * GB);
-                    let Ok(data_provider) = DataProviderTwiggy::from_path(
-                        unsafe { std::mem::transmute(&arena) },
-                        &path,
-                    ) else {
+                    next_state = self.finish_loading(loads, purpose);
+                }
+                AnalyzerState::AnalyzeBaseline { path } => {
+                    let Some(file_entry) = self.file_entries.get(self.active_file) else {
+                        return;
+                    };
+
+                    // The active file is the new build, compared against the baseline.
+                    let new_path = file_entry.path.clone();
+                    match self.file_index_of(&path) {
+                        Some(baseline_file_index) => {
+                            self.show_size_diff("Diff", baseline_file_index, self.active_file)
+                        }
+                        None => {
+                            next_state = Some(AnalyzerState::load_in_background(
+                                path,
+                                LoadPurpose::Baseline { new_path },
+                            ))
+                        }
+                    }
+                }
+                AnalyzerState::OptimizeWithWasmOpt => {
+                    let Some(file_entry) = self.file_entries.get(self.active_file) else {
                         return;
                     };
 
-                    self.file_entries.push(FileEntry {
-                        path,
-                        ty: FileType::Wasm,
-                        arena,
-                        data_provider: Some(data_provider),
+                    // The optimized copy shows what the active file would become.
+                    let original_path = file_entry.path.clone();
+                    next_state = Some(AnalyzerState::LoadingWasm {
+                        loads: vec![BackgroundLoad::optimize(original_path.clone())],
+                        purpose: LoadPurpose::Optimized { original_path },
                     });
+                }
+                AnalyzerState::AttributeFeatures { paths } => {
+                    let Some(file_entry) = self.file_entries.get(self.active_file) else {
+                        return;
+                    };
 
-                    // Reset the tree.
-                    self.tree = egui_dock::DockState::new(vec![
-                        DockTab::new(
-                            "WASM",
-                            TabContent::AssemblyViewer {
-                                asm: CodeViewer::for_language("wasm"),
-                                first_address: 0,
-                            },
-                        ),
-                        DockTab::new(
-                            "Source Code",
-                            TabContent::SourceCodeViewer {
-                                code_viewer: CodeViewer::for_language("rust"),
-                                file_path: "".into(),
-                                first_address: 0, //address that took us to that path.
-                            },
-                        ),
-                        DockTab::new(
-                            "Raw Binary",
-                            TabContent::RawBinaryViewer {
-                                file_index: self.file_entries.len() - 1,
-                            },
-                        ),
-                        DockTab::new(
-                            "Sections Binary",
-                            TabContent::SectionsBinaryViewer {
-                                file_index: self.file_entries.len() - 1,
-                                fn_index: 0,
-                            },
-                        ),
-                    ]);
+                    // The builds are analyzed together, the open ones are reused.
+                    let baseline_path = file_entry.path.clone();
+                    let loads = paths
+                        .iter()
+                        .filter(|path| self.file_index_of(path).is_none())
+                        .map(|path| BackgroundLoad::start(path.clone(), None))
+                        .collect();
+                    next_state = Some(AnalyzerState::LoadingWasm {
+                        loads,
+                        purpose: LoadPurpose::FeatureBuilds {
+                            baseline_path,
+                            build_paths: paths,
+                        },
+                    });
+                }
+            }
+        }
 
-                    // self.tree.split((0, 0), egui_dock::Split::Right, 0.5, )
+        self.analyzer_state = next_state;
+    }
 
-                    next_state = None;
+    /// Opens the files analyzed in the background for `purpose`, returns the
+    /// next state.
+    fn finish_loading(
+        &mut self,
+        loads: Vec<BackgroundLoad>,
+        purpose: LoadPurpose,
+    ) -> Option<AnalyzerState> {
+        let cancelled = loads.iter().any(|load| load.progress.is_cancelled());
+        let mut loaded_files = loads.into_iter().map(BackgroundLoad::finish);
+
+        let purpose = match purpose {
+            // The cancelled files of a project are closed like the missing ones.
+            LoadPurpose::Restore(project) => return self.restore_project(*project, loaded_files),
+            // The files already open stay as they were.
+            _ if cancelled => return None,
+            LoadPurpose::FeatureBuilds {
+                baseline_path,
+                build_paths,
+            } => {
+                self.attribute_features(&baseline_path, &build_paths, loaded_files);
+                return None;
+            }
+            purpose => purpose,
+        };
+
+        let (path, loaded_file) = match loaded_files.next()? {
+            Ok(loaded_file) => loaded_file,
+            Err(err) => {
+                self.analyzer_error = Some(err);
+                return None;
+            }
+        };
+
+        match purpose {
+            LoadPurpose::Open => self.open_loaded_file(path, loaded_file),
+            LoadPurpose::Reload { file_index } => {
+                // The file may have been closed in the meantime.
+                if self
+                    .file_entries
+                    .get(file_index)
+                    .is_some_and(|file_entry| file_entry.path == path)
+                {
+                    self.reload_file_entry(file_index, loaded_file);
+                }
+            }
+            LoadPurpose::Baseline { new_path } => {
+                let baseline_file_index = self.push_file_entry(path, loaded_file);
+                if let Some(new_file_index) = self.file_index_of(&new_path) {
+                    self.show_size_diff("Diff", baseline_file_index, new_file_index);
+                }
+            }
+            LoadPurpose::Optimized { original_path } => {
+                // The copy optimized before is replaced.
+                let optimized_file_index = match self.file_index_of(&path) {
+                    Some(file_index) => {
+                        self.reload_file_entry(file_index, loaded_file);
+                        file_index
+                    }
+                    None => self.push_file_entry(path, loaded_file),
+                };
+                if let Some(original_file_index) = self.file_index_of(&original_path) {
+                    self.show_size_diff("wasm-opt -Oz", original_file_index, optimized_file_index);
                 }
             }
+            LoadPurpose::Restore(_) | LoadPurpose::FeatureBuilds { .. } => {
+                unreachable!("opened above")
+            }
         }
 
-        self.analyzer_state = next_state;
+        None
+    }
+
+    /// Adds the entries of the feature builds analyzed in the background and
+    /// a tab attributing their sizes to the features.
+    fn attribute_features(
+        &mut self,
+        baseline_path: &Path,
+        build_paths: &[PathBuf],
+        loaded_files: impl Iterator<Item = Result<(PathBuf, LoadedFile), String>>,
+    ) {
+        for loaded_file in loaded_files {
+            match loaded_file {
+                Ok((path, loaded_file)) => {
+                    self.push_file_entry(path, loaded_file);
+                }
+                Err(err) => {
+                    self.analyzer_error = Some(err);
+                    return;
+                }
+            }
+        }
+
+        // The files may have been closed in the meantime.
+        let Some(baseline_file_index) = self.file_index_of(baseline_path) else {
+            return;
+        };
+        let Some(build_file_indices) = build_paths
+            .iter()
+            .map(|path| self.file_index_of(path))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+
+        // The baseline is built without the features.
+        self.tree
+            .main_surface_mut()
+            .push_to_first_leaf(DockTab::new(
+                "Features",
+                TabContent::FeatureAttribution {
+                    baseline_file_index,
+                    build_file_indices,
+                    report: None,
+                },
+            ));
+    }
+
+    /// Puts back the exploration of a project once its files are analyzed,
+    /// closing the tabs of the files that failed to load.
+    fn restore_project(
+        &mut self,
+        project: RestoredProject,
+        loaded_files: impl Iterator<Item = Result<(PathBuf, LoadedFile), String>>,
+    ) -> Option<AnalyzerState> {
+        let RestoredProject {
+            functions_explorers,
+            mut active_file,
+            mut tree,
+            mut detached_tabs,
+            then_open,
+            ..
+        } = project;
+
+        let mut functions_explorers = functions_explorers.into_iter();
+        self.file_entries.clear();
+        for loaded_file in loaded_files {
+            let functions_explorer = functions_explorers.next().unwrap_or_default();
+
+            let Ok((path, loaded_file)) = loaded_file else {
+                // The tabs of the files that are gone are closed.
+                remove_file_tabs(&mut tree, &mut detached_tabs, self.file_entries.len());
+                if active_file > self.file_entries.len() {
+                    active_file -= 1;
+                }
+                continue;
+            };
+
+            let file_index = self.push_file_entry(path, loaded_file);
+            self.file_entries[file_index].functions_explorer = functions_explorer;
+        }
+
+        self.active_file = active_file.min(self.file_entries.len().saturating_sub(1));
+        self.tree = tree;
+        self.detached_tabs = detached_tabs;
+        self.check_budgets();
+
+        then_open.map(|path| AnalyzerState::AnalyzeWasm { path })
+    }
+
+    /// Opens a tab comparing the sizes of two files.
+    fn show_size_diff(&mut self, title: &str, old_file_index: usize, new_file_index: usize) {
+        self.tree
+            .main_surface_mut()
+            .push_to_first_leaf(DockTab::new(
+                title,
+                TabContent::SizeDiffReport {
+                    old_file_index,
+                    new_file_index,
+                    report: None,
+                },
+            ));
+    }
+
+    /// Shows `file_index` in the functions explorer, the selection and the
+    /// history of the previous file not applying to it.
+    fn set_active_file(&mut self, file_index: usize) {
+        if file_index == self.active_file {
+            return;
+        }
+
+        self.active_file = file_index;
+        self.selected_row = None;
+        self.navigation_history.clear();
+    }
+
+    /// Closes the file and its tabs.
+    fn close_file(&mut self, file_index: usize) {
+        self.file_entries.remove(file_index);
+        remove_file_tabs(&mut self.tree, &mut self.detached_tabs, file_index);
+
+        if self.active_file == file_index {
+            self.selected_row = None;
+            self.navigation_history.clear();
+        }
+        if self.active_file >= file_index && self.active_file > 0 {
+            self.active_file -= 1;
+        }
+    }
+
+    fn file_index_of(&self, path: &Path) -> Option<usize> {
+        self.file_entries
+            .iter()
+            .position(|file_entry| file_entry.path == path)
+    }
+
+    /// Loads the uploaded files on this thread, browsers not running others.
+    /// A file uploaded again replaces the entry of the same name.
+    fn open_uploads(&mut self) {
+        let uploads = std::mem::take(&mut *self.uploads.lock().unwrap());
+        for (path, bytes) in uploads {
+            let Some(loaded_file) =
+                LoadedFile::upload(&path, &bytes, &|_, _| ControlFlow::Continue(()))
+            else {
+                self.analyzer_error = Some(format!("Failed to load {}", path.display()));
+                continue;
+            };
+
+            match self.file_index_of(&path) {
+                Some(file_index) => self.reload_file_entry(file_index, loaded_file),
+                None => self.open_loaded_file(path, loaded_file),
+            }
+        }
+    }
+
+    /// Adds the entry of a new file and shows it, in the tabs of the
+    /// previous files or in new ones.
+    fn open_loaded_file(&mut self, path: PathBuf, loaded_file: LoadedFile) {
+        let opened_file_count = self.file_entries.len();
+        let file_index = self.push_file_entry(path, loaded_file);
+        self.set_active_file(file_index);
+
+        // The new file is explored like the previous one.
+        let functions_explorer = match opened_file_count.checked_sub(1) {
+            Some(previous_file) => FunctionsExplorer::with_layout_of(
+                &self.file_entries[previous_file].functions_explorer,
+            ),
+            None => FunctionsExplorer::default(),
+        };
+        let file_entry = &mut self.file_entries[file_index];
+        file_entry.functions_explorer = functions_explorer;
+        if let Some(data_provider) = file_entry.module() {
+            file_entry.budget_violations = self
+                .settings
+                .size_budgets
+                .check(&file_entry.path, data_provider);
+        }
+
+        if self.tree.iter_all_tabs().next().is_some() {
+            self.tree
+                .main_surface_mut()
+                .push_to_first_leaf(DockTab::new(
+                    "Summary",
+                    TabContent::SectionSummary {
+                        file_index,
+                        summary: None,
+                    },
+                ));
+            return;
+        }
+
+        self.tree = egui_dock::DockState::new(vec![
+            DockTab::new(
+                "Summary",
+                TabContent::SectionSummary {
+                    file_index,
+                    summary: None,
+                },
+            ),
+            DockTab::new(
+                "WASM",
+                TabContent::AssemblyViewer {
+                    asm: CodeViewer::for_language("wasm"),
+                    first_address: 0,
+                },
+            ),
+            DockTab::new(
+                "Source Code",
+                TabContent::SourceCodeViewer {
+                    code_viewer: CodeViewer::for_language("rust"),
+                    file_path: "".into(),
+                    first_address: 0, //address that took us to that path.
+                },
+            ),
+            DockTab::new("Raw Binary", TabContent::RawBinaryViewer { file_index }),
+            DockTab::new(
+                "Sections Binary",
+                TabContent::SectionsBinaryViewer {
+                    file_index,
+                    fn_index: 0,
+                },
+            ),
+        ]);
+
+        // self.tree.split((0, 0), egui_dock::Split::Right, 0.5, )
+    }
+
+    /// Starts analyzing again the first file changed on disk since it was
+    /// analyzed, once the file watcher saw a change.
+    fn reload_changed_file(&mut self) {
+        // The change stays pending until the current analysis is done.
+        if self.analyzer_state.is_some()
+            || !self
+                .file_watcher
+                .as_ref()
+                .is_some_and(|file_watcher| file_watcher.take_changed())
+        {
+            return;
+        }
+
+        let Some(file_index) = self.file_entries.iter().position(|file_entry| {
+            let modified = PathExt::modified_time(&file_entry.path);
+            modified.is_some() && modified != file_entry.modified
+        }) else {
+            return;
+        };
+
+        // A file still being written fails to load, its next change reloads it.
+        let file_entry = &mut self.file_entries[file_index];
+        file_entry.modified = PathExt::modified_time(&file_entry.path);
+        self.analyzer_state = Some(AnalyzerState::load_in_background(
+            file_entry.path.clone(),
+            LoadPurpose::Reload { file_index },
+        ));
+    }
+
+    /// Puts the new analysis of a file in its entry, keeping the filters, the
+    /// selected function, matched by name, and the tabs showing it.
+    fn reload_file_entry(&mut self, file_index: usize, loaded_file: LoadedFile) {
+        let LoadedFile {
+            data_provider,
+            arena,
+            ty,
+        } = loaded_file;
+
+        let file_entry = &mut self.file_entries[file_index];
+        let selected_name = file_entry
+            .functions_explorer
+            .selected_row
+            .zip(file_entry.module())
+            .map(|(function, data_provider)| {
+                data_provider
+                    .wasm_data
+                    .functions_section
+                    .function_original_names[function]
+                    .to_owned()
+            });
+        let mut functions_explorer = std::mem::take(&mut file_entry.functions_explorer);
+
+        // The previous data provider is dropped before its arena.
+        *file_entry = FileEntry {
+            path: file_entry.path.clone(),
+            ty,
+            modified: file_entry.modified,
+            section_summary: section_summary(data_provider.as_ref()),
+            byte_kinds: file_byte_kinds(data_provider.as_ref()),
+            data_provider: Some(data_provider),
+            budget_violations: Vec::new(),
+            functions_explorer: FunctionsExplorer::default(),
+            arena,
+        };
+        if let Some(data_provider) = file_entry.module_mut() {
+            functions_explorer.reload(data_provider, &self.tags, selected_name.as_deref());
+        }
+        if let Some(data_provider) = file_entry.module() {
+            file_entry.budget_violations = self
+                .settings
+                .size_budgets
+                .check(&file_entry.path, data_provider);
+        }
+        file_entry.functions_explorer = functions_explorer;
+
+        for tab in all_tabs_mut(&mut self.tree, &mut self.detached_tabs) {
+            tab.contents.file_reloaded(file_index);
+        }
+        // The viewers of the selected function show it again.
+        if self.active_file == file_index {
+            self.selected_row = None;
+            self.navigation_history.clear();
+        }
+    }
+
+    /// Returns the index of the new file entry.
+    fn push_file_entry(&mut self, path: PathBuf, loaded_file: LoadedFile) -> usize {
+        let LoadedFile {
+            data_provider,
+            arena,
+            ty,
+        } = loaded_file;
+        self.file_entries.push(FileEntry {
+            modified: PathExt::modified_time(&path),
+            path,
+            ty,
+            arena,
+            section_summary: section_summary(data_provider.as_ref()),
+            byte_kinds: file_byte_kinds(data_provider.as_ref()),
+            data_provider: Some(data_provider),
+            budget_violations: Vec::new(),
+            functions_explorer: FunctionsExplorer::default(),
+        });
+
+        self.file_entries.len() - 1
     }
 }
 
 const SERIALIZABLE_FIELDS: &[&str] = &[
     "last_path_picked",
-    "functions_explorer",
+    "functions_explorers",
+    "active_file",
     "settings_src_folders",
+    "settings_budgets",
+    "settings_syntax_theme",
+    "settings_name_style",
+    "settings_auto_reload",
+    "settings_appearance",
+    "bookmarks",
+    "notes",
+    "tags",
     "file_entries",
     "tree",
+    "detached_tabs",
 ];
 
 impl serde::Serialize for TemplateApp {
@@ -670,20 +3565,53 @@ impl serde::Serialize for TemplateApp {
     where
         S: serde::Serializer,
     {
+        // A project still being restored is saved as it was opened.
+        let (tree, detached_tabs, files, functions_explorers, active_file) =
+            match &self.analyzer_state {
+                Some(AnalyzerState::LoadingWasm {
+                    purpose: LoadPurpose::Restore(project),
+                    ..
+                }) => (
+                    &project.tree,
+                    &project.detached_tabs,
+                    project.files.clone(),
+                    project.functions_explorers.iter().collect(),
+                    project.active_file,
+                ),
+                _ => (
+                    &self.tree,
+                    &self.detached_tabs,
+                    self.file_entries
+                        .iter()
+                        .map(|file_entry| (file_entry.path.clone(), file_entry.ty))
+                        .collect(),
+                    self.file_entries
+                        .iter()
+                        .map(|file_entry| &file_entry.functions_explorer)
+                        .collect::<Vec<_>>(),
+                    self.active_file,
+                ),
+            };
+
         let mut s = serializer.serialize_struct("TemplateApp", SERIALIZABLE_FIELDS.len())?;
-        s.serialize_field("tree", &self.tree)?;
+        s.serialize_field("tree", tree)?;
+        s.serialize_field("detached_tabs", detached_tabs)?;
         s.serialize_field("last_path_picked", &self.last_path_picked)?;
-        s.serialize_field("functions_explorer", &self.functions_explorer)?;
         s.serialize_field(
             "settings_src_folders",
             &self.settings.source_code_search_folders,
         )?;
-
-        let mut files: Vec<(PathBuf, FileType)> = Vec::with_capacity(self.file_entries.len());
-        for file_entry in &self.file_entries {
-            files.push((file_entry.path.clone(), file_entry.ty));
-        }
+        s.serialize_field("settings_budgets", &self.settings.size_budgets)?;
+        s.serialize_field("settings_syntax_theme", &self.settings.syntax_theme)?;
+        s.serialize_field("settings_name_style", &self.settings.name_style)?;
+        s.serialize_field("settings_auto_reload", &self.settings.auto_reload)?;
+        s.serialize_field("settings_appearance", &self.settings.appearance)?;
+        s.serialize_field("bookmarks", &self.bookmarks)?;
+        s.serialize_field("notes", &self.notes)?;
+        s.serialize_field("tags", &self.tags)?;
         s.serialize_field("file_entries", &files)?;
+        s.serialize_field("functions_explorers", &functions_explorers)?;
+        s.serialize_field("active_file", &active_file)?;
         s.end()
     }
 }
@@ -707,50 +3635,66 @@ impl<'de> serde::Deserialize<'de> for TemplateApp {
                 A: serde::de::MapAccess<'de>,
             {
                 let mut tree = None;
+                let mut detached_tabs: Vec<DetachedTab> = Vec::new();
                 let mut last_path_picked: Option<PathBuf> = None;
-                let mut functions_explorer = None;
-                let mut file_entries = None;
+                let mut functions_explorers: Vec<FunctionsExplorer> = Vec::new();
+                let mut files: Option<Vec<(PathBuf, FileType)>> = None;
+                let mut active_file = 0;
                 let mut settings = AppSettings::default();
+                let mut bookmarks = Bookmarks::default();
+                let mut notes = Notes::default();
+                let mut tags = Tags::default();
 
                 while let Some(key) = map.next_key()? {
                     match key {
                         "tree" => {
                             tree = Some(map.next_value()?);
                         }
+                        "detached_tabs" => {
+                            detached_tabs = map.next_value()?;
+                        }
                         "last_path_picked" => {
                             last_path_picked = Some(map.next_value()?);
                         }
+                        "functions_explorers" => {
+                            functions_explorers = map.next_value()?;
+                        }
+                        // Saved before several files could be opened.
                         "functions_explorer" => {
-                            functions_explorer = Some(map.next_value()?);
+                            functions_explorers = vec![map.next_value()?];
+                        }
+                        "active_file" => {
+                            active_file = map.next_value()?;
                         }
                         "settings_src_folders" => {
                             settings.source_code_search_folders = map.next_value()?;
                         }
+                        "settings_budgets" => {
+                            settings.size_budgets = map.next_value()?;
+                        }
+                        "settings_syntax_theme" => {
+                            settings.syntax_theme = map.next_value()?;
+                        }
+                        "settings_name_style" => {
+                            settings.name_style = map.next_value()?;
+                        }
+                        "settings_auto_reload" => {
+                            settings.auto_reload = map.next_value()?;
+                        }
+                        "settings_appearance" => {
+                            settings.appearance = map.next_value()?;
+                        }
+                        "bookmarks" => {
+                            bookmarks = map.next_value()?;
+                        }
+                        "notes" => {
+                            notes = map.next_value()?;
+                        }
+                        "tags" => {
+                            tags = map.next_value()?;
+                        }
                         "file_entries" => {
-                            let files: Vec<(PathBuf, FileType)> = map.next_value()?;
-
-                            let mut fe = Vec::with_capacity(files.len());
-                            for (path, ty) in files {
-                                let arena = Arena::new(64 * GB);
-                                let data_provider = match ty {
-                                    FileType::Wasm => DataProviderTwiggy::from_path(
-                                        unsafe { std::mem::transmute(&arena) },
-                                        &path,
-                                    ),
-                                };
-                                let Ok(data_provider) = data_provider else {
-                                    continue;
-                                };
-
-                                fe.push(FileEntry {
-                                    path,
-                                    ty,
-                                    arena,
-                                    data_provider: Some(data_provider),
-                                });
-                            }
-
-                            file_entries = Some(fe);
+                            files = Some(map.next_value()?);
                         }
                         _ => {
                             return Err(serde::de::Error::unknown_field(key, SERIALIZABLE_FIELDS));
@@ -758,24 +3702,71 @@ impl<'de> serde::Deserialize<'de> for TemplateApp {
                     }
                 }
 
-                let tree = tree.ok_or_else(|| serde::de::Error::missing_field("tree"))?;
+                let tree: egui_dock::DockState<DockTab> =
+                    tree.ok_or_else(|| serde::de::Error::missing_field("tree"))?;
                 let last_path_picked = last_path_picked
                     .ok_or_else(|| serde::de::Error::missing_field("last_path_picked"))?;
-                let functions_explorer = functions_explorer
-                    .ok_or_else(|| serde::de::Error::missing_field("functions_explorer"))?;
-                let file_entries = file_entries
-                    .ok_or_else(|| serde::de::Error::missing_field("functions_explorer"))?;
+                let files = files.ok_or_else(|| serde::de::Error::missing_field("file_entries"))?;
 
-                Ok(TemplateApp {
-                    file_dialog: FileDialog::default().initial_directory(last_path_picked.clone()),
+                let project = RestoredProject {
+                    files,
+                    functions_explorers,
+                    active_file,
+                    tree,
+                    detached_tabs,
+                    then_open: None,
+                };
+
+                let mut app = TemplateApp {
+                    file_dialog: open_file_dialog().initial_directory(last_path_picked.clone()),
                     last_path_picked,
+                    baseline_file_dialog: FileDialog::new(),
+                    feature_builds_file_dialog: FileDialog::new(),
+                    collapsed_stacks_file_dialog: FileDialog::new()
+                        .default_file_name("sizes.folded"),
+                    export_analysis_file_dialog: FileDialog::new()
+                        .default_file_name("analysis.json"),
+                    tree_graph_file_dialog: FileDialog::new().default_file_name("tree.dot"),
+                    call_graph_file_dialog: FileDialog::new().default_file_name("calls.dot"),
+                    open_project_dialog: project_file_dialog("Open project"),
+                    save_project_dialog: project_file_dialog("Save project"),
+                    project_path: None,
                     analyzer_state: None,
-                    functions_explorer,
-                    file_entries,
+                    analyzer_error: None,
+                    file_watcher: None,
+                    uploads: Uploads::default(),
+                    frame_times: VecDeque::new(),
+                    source_cache: SourceCache::default(),
+                    file_entries: Vec::new(),
+                    active_file: 0,
                     selected_row: None,
-                    tree,
+                    navigation_history: NavigationHistory::default(),
+                    bookmarks,
+                    notes,
+                    tags,
+                    linked_dominator_paths: ExpandedPaths::default(),
+                    tree: egui_dock::DockState::new(vec![]),
+                    detached_tabs: Vec::new(),
                     settings,
-                })
+                };
+
+                // The files are analyzed again in the background. Browsers don't
+                // run other threads and the uploads aren't saved, so the web
+                // build closes their tabs.
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    app.analyzer_state = Some(AnalyzerState::restore_in_background(project));
+                }
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let uploads: Vec<_> = project
+                        .files
+                        .iter()
+                        .map(|(path, _)| Err(format!("{} is not uploaded", path.display())))
+                        .collect();
+                    app.restore_project(project, uploads.into_iter());
+                }
+                Ok(app)
             }
         }
 
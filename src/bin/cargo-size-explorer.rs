@@ -0,0 +1,17 @@
+//! `cargo size-explorer`, builds the current crate with the arguments of
+//! `cargo build` and opens the built file in the explorer, like:
+//!
+//! ```text
+//! cargo size-explorer --target wasm32-unknown-unknown --release
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> std::process::ExitCode {
+    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+
+    binary_size_explorer::cargo_build::run()
+}
+
+// Cargo only runs natively, the workspace is still checked for the web.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
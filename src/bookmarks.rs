@@ -0,0 +1,160 @@
+use hashbrown::HashMap;
+
+use crate::{
+    analysis::symbol,
+    data_provider_twiggy::{DataProviderTwiggy, node_path},
+    dwarf::DwNodeType,
+};
+
+/// Functions and namespaces starred during an investigation, saved with the
+/// session.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Bookmarks {
+    /// Demangled names without the hash, which stay the same across builds.
+    functions: Vec<String>,
+    /// Paths of the nodes of the dominators, like `crate::module::Type`.
+    paths: Vec<String>,
+    /// Incremented on every change, for the views caching the sizes.
+    #[serde(skip)]
+    generation: u64,
+}
+
+pub struct BookmarkSize {
+    pub name: String,
+    pub is_path: bool,
+    /// Functions with the name, or functions under the path.
+    pub function_count: usize,
+    pub size_bytes: u64,
+    /// Biggest function with the name, to select it.
+    pub largest_function: Option<usize>,
+}
+
+impl Bookmarks {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.paths.is_empty()
+    }
+
+    pub fn is_function_bookmarked(&self, demangled_name: &str) -> bool {
+        let name = symbol::strip_hash(demangled_name);
+        self.functions.iter().any(|function| function == name)
+    }
+
+    pub fn toggle_function(&mut self, demangled_name: &str) {
+        let name = symbol::strip_hash(demangled_name);
+        toggle(&mut self.functions, name);
+        self.generation += 1;
+    }
+
+    pub fn is_path_bookmarked(&self, path: &str) -> bool {
+        self.paths.iter().any(|bookmarked| bookmarked == path)
+    }
+
+    pub fn toggle_path(&mut self, path: &str) {
+        toggle(&mut self.paths, path);
+        self.generation += 1;
+    }
+
+    /// Sizes of the bookmarks in `data_provider`, ignoring the filter. The
+    /// bookmarks missing from it have no functions.
+    pub fn sizes(&self, data_provider: &DataProviderTwiggy) -> Vec<BookmarkSize> {
+        let mut sizes: Vec<BookmarkSize> = self
+            .functions
+            .iter()
+            .map(|name| BookmarkSize {
+                name: name.clone(),
+                is_path: false,
+                function_count: 0,
+                size_bytes: 0,
+                largest_function: None,
+            })
+            .chain(self.paths.iter().map(|path| BookmarkSize {
+                name: path.clone(),
+                is_path: true,
+                function_count: 0,
+                size_bytes: 0,
+                largest_function: None,
+            }))
+            .collect();
+
+        let function_bookmarks: HashMap<&str, usize> = self
+            .functions
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.as_str(), idx))
+            .collect();
+        for (function, function_data) in data_provider.raw_data.iter().enumerate() {
+            let property = &function_data.function_property;
+            let Some(&idx) = function_bookmarks.get(symbol::strip_hash(property.raw_name)) else {
+                continue;
+            };
+
+            let size = &mut sizes[idx];
+            let is_largest = size.largest_function.is_none_or(|largest| {
+                data_provider.raw_data[largest]
+                    .function_property
+                    .retained_size_bytes
+                    < property.retained_size_bytes
+            });
+            if is_largest {
+                size.largest_function = Some(function);
+            }
+            size.function_count += 1;
+            size.size_bytes += property.retained_size_bytes as u64;
+        }
+
+        if self.paths.is_empty() {
+            return sizes;
+        }
+
+        // Function counts and sizes of the subtrees, children coming after
+        // their parents.
+        let tree = &data_provider.dominator_state.tree;
+        let mut subtrees: Vec<(usize, u64)> = (0..tree.len())
+            .map(|idx| match tree[idx].value.ty {
+                DwNodeType::FunctionInstance | DwNodeType::FunctionInlinedInstance => {
+                    (1, tree[idx].value.size as u64)
+                }
+                _ => (0, 0),
+            })
+            .collect();
+        for idx in (0..tree.len()).rev() {
+            if let Some(parent) = tree[idx].parent {
+                let (function_count, size_bytes) = subtrees[idx];
+                subtrees[parent].0 += function_count;
+                subtrees[parent].1 += size_bytes;
+            }
+        }
+
+        let path_bookmarks: HashMap<&str, usize> = self
+            .paths
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| (path.as_str(), self.functions.len() + idx))
+            .collect();
+        let mut path = String::new();
+        // The root has no path.
+        for (idx, &(function_count, size_bytes)) in subtrees.iter().enumerate().skip(1) {
+            node_path(tree, idx, &mut path);
+            if let Some(&bookmark) = path_bookmarks.get(path.as_str()) {
+                // Namespaces are split across compilation units.
+                sizes[bookmark].function_count += function_count;
+                sizes[bookmark].size_bytes += size_bytes;
+            }
+        }
+
+        sizes
+    }
+}
+
+fn toggle(names: &mut Vec<String>, name: &str) {
+    match names.iter().position(|bookmarked| bookmarked == name) {
+        Some(idx) => {
+            names.remove(idx);
+        }
+        None => names.push(name.to_owned()),
+    }
+}
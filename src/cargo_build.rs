@@ -0,0 +1,166 @@
+use std::{
+    ffi::OsString,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+};
+
+use clap::Parser;
+
+use crate::{cli::exit_code, provider_registry};
+
+#[derive(Parser)]
+#[command(name = "cargo", bin_name = "cargo")]
+enum Cargo {
+    /// Builds the current crate and opens what it built in the explorer.
+    SizeExplorer(SizeExplorer),
+}
+
+#[derive(clap::Args)]
+#[command(version)]
+struct SizeExplorer {
+    /// Arguments of `cargo build`, like `--target wasm32-unknown-unknown
+    /// --release`, or `--bin NAME` to pick one of the built files.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    cargo_args: Vec<OsString>,
+}
+
+/// A message of `cargo build --message-format=json`, only the built files
+/// being read.
+#[derive(serde::Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+enum Message {
+    CompilerArtifact {
+        target: Target,
+        filenames: Vec<PathBuf>,
+        executable: Option<PathBuf>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(serde::Deserialize)]
+struct Target {
+    kind: Vec<String>,
+}
+
+/// Runs `cargo size-explorer`, opening the window once the file is built.
+pub fn run() -> ExitCode {
+    let Cargo::SizeExplorer(args) = Cargo::parse();
+
+    let path = match build(&args.cargo_args) {
+        Ok(Some(path)) => path,
+        // Cargo printed why the build failed.
+        Ok(None) => return ExitCode::FAILURE,
+        Err(err) => return exit_code(Err(err)),
+    };
+
+    match crate::run_native(Some(path)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs `cargo build` with `cargo_args`, returns the file it built, `None`
+/// if the build failed.
+fn build(cargo_args: &[OsString]) -> io::Result<Option<PathBuf>> {
+    // Set by cargo for its subcommands, the same toolchain builds the crate.
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut child = Command::new(cargo)
+        .arg("build")
+        .arg("--message-format=json-render-diagnostics")
+        .args(cargo_args)
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let files = built_files(BufReader::new(child.stdout.take().unwrap()));
+    if !child.wait()?.success() {
+        return Ok(None);
+    }
+
+    match files?.as_slice() {
+        [] => Err(io::Error::other(
+            "No file the explorer can open was built, build for a target like \
+             `--target wasm32-unknown-unknown`",
+        )),
+        [file] => Ok(Some(file.clone())),
+        files => {
+            let mut message =
+                "Several files were built, pick one with `--bin`, `--example` or `--package`:"
+                    .to_string();
+            for file in files {
+                message.push_str(&format!("\n  {}", file.display()));
+            }
+            Err(io::Error::other(message))
+        }
+    }
+}
+
+/// The binaries and dynamic libraries built, from the JSON messages of cargo,
+/// with the extension of a registered format.
+fn built_files(messages: impl BufRead) -> io::Result<Vec<PathBuf>> {
+    let formats = provider_registry::formats();
+    let is_known = |path: &Path| {
+        path.extension().is_some_and(|ext| {
+            formats
+                .iter()
+                .any(|format| format.extensions.iter().any(|extension| ext == *extension))
+        })
+    };
+
+    let mut files = Vec::new();
+    for line in messages.lines() {
+        let line = line?;
+        // Build scripts may print lines which aren't messages.
+        let Ok(Message::CompilerArtifact {
+            target,
+            filenames,
+            executable,
+        }) = serde_json::from_str(&line)
+        else {
+            continue;
+        };
+
+        if !target
+            .kind
+            .iter()
+            .any(|kind| matches!(kind.as_str(), "bin" | "cdylib" | "example"))
+        {
+            continue;
+        }
+
+        for file in filenames.into_iter().chain(executable) {
+            if is_known(&file) && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::built_files;
+
+    #[test]
+    fn finds_the_built_wasm_files() {
+        let messages = r#"{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"dep"},"filenames":["/target/wasm32-unknown-unknown/release/libdep.rlib"],"executable":null}
+{"reason":"compiler-artifact","target":{"kind":["custom-build"],"name":"build-script-build"},"filenames":["/target/release/build/app/build-script-build"],"executable":"/target/release/build/app/build-script-build"}
+{"reason":"compiler-artifact","target":{"kind":["bin"],"name":"app"},"filenames":["/target/wasm32-unknown-unknown/release/app.wasm"],"executable":"/target/wasm32-unknown-unknown/release/app.wasm"}
+not a message
+{"reason":"build-finished","success":true}
+"#;
+
+        assert_eq!(
+            built_files(messages.as_bytes()).unwrap(),
+            [PathBuf::from(
+                "/target/wasm32-unknown-unknown/release/app.wasm"
+            )]
+        );
+    }
+}
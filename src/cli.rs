@@ -0,0 +1,650 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{ArgGroup, Parser, Subcommand};
+
+use crate::{
+    analysis::{
+        budget::SizeBudgets,
+        diff::{SizeDiffEntry, SizeDiffReport},
+        export::{AnalysisExport, SCHEMA_VERSION},
+        graphml::call_graph_graphml,
+        graphviz::{call_graph_dot, render_svg, tree_dot},
+        query::{Query, parse_size},
+    },
+    arena::{Arena, memory},
+    data_provider::{Filter, FilterOptions, FunctionsView, NameStyle},
+    data_provider_twiggy::DataProviderTwiggy,
+    provider_registry,
+};
+
+/// Explores what takes space in WebAssembly binaries. Opens the window
+/// without a command.
+#[derive(Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    /// File analyzed once the window opens, again if it was open.
+    #[arg(conflicts_with = "json")]
+    file: Option<PathBuf>,
+    /// Prints the analysis of the file as JSON, like the export of the window.
+    #[arg(long, value_name = "FILE")]
+    json: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the biggest functions of a file.
+    Top {
+        file: PathBuf,
+        /// Number of functions printed.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// Only prints the functions whose demangled name contains this text,
+        /// ignoring the case.
+        #[arg(long)]
+        filter: Option<String>,
+        /// Only prints the functions matching this query, like
+        /// `size > 10kb && crate == "serde" && !name ~ "test"`.
+        #[arg(long, conflicts_with = "filter")]
+        query: Option<String>,
+        /// Prints the mangled names.
+        #[arg(long)]
+        mangled: bool,
+    },
+    /// Prints the functions that grew and shrank the most between two builds,
+    /// matched like in the window.
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Number of grown and of shrunk functions printed.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// Prints the mangled names.
+        #[arg(long)]
+        mangled: bool,
+    },
+    /// Prints the namespace tree of a file as a Graphviz DOT graph, like the
+    /// export of the window, the functions being grouped by crate without
+    /// debug info. Prints the call graph with `--calls`.
+    Graph {
+        file: PathBuf,
+        /// Leaves out the nodes smaller than this size, like `1KB`, summing
+        /// them up in their parent in the tree.
+        #[arg(long, value_name = "SIZE", value_parser = parse_size, default_value = "0")]
+        min_size: u64,
+        /// Prints the calls between the functions instead of the tree.
+        #[arg(long)]
+        calls: bool,
+        /// Only prints the functions this one calls, directly or not, named
+        /// by its mangled or demangled name.
+        #[arg(long, value_name = "FUNCTION", requires = "calls")]
+        from: Option<String>,
+        /// Prints the calls in the GraphML format of Gephi and yEd.
+        #[arg(long, requires = "calls", conflicts_with = "svg")]
+        graphml: bool,
+        /// Prints the graph laid out as SVG by Graphviz, which must be
+        /// installed.
+        #[arg(long)]
+        svg: bool,
+        /// Prints the mangled names.
+        #[arg(long)]
+        mangled: bool,
+    },
+    /// Checks a file against size limits, exiting with an error when one is
+    /// exceeded, for continuous integration.
+    #[command(group(
+        ArgGroup::new("limits")
+            .args(["max_size", "baseline", "budgets"])
+            .multiple(true)
+            .required(true)
+    ))]
+    Check {
+        file: PathBuf,
+        /// Maximum size of the file, like `2MB`, `512KB` or `1000`.
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        max_size: Option<u64>,
+        /// Analysis of a previous build exported as JSON, with `--json` or
+        /// from the window, to check the growth against.
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<PathBuf>,
+        /// Maximum growth of the file over the baseline.
+        #[arg(long, value_name = "SIZE", value_parser = parse_size, default_value = "0", requires = "baseline")]
+        max_growth: u64,
+        /// Maximum growth of any function over the baseline, functions being
+        /// matched by name and new ones growing from nothing. Functions aren't
+        /// checked without it.
+        #[arg(long, value_name = "SIZE", value_parser = parse_size, requires = "baseline")]
+        max_function_growth: Option<u64>,
+        /// Size budgets of the files and crates, as JSON like
+        /// `{"files": [{"name": "app.wasm", "max_bytes": 2000000}],
+        /// "crates": [{"name": "serde_json", "max_bytes": 100000}]}`.
+        #[arg(long, value_name = "FILE")]
+        budgets: Option<PathBuf>,
+        /// Number of functions over their limit printed.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+    },
+}
+
+/// Runs the command of the command line, breaking with its exit code. It
+/// continues without one for the app to open its window, with the file to
+/// open in it.
+pub fn run() -> ControlFlow<ExitCode, Option<PathBuf>> {
+    let cli = Cli::parse();
+    if let Some(file) = cli.json {
+        return ControlFlow::Break(exit_code(with_analysis(&file, |data_provider| {
+            print_json(&file, data_provider).map(|()| ExitCode::SUCCESS)
+        })));
+    }
+
+    let Some(command) = cli.command else {
+        // Matched against the paths of the files saved with the app.
+        let file = cli
+            .file
+            .map(|file| std::path::absolute(&file).unwrap_or(file));
+        return ControlFlow::Continue(file);
+    };
+    let result = match command {
+        Command::Check {
+            file,
+            max_size,
+            baseline,
+            max_growth,
+            max_function_growth,
+            budgets,
+            count,
+        } => {
+            let limits = SizeLimits {
+                max_size,
+                max_growth,
+                max_function_growth,
+            };
+            return ControlFlow::Break(exit_code(with_analysis(&file, |data_provider| {
+                let baseline = baseline.as_deref().map(read_baseline).transpose()?;
+                let budgets = budgets.as_deref().map(read_budgets).transpose()?;
+                check(
+                    &file,
+                    data_provider,
+                    baseline.as_ref(),
+                    budgets.as_ref(),
+                    &limits,
+                    count,
+                )
+            })));
+        }
+        Command::Top {
+            file,
+            count,
+            filter,
+            query,
+            mangled,
+        } => with_analysis(&file, |data_provider| {
+            data_provider.name_style = name_style(mangled);
+            print_top(data_provider, count, filter.as_deref(), query.as_deref())
+        }),
+        Command::Diff {
+            old,
+            new,
+            count,
+            mangled,
+        } => with_analysis(&old, |old| {
+            with_analysis(&new, |new| {
+                old.name_style = name_style(mangled);
+                new.name_style = name_style(mangled);
+                print_diff(old, new, count)
+            })
+        }),
+        Command::Graph {
+            file,
+            min_size,
+            calls,
+            from,
+            graphml,
+            svg,
+            mangled,
+        } => {
+            let options = GraphOptions {
+                min_size: u32::try_from(min_size).unwrap_or(u32::MAX),
+                calls,
+                from,
+                graphml,
+                svg,
+            };
+            with_analysis(&file, |data_provider| {
+                data_provider.name_style = name_style(mangled);
+                print_graph(&file, data_provider, &options)
+            })
+        }
+    };
+    ControlFlow::Break(exit_code(result.map(|()| ExitCode::SUCCESS)))
+}
+
+pub(crate) fn exit_code(result: io::Result<ExitCode>) -> ExitCode {
+    match result {
+        Ok(exit_code) => exit_code,
+        // Piped to a command like `head` which didn't read everything.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn name_style(mangled: bool) -> NameStyle {
+    if mangled {
+        NameStyle::Mangled
+    } else {
+        NameStyle::Demangled
+    }
+}
+
+/// Calls `f` with the analysis of the file at `path`.
+fn with_analysis<R>(
+    path: &Path,
+    f: impl FnOnce(&mut DataProviderTwiggy) -> io::Result<R>,
+) -> io::Result<R> {
+    let arena = Arena::new(memory::MAX_RESERVE);
+    let bytes = provider_registry::read_file(&arena, path)
+        .map_err(|err| io::Error::other(format!("Failed to read {}: {}", path.display(), err)))?;
+    let format = provider_registry::detect_format(path, bytes)
+        .ok_or_else(|| io::Error::other(format!("Unsupported file format: {}", path.display())))?;
+    let mut data_provider = (format.load)(&arena, bytes, &|_, _| ControlFlow::Continue(()))
+        .map_err(|()| io::Error::other(format!("Failed to analyze {}", path.display())))?;
+    // The commands print the analyses of the code, which need a module.
+    let module = data_provider
+        .module_mut()
+        .ok_or_else(|| io::Error::other(format!("{} has no code to analyze", path.display())))?;
+    f(module)
+}
+
+fn print_json(path: &Path, data_provider: &DataProviderTwiggy) -> io::Result<()> {
+    let file = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy();
+    let mut out = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut out, &AnalysisExport::new(&file, data_provider))?;
+    writeln!(out)
+}
+
+/// Prints the `count` biggest functions by retained size, like the tops of
+/// the functions explorer.
+fn print_top(
+    data_provider: &mut DataProviderTwiggy,
+    count: usize,
+    filter: Option<&str>,
+    query: Option<&str>,
+) -> io::Result<()> {
+    if let Some(text) = filter {
+        data_provider.set_filter(Filter::name_filter(text), FilterOptions::default(), None);
+    }
+    if let Some(text) = query {
+        let query = Query::parse(text, &FilterOptions::default())
+            .map_err(|err| io::Error::other(format!("Invalid query: {}", err)))?;
+        data_provider.set_filter(Filter::query(&query), FilterOptions::default(), None);
+    }
+
+    write_top(&mut io::stdout().lock(), data_provider, count)
+}
+
+/// Writes the `count` biggest filtered functions, and the total of all of them.
+fn write_top(
+    out: &mut impl Write,
+    data_provider: &DataProviderTwiggy,
+    count: usize,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:>10} {:>8} {:>10} {:>8}  Name",
+        "Retained", "%", "Shallow", "%"
+    )?;
+
+    // The tops are ordered by increasing retained size.
+    let functions = &data_provider.top_view_items_filtered;
+    for &idx in functions.iter().rev().take(count) {
+        let property = &data_provider.raw_data[idx].function_property;
+        writeln!(
+            out,
+            "{:>10} {:>7.2}% {:>10} {:>7.2}%  {}",
+            property.retained_size_bytes,
+            property.retained_size_percent,
+            property.shallow_size_bytes,
+            property.shallow_size_percent,
+            data_provider.function_name(idx),
+        )?;
+    }
+
+    writeln!(
+        out,
+        "Total: {} functions, {} bytes, {:.2}% of the code",
+        functions.len(),
+        data_provider.get_total_size(),
+        data_provider.get_total_percent(),
+    )
+}
+
+/// Graph printed by the `graph` command.
+struct GraphOptions {
+    min_size: u32,
+    calls: bool,
+    from: Option<String>,
+    graphml: bool,
+    svg: bool,
+}
+
+fn print_graph(
+    path: &Path,
+    data_provider: &DataProviderTwiggy,
+    options: &GraphOptions,
+) -> io::Result<()> {
+    let graph = if options.calls {
+        let from = options
+            .from
+            .as_deref()
+            .map(|name| function_named(data_provider, name))
+            .transpose()?;
+        if options.graphml {
+            call_graph_graphml(data_provider, from, options.min_size)
+        } else {
+            call_graph_dot(data_provider, from, options.min_size)
+        }
+    } else {
+        let file = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        tree_dot(data_provider, &file, options.min_size)
+    };
+
+    let mut out = io::stdout().lock();
+    if options.svg {
+        out.write_all(&render_svg(&graph)?)
+    } else {
+        out.write_all(graph.as_bytes())
+    }
+}
+
+/// Index of the function whose mangled or demangled name is `name`.
+fn function_named(data_provider: &DataProviderTwiggy, name: &str) -> io::Result<usize> {
+    let functions_section = &data_provider.wasm_data.functions_section;
+    (0..functions_section.function_count)
+        .find(|&idx| {
+            functions_section.function_original_names[idx] == name
+                || functions_section.function_names[idx] == name
+        })
+        .ok_or_else(|| io::Error::other(format!("No function named {}", name)))
+}
+
+/// Prints the `count` functions that grew the most and the `count` that
+/// shrank the most from `old` to `new`, with the totals.
+fn print_diff(old: &DataProviderTwiggy, new: &DataProviderTwiggy, count: usize) -> io::Result<()> {
+    let report = SizeDiffReport::new(old, new);
+    let mut out = io::stdout().lock();
+
+    let (grown, shrunk): (Vec<&SizeDiffEntry>, Vec<&SizeDiffEntry>) = report
+        .entries
+        .iter()
+        .partition(|entry| entry.delta_bytes() > 0);
+    for (title, entries) in [("Grown", &grown), ("Shrunk", &shrunk)] {
+        let delta_bytes: i64 = entries.iter().map(|entry| entry.delta_bytes()).sum();
+        writeln!(
+            out,
+            "{}: {} functions, {:+} bytes",
+            title,
+            entries.len(),
+            delta_bytes
+        )?;
+        if entries.is_empty() {
+            writeln!(out)?;
+            continue;
+        }
+
+        writeln!(
+            out,
+            "{:>10} {:>10} {:>10}  {:<13}  Name",
+            "Delta", "Old", "New", "Matched by"
+        )?;
+        // The entries are ordered by decreasing absolute delta.
+        for entry in entries.iter().take(count) {
+            let (matched_by, name) = match (entry.match_kind, entry.new, entry.old) {
+                (Some(match_kind), Some(idx), _) => (match_kind.as_str(), new.function_name(idx)),
+                (None, Some(idx), _) => ("added", new.function_name(idx)),
+                (_, None, Some(idx)) => ("removed", old.function_name(idx)),
+                (_, None, None) => ("", ""),
+            };
+            writeln!(
+                out,
+                "{:>+10} {:>10} {:>10}  {:<13}  {}",
+                entry.delta_bytes(),
+                entry.old_size_bytes,
+                entry.new_size_bytes,
+                matched_by,
+                name,
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(
+        out,
+        "Total: {} -> {} bytes ({:+})",
+        report.old_total_bytes,
+        report.new_total_bytes,
+        report.new_total_bytes as i64 - report.old_total_bytes as i64
+    )
+}
+
+/// Limits checked by the `check` command.
+struct SizeLimits {
+    max_size: Option<u64>,
+    max_growth: u64,
+    max_function_growth: Option<u64>,
+}
+
+fn read_baseline(path: &Path) -> io::Result<AnalysisExport> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| io::Error::other(format!("Failed to read {}: {}", path.display(), err)))?;
+    let baseline: AnalysisExport = serde_json::from_str(&text)
+        .map_err(|err| io::Error::other(format!("Failed to parse {}: {}", path.display(), err)))?;
+    if baseline.schema_version != SCHEMA_VERSION {
+        return Err(io::Error::other(format!(
+            "{} has schema version {}, expected {}",
+            path.display(),
+            baseline.schema_version,
+            SCHEMA_VERSION
+        )));
+    }
+    Ok(baseline)
+}
+
+fn read_budgets(path: &Path) -> io::Result<SizeBudgets> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| io::Error::other(format!("Failed to read {}: {}", path.display(), err)))?;
+    serde_json::from_str(&text)
+        .map_err(|err| io::Error::other(format!("Failed to parse {}: {}", path.display(), err)))
+}
+
+/// Prints whether the file is within `limits` and `budgets`, with the
+/// `count` functions that grew the most over theirs, failing if any limit is
+/// exceeded.
+fn check(
+    path: &Path,
+    data_provider: &DataProviderTwiggy,
+    baseline: Option<&AnalysisExport>,
+    budgets: Option<&SizeBudgets>,
+    limits: &SizeLimits,
+    count: usize,
+) -> io::Result<ExitCode> {
+    let mut out = io::stdout().lock();
+    let status = |ok: bool| if ok { "ok" } else { "FAIL" };
+    let mut failures = 0;
+
+    let file_size = data_provider.wasm_data.bytes.len() as u64;
+    writeln!(out, "{}: {} bytes", path.display(), file_size)?;
+
+    if let Some(max_size) = limits.max_size {
+        let ok = file_size <= max_size;
+        failures += usize::from(!ok);
+        writeln!(
+            out,
+            "{:<4}  Size {} bytes, limit {} bytes",
+            status(ok),
+            file_size,
+            max_size
+        )?;
+    }
+
+    if let Some(baseline) = baseline {
+        let growth = file_size as i64 - baseline.file_size_bytes as i64;
+        let ok = growth <= limits.max_growth as i64;
+        failures += usize::from(!ok);
+        writeln!(
+            out,
+            "{:<4}  Growth over {} {:+} bytes, limit +{} bytes",
+            status(ok),
+            baseline.file,
+            growth,
+            limits.max_growth
+        )?;
+
+        if let Some(max_function_growth) = limits.max_function_growth {
+            // Functions are matched by their mangled names, which are unique
+            // when present, otherwise by their demangled names.
+            let mut baseline_sizes: HashMap<&str, u64> = HashMap::new();
+            for function in &baseline.functions {
+                let name = if function.mangled_name.is_empty() {
+                    &function.name
+                } else {
+                    &function.mangled_name
+                };
+                *baseline_sizes.entry(name).or_default() += function.shallow_size_bytes as u64;
+            }
+
+            let mut sizes: HashMap<&str, (u64, usize)> = HashMap::new();
+            for (idx, function_data) in data_provider.raw_data.iter().enumerate() {
+                let property = &function_data.function_property;
+                let mangled_name = data_provider
+                    .wasm_data
+                    .functions_section
+                    .function_original_names[idx];
+                let name = if mangled_name.is_empty() {
+                    property.raw_name
+                } else {
+                    mangled_name
+                };
+                sizes.entry(name).or_insert((0, idx)).0 += property.shallow_size_bytes as u64;
+            }
+
+            let mut grown: Vec<(i64, usize)> = sizes
+                .iter()
+                .map(|(name, &(size, idx))| {
+                    let old_size = baseline_sizes.get(name).copied().unwrap_or(0);
+                    (size as i64 - old_size as i64, idx)
+                })
+                .filter(|&(growth, _)| growth > max_function_growth as i64)
+                .collect();
+            grown.sort_by_key(|&(growth, idx)| (std::cmp::Reverse(growth), idx));
+
+            failures += usize::from(!grown.is_empty());
+            writeln!(
+                out,
+                "{:<4}  {} functions grew over the limit of +{} bytes",
+                status(grown.is_empty()),
+                grown.len(),
+                max_function_growth
+            )?;
+            for &(growth, idx) in grown.iter().take(count) {
+                writeln!(
+                    out,
+                    "      {:>+10}  {}",
+                    growth,
+                    data_provider.function_name(idx)
+                )?;
+            }
+        }
+    }
+
+    if let Some(budgets) = budgets {
+        let violations = budgets.check(path, data_provider);
+        failures += violations.len();
+        if violations.is_empty() {
+            writeln!(
+                out,
+                "{:<4}  Within the budgets of {} files and {} crates",
+                status(true),
+                budgets.files.len(),
+                budgets.crates.len()
+            )?;
+        }
+        for violation in &violations {
+            writeln!(out, "{:<4}  {}", status(false), violation)?;
+        }
+    }
+
+    if failures == 0 {
+        writeln!(out, "Passed")?;
+        Ok(ExitCode::SUCCESS)
+    } else {
+        writeln!(out, "Failed: {} limits exceeded", failures)?;
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::ControlFlow;
+
+    use super::write_top;
+    use crate::{
+        arena::{Arena, memory::MB},
+        data_provider::{Filter, FilterOptions, FunctionsView},
+        provider_registry::{self, FileType},
+    };
+
+    /// The sizes of the rows printed by `top`, and the total printed after them.
+    fn top_sizes(out: &str) -> (Vec<u32>, u32) {
+        let mut lines: Vec<&str> = out.lines().skip(1).collect();
+        let total = lines.pop().unwrap();
+        let rows = lines
+            .iter()
+            .map(|row| row.split_whitespace().nth(2).unwrap().parse().unwrap())
+            .collect();
+        let total_bytes = total.split(", ").nth(1).unwrap();
+        (
+            rows,
+            total_bytes.trim_end_matches(" bytes").parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn top_total_is_the_size_of_the_rows() {
+        let report = br#"[
+            {"name": "main", "shallow_size": 300},
+            {"name": "helper", "shallow_size": 120},
+            {"name": "tiny", "shallow_size": 20}
+        ]"#;
+        let arena = Arena::new(MB);
+        let format = provider_registry::format(FileType::TWIGGY).unwrap();
+        let mut data_provider =
+            (format.load)(&arena, report, &|_, _| ControlFlow::Continue(())).unwrap();
+        let data_provider = data_provider.module_mut().unwrap();
+
+        for filter in [Filter::All, Filter::name_filter("in")] {
+            data_provider.set_filter(filter, FilterOptions::default(), None);
+            let mut out = Vec::new();
+            write_top(&mut out, data_provider, usize::MAX).unwrap();
+
+            let (rows, total) = top_sizes(&String::from_utf8(out).unwrap());
+            assert!(!rows.is_empty());
+            assert_eq!(rows.iter().sum::<u32>(), total);
+        }
+    }
+}
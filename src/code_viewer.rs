@@ -1,5 +1,106 @@
+use egui::Color32;
+use egui_extras::syntax_highlighting::CodeTheme;
 use serde::{Deserialize, Serialize};
 
+use crate::gui::appearance::row_height;
+use crate::wasm::highlight::{WasmToken, tokenize};
+
+/// Colors of the dark theme of egui for the comments, keywords, literals,
+/// strings and punctuation, replaced by the ones of the bundled themes.
+const DARK_THEME_COLORS: [Color32; 5] = [
+    Color32::from_gray(120),
+    Color32::from_rgb(255, 100, 100),
+    Color32::from_rgb(87, 165, 171),
+    Color32::from_rgb(109, 147, 226),
+    Color32::LIGHT_GRAY,
+];
+
+/// Same colors for the light theme of egui.
+const LIGHT_THEME_COLORS: [Color32; 5] = [
+    Color32::GRAY,
+    Color32::from_rgb(235, 0, 0),
+    Color32::from_rgb(153, 134, 255),
+    Color32::from_rgb(37, 203, 105),
+    Color32::DARK_GRAY,
+];
+
+/// Syntax highlighting of the source and assembly viewers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyntaxTheme {
+    /// Dark or light like the rest of the UI.
+    #[default]
+    FollowUi,
+    Dark,
+    Light,
+    Monokai,
+    SolarizedDark,
+}
+
+impl SyntaxTheme {
+    pub const ALL: [SyntaxTheme; 5] = [
+        SyntaxTheme::FollowUi,
+        SyntaxTheme::Dark,
+        SyntaxTheme::Light,
+        SyntaxTheme::Monokai,
+        SyntaxTheme::SolarizedDark,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SyntaxTheme::FollowUi => "Same as the UI",
+            SyntaxTheme::Dark => "Dark",
+            SyntaxTheme::Light => "Light",
+            SyntaxTheme::Monokai => "Monokai",
+            SyntaxTheme::SolarizedDark => "Solarized dark",
+        }
+    }
+
+    /// Colors replacing `DARK_THEME_COLORS`, for the themes which aren't
+    /// the ones of egui.
+    fn palette(self) -> Option<[Color32; 5]> {
+        match self {
+            SyntaxTheme::FollowUi | SyntaxTheme::Dark | SyntaxTheme::Light => None,
+            SyntaxTheme::Monokai => Some([
+                Color32::from_rgb(117, 113, 94),
+                Color32::from_rgb(249, 38, 114),
+                Color32::from_rgb(174, 129, 255),
+                Color32::from_rgb(230, 219, 116),
+                Color32::from_rgb(248, 248, 242),
+            ]),
+            SyntaxTheme::SolarizedDark => Some([
+                Color32::from_rgb(88, 110, 117),
+                Color32::from_rgb(133, 153, 0),
+                Color32::from_rgb(203, 75, 22),
+                Color32::from_rgb(42, 161, 152),
+                Color32::from_rgb(147, 161, 161),
+            ]),
+        }
+    }
+
+    /// Colors of the comments, keywords, literals, strings and punctuation.
+    fn colors(self, dark_mode: bool) -> [Color32; 5] {
+        match self {
+            SyntaxTheme::FollowUi if !dark_mode => LIGHT_THEME_COLORS,
+            SyntaxTheme::FollowUi | SyntaxTheme::Dark => DARK_THEME_COLORS,
+            SyntaxTheme::Light => LIGHT_THEME_COLORS,
+            SyntaxTheme::Monokai | SyntaxTheme::SolarizedDark => {
+                self.palette().unwrap_or(DARK_THEME_COLORS)
+            }
+        }
+    }
+
+    fn code_theme(self, ui: &egui::Ui) -> CodeTheme {
+        let font_size = egui::TextStyle::Monospace.resolve(ui.style()).size;
+        match self {
+            SyntaxTheme::FollowUi => CodeTheme::from_memory(ui.ctx(), ui.style()),
+            SyntaxTheme::Light => CodeTheme::light(font_size),
+            SyntaxTheme::Dark | SyntaxTheme::Monokai | SyntaxTheme::SolarizedDark => {
+                CodeTheme::dark(font_size)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RowData {
     pub cells: Vec<String>,
@@ -7,6 +108,89 @@ pub struct RowData {
     pub tooltip: Option<String>,
 }
 
+/// Function a row refers to, like the callee of a `call`, selected by
+/// clicking the row.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RowLink {
+    pub function: usize,
+    pub name: String,
+}
+
+const SEARCH_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+const GO_TO_LINE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::G);
+/// For how long the line gone to is flashed.
+const FLASH_SECONDS: f64 = 1.5;
+
+const MINIMAP_WIDTH: f32 = 40.0;
+
+/// Width of a lane of the branch arrows gutter.
+const BRANCH_LANE_WIDTH: f32 = 6.0;
+/// Branches nested deeper are drawn in the last lane.
+const MAX_BRANCH_LANES: usize = 16;
+
+/// Arrow from a branch to the row it goes to, drawn in the gutter.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct BranchArrow {
+    from: usize,
+    to: usize,
+    /// Lane of the vertical line, the lanes further left going around the
+    /// arrows nested in the lanes on their right.
+    lane: usize,
+}
+
+impl BranchArrow {
+    fn rows(&self) -> std::ops::RangeInclusive<usize> {
+        self.from.min(self.to)..=self.from.max(self.to)
+    }
+}
+
+/// Search of a text in the rows, opened with Ctrl+F.
+#[derive(Debug, Default)]
+struct RowSearch {
+    open: bool,
+    text: String,
+    /// Rows containing the text, in order.
+    matches: Vec<usize>,
+    /// Index of the match gone to in `matches`.
+    current: usize,
+}
+
+impl RowSearch {
+    fn update_matches(&mut self, rows: &[RowData]) {
+        self.matches.clear();
+        self.current = 0;
+        if self.text.is_empty() {
+            return;
+        }
+
+        let text = self.text.to_lowercase();
+        self.matches
+            .extend(rows.iter().enumerate().filter_map(|(idx, row)| {
+                row.cells
+                    .iter()
+                    .any(|cell| cell.to_lowercase().contains(&text))
+                    .then_some(idx)
+            }));
+    }
+
+    /// Goes to the next match, or the previous one going `backward`, returning
+    /// its row.
+    fn step(&mut self, backward: bool) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        self.current = if backward {
+            (self.current + self.matches.len() - 1) % self.matches.len()
+        } else {
+            (self.current + 1) % self.matches.len()
+        };
+        Some(self.matches[self.current])
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CodeViewer {
     rows: Vec<RowData>,
@@ -15,6 +199,33 @@ pub struct CodeViewer {
     has_scrolled: bool,
     selected_row: Option<usize>,
     can_select_rows: bool,
+    /// Bytes of code generated by each row, shown in a gutter when set.
+    #[serde(default)]
+    row_bytes: Vec<u64>,
+    #[serde(default)]
+    branches: Vec<BranchArrow>,
+    #[serde(default)]
+    row_links: Vec<Option<RowLink>>,
+    /// Shown instead of the rows, like when the source is unavailable.
+    #[serde(default)]
+    placeholder: Option<String>,
+    /// Row under the pointer in the last frame, its branches are highlighted.
+    #[serde(skip)]
+    hovered_row: Option<usize>,
+    /// Part of the rows shown in the last frame, as fractions of the rows.
+    #[serde(skip)]
+    visible_fraction: Option<std::ops::Range<f32>>,
+    #[serde(skip)]
+    search: RowSearch,
+    /// Row to scroll to the next time the table is shown.
+    #[serde(skip)]
+    scroll_to_row: Option<usize>,
+    /// Text of the go to line box, opened with Ctrl+G.
+    #[serde(skip)]
+    go_to_line: Option<String>,
+    /// Row gone to with the time its flashing ends at.
+    #[serde(skip)]
+    flashed_row: Option<(usize, f64)>,
 }
 
 impl CodeViewer {
@@ -44,19 +255,72 @@ impl CodeViewer {
             function_start_line: 0,
             has_scrolled: false,
             can_select_rows: language == "rust",
+            row_bytes: Vec::new(),
+            branches: Vec::new(),
+            row_links: Vec::new(),
+            placeholder: None,
+            hovered_row: None,
+            visible_fraction: None,
+            search: RowSearch::default(),
+            scroll_to_row: None,
+            go_to_line: None,
+            flashed_row: None,
         }
     }
 
     pub fn set_source_code(&mut self, code: &[&str]) {
         self.rows = self.preprocess_code(code);
+        self.search.update_matches(&self.rows);
         self.has_scrolled = false;
     }
 
     pub fn set_row_data(&mut self, rows: Vec<RowData>) {
         self.rows = rows;
+        self.row_bytes.clear();
+        self.branches.clear();
+        self.row_links.clear();
+        self.placeholder = None;
+        self.search.update_matches(&self.rows);
         self.has_scrolled = false;
     }
 
+    /// Shows the bytes of code generated by each row, in the order of the rows.
+    pub fn set_row_bytes(&mut self, row_bytes: Vec<u64>) {
+        self.row_bytes = row_bytes;
+    }
+
+    pub fn set_placeholder(&mut self, placeholder: String) {
+        self.placeholder = Some(placeholder);
+    }
+
+    /// Links the rows to functions, in the order of the rows.
+    pub fn set_row_links(&mut self, row_links: Vec<Option<RowLink>>) {
+        self.row_links = row_links;
+    }
+
+    /// Draws arrows from the rows of the branches to the rows they go to, as
+    /// pairs of row indices.
+    pub fn set_branches(&mut self, mut branches: Vec<(usize, usize)>) {
+        // The shortest branches get the lanes closest to the code, the longer
+        // ones going around them.
+        branches.sort_by_key(|&(from, to)| from.abs_diff(to));
+
+        self.branches.clear();
+        for (from, to) in branches {
+            let rows = from.min(to)..=from.max(to);
+            let lane = (0..)
+                .find(|&lane| {
+                    !self.branches.iter().any(|arrow| {
+                        arrow.lane == lane
+                            && arrow.rows().start() <= rows.end()
+                            && rows.start() <= arrow.rows().end()
+                    })
+                })
+                .unwrap_or_default();
+            self.branches.push(BranchArrow { from, to, lane });
+        }
+    }
+
     pub fn set_highlighted_line(&mut self, line: usize) {
         self.function_start_line = line.min(self.rows.len());
     }
@@ -71,18 +335,265 @@ impl CodeViewer {
         }
     }
 
+    fn show_search_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.search.text)
+                    .id(ui.id().with("search"))
+                    .hint_text("Search")
+                    .desired_width(200.0),
+            );
+            if response.changed() {
+                self.search.update_matches(&self.rows);
+                self.scroll_to_row = self.search.matches.first().copied();
+            }
+
+            // Enter goes to the next match and shift + enter to the previous one.
+            let (enter, shift, escape) = ui.input(|input| {
+                (
+                    input.key_pressed(egui::Key::Enter),
+                    input.modifiers.shift,
+                    input.key_pressed(egui::Key::Escape),
+                )
+            });
+            let mut step = None;
+            if response.lost_focus() && enter {
+                step = Some(shift);
+                response.request_focus();
+            }
+            if ui.button("⬆").on_hover_text("Previous match").clicked() {
+                step = Some(true);
+            }
+            if ui.button("⬇").on_hover_text("Next match").clicked() {
+                step = Some(false);
+            }
+            if let Some(backward) = step {
+                self.scroll_to_row = self.search.step(backward);
+            }
+
+            if self.search.matches.is_empty() {
+                if !self.search.text.is_empty() {
+                    ui.colored_label(ui.visuals().error_fg_color, "No matches");
+                }
+            } else {
+                ui.label(format!(
+                    "{}/{}",
+                    self.search.current + 1,
+                    self.search.matches.len()
+                ));
+            }
+
+            if ui.button("✖").on_hover_text("Close the search").clicked()
+                || (escape && response.lost_focus())
+            {
+                self.search.open = false;
+            }
+        });
+    }
+
+    fn show_go_to_line_bar(&mut self, ui: &mut egui::Ui) {
+        let Some(text) = &mut self.go_to_line else {
+            return;
+        };
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label("Go to line");
+            let response = ui.add(
+                egui::TextEdit::singleline(text)
+                    .id(ui.id().with("go_to_line"))
+                    .hint_text("42")
+                    .desired_width(80.0),
+            );
+
+            let row = text.trim().parse::<usize>().ok().and_then(|line| {
+                self.rows.iter().position(|row| {
+                    row.cells
+                        .first()
+                        .is_some_and(|cell| cell.parse() == Ok(line))
+                })
+            });
+            let (enter, escape) = ui.input(|input| {
+                (
+                    input.key_pressed(egui::Key::Enter),
+                    input.key_pressed(egui::Key::Escape),
+                )
+            });
+            let submitted = response.lost_focus() && enter;
+            if (ui.button("Go").clicked() || submitted)
+                && let Some(row) = row
+            {
+                let now = ui.input(|input| input.time);
+                self.scroll_to_row = Some(row);
+                self.flashed_row = Some((row, now + FLASH_SECONDS));
+                close = true;
+            }
+            if row.is_none() && !text.trim().is_empty() {
+                ui.colored_label(ui.visuals().error_fg_color, "No such line");
+            }
+
+            if ui.button("✖").clicked() || (escape && response.lost_focus()) {
+                close = true;
+            }
+        });
+
+        if close {
+            self.go_to_line = None;
+        }
+    }
+
     #[profiling::function]
-    pub fn show_code_as_table(&mut self, ui: &mut egui::Ui) {
+    /// Shows the colors of all the rows and the search matches scaled down to
+    /// the height of the viewer, returning the row clicked or dragged to.
+    fn show_minimap(&self, ui: &mut egui::Ui) -> Option<usize> {
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let row_count = self.rows.len();
+        let row_height = rect.height() / row_count as f32;
+        let row_rect = |idx: usize, left: f32, right: f32| {
+            let top = rect.top() + idx as f32 * row_height;
+            egui::Rect::from_min_max(
+                egui::pos2(left, top),
+                egui::pos2(right, top + row_height.max(1.0)),
+            )
+        };
+
+        // The colors of the rows on the left, the search matches on the right.
+        let split = rect.left() + rect.width() * 0.65;
+        for (idx, row) in self.rows.iter().enumerate() {
+            if let Some(bg_color) = row.bg_color {
+                painter.rect_filled(row_rect(idx, rect.left(), split), 0.0, bg_color);
+            }
+        }
+        if self.search.open {
+            let match_color = ui.visuals().selection.bg_fill;
+            let current_match = self.search.matches.get(self.search.current);
+            for idx in &self.search.matches {
+                let color = if Some(idx) == current_match {
+                    ui.visuals().strong_text_color()
+                } else {
+                    match_color
+                };
+                painter.rect_filled(row_rect(*idx, split, rect.right()), 0.0, color);
+            }
+        }
+        if let Some((flashed_row, _)) = self.flashed_row {
+            painter.rect_filled(
+                row_rect(flashed_row, rect.left(), rect.right()),
+                0.0,
+                Color32::YELLOW,
+            );
+        }
+
+        if let Some(visible_fraction) = &self.visible_fraction {
+            let visible_rect = egui::Rect::from_x_y_ranges(
+                rect.x_range(),
+                rect.top() + visible_fraction.start * rect.height()
+                    ..=rect.top() + visible_fraction.end.min(1.0) * rect.height(),
+            );
+            painter.rect_stroke(
+                visible_rect,
+                0.0,
+                ui.visuals().widgets.active.fg_stroke,
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        let pos = response.interact_pointer_pos()?;
+        if !response.clicked() && !response.dragged() {
+            return None;
+        }
+        let row = ((pos.y - rect.top()) / row_height).max(0.0) as usize;
+        Some(row.min(row_count - 1))
+    }
+
+    /// Returns the function linked from the row clicked, if any.
+    pub fn show_code_as_table(&mut self, ui: &mut egui::Ui, theme: SyntaxTheme) -> Option<usize> {
+        if let Some(placeholder) = &self.placeholder {
+            ui.centered_and_justified(|ui| {
+                ui.weak(placeholder.as_str());
+            });
+            return None;
+        }
+
+        if ui.ui_contains_pointer()
+            && ui.input_mut(|input| input.consume_shortcut(&SEARCH_SHORTCUT))
+        {
+            self.search.open = true;
+            ui.memory_mut(|memory| memory.request_focus(ui.id().with("search")));
+        }
+        if self.search.open {
+            self.show_search_bar(ui);
+        }
+
+        // Only the source code has line numbers in its first column.
+        if self.language == "rust"
+            && ui.ui_contains_pointer()
+            && ui.input_mut(|input| input.consume_shortcut(&GO_TO_LINE_SHORTCUT))
+        {
+            self.go_to_line = Some(String::new());
+            ui.memory_mut(|memory| memory.request_focus(ui.id().with("go_to_line")));
+        }
+        if self.go_to_line.is_some() {
+            self.show_go_to_line_bar(ui);
+        }
+
+        let now = ui.input(|input| input.time);
+        if self
+            .flashed_row
+            .is_some_and(|(_, flash_end)| flash_end <= now)
+        {
+            self.flashed_row = None;
+        }
+        if self.flashed_row.is_some() {
+            ui.ctx().request_repaint();
+        }
+
+        if !self.rows.is_empty() {
+            egui::SidePanel::right(ui.id().with("minimap"))
+                .resizable(false)
+                .exact_width(MINIMAP_WIDTH)
+                .show_inside(ui, |ui| {
+                    if let Some(row) = self.show_minimap(ui) {
+                        self.scroll_to_row = Some(row);
+                    }
+                });
+        }
+
+        let mut clicked_link = None;
         egui::ScrollArea::vertical().show(ui, |ui| {
             let old_selectable_labels = ui.style().interaction.selectable_labels;
             ui.style_mut().interaction.selectable_labels = false;
 
             let available_height = ui.available_height();
+            let match_color = ui.visuals().selection.bg_fill;
             let mut table = egui_extras::TableBuilder::new(ui)
                 .striped(true)
                 .resizable(true)
-                .cell_layout(egui::Layout::left_to_right(egui::Align::LEFT))
-                .column(egui_extras::Column::auto().resizable(false))
+                .cell_layout(egui::Layout::left_to_right(egui::Align::LEFT));
+            let show_branches = !self.branches.is_empty();
+            if show_branches {
+                let lanes = self
+                    .branches
+                    .iter()
+                    .map(|arrow| arrow.lane + 1)
+                    .max()
+                    .unwrap_or_default()
+                    .min(MAX_BRANCH_LANES);
+                table = table.column(
+                    egui_extras::Column::exact(lanes as f32 * BRANCH_LANE_WIDTH + 8.0)
+                        .resizable(false),
+                );
+            }
+            table = table.column(egui_extras::Column::auto().resizable(false));
+            let show_row_bytes = !self.row_bytes.is_empty();
+            if show_row_bytes {
+                table = table.column(egui_extras::Column::exact(60.0).resizable(false));
+            }
+            table = table
                 .column(egui_extras::Column::remainder())
                 .max_scroll_height(available_height)
                 .sense(egui::Sense::click());
@@ -104,53 +615,246 @@ impl CodeViewer {
                 table = table.scroll_to_row(self.function_start_line, Some(egui::Align::TOP));
                 self.has_scrolled = true;
             }
+            if let Some(row) = self.scroll_to_row.take() {
+                table = table.scroll_to_row(row, Some(egui::Align::Center));
+            }
 
-            table
+            let search_matches: &[usize] = if self.search.open {
+                &self.search.matches
+            } else {
+                &[]
+            };
+            let current_match = search_matches.get(self.search.current).copied();
+
+            let output = table
                 .header(20.0, |mut header| {
+                    if show_branches {
+                        header.col(|_| {});
+                    }
                     header.col(|ui| {
                         ui.strong("Line");
                     });
+                    if show_row_bytes {
+                        header.col(|ui| {
+                            ui.strong("Bytes")
+                                .on_hover_text("Bytes of code generated by the line");
+                        });
+                    }
                     header.col(|ui| {
                         ui.strong("Code");
                     });
                 })
-                .body(|body| {
-                    body.rows(20.0, self.rows.len(), |mut row| {
+                .body(|mut body| {
+                    let row_height = row_height(body.ui_mut());
+                    let max_row_bytes = self.row_bytes.iter().copied().max().unwrap_or(0).max(1);
+                    let bar_color = body.ui_mut().visuals().warn_fg_color.gamma_multiply(0.35);
+                    let mut hovered_row = None;
+                    body.rows(row_height, self.rows.len(), |mut row| {
                         let idx = row.index();
 
+                        if show_branches {
+                            row.col(|ui| paint_branches(ui, &self.branches, self.hovered_row, idx));
+                        }
+
                         if let Some(selected_row) = self.selected_row {
                             row.set_selected(idx == selected_row);
                         }
 
-                        for cell in self.rows[idx].cells.iter() {
+                        for (cell_idx, cell) in self.rows[idx].cells.iter().enumerate() {
                             row.col(|ui| {
                                 if let Some(bg_color) = self.rows[idx].bg_color {
                                     // Get the row's rect and paint it
                                     let rect = ui.available_rect_before_wrap();
                                     ui.painter().rect_filled(rect, 0.0, bg_color);
                                 }
-                                code_view_ui(ui, cell, &self.language);
+                                if search_matches.binary_search(&idx).is_ok() {
+                                    let color = if current_match == Some(idx) {
+                                        match_color
+                                    } else {
+                                        match_color.gamma_multiply(0.4)
+                                    };
+                                    let rect = ui.available_rect_before_wrap();
+                                    ui.painter().rect_filled(rect, 0.0, color);
+                                }
+                                if let Some((flashed_row, flash_end)) = self.flashed_row
+                                    && flashed_row == idx
+                                {
+                                    let fade = ((flash_end - now) / FLASH_SECONDS) as f32;
+                                    let rect = ui.available_rect_before_wrap();
+                                    ui.painter().rect_filled(
+                                        rect,
+                                        0.0,
+                                        Color32::YELLOW.gamma_multiply(0.6 * fade),
+                                    );
+                                }
+                                code_view_ui(ui, cell, &self.language, theme);
                             });
+
+                            if cell_idx == 0 && show_row_bytes {
+                                let bytes = self.row_bytes.get(idx).copied().unwrap_or(0);
+                                row.col(|ui| {
+                                    if bytes == 0 {
+                                        return;
+                                    }
+
+                                    // The bar makes the heavy lines stand out.
+                                    let mut rect = ui.available_rect_before_wrap();
+                                    rect.set_width(
+                                        rect.width() * bytes as f32 / max_row_bytes as f32,
+                                    );
+                                    ui.painter().rect_filled(rect, 0.0, bar_color);
+                                    ui.monospace(bytes.to_string());
+                                });
+                            }
                         }
 
                         if row.response().clicked() && self.can_select_rows {
                             self.selected_row = Some(idx);
                         }
+                        if row.response().hovered() {
+                            hovered_row = Some(idx);
+                        }
+
+                        let link = self.row_links.get(idx).and_then(Option::as_ref);
+                        if let Some(link) = link {
+                            if row.response().clicked() {
+                                clicked_link = Some(link.function);
+                            }
+                            row.response()
+                                .on_hover_cursor(egui::CursorIcon::PointingHand);
+                        }
 
-                        if let Some(tooltip) = &self.rows[idx].tooltip {
+                        let tooltip = &self.rows[idx].tooltip;
+                        if tooltip.is_some() || link.is_some() {
                             row.response().on_hover_ui(|ui| {
-                                ui.label(tooltip);
+                                if let Some(link) = link {
+                                    ui.strong(format!("Go to {}", link.name));
+                                }
+                                if let Some(tooltip) = tooltip {
+                                    ui.label(tooltip);
+                                }
                             });
                         }
                     });
+                    self.hovered_row = hovered_row;
                 });
+            self.visible_fraction = (output.content_size.y > 0.0).then(|| {
+                let top = output.state.offset.y / output.content_size.y;
+                top..top + output.inner_rect.height() / output.content_size.y
+            });
 
             ui.style_mut().interaction.selectable_labels = old_selectable_labels;
         });
+
+        clicked_link
     }
 }
 
-fn code_view_ui(ui: &mut egui::Ui, code: &str, language: &str) {
-    let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style());
-    egui_extras::syntax_highlighting::code_view_ui(ui, &theme, code, language);
+/// Colors the wasm instructions with their own tokenizer, the generic
+/// highlighter of egui not knowing them.
+fn wasm_layout_job(ui: &egui::Ui, code: &str, theme: SyntaxTheme) -> egui::text::LayoutJob {
+    let [comment, keyword, literal, string, punctuation] = theme.colors(ui.visuals().dark_mode);
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+
+    let mut layout_job = egui::text::LayoutJob::default();
+    for (range, token) in tokenize(code) {
+        let color = match token {
+            WasmToken::Comment => comment,
+            WasmToken::Opcode => keyword,
+            WasmToken::Index => string,
+            WasmToken::Immediate => literal,
+            WasmToken::Field | WasmToken::Type | WasmToken::Punctuation => punctuation,
+            WasmToken::Whitespace => Color32::TRANSPARENT,
+        };
+        layout_job.append(
+            &code[range],
+            0.0,
+            egui::TextFormat::simple(font_id.clone(), color),
+        );
+    }
+    layout_job
+}
+
+fn code_view_ui(ui: &mut egui::Ui, code: &str, language: &str, theme: SyntaxTheme) {
+    if language == "wasm" {
+        let layout_job = wasm_layout_job(ui, code, theme);
+        ui.add(egui::Label::new(layout_job).selectable(true));
+        return;
+    }
+
+    let code_theme = theme.code_theme(ui);
+    let mut layout_job = egui_extras::syntax_highlighting::highlight(
+        ui.ctx(),
+        ui.style(),
+        &code_theme,
+        code,
+        language,
+    );
+
+    if let Some(palette) = theme.palette() {
+        for section in &mut layout_job.sections {
+            if let Some(idx) = DARK_THEME_COLORS
+                .iter()
+                .position(|&color| color == section.format.color)
+            {
+                section.format.color = palette[idx];
+            }
+        }
+    }
+
+    ui.add(egui::Label::new(layout_job).selectable(true));
+}
+
+/// Paints the parts of the branch arrows going through the row `idx`, in the
+/// cell of the gutter.
+fn paint_branches(ui: &egui::Ui, branches: &[BranchArrow], hovered_row: Option<usize>, idx: usize) {
+    // Rows are spaced, the lines go over the spacing to join.
+    let rect = ui
+        .max_rect()
+        .expand2(egui::vec2(0.0, ui.spacing().item_spacing.y * 0.5));
+    let center_y = rect.center().y;
+    let visuals = ui.visuals();
+
+    for arrow in branches.iter().filter(|arrow| arrow.rows().contains(&idx)) {
+        let lane = arrow.lane.min(MAX_BRANCH_LANES - 1);
+        let x = rect.right() - 4.0 - (lane + 1) as f32 * BRANCH_LANE_WIDTH;
+        let highlighted = hovered_row.is_some_and(|row| row == arrow.from || row == arrow.to);
+        let color = if highlighted {
+            visuals.strong_text_color()
+        } else if arrow.to < arrow.from {
+            // Back to the start of a loop.
+            visuals.warn_fg_color
+        } else {
+            visuals.weak_text_color()
+        };
+        let stroke = egui::Stroke {
+            width: if highlighted { 2.0 } else { 1.0 },
+            color,
+        };
+
+        let top = if idx == *arrow.rows().start() {
+            center_y
+        } else {
+            rect.top()
+        };
+        let bottom = if idx == *arrow.rows().end() {
+            center_y
+        } else {
+            rect.bottom()
+        };
+        let painter = ui.painter();
+        painter.line_segment([egui::pos2(x, top), egui::pos2(x, bottom)], stroke);
+
+        if idx == arrow.from || idx == arrow.to {
+            let end = egui::pos2(rect.right() - 2.0, center_y);
+            painter.line_segment([egui::pos2(x, center_y), end], stroke);
+            if idx == arrow.to {
+                painter.add(egui::Shape::convex_polygon(
+                    vec![end, end - egui::vec2(5.0, 3.0), end - egui::vec2(5.0, -3.0)],
+                    color,
+                    egui::Stroke::NONE,
+                ));
+            }
+        }
+    }
 }
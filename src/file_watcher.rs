@@ -0,0 +1,84 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use crate::path::PathExt;
+
+/// Watched files and their modification time when last checked.
+type WatchedFiles = Mutex<Vec<(PathBuf, Option<SystemTime>)>>;
+
+/// Checks files for changes on another thread, only waking the UI once one
+/// changed, rather than repainting to check them.
+pub struct FileWatcher {
+    files: Arc<WatchedFiles>,
+    changed: Arc<AtomicBool>,
+}
+
+impl FileWatcher {
+    /// Checks the files every `interval`, until the watcher is dropped.
+    pub fn new(ctx: egui::Context, interval: Duration) -> Self {
+        let files = Arc::new(WatchedFiles::default());
+        let changed = Arc::new(AtomicBool::new(false));
+
+        let watched_files = Arc::downgrade(&files);
+        let changed_flag = changed.clone();
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                let Some(files) = watched_files.upgrade() else {
+                    return;
+                };
+
+                let mut any_changed = false;
+                for (path, modified) in files.lock().unwrap().iter_mut() {
+                    let new_modified = PathExt::modified_time(path);
+                    if new_modified != *modified {
+                        *modified = new_modified;
+                        any_changed = true;
+                    }
+                }
+
+                if any_changed {
+                    changed_flag.store(true, Ordering::Relaxed);
+                    ctx.request_repaint();
+                }
+            }
+        });
+
+        Self { files, changed }
+    }
+
+    /// Watches `paths` instead of the previous files, those already watched
+    /// keeping their last modification time.
+    pub fn watch<'p>(&self, paths: impl ExactSizeIterator<Item = &'p Path> + Clone) {
+        let mut files = self.files.lock().unwrap();
+        if files.len() == paths.len()
+            && files
+                .iter()
+                .zip(paths.clone())
+                .all(|((watched, _), path)| watched == path)
+        {
+            return;
+        }
+
+        let mut new_files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let modified = match files.iter().find(|(watched, _)| watched == path) {
+                Some((_, modified)) => *modified,
+                None => PathExt::modified_time(path),
+            };
+            new_files.push((path.to_path_buf(), modified));
+        }
+        *files = new_files;
+    }
+
+    /// Whether a file changed since the last call.
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}
@@ -4,13 +4,39 @@ use egui::{
 };
 
 use crate::{
-    arena::{array::Array, scratch::scratch_arena},
-    data_provider::{Filter, FunctionsView, ViewMode},
-    data_provider_twiggy::DataProviderTwiggy,
-    dwarf::DwNodeType,
-    gui::tree_view::TreeView,
+    analysis::{budget::BudgetViolation, query::Query},
+    arena::{array::Array, scratch::scratch_arena, tree::Tree},
+    bookmarks::Bookmarks,
+    data_provider::{
+        Filter, FilterNames, FilterOptions, FunctionProperty, FunctionsView, NameStyle, ViewMode,
+    },
+    data_provider_twiggy::{DataProviderTwiggy, FunctionItemState, node_path},
+    dwarf::{DwNode, DwNodeType},
+    gui::{
+        appearance::row_height,
+        tree_view::{TreeColumn, TreeView},
+    },
+    notes::Notes,
+    tags::{Tag, TagTotal, Tags},
 };
 use core::str;
+use egui_file_dialog::FileDialog;
+use hashbrown::HashSet;
+use std::cmp::{Ordering, Reverse};
+
+/// Sort keys after the first one only break the ties of the previous ones.
+const MAX_SORT_KEYS: usize = 3;
+/// Seconds without typing before the edited filter text is applied, to filter
+/// once per word rather than once per key on big files.
+const FILTER_DEBOUNCE_DELAY: f64 = 0.15;
+
+const QUERY_HELP: &str = "Conditions like size > 10kb && crate == \"serde\" && !name ~ \"test\"
+
+Sizes: size (retained), shallow, compressed, with kb, mb or gb units
+Names: name, mangled, crate, generic, compared with == or != to a text, or with ~ to a regex
+Combined with !, &&, || and parentheses
+
+The dominators match the paths and sizes of the nodes";
 
 #[derive(Clone, Copy, Default, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 enum FunctionsExplorerMode {
@@ -19,23 +45,287 @@ enum FunctionsExplorerMode {
     Dominators,
 }
 
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum FilterMode {
+    #[default]
+    Substring,
+    Regex,
+    /// Orders the matches by score instead of size.
+    Fuzzy,
+    /// Conditions on the sizes and names, see `Query`.
+    Query,
+}
+
+/// Columns of the tops table the rows can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum SortColumn {
+    Size,
+    ShallowSize,
+    CompressedSize,
+    Crate,
+    Name,
+    MonomorphizationOf,
+}
+
+impl SortColumn {
+    fn compare(self, a: &FunctionProperty, b: &FunctionProperty) -> Ordering {
+        match self {
+            SortColumn::Size => a.retained_size_bytes.cmp(&b.retained_size_bytes),
+            SortColumn::ShallowSize => a.shallow_size_bytes.cmp(&b.shallow_size_bytes),
+            SortColumn::CompressedSize => a.compressed_size_bytes.cmp(&b.compressed_size_bytes),
+            SortColumn::Crate => a.crate_name.cmp(&b.crate_name),
+            SortColumn::Name => a.raw_name.cmp(b.raw_name),
+            SortColumn::MonomorphizationOf => a.monomorphization_of.cmp(&b.monomorphization_of),
+        }
+    }
+
+    /// Sizes are sorted biggest first, names alphabetically.
+    fn is_descending_by_default(self) -> bool {
+        matches!(
+            self,
+            SortColumn::Size | SortColumn::ShallowSize | SortColumn::CompressedSize
+        )
+    }
+}
+
+/// Size columns of the dominators the children of the nodes are sorted by,
+/// biggest first.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+enum DominatorsSort {
+    Shallow,
+    #[default]
+    Retained,
+}
+
+impl DominatorsSort {
+    /// In the order of the columns.
+    const COLUMNS: [DominatorsSort; 2] = [DominatorsSort::Shallow, DominatorsSort::Retained];
+
+    fn title(self) -> &'static str {
+        match self {
+            DominatorsSort::Shallow => "Shallow (bytes)",
+            DominatorsSort::Retained => "Retained (bytes)",
+        }
+    }
+
+    fn sort_fn(
+        self,
+    ) -> fn((&DwNode, &FunctionItemState), (&DwNode, &FunctionItemState)) -> Ordering {
+        match self {
+            DominatorsSort::Shallow => |(_, a), (_, b)| {
+                b.shallow_size
+                    .cmp(&a.shallow_size)
+                    .then(b.size.cmp(&a.size))
+            },
+            DominatorsSort::Retained => |(_, a), (_, b)| b.size.cmp(&a.size),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+struct SortKey {
+    column: SortColumn,
+    descending: bool,
+}
+
+impl SortKey {
+    /// `ordering` of the column, in the direction of the key.
+    fn apply(self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Optional columns of the tops table, picked from the context menu of the
+/// header.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct TopsColumns {
+    compressed_size: bool,
+    crate_name: bool,
+    mangled_name: bool,
+    monomorphization_of: bool,
+    /// Style of the names in the name column, the one of the settings when
+    /// `None`.
+    name_style: Option<NameStyle>,
+}
+
+impl Default for TopsColumns {
+    fn default() -> Self {
+        Self {
+            compressed_size: true,
+            crate_name: true,
+            mangled_name: false,
+            monomorphization_of: true,
+            name_style: None,
+        }
+    }
+}
+
+impl TopsColumns {
+    /// Columns that are always shown: the sizes, the percentages and the name.
+    const FIXED_COLUMN_COUNT: usize = 5;
+
+    fn count(&self) -> usize {
+        Self::FIXED_COLUMN_COUNT
+            + [
+                self.compressed_size,
+                self.crate_name,
+                self.mangled_name,
+                self.monomorphization_of,
+            ]
+            .into_iter()
+            .filter(|&shown| shown)
+            .count()
+    }
+
+    fn show_menu(&mut self, ui: &mut egui::Ui) {
+        ui.label("Columns");
+        ui.checkbox(&mut self.compressed_size, "Compressed (bytes)");
+        ui.checkbox(&mut self.crate_name, "Crate");
+        ui.checkbox(&mut self.mangled_name, "Mangled name");
+        ui.checkbox(&mut self.monomorphization_of, "Monomorphization of");
+
+        ui.separator();
+        ui.label("Names");
+        ui.radio_value(&mut self.name_style, None, "As in the settings");
+        for name_style in NameStyle::ALL {
+            ui.radio_value(&mut self.name_style, Some(name_style), name_style.title());
+        }
+    }
+}
+
+/// Formats the tops table is exported in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TableFormat {
+    Csv,
+    /// Tab separated, pasted in spreadsheets as columns.
+    Tsv,
+}
+
+impl TableFormat {
+    /// Joins the fields of a row, quoting the CSV fields with commas, quotes
+    /// or new lines, like `"Vec<T, A>"`.
+    fn row(self, fields: &[String]) -> String {
+        match self {
+            TableFormat::Tsv => fields.join("\t"),
+            TableFormat::Csv => fields
+                .iter()
+                .map(|field| {
+                    if field.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        }
+    }
+}
+
 // This thing is used to explore the functions, sort by sizes and such things.
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 pub struct FunctionsExplorer {
     #[serde(skip)]
     mode: ViewMode,
     reversed_size_bytes: bool,
+    /// Collapse all the instantiations of a generic function into a single row.
+    #[serde(default)]
+    group_monomorphizations: bool,
     pub selected_row: Option<usize>,
+    /// Functions selected with ctrl and shift in tops, `selected_row` being
+    /// the last clicked one.
+    #[serde(skip)]
+    multi_selection: HashSet<usize>,
+    /// Row the shift-click selections start from, its function and whether
+    /// it's one of the pinned rows, shown above the filtered ones.
+    #[serde(skip)]
+    selection_anchor: Option<(usize, bool)>,
+    /// Functions shown above the other rows of tops whatever the filter and
+    /// the order, in the order they were pinned.
+    #[serde(skip)]
+    pinned: std::vec::Vec<usize>,
 
     filter_text: String,
+    /// Time the filter text was last edited at, while it isn't applied yet.
+    #[serde(skip)]
+    filter_edited_at: Option<f64>,
+    #[serde(default)]
+    filter_mode: FilterMode,
+    #[serde(default)]
+    filter_options: FilterOptions,
+    #[serde(skip)]
+    filter_error: Option<String>,
+
+    /// Order of the tops, the first key being the main one. The tops are
+    /// ordered by size while it's empty.
+    #[serde(default)]
+    sort_keys: Vec<SortKey>,
+    /// Whether the tops are ordered by `sort_keys`, filtering reorders them.
+    #[serde(skip)]
+    tops_sorted: bool,
+    #[serde(default)]
+    columns: TopsColumns,
+    /// Picks the file the tops table is exported to.
+    #[serde(skip)]
+    table_file_dialog: FileDialog,
+    #[serde(skip)]
+    table_export_error: Option<String>,
+
+    /// Only shows the functions with this tag in tops.
+    #[serde(skip)]
+    tag_filter: Option<String>,
+    /// Generation of the tags the filter was applied with, tagging functions
+    /// changes the filtered ones.
+    #[serde(skip)]
+    filtered_tags_generation: u64,
+    /// Totals of the tags over the filtered tops, with the generation of the
+    /// tags they were computed for.
+    #[serde(skip)]
+    tag_totals: Option<(u64, Vec<TagTotal>)>,
+    #[serde(skip)]
+    new_tag_name: String,
+
+    /// Levels opened under the top level of the dominators by the expand to
+    /// depth button.
+    #[serde(default)]
+    expand_depth: u8,
+    #[serde(skip)]
+    dominators_sort: DominatorsSort,
 }
 
 impl FunctionsExplorer {
+    /// Explorer of another file, with the same layout of the table.
+    pub fn with_layout_of(other: &Self) -> Self {
+        Self {
+            sort_keys: other.sort_keys.clone(),
+            columns: other.columns,
+            ..Self::default()
+        }
+    }
+
     pub fn show_functions_table(
         &mut self,
         ui: &mut egui::Ui,
         functions_data: &mut DataProviderTwiggy,
+        budget_violations: &[BudgetViolation],
+        bookmarks: &mut Bookmarks,
+        notes: &mut Notes,
+        tags: &mut Tags,
     ) {
+        if let Some(tag) = &self.tag_filter
+            && tags.get(tag).is_none()
+        {
+            self.tag_filter = None;
+            self.apply_filter(functions_data, tags);
+        } else if self.tag_filter.is_some() && self.filtered_tags_generation != tags.generation() {
+            self.refilter(functions_data, tags);
+        }
+
         ComboBox::from_label("Mode")
             .selected_text(format!("{:?}", self.mode))
             .show_ui(ui, |ui| {
@@ -43,40 +333,150 @@ impl FunctionsExplorer {
                 ui.selectable_value(&mut self.mode, ViewMode::Dominators, "Dominators");
             });
 
+        if self.mode == ViewMode::Tops {
+            ui.checkbox(&mut self.group_monomorphizations, "Group monomorphizations");
+        }
+
         functions_data.set_view_mode(self.mode);
 
+        self.table_file_dialog.update(ui.ctx());
+        if let Some(path) = self.table_file_dialog.take_picked() {
+            // Tab separated for `.tsv` files, comma separated otherwise.
+            let format = if path.extension().is_some_and(|extension| extension == "tsv") {
+                TableFormat::Tsv
+            } else {
+                TableFormat::Csv
+            };
+            self.table_export_error =
+                std::fs::write(&path, self.tops_table(functions_data, format))
+                    .err()
+                    .map(|err| format!("Failed to write {}: {}", path.display(), err));
+        }
+
+        let show_calls = self.mode == ViewMode::Tops && self.selected_row.is_some();
+
         use egui_extras::{Size, StripBuilder};
         egui::ScrollArea::vertical().show(ui, |ui| {
-            StripBuilder::new(ui)
-                .size(Size::remainder().at_least(100.0)) // for the table
-                .size(Size::exact(120.0))
+            let mut strip_builder = StripBuilder::new(ui).size(Size::remainder().at_least(100.0)); // for the table
+            if show_calls {
+                strip_builder = strip_builder.size(Size::exact(140.0));
+            }
+            strip_builder
+                .size(Size::exact(180.0))
                 .vertical(|mut strip| {
                     strip.cell(|ui| {
                         // Render actual view
                         match self.mode {
-                            ViewMode::Tops => self.show_tops(ui, functions_data),
-                            ViewMode::Dominators => self.show_dominators(ui, functions_data),
+                            ViewMode::Tops if self.group_monomorphizations => {
+                                self.show_tops_grouped(ui, functions_data)
+                            }
+                            ViewMode::Tops => self.show_tops(
+                                ui,
+                                functions_data,
+                                budget_violations,
+                                bookmarks,
+                                notes,
+                                tags,
+                            ),
+                            ViewMode::Dominators => {
+                                self.show_dominators(ui, functions_data, bookmarks, notes)
+                            }
                         }
                     });
+                    if show_calls {
+                        strip.cell(|ui| {
+                            ui.separator();
+                            self.show_calls(ui, functions_data);
+                        });
+                    }
                     strip.cell(|ui| {
                         ui.vertical(|ui| {
                             ui.separator();
 
                             ui.horizontal(|ui| {
                                 ui.label("Filter: ");
-                                if ui.text_edit_singleline(&mut self.filter_text).changed() {
-                                    self.selected_row = None; // Reset selected row.
-                                    if !self.filter_text.is_empty() {
-                                        functions_data
-                                            .set_filter(Filter::name_filter(&self.filter_text));
-                                    } else {
-                                        functions_data.set_filter(Filter::All);
+                                let response = ui.text_edit_singleline(&mut self.filter_text);
+                                let now = ui.input(|input| input.time);
+                                if response.changed() {
+                                    self.filter_edited_at = Some(now);
+                                }
+                                // Enter applies the text without waiting.
+                                let text_changed = self.filter_edited_at.is_some_and(|edited_at| {
+                                    let remaining = edited_at + FILTER_DEBOUNCE_DELAY - now;
+                                    if remaining > 0.0 && !response.lost_focus() {
+                                        ui.ctx().request_repaint_after_secs(remaining as f32);
+                                        return false;
                                     }
+                                    true
+                                });
+                                let mut mode_changed = false;
+                                for (mode, label) in [
+                                    (FilterMode::Substring, "Substring"),
+                                    (FilterMode::Regex, "Regex"),
+                                    (FilterMode::Fuzzy, "Fuzzy"),
+                                    (FilterMode::Query, "Query"),
+                                ] {
+                                    mode_changed |= ui
+                                        .selectable_value(&mut self.filter_mode, mode, label)
+                                        .changed();
+                                }
+                                if self.filter_mode == FilterMode::Query {
+                                    ui.label("(?)").on_hover_text(QUERY_HELP);
+                                }
+                                if text_changed || mode_changed {
+                                    self.apply_filter(functions_data, tags);
+                                }
+
+                                if let Some(error) = &self.filter_error {
+                                    let label = if self.filter_mode == FilterMode::Query {
+                                        "Invalid query"
+                                    } else {
+                                        "Invalid regex"
+                                    };
+                                    ui.colored_label(ui.visuals().error_fg_color, label)
+                                        .on_hover_text(error);
+                                }
+                            });
+
+                            ui.horizontal(|ui| {
+                                let options = &mut self.filter_options;
+                                let mut options_changed = ui
+                                    .checkbox(&mut options.case_sensitive, "Match case")
+                                    .changed();
+                                options_changed |= ui
+                                    .add_enabled(
+                                        self.filter_mode != FilterMode::Fuzzy,
+                                        egui::Checkbox::new(&mut options.whole_word, "Whole word"),
+                                    )
+                                    .changed();
+
+                                ui.separator();
+                                ui.label("Names: ")
+                                    .on_hover_text("The dominators only match demangled names");
+                                for (names, label) in [
+                                    (FilterNames::Demangled, "Demangled"),
+                                    (FilterNames::Mangled, "Mangled"),
+                                    (FilterNames::Both, "Both"),
+                                ] {
+                                    options_changed |= ui
+                                        .selectable_value(&mut options.names, names, label)
+                                        .changed();
+                                }
+
+                                ui.separator();
+                                let tag_changed =
+                                    tag_filter_selector(ui, &mut self.tag_filter, tags.tags());
+
+                                if options_changed || tag_changed {
+                                    self.apply_filter(functions_data, tags);
                                 }
                             });
 
                             ui.separator();
 
+                            if let Some(error) = &self.table_export_error {
+                                ui.colored_label(ui.visuals().error_fg_color, error);
+                            }
                             ui.label("Stats");
 
                             if self.mode == ViewMode::Tops {
@@ -86,6 +486,8 @@ impl FunctionsExplorer {
                                     functions_data.get_total_size() as f32 / (1024.0 * 1024.0),
                                     functions_data.get_total_percent(),
                                 ));
+                                self.show_selection_total(ui, functions_data);
+                                self.show_tag_totals(ui, functions_data, tags);
                             } else {
                                 ui.label(format!(
                                     "Total size (MB): {:.2}, Total %: {:.4?}%",
@@ -99,8 +501,414 @@ impl FunctionsExplorer {
         });
     }
 
-    fn show_tops(&mut self, ui: &mut egui::Ui, filtered_view: &mut DataProviderTwiggy) {
-        let table_rows_count = filtered_view.top_view_items_filtered.len();
+    /// Shows a new analysis of the file, filtered like the previous one, with
+    /// the function of mangled name `selected_name` selected again.
+    pub fn reload(
+        &mut self,
+        functions_data: &mut DataProviderTwiggy,
+        tags: &Tags,
+        selected_name: Option<&str>,
+    ) {
+        self.apply_filter(functions_data, tags);
+        self.pinned.clear();
+        self.selected_row =
+            selected_name.and_then(|name| functions_data.function_with_linkage_name(name));
+    }
+
+    fn apply_filter(&mut self, functions_data: &mut DataProviderTwiggy, tags: &Tags) {
+        self.selected_row = None; // Reset selected row.
+        self.multi_selection.clear();
+        self.selection_anchor = None;
+        self.refilter(functions_data, tags);
+    }
+
+    /// Filters again, keeping the selection.
+    fn refilter(&mut self, functions_data: &mut DataProviderTwiggy, tags: &Tags) {
+        self.filter_edited_at = None;
+        self.filter_error = None;
+        self.tops_sorted = false;
+        self.tag_totals = None;
+        self.filtered_tags_generation = tags.generation();
+
+        let tagged = self.tag_filter.as_ref().map(|tag| tags.functions_with(tag));
+        let tagged = tagged.as_ref();
+        let options = self.filter_options;
+        if self.filter_text.is_empty() {
+            functions_data.set_filter(Filter::All, options, tagged);
+            return;
+        }
+
+        match self.filter_mode {
+            FilterMode::Substring => {
+                functions_data.set_filter(Filter::name_filter(&self.filter_text), options, tagged)
+            }
+            FilterMode::Regex => {
+                let pattern = if options.whole_word {
+                    format!(r"\b(?:{})\b", self.filter_text)
+                } else {
+                    self.filter_text.clone()
+                };
+                // An invalid pattern keeps the previous filter while it's being typed.
+                match regex::RegexBuilder::new(&pattern)
+                    .case_insensitive(!options.case_sensitive)
+                    .build()
+                {
+                    Ok(regex) => functions_data.set_filter(Filter::regex(&regex), options, tagged),
+                    Err(err) => self.filter_error = Some(err.to_string()),
+                }
+            }
+            FilterMode::Fuzzy => {
+                functions_data.set_filter(Filter::fuzzy(&self.filter_text), options, tagged)
+            }
+            FilterMode::Query => match Query::parse(&self.filter_text, &options) {
+                Ok(query) => functions_data.set_filter(Filter::query(&query), options, tagged),
+                Err(err) => self.filter_error = Some(err.to_string()),
+            },
+        }
+    }
+
+    /// Functions selected with ctrl and shift, which stop counting once
+    /// another function is selected from outside the table.
+    fn multi_selection(&self) -> Option<&HashSet<usize>> {
+        let selected_row = self.selected_row?;
+        (self.multi_selection.len() > 1 && self.multi_selection.contains(&selected_row))
+            .then_some(&self.multi_selection)
+    }
+
+    fn is_row_selected(&self, function: usize) -> bool {
+        match self.multi_selection() {
+            Some(selection) => selection.contains(&function),
+            None => self.selected_row == Some(function),
+        }
+    }
+
+    /// Clicking selects a row, ctrl-clicking adds it to or removes it from the
+    /// selection and shift-clicking selects the rows from the last clicked
+    /// one. `rows` are the functions as shown and `row` is the position of
+    /// the clicked one.
+    fn click_row(&mut self, rows: &[usize], row: usize, modifiers: egui::Modifiers) {
+        if self.multi_selection().is_none() {
+            self.multi_selection.clear();
+            self.multi_selection.extend(self.selected_row);
+        }
+
+        let function = rows[row];
+        if modifiers.shift
+            && let Some(anchor_row) = self.anchor_row(rows)
+        {
+            if !modifiers.command {
+                self.multi_selection.clear();
+            }
+            self.multi_selection
+                .extend(&rows[anchor_row.min(row)..=anchor_row.max(row)]);
+            self.selected_row = Some(function);
+            return;
+        }
+
+        self.selection_anchor = Some((function, row < self.pinned.len()));
+        if !modifiers.command {
+            self.multi_selection.clear();
+            self.multi_selection.insert(function);
+            self.selected_row = Some(function);
+        } else if self.multi_selection.remove(&function) {
+            self.selected_row = self.multi_selection.iter().next().copied();
+        } else {
+            self.multi_selection.insert(function);
+            self.selected_row = Some(function);
+        }
+    }
+
+    /// Functions in the order of the rows of tops: the pinned ones, then the
+    /// other filtered ones. The pinned functions are only shown once.
+    fn shown_rows(&self, filtered: &[usize]) -> std::vec::Vec<usize> {
+        let unpinned = filtered
+            .iter()
+            .copied()
+            .filter(|function| !self.pinned.contains(function));
+        let mut rows = self.pinned.clone();
+        if self.is_size_order_reversed() {
+            rows.extend(unpinned.rev());
+        } else {
+            rows.extend(unpinned);
+        }
+        rows
+    }
+
+    /// Position of the anchor in `rows`, `None` once it's unpinned, pinned
+    /// or filtered out.
+    fn anchor_row(&self, rows: &[usize]) -> Option<usize> {
+        let (anchor, pinned) = self.selection_anchor?;
+        let (pinned_rows, unpinned_rows) = rows.split_at(self.pinned.len());
+        if pinned {
+            return pinned_rows.iter().position(|&function| function == anchor);
+        }
+        let position = unpinned_rows
+            .iter()
+            .position(|&function| function == anchor)?;
+        Some(self.pinned.len() + position)
+    }
+
+    /// Pins a function to the top of the table, or the whole selection if
+    /// it's part of it, or unpins them.
+    fn toggle_pin(&mut self, function: usize, functions_data: &DataProviderTwiggy) {
+        let mut functions: std::vec::Vec<usize> = match self.multi_selection() {
+            Some(selection) if selection.contains(&function) => selection.iter().copied().collect(),
+            _ => vec![function],
+        };
+
+        if self.pinned.contains(&function) {
+            self.pinned.retain(|pinned| !functions.contains(pinned));
+            return;
+        }
+
+        functions.sort_by_key(|&function| {
+            Reverse(
+                functions_data.raw_data[function]
+                    .function_property
+                    .retained_size_bytes,
+            )
+        });
+        for function in functions {
+            if !self.pinned.contains(&function) {
+                self.pinned.push(function);
+            }
+        }
+    }
+
+    /// Functions calling the selected one and called by it, biggest first.
+    /// Clicking one selects it.
+    fn show_calls(&mut self, ui: &mut egui::Ui, functions_data: &DataProviderTwiggy) {
+        let Some(function) = self.selected_row else {
+            return;
+        };
+
+        let call_graph = &functions_data.call_graph;
+        let mut clicked = None;
+        ui.columns(2, |columns| {
+            let lists = [
+                ("Callers", call_graph.callers(function)),
+                ("Callees", call_graph.callees(function)),
+            ];
+            for (ui, (title, functions)) in columns.iter_mut().zip(lists) {
+                let mut functions = functions.to_vec();
+                functions.sort_by_key(|&other| {
+                    Reverse(
+                        functions_data.raw_data[other as usize]
+                            .function_property
+                            .shallow_size_bytes,
+                    )
+                });
+
+                ui.strong(format!("{} ({})", title, functions.len()));
+                egui::ScrollArea::vertical()
+                    .id_salt(title)
+                    .auto_shrink(false)
+                    .show(ui, |ui| {
+                        for other in functions {
+                            let property =
+                                &functions_data.raw_data[other as usize].function_property;
+                            ui.horizontal(|ui| {
+                                ui.monospace(format!("{:>8}", property.shallow_size_bytes));
+                                let name = egui::RichText::new(
+                                    functions_data.function_name(other as usize),
+                                )
+                                .color(ui.visuals().hyperlink_color);
+                                let response = ui
+                                    .add(egui::Label::new(name).truncate().sense(Sense::click()))
+                                    .on_hover_cursor(egui::CursorIcon::PointingHand);
+                                if response.clicked() {
+                                    clicked = Some(other as usize);
+                                }
+                            });
+                        }
+                    });
+            }
+        });
+
+        if let Some(function) = clicked {
+            self.multi_selection.clear();
+            self.selection_anchor = Some((function, false));
+            self.selected_row = Some(function);
+        }
+    }
+
+    /// What removing the selected functions would save.
+    fn show_selection_total(&self, ui: &mut egui::Ui, functions_data: &DataProviderTwiggy) {
+        let Some(selection) = self.multi_selection() else {
+            return;
+        };
+
+        let (size_bytes, size_percent) =
+            selection
+                .iter()
+                .fold((0u64, 0.0), |(size_bytes, size_percent), &function| {
+                    let property = &functions_data.raw_data[function].function_property;
+                    (
+                        size_bytes + property.retained_size_bytes as u64,
+                        size_percent + property.retained_size_percent,
+                    )
+                });
+        ui.label(format!(
+            "Selection: {} functions, {} bytes, {:.4?}%",
+            selection.len(),
+            size_bytes,
+            size_percent,
+        ))
+        .on_hover_text(
+            "Sum of the retained sizes, functions retained by other selected ones count twice",
+        );
+    }
+
+    /// Number and size of the filtered functions with each tag.
+    fn show_tag_totals(
+        &mut self,
+        ui: &mut egui::Ui,
+        functions_data: &DataProviderTwiggy,
+        tags: &Tags,
+    ) {
+        if self
+            .tag_totals
+            .as_ref()
+            .is_none_or(|(generation, _)| *generation != tags.generation())
+        {
+            let totals = tags.totals(functions_data, &functions_data.top_view_items_filtered);
+            self.tag_totals = Some((tags.generation(), totals));
+        }
+        let (_, totals) = self.tag_totals.as_ref().unwrap();
+
+        ui.horizontal_wrapped(|ui| {
+            for (tag, total) in tags.tags().iter().zip(totals) {
+                if total.function_count == 0 {
+                    continue;
+                }
+                tag_chip(ui, tag);
+                ui.label(format!(
+                    "{} functions, {:.2} MB",
+                    total.function_count,
+                    total.size_bytes as f32 / (1024.0 * 1024.0),
+                ));
+            }
+        });
+    }
+
+    /// Whether the tops ordered by size are shown smallest first.
+    fn is_size_order_reversed(&self) -> bool {
+        self.reversed_size_bytes && !self.is_ordered_by_score() && self.sort_keys.is_empty()
+    }
+
+    /// The tops as shown, the shown columns of the pinned functions then of
+    /// the other filtered ones, with a header row.
+    fn tops_table(&self, functions_data: &DataProviderTwiggy, format: TableFormat) -> String {
+        let functions = self.shown_rows(&functions_data.top_view_items_filtered);
+
+        let mut table = format.row(&tops_header_fields(self.columns));
+        table.push('\n');
+        for function in functions {
+            let name = functions_data.function_name_in(
+                self.columns.name_style.unwrap_or(functions_data.name_style),
+                function,
+            );
+            let mangled_name = functions_data
+                .wasm_data
+                .functions_section
+                .function_original_names[function];
+            table.push_str(&format.row(&tops_row_fields(
+                &functions_data.raw_data[function].function_property,
+                name,
+                mangled_name,
+                self.columns,
+            )));
+            table.push('\n');
+        }
+        table
+    }
+
+    /// Whether the rows are ordered by match score rather than size.
+    fn is_ordered_by_score(&self) -> bool {
+        self.filter_mode == FilterMode::Fuzzy && !self.filter_text.is_empty()
+    }
+
+    /// Clicking a column header sorts by it, or flips the order if it's
+    /// already the main key. Shift-clicking adds it as a tiebreaker.
+    fn click_sort_column(&mut self, column: SortColumn, add_tiebreaker: bool) {
+        let position = self.sort_keys.iter().position(|key| key.column == column);
+        let new_key = SortKey {
+            column,
+            descending: column.is_descending_by_default(),
+        };
+
+        match position {
+            Some(position) if add_tiebreaker || position == 0 => {
+                self.sort_keys[position].descending ^= true;
+            }
+            _ if add_tiebreaker => {
+                self.sort_keys.truncate(MAX_SORT_KEYS - 1);
+                self.sort_keys.push(new_key);
+            }
+            _ => self.sort_keys = vec![new_key],
+        }
+
+        self.tops_sorted = false;
+    }
+
+    /// Orders the tops, and their monomorphization groups, by `sort_keys`.
+    /// A group is compared by its size and name, and by its biggest
+    /// instantiation for the other columns.
+    fn sort_tops(&mut self, functions_data: &mut DataProviderTwiggy) {
+        let DataProviderTwiggy {
+            raw_data,
+            top_view_items_filtered,
+            top_view_groups,
+            ..
+        } = functions_data;
+
+        top_view_items_filtered.sort_by(|&a, &b| {
+            let a = &raw_data[a].function_property;
+            let b = &raw_data[b].function_property;
+            self.sort_keys
+                .iter()
+                .map(|key| key.apply(key.column.compare(a, b)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        top_view_groups.sort_by(|a, b| {
+            self.sort_keys
+                .iter()
+                .map(|key| {
+                    key.apply(match key.column {
+                        SortColumn::Size => a.size_bytes.cmp(&b.size_bytes),
+                        SortColumn::Name | SortColumn::MonomorphizationOf => a.name.cmp(b.name),
+                        column => column.compare(
+                            &raw_data[a.largest_instance].function_property,
+                            &raw_data[b.largest_instance].function_property,
+                        ),
+                    })
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        self.tops_sorted = true;
+    }
+
+    fn show_tops(
+        &mut self,
+        ui: &mut egui::Ui,
+        filtered_view: &mut DataProviderTwiggy,
+        budget_violations: &[BudgetViolation],
+        bookmarks: &mut Bookmarks,
+        notes: &mut Notes,
+        tags: &mut Tags,
+    ) {
+        let ordered_by_score = self.is_ordered_by_score();
+        if !ordered_by_score && !self.tops_sorted && !self.sort_keys.is_empty() {
+            self.sort_tops(filtered_view);
+        }
+
+        let columns = self.columns;
+        let rows = self.shown_rows(&filtered_view.top_view_items_filtered);
+        let pinned_count = self.pinned.len();
         egui::ScrollArea::vertical().show(ui, |ui| {
             let old_selectable_labels = ui.style().interaction.selectable_labels;
             ui.style_mut().interaction.selectable_labels = false;
@@ -110,27 +918,47 @@ impl FunctionsExplorer {
                     .striped(true)
                     .resizable(true)
                     .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                    .column(egui_extras::Column::auto())
-                    .column(egui_extras::Column::auto())
-                    .column(egui_extras::Column::auto())
-                    .column(egui_extras::Column::auto())
-                    .column(egui_extras::Column::auto())
-                    .column(egui_extras::Column::auto())
+                    .columns(egui_extras::Column::auto(), columns.count())
                     .min_scrolled_height(0.0)
                     .max_scroll_height(available_height);
 
                 // Prepare it so it is clickable and we see when we hover rows.
                 table = table.sense(egui::Sense::click());
 
+                let mut clicked_sort = None;
+                let mut toggled_pin = None;
+                let mut unpin_all = false;
+                let mut copy_table = false;
+                let mut export_table = false;
+                let mut sort_column_header =
+                    |ui: &mut egui::Ui, column: SortColumn, label: &str| {
+                        let response =
+                            sort_header(ui, &self.sort_keys, column, label, !ordered_by_score);
+                        if response.clicked() {
+                            let add_tiebreaker = ui.input(|input| input.modifiers.shift);
+                            clicked_sort = Some((column, add_tiebreaker));
+                        }
+                        response
+                    };
+
                 table
                     .header(20.0, |mut header| {
                         header.col(|ui| {
                             egui::Sides::new().show(
                                 ui,
                                 |ui| {
-                                    ui.strong("Size (bytes)");
+                                    sort_column_header(ui, SortColumn::Size, "Size (bytes)");
                                 },
                                 |ui| {
+                                    if ordered_by_score {
+                                        ui.label("by match").on_hover_text(
+                                            "Fuzzy search results are ordered by match score",
+                                        );
+                                        return;
+                                    }
+                                    if !self.sort_keys.is_empty() {
+                                        return;
+                                    }
                                     self.reversed_size_bytes ^= ui
                                         .button(if self.reversed_size_bytes {
                                             "⬇"
@@ -142,38 +970,85 @@ impl FunctionsExplorer {
                             );
                         });
                         header.col(|ui| {
-                            ui.strong("Shallow Size (bytes)");
+                            sort_column_header(ui, SortColumn::ShallowSize, "Shallow Size (bytes)");
                         });
+                        if columns.compressed_size {
+                            header.col(|ui| {
+                                sort_column_header(
+                                    ui,
+                                    SortColumn::CompressedSize,
+                                    "Compressed (bytes)",
+                                )
+                                .on_hover_text("Estimated contribution to the gzip size");
+                            });
+                        }
                         header.col(|ui| {
                             ui.strong("Size (%)");
                         });
                         header.col(|ui| {
                             ui.strong("Shallow Size (%)");
                         });
+                        if columns.crate_name {
+                            header.col(|ui| {
+                                sort_column_header(ui, SortColumn::Crate, "Crate");
+                            });
+                        }
                         header.col(|ui| {
-                            ui.strong("Name");
-                        });
-                        header.col(|ui| {
-                            ui.strong("Monomorphization of");
+                            sort_column_header(ui, SortColumn::Name, "Name");
                         });
-                    })
-                    .body(|body| {
-                        body.rows(20.0, table_rows_count, |mut row| {
-                            let row_index = if self.reversed_size_bytes {
-                                table_rows_count - 1 - row.index()
-                            } else {
-                                row.index()
-                            };
+                        if columns.mangled_name {
+                            header.col(|ui| {
+                                ui.strong("Mangled name");
+                            });
+                        }
+                        if columns.monomorphization_of {
+                            header.col(|ui| {
+                                sort_column_header(
+                                    ui,
+                                    SortColumn::MonomorphizationOf,
+                                    "Monomorphization of",
+                                );
+                            });
+                        }
 
-                            let symbol_index = filtered_view.top_view_items_filtered[row_index];
+                        header.response().context_menu(|ui| {
+                            self.columns.show_menu(ui);
 
-                            if let Some(selected_row) = self.selected_row {
-                                row.set_selected(symbol_index == selected_row);
+                            ui.separator();
+                            if ui
+                                .button("Copy table as TSV")
+                                .on_hover_text(
+                                    "The shown rows and columns, to paste in spreadsheets",
+                                )
+                                .clicked()
+                            {
+                                copy_table = true;
+                                ui.close_menu();
                             }
+                            if ui.button("Export table...").clicked() {
+                                export_table = true;
+                                ui.close_menu();
+                            }
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, rows.len(), |mut row| {
+                            let pinned = row.index() < pinned_count;
+                            let symbol_index = rows[row.index()];
+
+                            row.set_selected(self.is_row_selected(symbol_index));
 
                             let filtered_item =
                                 &filtered_view.raw_data[symbol_index].function_property;
 
+                            // Highlight functions of crates that are over their size budget.
+                            let over_budget = filtered_item.crate_name.is_some_and(|crate_name| {
+                                budget_violations
+                                    .iter()
+                                    .any(|violation| violation.is_crate(crate_name))
+                            });
+
                             row.col(|ui| {
                                 ui.label_memory(filtered_item.retained_size_bytes);
                             });
@@ -182,6 +1057,12 @@ impl FunctionsExplorer {
                                 ui.label_memory(filtered_item.shallow_size_bytes);
                             });
 
+                            if columns.compressed_size {
+                                row.col(|ui| {
+                                    ui.label_memory(filtered_item.compressed_size_bytes);
+                                });
+                            }
+
                             row.col(|ui| {
                                 ui.label_percentage(filtered_item.retained_size_percent);
                             });
@@ -190,152 +1071,739 @@ impl FunctionsExplorer {
                                 ui.label_percentage(filtered_item.shallow_size_percent);
                             });
 
+                            if columns.crate_name {
+                                row.col(|ui| {
+                                    ui.label(filtered_item.crate_name.unwrap_or(""));
+                                });
+                            }
+
+                            let mangled_name = filtered_view
+                                .wasm_data
+                                .functions_section
+                                .function_original_names[symbol_index];
+                            let bookmarked =
+                                bookmarks.is_function_bookmarked(filtered_item.raw_name);
+                            let name = filtered_view.function_name_in(
+                                columns.name_style.unwrap_or(filtered_view.name_style),
+                                symbol_index,
+                            );
+                            row.col(|ui| {
+                                if pinned {
+                                    ui.label("📌");
+                                }
+                                if bookmarked {
+                                    ui.label("★");
+                                }
+                                if let Some(note) = notes.get(mangled_name) {
+                                    ui.label("📝").on_hover_text(note);
+                                }
+                                for tag in tags.tags_of(mangled_name) {
+                                    tag_chip(ui, tag);
+                                }
+                                if over_budget {
+                                    ui.colored_label(ui.visuals().error_fg_color, name);
+                                } else {
+                                    ui.label(name);
+                                }
+                            });
+
+                            if columns.mangled_name {
+                                row.col(|ui| {
+                                    ui.label(mangled_name);
+                                });
+                            }
+
+                            if columns.monomorphization_of {
+                                row.col(|ui| {
+                                    ui.label(filtered_item.monomorphization_of.unwrap_or(""));
+                                });
+                            }
+
+                            let response = row.response();
+                            if response.clicked() {
+                                let modifiers = response.ctx.input(|input| input.modifiers);
+                                self.click_row(&rows, row.index(), modifiers);
+                            }
+
+                            response.context_menu(|ui| {
+                                copy_menu(
+                                    ui,
+                                    filtered_item.raw_name,
+                                    Some(mangled_name),
+                                    filtered_item.retained_size_bytes,
+                                    || {
+                                        TableFormat::Tsv.row(&tops_row_fields(
+                                            filtered_item,
+                                            name,
+                                            mangled_name,
+                                            columns,
+                                        ))
+                                    },
+                                );
+                                ui.separator();
+                                if ui
+                                    .button(if self.pinned.contains(&symbol_index) {
+                                        "Unpin"
+                                    } else {
+                                        "Pin to top"
+                                    })
+                                    .on_hover_text(
+                                        "Pinned rows stay at the top when filtering and sorting",
+                                    )
+                                    .clicked()
+                                {
+                                    toggled_pin = Some(symbol_index);
+                                    ui.close_menu();
+                                }
+                                if pinned_count > 0 && ui.button("Unpin all").clicked() {
+                                    unpin_all = true;
+                                    ui.close_menu();
+                                }
+                                if bookmark_button(ui, bookmarked) {
+                                    bookmarks.toggle_function(filtered_item.raw_name);
+                                }
+                                tags_menu(ui, tags, mangled_name, &mut self.new_tag_name);
+                                note_editor(ui, notes, mangled_name, filtered_item.raw_name);
+                            });
+                        });
+                    });
+
+                if let Some((column, add_tiebreaker)) = clicked_sort {
+                    self.click_sort_column(column, add_tiebreaker);
+                }
+                if let Some(function) = toggled_pin {
+                    self.toggle_pin(function, filtered_view);
+                }
+                if unpin_all {
+                    self.pinned.clear();
+                }
+                if copy_table {
+                    ui.ctx()
+                        .copy_text(self.tops_table(filtered_view, TableFormat::Tsv));
+                }
+                if export_table {
+                    self.table_file_dialog = FileDialog::new().default_file_name("tops.csv");
+                    self.table_file_dialog.save_file();
+                }
+            });
+            ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+        });
+    }
+
+    fn show_tops_grouped(&mut self, ui: &mut egui::Ui, filtered_view: &mut DataProviderTwiggy) {
+        // The groups are ordered like the tops, by their best match in a
+        // fuzzy search.
+        let ordered_by_score = self.is_ordered_by_score();
+        if !ordered_by_score && !self.tops_sorted && !self.sort_keys.is_empty() {
+            self.sort_tops(filtered_view);
+        }
+        let reversed = self.is_size_order_reversed();
+
+        let table_rows_count = filtered_view.top_view_groups.len();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let old_selectable_labels = ui.style().interaction.selectable_labels;
+            ui.style_mut().interaction.selectable_labels = false;
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                let available_height = ui.available_height();
+                let table = egui_extras::TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .min_scrolled_height(0.0)
+                    .max_scroll_height(available_height)
+                    .sense(egui::Sense::click());
+
+                let mut clicked_sort = None;
+                let mut sort_column_header =
+                    |ui: &mut egui::Ui, column: SortColumn, label: &str| {
+                        let response =
+                            sort_header(ui, &self.sort_keys, column, label, !ordered_by_score);
+                        if response.clicked() {
+                            let add_tiebreaker = ui.input(|input| input.modifiers.shift);
+                            clicked_sort = Some((column, add_tiebreaker));
+                        }
+                    };
+
+                table
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            egui::Sides::new().show(
+                                ui,
+                                |ui| {
+                                    sort_column_header(ui, SortColumn::Size, "Size (bytes)");
+                                },
+                                |ui| {
+                                    if ordered_by_score {
+                                        ui.label("by match").on_hover_text(
+                                            "Fuzzy search results are ordered by match score",
+                                        );
+                                        return;
+                                    }
+                                    if !self.sort_keys.is_empty() {
+                                        return;
+                                    }
+                                    self.reversed_size_bytes ^= ui
+                                        .button(if self.reversed_size_bytes {
+                                            "⬇"
+                                        } else {
+                                            "⬆"
+                                        })
+                                        .clicked();
+                                },
+                            );
+                        });
+                        header.col(|ui| {
+                            ui.strong("Instances");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Size (%)");
+                        });
+                        header.col(|ui| {
+                            sort_column_header(ui, SortColumn::Name, "Name");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, table_rows_count, |mut row| {
+                            let row_index = if reversed {
+                                table_rows_count - 1 - row.index()
+                            } else {
+                                row.index()
+                            };
+
+                            let group = &filtered_view.top_view_groups[row_index];
+
+                            if let Some(selected_row) = self.selected_row {
+                                row.set_selected(group.largest_instance == selected_row);
+                            }
+
+                            row.col(|ui| {
+                                ui.label_memory(group.size_bytes);
+                            });
+
                             row.col(|ui| {
-                                ui.label(filtered_item.raw_name);
+                                ui.label(group.instance_count.to_string());
                             });
 
                             row.col(|ui| {
-                                ui.label(filtered_item.monomorphization_of.unwrap_or(""));
+                                ui.label_percentage(group.size_percent);
                             });
 
+                            row.col(|ui| {
+                                ui.label(group.name);
+                            });
+
+                            // Selecting a group shows its biggest instantiation.
                             if row.response().clicked() {
-                                self.selected_row = Some(symbol_index);
+                                self.selected_row = Some(group.largest_instance);
                             }
                         });
                     });
+
+                if let Some((column, add_tiebreaker)) = clicked_sort {
+                    self.click_sort_column(column, add_tiebreaker);
+                }
             });
             ui.style_mut().interaction.selectable_labels = old_selectable_labels;
         });
     }
 
-    fn show_dominators(&mut self, ui: &mut egui::Ui, dominator_view: &mut DataProviderTwiggy) {
+    fn show_dominators(
+        &mut self,
+        ui: &mut egui::Ui,
+        dominator_view: &mut DataProviderTwiggy,
+        bookmarks: &mut Bookmarks,
+        notes: &Notes,
+    ) {
         let state = &mut dominator_view.dominator_state;
 
-        TreeView.body(ui, state, 20.0, |ui, tree_item| {
-            let dw_node = tree_item.item;
-            let item_ui_data = tree_item.item_state;
+        ui.horizontal(|ui| {
+            if ui.button("Expand all").clicked() {
+                state.open_levels(None);
+            }
+            if ui.button("Collapse all").clicked() {
+                state.open_levels(Some(0));
+            }
+            ui.separator();
+            if ui.button("Expand to depth").clicked() {
+                state.open_levels(Some(self.expand_depth));
+            }
+            ui.add(egui::DragValue::new(&mut self.expand_depth).range(0..=32))
+                .on_hover_text("Levels opened under the top level");
+        });
+
+        if state.selected_index < state.tree.len()
+            && let Some(index) = breadcrumbs(ui, &state.tree, state.selected_index)
+        {
+            state.select(index);
+        }
 
-            let label = match dw_node.ty {
-                DwNodeType::Struct => {
-                    format!("struct {}", dw_node.name.as_str())
-                }
-                DwNodeType::Impl => {
-                    format!("impl {} - {}", dw_node.name.as_str(), item_ui_data.size)
-                }
-                DwNodeType::FunctionInlinedInstance => {
-                    format!("[inlined] {}", dw_node.name.as_str())
-                }
-                _ => {
-                    format!("{} - {}", dw_node.name.as_str(), item_ui_data.size,)
+        let titles = DominatorsSort::COLUMNS.map(|sort| {
+            if sort == self.dominators_sort {
+                format!("{} ⬇", sort.title())
+            } else {
+                sort.title().to_owned()
+            }
+        });
+        let columns = titles.each_ref().map(|title| TreeColumn {
+            title,
+            width: 120.0,
+        });
+
+        let add_cell =
+            |ui: &mut egui::Ui, column, dw_node: &DwNode, item_ui_data: &FunctionItemState| {
+                match DominatorsSort::COLUMNS[column] {
+                    DominatorsSort::Shallow => {
+                        if matches!(
+                            dw_node.ty,
+                            DwNodeType::FunctionInstance | DwNodeType::FunctionInlinedInstance
+                        ) {
+                            ui.label(item_ui_data.shallow_size.to_string());
+                        }
+                    }
+                    DominatorsSort::Retained => {
+                        ui.label(item_ui_data.size.to_string());
+                    }
                 }
             };
 
-            let mut retained_size_percent = 0.0;
-            if dominator_view.total_size > 0 {
-                retained_size_percent =
-                    100.0 * (item_ui_data.size as f32 / dominator_view.total_size as f32);
-            }
+        let clicked_column = TreeView.body(
+            ui,
+            state,
+            20.0,
+            &columns,
+            |ui, tree_item| {
+                let dw_node = tree_item.item;
+                let item_ui_data = tree_item.item_state;
 
-            let available = ui.available_rect_before_wrap();
+                let note = if !notes.is_empty() && dw_node.ty == DwNodeType::FunctionInstance {
+                    let mut path = String::new();
+                    node_path(tree_item.tree, tree_item.index, &mut path);
+                    notes.get_by_path(&path)
+                } else {
+                    None
+                };
 
-            const PERCENTAGE_WIDTH: f32 = 50.0;
-            const PERCENTAGE_BAR_HEIGHT: f32 = 2.0;
+                let label = match dw_node.ty {
+                    DwNodeType::Struct => {
+                        format!("struct {}", dw_node.name.as_str())
+                    }
+                    DwNodeType::Impl => {
+                        format!("impl {}", dw_node.name.as_str())
+                    }
+                    DwNodeType::FunctionInlinedInstance => {
+                        format!("[inlined] {}", dw_node.name.as_str())
+                    }
+                    _ => dw_node.name.as_str().to_owned(),
+                };
+                let label = match note {
+                    Some(note) => {
+                        tree_item.response.clone().on_hover_text(note);
+                        format!("📝 {}", label)
+                    }
+                    None => label,
+                };
 
-            let percentage_text_pos = available.min;
-            let percentage_text: WidgetText = format!("{:.2}%", retained_size_percent).into();
-            let percentage_galley = percentage_text.into_galley(
-                ui,
-                Some(TextWrapMode::Extend),
-                PERCENTAGE_WIDTH,
-                TextStyle::Button,
-            );
+                let mut retained_size_percent = 0.0;
+                if dominator_view.total_size > 0 {
+                    retained_size_percent =
+                        100.0 * (item_ui_data.size as f32 / dominator_view.total_size as f32);
+                }
 
-            let text_pos = available.min + vec2(PERCENTAGE_WIDTH, 0.0);
-            let wrap_width = available.right() - text_pos.x;
+                let available = ui.available_rect_before_wrap();
 
-            // TODO: build galley from scratch?
-            let text: WidgetText = label.as_str().into();
-            let symbol_galley = text.into_galley(
-                ui,
-                Some(TextWrapMode::Extend),
-                wrap_width,
-                TextStyle::Button,
-            );
+                const PERCENTAGE_WIDTH: f32 = 50.0;
+                const PERCENTAGE_BAR_HEIGHT: f32 = 2.0;
 
-            let button_padding = ui.spacing().button_padding;
-            let text_max_x = text_pos.x + symbol_galley.size().x;
-            let desired_width = text_max_x + button_padding.x - available.left();
-            let desired_size = vec2(
-                desired_width,
-                symbol_galley.size().y + 2.0 * button_padding.y + 2.0 * PERCENTAGE_BAR_HEIGHT,
-            );
+                let percentage_text_pos = available.min;
+                let percentage_text: WidgetText = format!("{:.2}%", retained_size_percent).into();
+                let percentage_galley = percentage_text.into_galley(
+                    ui,
+                    Some(TextWrapMode::Extend),
+                    PERCENTAGE_WIDTH,
+                    TextStyle::Button,
+                );
 
-            let (_, rect) = ui.allocate_space(desired_size);
+                let text_pos = available.min + vec2(PERCENTAGE_WIDTH, 0.0);
+                let wrap_width = available.right() - text_pos.x;
 
-            // Center text element on the vertical axis
-            let percentage_text_pos = pos2(
-                percentage_text_pos.x,
-                available.center().y - percentage_galley.size().y / 2.0,
-            );
-            let symbol_text_pos = pos2(
-                text_pos.x,
-                available.center().y - symbol_galley.size().y / 2.0,
-            );
+                // TODO: build galley from scratch?
+                let text: WidgetText = label.as_str().into();
+                let symbol_galley = text.into_galley(
+                    ui,
+                    Some(TextWrapMode::Extend),
+                    wrap_width,
+                    TextStyle::Button,
+                );
 
-            let percentage_response = ui.interact(
-                Rect {
-                    min: percentage_text_pos,
-                    max: percentage_text_pos + percentage_galley.size(),
-                },
-                Id::new(label),
-                Sense::hover(),
-            );
+                let button_padding = ui.spacing().button_padding;
+                let text_max_x = text_pos.x + symbol_galley.size().x;
+                let desired_width = text_max_x + button_padding.x - available.left();
+                let desired_size = vec2(
+                    desired_width,
+                    symbol_galley.size().y + 2.0 * button_padding.y + 2.0 * PERCENTAGE_BAR_HEIGHT,
+                );
 
-            let visuals = ui
-                .style()
-                .interact_selectable(&tree_item.response, tree_item.selected);
-
-            // Percentage label
-            ui.painter()
-                .galley(percentage_text_pos, percentage_galley, visuals.text_color());
-            ui.painter().add(Shape::Rect(RectShape::filled(
-                Rect {
-                    min: pos2(
-                        percentage_text_pos.x,
-                        rect.min.y + rect.height() - PERCENTAGE_BAR_HEIGHT,
-                    ),
-                    max: pos2(
-                        percentage_text_pos.x + (retained_size_percent / 100.0) * PERCENTAGE_WIDTH,
-                        rect.min.y + rect.height(),
-                    ),
-                },
-                0.0,
-                Color32::GREEN,
-            )));
+                let (_, rect) = ui.allocate_space(desired_size);
 
-            // Percentage tooltip
-            if percentage_response.hovered() {
-                let scratch = scratch_arena(&[]);
-                let mut buffer: Array<'_, u8> = Array::new(&scratch, 1024);
-
-                // TODO: (bruno) probably should just use auto-layout here
-                use std::fmt::Write;
-                _ = writeln!(
-                    &mut buffer,
-                    "Size: {:5.2}(MB)",
-                    item_ui_data.size as f32 / (1024.0 * 1024.0)
+                // Center text element on the vertical axis
+                let percentage_text_pos = pos2(
+                    percentage_text_pos.x,
+                    available.center().y - percentage_galley.size().y / 2.0,
                 );
+                let symbol_text_pos = pos2(
+                    text_pos.x,
+                    available.center().y - symbol_galley.size().y / 2.0,
+                );
+
+                let percentage_response = ui.interact(
+                    Rect {
+                        min: percentage_text_pos,
+                        max: percentage_text_pos + percentage_galley.size(),
+                    },
+                    Id::new(label),
+                    Sense::hover(),
+                );
+
+                let visuals = ui
+                    .style()
+                    .interact_selectable(&tree_item.response, tree_item.selected);
+
+                // Percentage label
+                ui.painter()
+                    .galley(percentage_text_pos, percentage_galley, visuals.text_color());
+                ui.painter().add(Shape::Rect(RectShape::filled(
+                    Rect {
+                        min: pos2(
+                            percentage_text_pos.x,
+                            rect.min.y + rect.height() - PERCENTAGE_BAR_HEIGHT,
+                        ),
+                        max: pos2(
+                            percentage_text_pos.x
+                                + (retained_size_percent / 100.0) * PERCENTAGE_WIDTH,
+                            rect.min.y + rect.height(),
+                        ),
+                    },
+                    0.0,
+                    Color32::GREEN,
+                )));
 
-                percentage_response.show_tooltip_ui(|ui| {
-                    ui.monospace(std::str::from_utf8(&buffer).unwrap());
+                // Percentage tooltip
+                if percentage_response.hovered() {
+                    let scratch = scratch_arena(&[]);
+                    let mut buffer: Array<'_, u8> = Array::new(&scratch, 1024);
+
+                    // TODO: (bruno) probably should just use auto-layout here
+                    use std::fmt::Write;
+                    _ = writeln!(
+                        &mut buffer,
+                        "Size: {:5.2}(MB)",
+                        item_ui_data.size as f32 / (1024.0 * 1024.0)
+                    );
+
+                    percentage_response.show_tooltip_ui(|ui| {
+                        ui.monospace(std::str::from_utf8(&buffer).unwrap());
+                    });
+                }
+
+                // Symbol label
+                ui.painter()
+                    .galley(symbol_text_pos, symbol_galley, visuals.text_color());
+
+                tree_item.response.context_menu(|ui| {
+                    let mut path = String::new();
+                    node_path(tree_item.tree, tree_item.index, &mut path);
+                    copy_menu(ui, &path, None, item_ui_data.size, || {
+                        format!(
+                            "{}\t{:.2}\t{}",
+                            item_ui_data.size, retained_size_percent, path
+                        )
+                    });
+                    ui.separator();
+                    if bookmark_button(ui, bookmarks.is_path_bookmarked(&path)) {
+                        bookmarks.toggle_path(&path);
+                    }
                 });
+            },
+            add_cell,
+        );
+
+        if let Some(column) = clicked_column {
+            self.dominators_sort = DominatorsSort::COLUMNS[column];
+            state.sort_fn = self.dominators_sort.sort_fn();
+            state.recompute_indices();
+        }
+    }
+}
+
+/// Path of the selected node, `root > tokio > runtime > task`, to go back up
+/// deep selections. Returns the clicked ancestor.
+fn breadcrumbs(ui: &mut egui::Ui, tree: &Tree<DwNode>, selected: usize) -> Option<usize> {
+    let mut path = std::vec::Vec::new();
+    let mut idx = selected;
+    while let Some(parent_idx) = tree.get_parent_index(idx) {
+        path.push(idx);
+        idx = parent_idx;
+    }
+
+    let mut clicked = None;
+    egui::ScrollArea::horizontal()
+        .id_salt("breadcrumbs")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.weak("root");
+                for &idx in path.iter().rev() {
+                    ui.weak(">");
+                    if ui
+                        .selectable_label(idx == selected, tree[idx].value.name.as_str())
+                        .clicked()
+                    {
+                        clicked = Some(idx);
+                    }
+                }
+            });
+        });
+    clicked
+}
+
+/// Context menu of a row copying its fields, the dominators not having the
+/// mangled names.
+fn copy_menu(
+    ui: &mut egui::Ui,
+    demangled_name: &str,
+    mangled_name: Option<&str>,
+    size_bytes: u32,
+    row_tsv: impl FnOnce() -> String,
+) {
+    let mut copied = None;
+    if ui.button("Copy demangled name").clicked() {
+        copied = Some(demangled_name.to_owned());
+    }
+    if ui
+        .add_enabled(
+            mangled_name.is_some(),
+            egui::Button::new("Copy mangled name"),
+        )
+        .on_disabled_hover_text("The debug info only has the demangled names")
+        .clicked()
+    {
+        copied = mangled_name.map(str::to_owned);
+    }
+    if ui.button("Copy size").clicked() {
+        copied = Some(size_bytes.to_string());
+    }
+    if ui.button("Copy row as TSV").clicked() {
+        copied = Some(row_tsv());
+    }
+
+    if let Some(text) = copied {
+        ui.ctx().copy_text(text);
+        ui.close_menu();
+    }
+}
+
+fn tag_chip(ui: &mut egui::Ui, tag: &Tag) {
+    ui.label(
+        egui::RichText::new(&tag.name)
+            .small()
+            .color(Color32::WHITE)
+            .background_color(tag.color32()),
+    );
+}
+
+/// Returns whether the tag the tops are filtered by changed.
+fn tag_filter_selector(ui: &mut egui::Ui, tag_filter: &mut Option<String>, tags: &[Tag]) -> bool {
+    let mut changed = false;
+    ComboBox::from_label("Tag")
+        .selected_text(tag_filter.as_deref().unwrap_or("Any"))
+        .show_ui(ui, |ui| {
+            changed |= ui.selectable_value(tag_filter, None, "Any").changed();
+            for tag in tags {
+                changed |= ui
+                    .selectable_value(
+                        tag_filter,
+                        Some(tag.name.clone()),
+                        egui::RichText::new(&tag.name).color(tag.color32()),
+                    )
+                    .changed();
+            }
+        })
+        .response
+        .on_hover_text("The dominators aren't filtered by tag");
+    changed
+}
+
+/// Submenu tagging a function, creating and deleting the tags.
+fn tags_menu(ui: &mut egui::Ui, tags: &mut Tags, mangled_name: &str, new_tag_name: &mut String) {
+    // Functions without a name can't be found again in the next session.
+    if mangled_name.is_empty() {
+        return;
+    }
+
+    ui.menu_button("Tags", |ui| {
+        let mut toggled = None;
+        let mut recolored = None;
+        let mut deleted = None;
+        for tag in tags.tags() {
+            ui.horizontal(|ui| {
+                let mut color = tag.color;
+                if ui.color_edit_button_srgb(&mut color).changed() {
+                    recolored = Some((tag.name.clone(), color));
+                }
+                let mut tagged = tags.has_tag(mangled_name, &tag.name);
+                if ui.checkbox(&mut tagged, &tag.name).changed() {
+                    toggled = Some(tag.name.clone());
+                }
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text("Delete the tag from all the functions")
+                    .clicked()
+                {
+                    deleted = Some(tag.name.clone());
+                }
+            });
+        }
+
+        if let Some(tag) = toggled {
+            tags.toggle(mangled_name, &tag);
+        }
+        if let Some((tag, color)) = recolored {
+            tags.set_color(&tag, color);
+        }
+        if let Some(tag) = deleted {
+            tags.remove_tag(&tag);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(new_tag_name)
+                    .hint_text("New tag")
+                    .desired_width(120.0),
+            );
+            let submitted =
+                response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            if (ui.button("Add").clicked() || submitted) && tags.add_tag(new_tag_name) {
+                tags.toggle(mangled_name, new_tag_name.trim());
+                new_tag_name.clear();
             }
+        });
+    });
+}
+
+/// Text box editing the note of a function, which is saved with the session.
+fn note_editor(ui: &mut egui::Ui, notes: &mut Notes, mangled_name: &str, demangled_name: &str) {
+    // Functions without a name can't be found again in the next session.
+    if mangled_name.is_empty() {
+        return;
+    }
 
-            // Symbol label
-            ui.painter()
-                .galley(symbol_text_pos, symbol_galley, visuals.text_color());
+    ui.separator();
+    ui.label("Note");
+    let mut text = notes.get(mangled_name).unwrap_or_default().to_owned();
+    if ui
+        .add(egui::TextEdit::multiline(&mut text).desired_rows(3))
+        .changed()
+    {
+        notes.set(mangled_name, demangled_name, text);
+    }
+}
+
+/// Returns whether the bookmark was toggled.
+fn bookmark_button(ui: &mut egui::Ui, bookmarked: bool) -> bool {
+    let clicked = ui
+        .button(if bookmarked {
+            "Remove bookmark"
+        } else {
+            "Bookmark"
+        })
+        .clicked();
+    if clicked {
+        ui.close_menu();
+    }
+    clicked
+}
+
+/// Titles of the shown columns of the tops.
+fn tops_header_fields(columns: TopsColumns) -> Vec<String> {
+    let mut fields = vec!["Size (bytes)", "Shallow Size (bytes)"];
+    if columns.compressed_size {
+        fields.push("Compressed (bytes)");
+    }
+    fields.extend(["Size (%)", "Shallow Size (%)"]);
+    if columns.crate_name {
+        fields.push("Crate");
+    }
+    fields.push("Name");
+    if columns.mangled_name {
+        fields.push("Mangled name");
+    }
+    if columns.monomorphization_of {
+        fields.push("Monomorphization of");
+    }
+    fields.into_iter().map(str::to_owned).collect()
+}
+
+/// The shown columns of a row of the tops, `name` in the style of the name
+/// column.
+fn tops_row_fields(
+    property: &FunctionProperty,
+    name: &str,
+    mangled_name: &str,
+    columns: TopsColumns,
+) -> Vec<String> {
+    let mut fields = vec![
+        property.retained_size_bytes.to_string(),
+        property.shallow_size_bytes.to_string(),
+    ];
+    if columns.compressed_size {
+        fields.push(property.compressed_size_bytes.to_string());
+    }
+    fields.push(format!("{:.2}", property.retained_size_percent));
+    fields.push(format!("{:.2}", property.shallow_size_percent));
+    if columns.crate_name {
+        fields.push(property.crate_name.unwrap_or("").to_owned());
+    }
+    fields.push(name.to_owned());
+    if columns.mangled_name {
+        fields.push(mangled_name.to_owned());
+    }
+    if columns.monomorphization_of {
+        fields.push(property.monomorphization_of.unwrap_or("").to_owned());
+    }
+    fields
+}
+
+/// Header of a column the tops can be sorted by, showing the direction and
+/// the rank of its sort key.
+fn sort_header(
+    ui: &mut egui::Ui,
+    sort_keys: &[SortKey],
+    column: SortColumn,
+    label: &str,
+    enabled: bool,
+) -> egui::Response {
+    let mut text = label.to_owned();
+    if enabled && let Some(position) = sort_keys.iter().position(|key| key.column == column) {
+        text.push_str(if sort_keys[position].descending {
+            " ⬇"
+        } else {
+            " ⬆"
         });
+        if sort_keys.len() > 1 {
+            text.push_str(&(position + 1).to_string());
+        }
     }
+
+    ui.add_enabled(
+        enabled,
+        egui::Label::new(egui::RichText::new(text).strong()).sense(Sense::click()),
+    )
+    .on_hover_text("Click to sort, shift-click to break the ties with this column")
 }
 
 trait WidgetMemory {
@@ -363,3 +1831,45 @@ impl WidgetMemory for egui::Ui {
         self.label(format!("{:.2}", percentage))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{FunctionsExplorer, TableFormat};
+
+    #[test]
+    fn csv_fields_are_quoted() {
+        let fields = [
+            "812".to_owned(),
+            "alloc::vec::Vec<T, A>::push".to_owned(),
+            "a \"quoted\" name".to_owned(),
+        ];
+        assert_eq!(
+            TableFormat::Csv.row(&fields),
+            r#"812,"alloc::vec::Vec<T, A>::push","a ""quoted"" name""#
+        );
+        assert_eq!(
+            TableFormat::Tsv.row(&fields),
+            "812\talloc::vec::Vec<T, A>::push\ta \"quoted\" name"
+        );
+    }
+
+    #[test]
+    fn shift_click_selects_the_shown_rows_from_a_pinned_anchor() {
+        let filtered = [10, 11, 12, 13];
+        let mut explorer = FunctionsExplorer {
+            pinned: vec![12],
+            ..FunctionsExplorer::default()
+        };
+
+        // The rows are 12 (pinned), 10, 11 and 13.
+        let rows = explorer.shown_rows(&filtered);
+        assert_eq!(rows, [12, 10, 11, 13]);
+        explorer.click_row(&rows, 0, egui::Modifiers::NONE);
+        explorer.click_row(&rows, 2, egui::Modifiers::SHIFT);
+
+        let mut selection: Vec<usize> = explorer.multi_selection.iter().copied().collect();
+        selection.sort();
+        assert_eq!(selection, [10, 11, 12]);
+        assert_eq!(explorer.selected_row, Some(11));
+    }
+}
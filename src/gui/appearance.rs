@@ -0,0 +1,112 @@
+use std::{path::PathBuf, sync::Arc};
+
+/// Name of the font loaded from `Appearance::monospace_font`.
+const MONOSPACE_FONT_NAME: &str = "monospace_font";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum RowHeight {
+    Compact,
+    #[default]
+    Normal,
+    Comfortable,
+}
+
+impl RowHeight {
+    pub const ALL: [RowHeight; 3] = [
+        RowHeight::Compact,
+        RowHeight::Normal,
+        RowHeight::Comfortable,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RowHeight::Compact => "Compact",
+            RowHeight::Normal => "Normal",
+            RowHeight::Comfortable => "Comfortable",
+        }
+    }
+
+    fn points(self) -> f32 {
+        match self {
+            RowHeight::Compact => 16.0,
+            RowHeight::Normal => 20.0,
+            RowHeight::Comfortable => 26.0,
+        }
+    }
+}
+
+/// Looks of the UI, picked in the settings window.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub ui_scale: f32,
+    /// Font file used instead of the monospace font of egui, mostly by the
+    /// code and memory viewers.
+    pub monospace_font: Option<PathBuf>,
+    pub monospace_size: f32,
+    pub row_height: RowHeight,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            monospace_font: None,
+            monospace_size: 12.0,
+            row_height: RowHeight::default(),
+        }
+    }
+}
+
+impl Appearance {
+    /// Applies the changes since `previous`, the fonts being only reloaded
+    /// when the font file changed.
+    pub fn apply(&self, ctx: &egui::Context, previous: Option<&Appearance>) -> Result<(), String> {
+        ctx.set_zoom_factor(self.ui_scale);
+        ctx.all_styles_mut(|style| {
+            style.text_styles.insert(
+                egui::TextStyle::Monospace,
+                egui::FontId::monospace(self.monospace_size),
+            );
+        });
+        ctx.data_mut(|data| data.insert_temp(row_height_id(), self.row_height.points()));
+
+        if previous.is_some_and(|previous| previous.monospace_font == self.monospace_font) {
+            return Ok(());
+        }
+
+        let mut fonts = egui::FontDefinitions::default();
+        let mut result = Ok(());
+        if let Some(path) = &self.monospace_font {
+            match std::fs::read(path) {
+                Ok(bytes) => {
+                    fonts.font_data.insert(
+                        MONOSPACE_FONT_NAME.to_owned(),
+                        Arc::new(egui::FontData::from_owned(bytes)),
+                    );
+                    fonts
+                        .families
+                        .entry(egui::FontFamily::Monospace)
+                        .or_default()
+                        .insert(0, MONOSPACE_FONT_NAME.to_owned());
+                }
+                Err(err) => {
+                    result = Err(format!("Failed to load {}: {}", path.display(), err));
+                }
+            }
+        }
+        ctx.set_fonts(fonts);
+
+        result
+    }
+}
+
+fn row_height_id() -> egui::Id {
+    egui::Id::new("__table_row_height")
+}
+
+/// Height of the rows of the tables, picked in the settings.
+pub fn row_height(ui: &egui::Ui) -> f32 {
+    ui.data(|data| data.get_temp(row_height_id()))
+        .unwrap_or(RowHeight::default().points())
+}
@@ -0,0 +1,33 @@
+use crate::arena::ArenaUsage;
+
+pub struct ArenaUsageViewer;
+
+impl ArenaUsageViewer {
+    /// Shows the usage of the arenas, by name.
+    pub fn show(ui: &mut egui::Ui, arenas: &[(String, ArenaUsage)]) {
+        let mb = |bytes: usize| format!("{:.1} MB", bytes as f32 / (1024.0 * 1024.0));
+
+        egui::Grid::new("arena_usage")
+            .num_columns(6)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Arena");
+                ui.strong("Used");
+                ui.strong("Committed");
+                ui.strong("Reserved");
+                ui.strong("Blocks");
+                ui.strong("Allocations");
+                ui.end_row();
+
+                for (name, usage) in arenas {
+                    ui.label(name);
+                    ui.label(mb(usage.used_bytes));
+                    ui.label(mb(usage.commited_bytes));
+                    ui.label(mb(usage.reserved_bytes));
+                    ui.label(usage.block_count.to_string());
+                    ui.label(usage.allocation_count.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+}
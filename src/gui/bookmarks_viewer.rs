@@ -0,0 +1,119 @@
+use crate::{
+    bookmarks::{BookmarkSize, Bookmarks},
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::appearance::row_height,
+};
+
+/// Starred functions and namespaces with their sizes in the opened file.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct BookmarksViewer {
+    /// Sizes of the bookmarks, with the generation of the bookmarks they
+    /// were computed for.
+    #[serde(skip)]
+    sizes: Option<(u64, Vec<BookmarkSize>)>,
+}
+
+impl BookmarksViewer {
+    /// Returns the function to select when a function bookmark is clicked.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        bookmarks: &mut Bookmarks,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        if bookmarks.is_empty() {
+            ui.label("No bookmarks, star functions and namespaces from the context menu of the functions explorer.");
+            return None;
+        }
+
+        if self
+            .sizes
+            .as_ref()
+            .is_none_or(|(generation, _)| *generation != bookmarks.generation())
+        {
+            self.sizes = Some((bookmarks.generation(), bookmarks.sizes(data_provider)));
+        }
+        let (_, sizes) = self.sizes.as_ref().unwrap();
+
+        let mut clicked_function = None;
+        let mut removed_bookmark = None;
+
+        let old_selectable_labels = ui.style().interaction.selectable_labels;
+        ui.style_mut().interaction.selectable_labels = false;
+
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("bookmarks")
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .sense(egui::Sense::click())
+            .header(20.0, |mut header| {
+                header.col(|_| {});
+                header.col(|ui| {
+                    ui.strong("Kind");
+                });
+                header.col(|ui| {
+                    ui.strong("Functions");
+                });
+                header.col(|ui| {
+                    ui.strong("Size (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Name");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, sizes.len(), |mut row| {
+                    let bookmark = &sizes[row.index()];
+
+                    row.col(|ui| {
+                        if ui.small_button("★").on_hover_text("Remove").clicked() {
+                            removed_bookmark = Some(bookmark);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(if bookmark.is_path {
+                            "Namespace"
+                        } else {
+                            "Function"
+                        });
+                    });
+                    row.col(|ui| {
+                        ui.label(bookmark.function_count.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(bookmark.size_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        if bookmark.function_count == 0 {
+                            ui.weak(&bookmark.name).on_hover_text("Not in this file");
+                        } else {
+                            ui.label(&bookmark.name);
+                        }
+                    });
+
+                    if row.response().clicked() {
+                        clicked_function = bookmark.largest_function;
+                    }
+                });
+            });
+
+        ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+        if let Some(bookmark) = removed_bookmark {
+            if bookmark.is_path {
+                bookmarks.toggle_path(&bookmark.name);
+            } else {
+                bookmarks.toggle_function(&bookmark.name);
+            }
+        }
+
+        clicked_function
+    }
+}
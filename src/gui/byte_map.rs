@@ -0,0 +1,134 @@
+use std::ops::Range;
+
+use egui::Color32;
+
+use crate::{gui::treemap::color_for_name, wasm::parser::WasmData};
+
+const CUSTOM_SECTION_ID: u8 = 0;
+const TYPE_SECTION_ID: u8 = 1;
+const CODE_SECTION_ID: u8 = 10;
+const DATA_SECTION_ID: u8 = 11;
+const DATA_COUNT_SECTION_ID: u8 = 12;
+
+/// What the bytes colored in the raw binary viewer are part of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteKind {
+    /// The preamble, the section headers and the sections describing the
+    /// module structure other than the types.
+    Header,
+    Types,
+    Code,
+    Data,
+    Custom,
+}
+
+impl ByteKind {
+    pub const ALL: [ByteKind; 5] = [
+        ByteKind::Header,
+        ByteKind::Types,
+        ByteKind::Code,
+        ByteKind::Data,
+        ByteKind::Custom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ByteKind::Header => "Header",
+            ByteKind::Types => "Types",
+            ByteKind::Code => "Code",
+            ByteKind::Data => "Data",
+            ByteKind::Custom => "Custom",
+        }
+    }
+
+    pub fn color(self, dark_mode: bool) -> Color32 {
+        color_for_name(self.name(), dark_mode).gamma_multiply(0.5)
+    }
+
+    fn of_section(id: u8) -> Self {
+        match id {
+            TYPE_SECTION_ID => ByteKind::Types,
+            CODE_SECTION_ID => ByteKind::Code,
+            DATA_SECTION_ID | DATA_COUNT_SECTION_ID => ByteKind::Data,
+            CUSTOM_SECTION_ID => ByteKind::Custom,
+            _ => ByteKind::Header,
+        }
+    }
+}
+
+/// Kinds of all the bytes of the file, in order of address.
+pub fn byte_kinds(wasm_data: &WasmData) -> Vec<(Range<usize>, ByteKind)> {
+    let mut kinds: Vec<(Range<usize>, ByteKind)> = Vec::new();
+    let mut push = |range: Range<usize>, kind: ByteKind| {
+        if range.is_empty() {
+            return;
+        }
+        match kinds.last_mut() {
+            Some((last_range, last_kind))
+                if *last_kind == kind && last_range.end == range.start =>
+            {
+                last_range.end = range.end;
+            }
+            _ => kinds.push((range, kind)),
+        }
+    };
+
+    let mut end = 0;
+    for section in wasm_data.sections.iter() {
+        // The id and the size of the section are before its range.
+        push(end..section.range.start, ByteKind::Header);
+        push(section.range.clone(), ByteKind::of_section(section.id));
+        end = section.range.end;
+    }
+    push(end..wasm_data.bytes.len(), ByteKind::Header);
+
+    kinds
+}
+
+/// Shows the colors of the kinds of bytes.
+pub fn show_legend(ui: &mut egui::Ui) {
+    let dark_mode = ui.visuals().dark_mode;
+    ui.horizontal(|ui| {
+        for kind in ByteKind::ALL {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, kind.color(dark_mode));
+            ui.label(kind.name());
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ByteKind, byte_kinds};
+    use crate::{
+        arena::{Arena, memory::MB},
+        wasm::parser::WasmData,
+    };
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn every_byte_has_a_kind() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            // Type section with a `fn()` type.
+            0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            // Custom section "foo" with a single byte.
+            0x00, 0x05, 0x03, b'f', b'o', b'o', 0x2a,
+        ];
+
+        let arena = Arena::new(MB);
+        let wasm_data =
+            WasmData::from_bytes(&arena, &bytes, &|_| ControlFlow::Continue(())).unwrap();
+        let kinds = byte_kinds(&wasm_data);
+
+        assert_eq!(kinds.first().map(|(range, _)| range.start), Some(0));
+        assert_eq!(kinds.last().map(|(range, _)| range.end), Some(bytes.len()));
+        assert!(
+            kinds
+                .windows(2)
+                .all(|pair| pair[0].0.end == pair[1].0.start)
+        );
+        assert!(kinds.contains(&(10..14, ByteKind::Types)));
+    }
+}
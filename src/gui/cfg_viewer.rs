@@ -0,0 +1,202 @@
+use egui::{
+    Color32, Pos2, Rect, Sense, Shape, Stroke, StrokeKind, Vec2, epaint::CubicBezierShape, vec2,
+};
+
+use crate::{analysis::cfg::ControlFlowGraph, data_provider_twiggy::DataProviderTwiggy};
+
+const BLOCK_HEIGHT: f32 = 36.0;
+const BLOCK_GAP: f32 = 28.0;
+/// Horizontal offset of a block per structured block it is nested in.
+const INDENT: f32 = 24.0;
+const MIN_BLOCK_WIDTH: f32 = 120.0;
+const MAX_BLOCK_WIDTH: f32 = 320.0;
+/// Space between the edges going back to a loop, drawn right of the blocks.
+const BACK_EDGE_LANE: f32 = 10.0;
+/// Instructions listed in the tooltip of a block, the rest being elided.
+const MAX_TOOLTIP_OPS: usize = 40;
+
+const SMALL_BLOCK_COLOR: Color32 = Color32::from_rgb(70, 130, 180);
+const LARGE_BLOCK_COLOR: Color32 = Color32::from_rgb(220, 80, 60);
+
+/// Control-flow graph of a function: its basic blocks top down in the order
+/// of the code, indented by their nesting. The more bytes a block takes, the
+/// wider and redder it is.
+pub struct CfgViewer;
+
+impl CfgViewer {
+    pub fn show(ui: &mut egui::Ui, cfg: &ControlFlowGraph, data_provider: &DataProviderTwiggy) {
+        ui.heading(data_provider.function_name(cfg.function));
+        ui.label(format!(
+            "{} bytes in {} basic blocks",
+            cfg.size_bytes(),
+            cfg.blocks.len()
+        ));
+        if cfg.blocks.is_empty() {
+            ui.label("The function has no instructions.");
+            return;
+        }
+
+        ui.separator();
+
+        let max_size = cfg
+            .blocks
+            .iter()
+            .map(|block| block.size_bytes)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let max_depth = cfg
+            .blocks
+            .iter()
+            .map(|block| block.depth)
+            .max()
+            .unwrap_or_default();
+        let back_edge_count: usize = cfg
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(idx, block)| {
+                block
+                    .successors
+                    .iter()
+                    .filter(|&&successor| successor <= idx)
+                    .count()
+            })
+            .sum();
+
+        let blocks_width = max_depth as f32 * INDENT + MAX_BLOCK_WIDTH;
+        let graph_size = vec2(
+            blocks_width + (back_edge_count + 1) as f32 * BACK_EDGE_LANE,
+            cfg.blocks.len() as f32 * (BLOCK_HEIGHT + BLOCK_GAP),
+        );
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            let (response, painter) = ui.allocate_painter(graph_size, Sense::hover());
+            let origin = response.rect.min;
+
+            let rects: Vec<Rect> = cfg
+                .blocks
+                .iter()
+                .enumerate()
+                .map(|(idx, block)| {
+                    let share = (block.size_bytes as f32 / max_size as f32).sqrt();
+                    Rect::from_min_size(
+                        origin
+                            + vec2(
+                                block.depth as f32 * INDENT,
+                                idx as f32 * (BLOCK_HEIGHT + BLOCK_GAP),
+                            ),
+                        vec2(
+                            MIN_BLOCK_WIDTH + (MAX_BLOCK_WIDTH - MIN_BLOCK_WIDTH) * share,
+                            BLOCK_HEIGHT,
+                        ),
+                    )
+                })
+                .collect();
+
+            // Edges going down leave from the bottom of a block, the ones
+            // going back to a loop go around the blocks on the right.
+            let edge_stroke = Stroke {
+                width: 1.5,
+                color: ui.visuals().weak_text_color(),
+            };
+            let back_edge_stroke = Stroke {
+                width: 1.5,
+                color: ui.visuals().warn_fg_color,
+            };
+            let mut back_edge_idx = 0;
+            for (idx, block) in cfg.blocks.iter().enumerate() {
+                for &successor in &block.successors {
+                    let (from, to) = (rects[idx], rects[successor]);
+                    if successor > idx {
+                        let start = from.center_bottom();
+                        let end = to.center_top();
+                        let bend = vec2(0.0, (end.y - start.y) / 2.0);
+                        painter.add(CubicBezierShape::from_points_stroke(
+                            [start, start + bend, end - bend, end],
+                            false,
+                            Color32::TRANSPARENT,
+                            edge_stroke,
+                        ));
+                        arrow_head(&painter, end, Vec2::DOWN, edge_stroke.color);
+                    } else {
+                        back_edge_idx += 1;
+                        let x = origin.x + blocks_width + back_edge_idx as f32 * BACK_EDGE_LANE;
+                        let start = from.right_center();
+                        let end = to.right_center();
+                        painter.add(Shape::line(
+                            vec![start, Pos2::new(x, start.y), Pos2::new(x, end.y), end],
+                            back_edge_stroke,
+                        ));
+                        arrow_head(&painter, end, Vec2::LEFT, back_edge_stroke.color);
+                    }
+                }
+            }
+
+            let hovered = response
+                .hover_pos()
+                .and_then(|pos| rects.iter().position(|rect| rect.contains(pos)));
+
+            let stroke = Stroke {
+                width: 1.0,
+                color: ui.visuals().extreme_bg_color,
+            };
+            let font_id = egui::TextStyle::Small.resolve(ui.style());
+            for (idx, (block, rect)) in cfg.blocks.iter().zip(&rects).enumerate() {
+                let mut fill = SMALL_BLOCK_COLOR
+                    .lerp_to_gamma(LARGE_BLOCK_COLOR, block.size_bytes as f32 / max_size as f32);
+                if hovered == Some(idx) {
+                    fill = fill.gamma_multiply(1.3);
+                }
+                painter.rect(*rect, 4.0, fill, stroke, StrokeKind::Inside);
+
+                painter.with_clip_rect(rect.shrink(2.0)).text(
+                    rect.left_top() + vec2(6.0, 4.0),
+                    egui::Align2::LEFT_TOP,
+                    format!(
+                        "#{} 0x{:04x}\n{} bytes, {} instructions",
+                        idx,
+                        block.address,
+                        block.size_bytes,
+                        block.ops.len()
+                    ),
+                    font_id.clone(),
+                    Color32::WHITE,
+                );
+            }
+
+            let Some(hovered) = hovered else {
+                return;
+            };
+
+            let block = &cfg.blocks[hovered];
+            let ops =
+                &data_provider.raw_data[cfg.function].debug_info.function_ops[block.ops.clone()];
+            egui::show_tooltip_at_pointer(
+                ui.ctx(),
+                ui.layer_id(),
+                egui::Id::new("cfg_tooltip"),
+                |ui| {
+                    ui.strong(format!("Block #{}", hovered));
+                    for op in ops.iter().take(MAX_TOOLTIP_OPS) {
+                        ui.monospace(format!("0x{:04x} {:?}", op.address, op.op));
+                    }
+                    if ops.len() > MAX_TOOLTIP_OPS {
+                        ui.label(format!("… {} more", ops.len() - MAX_TOOLTIP_OPS));
+                    }
+                },
+            );
+        });
+    }
+}
+
+/// Draws the head of an edge ending at `tip`, pointing in `direction`.
+fn arrow_head(painter: &egui::Painter, tip: Pos2, direction: Vec2, color: Color32) {
+    let back = tip - direction * 8.0;
+    let side = direction.rot90() * 4.0;
+    painter.add(Shape::convex_polygon(
+        vec![tip, back + side, back - side],
+        color,
+        Stroke::NONE,
+    ));
+}
@@ -0,0 +1,229 @@
+use std::path::Path;
+
+use crate::{
+    analysis::{
+        cargo_metadata::CrateGraph,
+        code_origin::{CodeOrigin, CodeOriginReport},
+    },
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::{appearance::row_height, function_list::show_function_list, treemap::color_for_name},
+};
+
+/// Stacked bar of the std, third-party and workspace code, with the crates
+/// and functions of the origins shown by the filters.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct CodeOriginViewer {
+    /// `Cargo.toml` of the workspace the binary was built from.
+    manifest_path: String,
+    /// Origins filtered out, in the order of `CodeOrigin::ALL`.
+    hidden_origins: [bool; 4],
+
+    #[serde(skip)]
+    report: Option<CodeOriginReport>,
+    #[serde(skip)]
+    crate_graph: Option<CrateGraph>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl CodeOriginViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(&mut self, ui: &mut egui::Ui, data_provider: &DataProviderTwiggy) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Cargo.toml:");
+            ui.text_edit_singleline(&mut self.manifest_path);
+
+            if ui
+                .add_enabled(
+                    !self.manifest_path.is_empty(),
+                    egui::Button::new("Run cargo metadata"),
+                )
+                .clicked()
+            {
+                match CrateGraph::from_manifest(Path::new(&self.manifest_path)) {
+                    Ok(crate_graph) => {
+                        self.crate_graph = Some(crate_graph);
+                        self.error = None;
+                    }
+                    Err(err) => {
+                        self.crate_graph = None;
+                        self.error = Some(format!("{:#}", err));
+                    }
+                }
+                self.report = None;
+            }
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let crate_graph = self.crate_graph.as_ref();
+        let report = self
+            .report
+            .get_or_insert_with(|| CodeOriginReport::new(data_provider, crate_graph));
+        let total_bytes = report.total_bytes().max(1);
+        let dark_mode = ui.visuals().dark_mode;
+
+        ui.separator();
+
+        show_stacked_bar(ui, report, dark_mode);
+
+        ui.horizontal_wrapped(|ui| {
+            for (origin, hidden) in CodeOrigin::ALL.into_iter().zip(&mut self.hidden_origins) {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 2.0, color_for_name(origin.as_str(), dark_mode));
+
+                let mut shown = !*hidden;
+                ui.checkbox(
+                    &mut shown,
+                    format!(
+                        "{} {:.1} KB ({:.1}%)",
+                        origin.as_str(),
+                        report.bytes_of(origin) as f32 / 1024.0,
+                        report.bytes_of(origin) as f32 / total_bytes as f32 * 100.0
+                    ),
+                );
+                *hidden = !shown;
+            }
+        });
+
+        if report.bytes_of(CodeOrigin::Unknown) > 0 && crate_graph.is_none() {
+            ui.label(
+                "Without debug info, run cargo metadata to tell the workspace crates from the dependencies.",
+            );
+        }
+
+        ui.separator();
+
+        let is_shown = |origin: CodeOrigin| !self.hidden_origins[origin as usize];
+        let crates: Vec<usize> = (0..report.crates.len())
+            .filter(|&idx| is_shown(report.crates[idx].origin))
+            .collect();
+        let functions: Vec<usize> = (0..report.function_origins.len())
+            .filter(|&function| is_shown(report.function_origins[function]))
+            .collect();
+
+        egui::CollapsingHeader::new(format!("Crates ({})", crates.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                let old_selectable_labels = ui.style().interaction.selectable_labels;
+                ui.style_mut().interaction.selectable_labels = false;
+
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("code_origin_crates")
+                    .striped(true)
+                    .resizable(true)
+                    .max_scroll_height(300.0)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .sense(egui::Sense::click())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Size (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Functions");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Origin");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Crate");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, crates.len(), |mut row| {
+                            let crate_origin = &report.crates[crates[row.index()]];
+
+                            row.col(|ui| {
+                                ui.label(crate_origin.size_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(crate_origin.function_count.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(crate_origin.origin.as_str());
+                            });
+                            row.col(|ui| {
+                                ui.label(&crate_origin.name);
+                            });
+
+                            if row.response().clicked() {
+                                clicked_function = Some(crate_origin.largest_function);
+                            }
+                        });
+                    });
+
+                ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+            });
+
+        egui::CollapsingHeader::new(format!("Functions ({})", functions.len())).show(ui, |ui| {
+            let origin = |row: usize| report.function_origins[functions[row]].as_str().to_string();
+
+            clicked_function = clicked_function.or(show_function_list(
+                ui,
+                "code_origin_functions",
+                data_provider,
+                functions.len(),
+                |row| functions[row],
+                Some(("Origin", &origin)),
+            ));
+        });
+
+        clicked_function
+    }
+}
+
+/// Shows the bytes of each origin as segments of a single bar.
+fn show_stacked_bar(ui: &mut egui::Ui, report: &CodeOriginReport, dark_mode: bool) {
+    let total_bytes = report.total_bytes().max(1);
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 24.0), egui::Sense::hover());
+
+    let mut left = rect.left();
+    for origin in CodeOrigin::ALL {
+        let width = rect.width() * report.bytes_of(origin) as f32 / total_bytes as f32;
+        let segment = egui::Rect::from_x_y_ranges(left..=left + width, rect.y_range());
+        left += width;
+
+        ui.painter()
+            .rect_filled(segment, 0.0, color_for_name(origin.as_str(), dark_mode));
+        if width > 80.0 {
+            ui.painter().text(
+                segment.center(),
+                egui::Align2::CENTER_CENTER,
+                origin.as_str(),
+                egui::FontId::default(),
+                ui.visuals().strong_text_color(),
+            );
+        }
+
+        if response
+            .hover_pos()
+            .is_some_and(|pos| segment.contains(pos))
+        {
+            egui::show_tooltip_at_pointer(
+                ui.ctx(),
+                ui.layer_id(),
+                egui::Id::new("code_origin_tooltip"),
+                |ui| {
+                    ui.strong(origin.as_str());
+                    ui.label(format!(
+                        "{:.1} KB ({:.2}%)",
+                        report.bytes_of(origin) as f32 / 1024.0,
+                        report.bytes_of(origin) as f32 / total_bytes as f32 * 100.0
+                    ));
+                },
+            );
+        }
+    }
+}
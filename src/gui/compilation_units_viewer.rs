@@ -0,0 +1,111 @@
+use crate::analysis::compilation_units::{CompilationUnitReport, CompilationUnitSortKey};
+use crate::gui::appearance::row_height;
+
+pub struct CompilationUnitsViewer;
+
+impl CompilationUnitsViewer {
+    /// Shows the units as a table sorted by `sort_key`.
+    ///
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &mut CompilationUnitReport,
+        sort_key: &mut CompilationUnitSortKey,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+        let mut new_sort_key = *sort_key;
+
+        if report.units.is_empty() {
+            ui.label("No compilation units, the module has no DWARF debug info.");
+            return None;
+        }
+
+        ui.strong(format!(
+            "{} compilation units, {:.1} KB in {} functions without debug info",
+            report.units.len(),
+            report.unattributed_bytes as f32 / 1024.0,
+            report.unattributed_function_count
+        ));
+
+        ui.separator();
+
+        let old_selectable_labels = ui.style().interaction.selectable_labels;
+        ui.style_mut().interaction.selectable_labels = false;
+
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("compilation_units")
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .sense(egui::Sense::click())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.selectable_value(
+                        &mut new_sort_key,
+                        CompilationUnitSortKey::Size,
+                        "Size (bytes)",
+                    );
+                });
+                header.col(|ui| {
+                    ui.selectable_value(
+                        &mut new_sort_key,
+                        CompilationUnitSortKey::FunctionCount,
+                        "Functions",
+                    );
+                });
+                header.col(|ui| {
+                    ui.selectable_value(
+                        &mut new_sort_key,
+                        CompilationUnitSortKey::Name,
+                        "Source file",
+                    );
+                });
+                header.col(|ui| {
+                    ui.strong("Codegen unit");
+                });
+                header.col(|ui| {
+                    ui.strong("Directory");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, report.units.len(), |mut row| {
+                    let unit = &report.units[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(unit.size_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(unit.function_count.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(&unit.source_file);
+                    });
+                    row.col(|ui| {
+                        ui.label(unit.codegen_unit.as_deref().unwrap_or(""));
+                    });
+                    row.col(|ui| {
+                        ui.label(unit.comp_dir.to_string_lossy());
+                    });
+
+                    if row.response().clicked() {
+                        clicked_function = Some(unit.largest_function);
+                    }
+                });
+            });
+
+        ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+        if new_sort_key != *sort_key {
+            *sort_key = new_sort_key;
+            report.sort(new_sort_key);
+        }
+
+        clicked_function
+    }
+}
@@ -0,0 +1,197 @@
+use std::{collections::HashMap, path::Path};
+
+use egui::Stroke;
+
+use crate::{
+    analysis::cargo_metadata::CrateGraph,
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::treemap::{color_for_name, show_treemap},
+};
+
+/// Functions that couldn't be attributed to a crate (C code, intrinsics, etc.).
+const UNATTRIBUTED: &str = "<unattributed>";
+
+struct CrateSize {
+    name: String,
+    size_bytes: u64,
+    largest_function: usize,
+}
+
+/// Treemap of the crates of a binary, sized by the bytes attributed to them.
+///
+/// The crate graph from `cargo metadata` is optional, it adds the dependency
+/// edges and tells the workspace crates apart.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct CrateTreemapViewer {
+    /// `Cargo.toml` of the workspace the binary was built from.
+    manifest_path: String,
+
+    #[serde(skip)]
+    crate_sizes: Option<Vec<CrateSize>>,
+    #[serde(skip)]
+    crate_graph: Option<CrateGraph>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl CrateTreemapViewer {
+    /// Returns the index of the function to select when a crate is clicked
+    /// (its largest function).
+    pub fn show(&mut self, ui: &mut egui::Ui, data_provider: &DataProviderTwiggy) -> Option<usize> {
+        ui.horizontal(|ui| {
+            ui.label("Cargo.toml:");
+            ui.text_edit_singleline(&mut self.manifest_path);
+
+            if ui
+                .add_enabled(
+                    !self.manifest_path.is_empty(),
+                    egui::Button::new("Run cargo metadata"),
+                )
+                .clicked()
+            {
+                match CrateGraph::from_manifest(Path::new(&self.manifest_path)) {
+                    Ok(crate_graph) => {
+                        self.crate_graph = Some(crate_graph);
+                        self.error = None;
+                    }
+                    Err(err) => {
+                        self.crate_graph = None;
+                        self.error = Some(format!("{:#}", err));
+                    }
+                }
+            }
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let crate_sizes = self
+            .crate_sizes
+            .get_or_insert_with(|| crate_sizes(data_provider));
+        let crate_graph = self.crate_graph.as_ref();
+
+        // Index of each crate in the crate graph.
+        let graph_nodes: Vec<Option<usize>> = crate_sizes
+            .iter()
+            .map(|crate_size| crate_graph.and_then(|graph| graph.find(&crate_size.name)))
+            .collect();
+
+        let total_size_bytes: u64 = crate_sizes
+            .iter()
+            .map(|crate_size| crate_size.size_bytes)
+            .sum();
+        let sizes: Vec<u64> = crate_sizes
+            .iter()
+            .map(|crate_size| crate_size.size_bytes)
+            .collect();
+
+        let dark_mode = ui.visuals().dark_mode;
+        let faded_color = ui.visuals().faint_bg_color;
+        let treemap = show_treemap(
+            ui,
+            &sizes,
+            |idx| &crate_sizes[idx].name,
+            |idx| {
+                // With the crate graph, dependencies outside of the workspace are faded.
+                let is_dependency = crate_graph.is_some()
+                    && !graph_nodes[idx]
+                        .and_then(|node| crate_graph.map(|graph| &graph.crates[node]))
+                        .is_some_and(|crate_node| crate_node.is_workspace_member);
+
+                let color = color_for_name(&crate_sizes[idx].name, dark_mode);
+                if is_dependency {
+                    color.lerp_to_gamma(faded_color, 0.5)
+                } else {
+                    color
+                }
+            },
+        );
+
+        let hovered = treemap.hovered?;
+        let crate_size = &crate_sizes[hovered];
+        let crate_node =
+            graph_nodes[hovered].and_then(|node| crate_graph.map(|graph| &graph.crates[node]));
+
+        // Dependency edges of the hovered crate.
+        let mut dependencies = Vec::new();
+        if let (Some(crate_node), Some(crate_graph)) = (crate_node, crate_graph) {
+            let stroke = Stroke {
+                width: 2.0,
+                color: ui.visuals().strong_text_color(),
+            };
+            let from = treemap.rects[hovered].center();
+
+            for &dependency in &crate_node.dependencies {
+                let dependency_name = &crate_graph.crates[dependency].name;
+                dependencies.push(dependency_name.as_str());
+
+                let Some(idx) = crate_sizes
+                    .iter()
+                    .position(|crate_size| &crate_size.name == dependency_name)
+                else {
+                    continue;
+                };
+                if treemap.rects[idx].is_positive() {
+                    let to = treemap.rects[idx].center();
+                    ui.painter().arrow(from, to - from, stroke);
+                }
+            }
+        }
+
+        egui::show_tooltip_at_pointer(
+            ui.ctx(),
+            ui.layer_id(),
+            egui::Id::new("crate_treemap_tooltip"),
+            |ui| {
+                match crate_node {
+                    Some(crate_node) => {
+                        ui.strong(format!("{} {}", crate_size.name, crate_node.version))
+                    }
+                    None => ui.strong(&crate_size.name),
+                };
+                ui.label(format!(
+                    "{:.1} KB ({:.2}%)",
+                    crate_size.size_bytes as f32 / 1024.0,
+                    crate_size.size_bytes as f32 / total_size_bytes.max(1) as f32 * 100.0
+                ));
+                if !dependencies.is_empty() {
+                    ui.label(format!("Depends on: {}", dependencies.join(", ")));
+                }
+            },
+        );
+
+        treemap.clicked.map(|idx| crate_sizes[idx].largest_function)
+    }
+}
+
+fn crate_sizes(data_provider: &DataProviderTwiggy) -> Vec<CrateSize> {
+    let mut crate_lookup: HashMap<&str, usize> = HashMap::new();
+    let mut crate_sizes: Vec<CrateSize> = Vec::new();
+
+    for (idx, function_data) in data_provider.raw_data.iter().enumerate() {
+        let property = &function_data.function_property;
+        let name = property.crate_name.unwrap_or(UNATTRIBUTED);
+
+        let crate_idx = *crate_lookup.entry(name).or_insert_with(|| {
+            crate_sizes.push(CrateSize {
+                name: name.to_string(),
+                size_bytes: 0,
+                largest_function: idx,
+            });
+            crate_sizes.len() - 1
+        });
+
+        let crate_size = &mut crate_sizes[crate_idx];
+        crate_size.size_bytes += property.shallow_size_bytes as u64;
+        if data_provider.raw_data[crate_size.largest_function]
+            .function_property
+            .shallow_size_bytes
+            < property.shallow_size_bytes
+        {
+            crate_size.largest_function = idx;
+        }
+    }
+
+    crate_sizes
+}
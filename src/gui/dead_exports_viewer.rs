@@ -0,0 +1,82 @@
+use crate::{
+    analysis::dead_exports::{DeadExportsReport, ExportUsage},
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::appearance::row_height,
+};
+
+pub struct DeadExportsViewer;
+
+impl DeadExportsViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &DeadExportsReport,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.strong(format!(
+            "{} unused exports, retaining {:.1} KB",
+            report.dead_exports().count(),
+            report.dead_size_bytes as f32 / 1024.0
+        ));
+
+        let old_selectable_labels = ui.style().interaction.selectable_labels;
+        ui.style_mut().interaction.selectable_labels = false;
+
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .sense(egui::Sense::click())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Exclusive size (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Usage");
+                });
+                header.col(|ui| {
+                    ui.strong("Export");
+                });
+                header.col(|ui| {
+                    ui.strong("Function");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, report.exports.len(), |mut row| {
+                    let export = &report.exports[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(export.exclusive_size_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        if export.usage == ExportUsage::Unused {
+                            ui.colored_label(ui.visuals().warn_fg_color, export.usage.as_str());
+                        } else {
+                            ui.label(export.usage.as_str());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(&export.name);
+                    });
+                    row.col(|ui| {
+                        ui.label(data_provider.function_name(export.function));
+                    });
+
+                    if row.response().clicked() {
+                        clicked_function = Some(export.function);
+                    }
+                });
+            });
+
+        ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+        clicked_function
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    data_provider_twiggy::{DataProviderTwiggy, FunctionItemState},
+    dwarf::{DwNode, DwNodeType},
+    tree_state::TreeState,
+};
+
+/// Paths of the opened nodes of the dominators, like `crate::module`, which
+/// are the same in the builds of a crate.
+#[derive(Debug, Default)]
+pub struct ExpandedPaths {
+    paths: BTreeSet<String>,
+    /// Incremented on every change, for the viewers caching their rows.
+    generation: u64,
+}
+
+impl ExpandedPaths {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn is_expanded(&self, path: &str) -> bool {
+        self.paths.contains(path)
+    }
+
+    pub fn toggle(&mut self, path: &str) {
+        if !self.paths.remove(path) {
+            self.paths.insert(path.to_owned());
+        }
+        self.generation += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.generation += 1;
+    }
+}
+
+struct Row {
+    node: usize,
+    depth: usize,
+    path: String,
+}
+
+/// Dominators of a file in a tab. The linked viewers open and close the same
+/// namespaces, to compare two files side by side.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct DominatorsViewer {
+    linked: bool,
+
+    /// Expanded paths of the viewer when it isn't linked.
+    #[serde(skip)]
+    expanded_paths: ExpandedPaths,
+    /// Rows with the generation of the expanded paths they were computed for.
+    #[serde(skip)]
+    rows: Option<(u64, Vec<Row>)>,
+}
+
+impl DominatorsViewer {
+    pub fn linked() -> Self {
+        Self {
+            linked: true,
+            ..Self::default()
+        }
+    }
+
+    /// Drops the rows computed for the previous file of the tab.
+    pub fn clear_rows(&mut self) {
+        self.rows = None;
+    }
+
+    /// `linked_paths` are the paths expanded by all the linked viewers.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        data_provider: &DataProviderTwiggy,
+        linked_paths: &mut ExpandedPaths,
+    ) {
+        ui.horizontal(|ui| {
+            if ui
+                .checkbox(&mut self.linked, "Linked")
+                .on_hover_text("Opens the same namespaces as the other linked tabs")
+                .changed()
+            {
+                self.rows = None;
+            }
+            if ui.button("Collapse all").clicked() {
+                self.expanded_paths(linked_paths).clear();
+            }
+        });
+
+        let state = &data_provider.dominator_state;
+        let expanded_paths = if self.linked {
+            &*linked_paths
+        } else {
+            &self.expanded_paths
+        };
+        if self
+            .rows
+            .as_ref()
+            .is_none_or(|(generation, _)| *generation != expanded_paths.generation())
+        {
+            self.rows = Some((
+                expanded_paths.generation(),
+                compute_rows(state, expanded_paths),
+            ));
+        }
+        let (_, rows) = self.rows.as_ref().unwrap();
+
+        let mut toggled_path = None;
+
+        let old_selectable_labels = ui.style().interaction.selectable_labels;
+        ui.style_mut().interaction.selectable_labels = false;
+
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("dominators")
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::remainder())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .sense(egui::Sense::click())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Name");
+                });
+                header.col(|ui| {
+                    ui.strong("Retained (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Retained %");
+                });
+            })
+            .body(|body| {
+                body.rows(18.0, rows.len(), |mut row| {
+                    let dominator_row = &rows[row.index()];
+                    let node = dominator_row.node;
+                    let dw_node = &state.tree[node].value;
+                    let size = state.items_ui_data[node].size;
+
+                    row.col(|ui| {
+                        ui.add_space(16.0 * dominator_row.depth as f32);
+                        if state.tree.has_children(node) {
+                            let icon = if expanded_paths.is_expanded(&dominator_row.path) {
+                                "▼"
+                            } else {
+                                "▶"
+                            };
+                            ui.label(icon);
+                        } else {
+                            ui.add_space(ui.spacing().icon_width);
+                        }
+                        ui.label(label(dw_node))
+                            .on_hover_text(dominator_row.path.as_str());
+                    });
+                    row.col(|ui| {
+                        ui.label(size.to_string());
+                    });
+                    row.col(|ui| {
+                        if data_provider.total_size > 0 {
+                            let percent = 100.0 * size as f32 / data_provider.total_size as f32;
+                            ui.label(format!("{:.2}", percent));
+                        }
+                    });
+
+                    if row.response().clicked() && state.tree.has_children(node) {
+                        toggled_path = Some(dominator_row.path.clone());
+                    }
+                });
+            });
+
+        ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+        if let Some(path) = toggled_path {
+            self.expanded_paths(linked_paths).toggle(&path);
+        }
+    }
+
+    fn expanded_paths<'p>(
+        &'p mut self,
+        linked_paths: &'p mut ExpandedPaths,
+    ) -> &'p mut ExpandedPaths {
+        if self.linked {
+            linked_paths
+        } else {
+            &mut self.expanded_paths
+        }
+    }
+}
+
+fn label(dw_node: &DwNode) -> String {
+    match dw_node.ty {
+        DwNodeType::Struct => format!("struct {}", dw_node.name.as_str()),
+        DwNodeType::Impl => format!("impl {}", dw_node.name.as_str()),
+        DwNodeType::FunctionInlinedInstance => {
+            format!("[inlined] {}", dw_node.name.as_str())
+        }
+        _ => dw_node.name.as_str().to_owned(),
+    }
+}
+
+/// Rows of the shown nodes, the children being ordered by retained size
+/// under the expanded paths. The root isn't shown.
+fn compute_rows(
+    state: &TreeState<DwNode, FunctionItemState>,
+    expanded_paths: &ExpandedPaths,
+) -> Vec<Row> {
+    let tree = &state.tree;
+    let mut rows = Vec::new();
+    if tree.is_empty() {
+        return rows;
+    }
+
+    let sorted_children = |idx: usize| {
+        let mut children: Vec<usize> = tree
+            .get_children(idx)
+            .filter(|&child_idx| state.items_state[child_idx].visible())
+            .collect();
+        children.sort_by_key(|&child_idx| std::cmp::Reverse(state.items_ui_data[child_idx].size));
+        children
+    };
+
+    let mut stack: Vec<(usize, usize, String)> = sorted_children(0)
+        .into_iter()
+        .rev()
+        .map(|child_idx| (child_idx, 0, tree[child_idx].value.name.as_str().to_owned()))
+        .collect();
+    while let Some((node, depth, path)) = stack.pop() {
+        if expanded_paths.is_expanded(&path) {
+            for child_idx in sorted_children(node).into_iter().rev() {
+                let child_path = format!("{}::{}", path, tree[child_idx].value.name.as_str());
+                stack.push((child_idx, depth + 1, child_path));
+            }
+        }
+        rows.push(Row { node, depth, path });
+    }
+
+    rows
+}
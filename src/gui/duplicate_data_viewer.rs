@@ -0,0 +1,100 @@
+use crate::analysis::duplicate_data::{DataLocation, DuplicateDataReport};
+use crate::gui::appearance::row_height;
+
+/// Number of copies listed in the locations column.
+const MAX_LISTED_COPIES: usize = 4;
+
+pub struct DuplicateDataViewer;
+
+impl DuplicateDataViewer {
+    pub fn show(ui: &mut egui::Ui, report: &DuplicateDataReport) {
+        ui.strong(format!(
+            "{} duplicated blobs: {:.1} KB recoverable out of {:.1} KB of data",
+            report.blobs.len(),
+            report.recoverable_bytes as f32 / 1024.0,
+            report.data_bytes as f32 / 1024.0
+        ));
+
+        ui.separator();
+
+        egui_extras::TableBuilder::new(ui)
+            .id_salt("duplicate_data_blobs")
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Recoverable (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Size (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Copies");
+                });
+                header.col(|ui| {
+                    ui.strong("Locations");
+                });
+                header.col(|ui| {
+                    ui.strong("Preview");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, report.blobs.len(), |mut row| {
+                    let blob = &report.blobs[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(blob.recoverable_bytes().to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(blob.size_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(blob.copies.len().to_string());
+                    });
+                    row.col(|ui| {
+                        let mut locations: Vec<String> = blob
+                            .copies
+                            .iter()
+                            .take(MAX_LISTED_COPIES)
+                            .map(location_label)
+                            .collect();
+                        if blob.copies.len() > MAX_LISTED_COPIES {
+                            locations.push(String::from("..."));
+                        }
+                        ui.label(locations.join(", "));
+                    });
+                    row.col(|ui| {
+                        ui.monospace(preview(&blob.preview));
+                    });
+                });
+            });
+    }
+}
+
+fn location_label(location: &DataLocation) -> String {
+    match location.address {
+        Some(address) => format!("0x{:08x}", address),
+        None => format!("segment {} + 0x{:x}", location.segment, location.offset),
+    }
+}
+
+/// The bytes as ASCII, with `.` for the bytes that aren't printable.
+fn preview(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use crate::analysis::feature_attribution::FeatureAttributionReport;
+use crate::gui::appearance::row_height;
+
+pub struct FeatureAttributionViewer;
+
+impl FeatureAttributionViewer {
+    pub fn show(ui: &mut egui::Ui, report: &FeatureAttributionReport) {
+        ui.strong(format!(
+            "{} builds compared with the baseline ({:.1} KB of code)",
+            report.builds.len(),
+            report.baseline_total_bytes as f32 / 1024.0
+        ));
+
+        egui::Grid::new("feature_attribution_builds")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Build");
+                ui.strong("Features");
+                ui.strong("Delta (bytes)");
+                ui.end_row();
+
+                for build in &report.builds {
+                    ui.label(&build.file_name);
+                    ui.label(build.features.join(", "));
+                    ui.label(format!("{:+}", build.delta_bytes));
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        for (idx, attribution) in report.features.iter().enumerate() {
+            egui::CollapsingHeader::new(format!(
+                "{}: {:+} bytes in {} functions",
+                attribution.feature,
+                attribution.delta_bytes,
+                attribution.functions.len()
+            ))
+            .id_salt(("feature_attribution", idx))
+            .show(ui, |ui| {
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt(("feature_attribution_functions", idx))
+                    .striped(true)
+                    .resizable(true)
+                    .max_scroll_height(300.0)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Delta (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Builds");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Function");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, attribution.functions.len(), |mut row| {
+                            let function = &attribution.functions[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(format!("{:+}", function.delta_bytes));
+                            });
+                            row.col(|ui| {
+                                ui.label(function.build_count.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(&function.name);
+                            });
+                        });
+                    });
+            });
+        }
+    }
+}
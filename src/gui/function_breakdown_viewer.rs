@@ -0,0 +1,165 @@
+use crate::{
+    analysis::function_breakdown::{BreakdownSortKey, FunctionBreakdown, SourceBytes},
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::appearance::row_height,
+};
+
+pub struct FunctionBreakdownViewer;
+
+impl FunctionBreakdownViewer {
+    /// Shows where the bytes of the function come from, sorted by `sort_key`.
+    pub fn show(
+        ui: &mut egui::Ui,
+        breakdown: &mut FunctionBreakdown,
+        sort_key: &mut BreakdownSortKey,
+        data_provider: &DataProviderTwiggy,
+    ) {
+        let mut new_sort_key = *sort_key;
+
+        ui.heading(data_provider.function_name(breakdown.function));
+        ui.label(format!("{} bytes", breakdown.size_bytes));
+
+        ui.horizontal(|ui| {
+            ui.label("Sort by:");
+            ui.selectable_value(&mut new_sort_key, BreakdownSortKey::Size, "Size");
+            ui.selectable_value(&mut new_sort_key, BreakdownSortKey::Name, "Name");
+        });
+
+        if !breakdown.has_line_info() {
+            ui.label("No line info for this function, the binary needs DWARF debug info.");
+        }
+
+        egui::CollapsingHeader::new(format!("Source files ({})", breakdown.files.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                show_source_table(
+                    ui,
+                    "breakdown_files",
+                    &breakdown.files,
+                    breakdown.size_bytes,
+                );
+            });
+
+        egui::CollapsingHeader::new(format!("Source lines ({})", breakdown.lines.len())).show(
+            ui,
+            |ui| {
+                show_source_table(
+                    ui,
+                    "breakdown_lines",
+                    &breakdown.lines,
+                    breakdown.size_bytes,
+                );
+            },
+        );
+
+        egui::CollapsingHeader::new(format!(
+            "Inlined calls ({})",
+            breakdown.inlined_frames.len().saturating_sub(1)
+        ))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui_extras::TableBuilder::new(ui)
+                .id_salt("breakdown_inlined_frames")
+                .striped(true)
+                .resizable(true)
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .column(egui_extras::Column::auto())
+                .column(egui_extras::Column::auto())
+                .column(egui_extras::Column::auto())
+                .column(egui_extras::Column::remainder())
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Size (bytes)");
+                    });
+                    header.col(|ui| {
+                        ui.strong("%");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Copies");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Name");
+                    });
+                })
+                .body(|mut body| {
+                    let row_height = row_height(body.ui_mut());
+                    body.rows(row_height, breakdown.inlined_frames.len(), |mut row| {
+                        let inlined_frame = &breakdown.inlined_frames[row.index()];
+
+                        row.col(|ui| {
+                            ui.label(inlined_frame.size_bytes.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(percent(inlined_frame.size_bytes, breakdown.size_bytes));
+                        });
+                        row.col(|ui| {
+                            ui.label(inlined_frame.instance_count.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(&inlined_frame.name);
+                        });
+                    });
+                });
+        });
+
+        if new_sort_key != *sort_key {
+            *sort_key = new_sort_key;
+            breakdown.sort(new_sort_key);
+        }
+    }
+}
+
+fn show_source_table(ui: &mut egui::Ui, id_salt: &str, sources: &[SourceBytes], total_bytes: u64) {
+    egui_extras::TableBuilder::new(ui)
+        .id_salt(id_salt)
+        .striped(true)
+        .resizable(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(egui_extras::Column::auto())
+        .column(egui_extras::Column::auto())
+        .column(egui_extras::Column::auto())
+        .column(egui_extras::Column::remainder())
+        .header(20.0, |mut header| {
+            header.col(|ui| {
+                ui.strong("Size (bytes)");
+            });
+            header.col(|ui| {
+                ui.strong("%");
+            });
+            header.col(|ui| {
+                ui.strong("Instructions");
+            });
+            header.col(|ui| {
+                ui.strong("Location");
+            });
+        })
+        .body(|mut body| {
+            let row_height = row_height(body.ui_mut());
+            body.rows(row_height, sources.len(), |mut row| {
+                let source = &sources[row.index()];
+
+                row.col(|ui| {
+                    ui.label(source.size_bytes.to_string());
+                });
+                row.col(|ui| {
+                    ui.label(percent(source.size_bytes, total_bytes));
+                });
+                row.col(|ui| {
+                    ui.label(source.instruction_count.to_string());
+                });
+                row.col(|ui| {
+                    match source.line {
+                        Some(line) => ui.label(format!("{}:{}", source.file, line)),
+                        None => ui.label(&source.file),
+                    };
+                });
+            });
+        });
+}
+
+fn percent(size_bytes: u64, total_bytes: u64) -> String {
+    format!(
+        "{:.1}%",
+        size_bytes as f32 / total_bytes.max(1) as f32 * 100.0
+    )
+}
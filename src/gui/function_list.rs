@@ -0,0 +1,78 @@
+use crate::data_provider_twiggy::DataProviderTwiggy;
+use crate::gui::appearance::row_height;
+
+/// Extra column of a function list: the header and the value of each row.
+pub type ExtraColumn<'c> = (&'c str, &'c dyn Fn(usize) -> String);
+
+/// Shows a table with the size and name of `row_count` functions, where
+/// `function_at(row)` returns the function index shown in each row.
+///
+/// Returns the index of the function that was clicked, if any.
+pub fn show_function_list(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    data_provider: &DataProviderTwiggy,
+    row_count: usize,
+    function_at: impl Fn(usize) -> usize,
+    extra_column: Option<ExtraColumn<'_>>,
+) -> Option<usize> {
+    let mut clicked_function = None;
+
+    let old_selectable_labels = ui.style().interaction.selectable_labels;
+    ui.style_mut().interaction.selectable_labels = false;
+
+    let mut table = egui_extras::TableBuilder::new(ui)
+        .id_salt(id_salt)
+        .striped(true)
+        .resizable(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(egui_extras::Column::auto())
+        .sense(egui::Sense::click());
+    if extra_column.is_some() {
+        table = table.column(egui_extras::Column::auto());
+    }
+    table = table.column(egui_extras::Column::remainder());
+
+    table
+        .header(20.0, |mut header| {
+            header.col(|ui| {
+                ui.strong("Size (bytes)");
+            });
+            if let Some((title, _)) = extra_column {
+                header.col(|ui| {
+                    ui.strong(title);
+                });
+            }
+            header.col(|ui| {
+                ui.strong("Name");
+            });
+        })
+        .body(|mut body| {
+            let row_height = row_height(body.ui_mut());
+            body.rows(row_height, row_count, |mut row| {
+                let row_index = row.index();
+                let function_idx = function_at(row_index);
+                let property = &data_provider.raw_data[function_idx].function_property;
+
+                row.col(|ui| {
+                    ui.label(property.shallow_size_bytes.to_string());
+                });
+                if let Some((_, value)) = extra_column {
+                    row.col(|ui| {
+                        ui.label(value(row_index));
+                    });
+                }
+                row.col(|ui| {
+                    ui.label(data_provider.function_name(function_idx));
+                });
+
+                if row.response().clicked() {
+                    clicked_function = Some(function_idx);
+                }
+            });
+        });
+
+    ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+    clicked_function
+}
@@ -0,0 +1,96 @@
+use crate::analysis::generic_bloat::{GenericBloatReport, GenericBloatSortKey};
+use crate::gui::appearance::row_height;
+
+pub struct GenericBloatViewer;
+
+impl GenericBloatViewer {
+    /// Shows the report as a table sorted by `sort_key`.
+    ///
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &mut GenericBloatReport,
+        sort_key: &mut GenericBloatSortKey,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+        let mut new_sort_key = *sort_key;
+
+        let old_selectable_labels = ui.style().interaction.selectable_labels;
+        ui.style_mut().interaction.selectable_labels = false;
+
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .sense(egui::Sense::click())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.selectable_value(
+                        &mut new_sort_key,
+                        GenericBloatSortKey::TotalSize,
+                        "Total size (bytes)",
+                    );
+                });
+                header.col(|ui| {
+                    ui.selectable_value(
+                        &mut new_sort_key,
+                        GenericBloatSortKey::InstanceCount,
+                        "Instances",
+                    );
+                });
+                header.col(|ui| {
+                    ui.selectable_value(
+                        &mut new_sort_key,
+                        GenericBloatSortKey::DistinctTypeArgs,
+                        "Distinct type args",
+                    );
+                });
+                header.col(|ui| {
+                    ui.strong("Name");
+                });
+                header.col(|ui| {
+                    ui.strong("Suggestion");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, report.entries.len(), |mut row| {
+                    let entry = &report.entries[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(entry.total_size_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.instance_count.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.distinct_type_args.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(&entry.name);
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.suggestion.unwrap_or(""));
+                    });
+
+                    if row.response().clicked() {
+                        clicked_function = Some(entry.largest_instance);
+                    }
+                });
+            });
+
+        ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+        if new_sort_key != *sort_key {
+            *sort_key = new_sort_key;
+            report.sort(new_sort_key);
+        }
+
+        clicked_function
+    }
+}
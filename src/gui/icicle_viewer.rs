@@ -0,0 +1,207 @@
+use egui::{Rect, Sense, Stroke, StrokeKind, pos2, vec2};
+
+use crate::{data_provider_twiggy::DataProviderTwiggy, gui::treemap::color_for_name};
+
+const ROW_HEIGHT: f32 = 20.0;
+
+/// Nodes narrower than this are not drawn, nor their children.
+const MIN_WIDTH: f32 = 2.0;
+
+/// A node of the size tree laid out in the chart.
+struct IcicleBox<'n> {
+    node: usize,
+    /// Horizontal position and width, as fractions of the chart width.
+    x: f32,
+    width: f32,
+    /// Row of the node, the zoomed node being on the first one.
+    depth: usize,
+    /// Name of the top level namespace, which gives the color.
+    color_name: &'n str,
+}
+
+/// Icicle chart of the namespace tree from the debug info: the width of a
+/// node is its size, its children are laid out below it.
+///
+/// Sizes follow the filter of the functions explorer. Clicking a node zooms
+/// on it, clicking the top node zooms out.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct IcicleViewer {
+    /// Node shown over the whole width, the root when not zoomed.
+    #[serde(skip)]
+    zoom_node: usize,
+}
+
+impl IcicleViewer {
+    pub fn show(&mut self, ui: &mut egui::Ui, data_provider: &DataProviderTwiggy) {
+        let state = &data_provider.dominator_state;
+        if state.tree.len() <= 1 {
+            ui.label("No namespaces, the module has no DWARF debug info.");
+            return;
+        }
+        if self.zoom_node >= state.tree.len() || !state.items_state[self.zoom_node].visible() {
+            self.zoom_node = 0;
+        }
+
+        let name_of = |node: usize| match node {
+            0 => "all",
+            _ => state.tree[node].value.name.as_str(),
+        };
+        let size_of = |node: usize| state.items_ui_data[node].size as u64;
+
+        // Path from the root to the zoomed node.
+        let mut zoom_path = vec![self.zoom_node];
+        while let Some(parent) = state.tree.get_parent_index(zoom_path[zoom_path.len() - 1]) {
+            zoom_path.push(parent);
+        }
+        zoom_path.reverse();
+
+        let mut new_zoom_node = None;
+        ui.horizontal_wrapped(|ui| {
+            for (idx, &node) in zoom_path.iter().enumerate() {
+                if idx > 0 {
+                    ui.label(">");
+                }
+                if ui.link(name_of(node)).clicked() {
+                    new_zoom_node = Some(node);
+                }
+            }
+        });
+
+        ui.separator();
+
+        // The nodes are laid out top down, each child taking a share of its
+        // parent's width proportional to its size.
+        let color_name = zoom_path.get(1).map_or(name_of(0), |&node| name_of(node));
+        let mut boxes = Vec::new();
+        let mut pending = vec![IcicleBox {
+            node: self.zoom_node,
+            x: 0.0,
+            width: 1.0,
+            depth: 0,
+            color_name,
+        }];
+        let width = ui.available_width();
+
+        while let Some(icicle_box) = pending.pop() {
+            let children: Vec<usize> = state
+                .tree
+                .get_children(icicle_box.node)
+                .filter(|&child| state.items_state[child].visible() && size_of(child) > 0)
+                .collect();
+
+            // Inlined instances are part of their function, their sizes can
+            // add up to more than it.
+            let children_size: u64 = children.iter().map(|&child| size_of(child)).sum();
+            let scale =
+                icicle_box.width / size_of(icicle_box.node).max(children_size).max(1) as f32;
+
+            let mut x = icicle_box.x;
+            for child in children {
+                let child_width = size_of(child) as f32 * scale;
+                if child_width * width >= MIN_WIDTH {
+                    pending.push(IcicleBox {
+                        node: child,
+                        x,
+                        width: child_width,
+                        depth: icicle_box.depth + 1,
+                        color_name: if icicle_box.node == 0 {
+                            name_of(child)
+                        } else {
+                            icicle_box.color_name
+                        },
+                    });
+                }
+                x += child_width;
+            }
+
+            boxes.push(icicle_box);
+        }
+
+        let row_count = boxes
+            .iter()
+            .map(|icicle_box| icicle_box.depth + 1)
+            .max()
+            .unwrap_or(1);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let (response, painter) =
+                ui.allocate_painter(vec2(width, row_count as f32 * ROW_HEIGHT), Sense::click());
+
+            let rect_of = |icicle_box: &IcicleBox| {
+                Rect::from_min_size(
+                    response.rect.min
+                        + vec2(icicle_box.x * width, icicle_box.depth as f32 * ROW_HEIGHT),
+                    vec2(icicle_box.width * width, ROW_HEIGHT),
+                )
+            };
+
+            let hovered = response.hover_pos().and_then(|pos| {
+                boxes
+                    .iter()
+                    .find(|icicle_box| rect_of(icicle_box).contains(pos))
+            });
+
+            let dark_mode = ui.visuals().dark_mode;
+            let text_color = ui.visuals().strong_text_color();
+            let stroke = Stroke {
+                width: 1.0,
+                color: ui.visuals().extreme_bg_color,
+            };
+            let font_id = egui::TextStyle::Small.resolve(ui.style());
+
+            for icicle_box in &boxes {
+                let rect = rect_of(icicle_box);
+                let mut fill = color_for_name(icicle_box.color_name, dark_mode);
+                if hovered.is_some_and(|hovered| hovered.node == icicle_box.node) {
+                    fill = fill.gamma_multiply(1.4);
+                }
+                painter.rect(rect, 0.0, fill, stroke, StrokeKind::Inside);
+
+                if rect.width() > 24.0 {
+                    painter.with_clip_rect(rect.shrink(2.0)).text(
+                        pos2(rect.left() + 4.0, rect.center().y),
+                        egui::Align2::LEFT_CENTER,
+                        name_of(icicle_box.node),
+                        font_id.clone(),
+                        text_color,
+                    );
+                }
+            }
+
+            let Some(hovered) = hovered else {
+                return;
+            };
+
+            if response.clicked() {
+                new_zoom_node = if hovered.node == self.zoom_node {
+                    state.tree.get_parent_index(hovered.node)
+                } else {
+                    Some(hovered.node)
+                };
+            }
+
+            let total_size = size_of(0).max(1);
+            egui::show_tooltip_at_pointer(
+                ui.ctx(),
+                ui.layer_id(),
+                egui::Id::new("icicle_tooltip"),
+                |ui| {
+                    ui.strong(name_of(hovered.node));
+                    ui.label(format!("{:?}", state.tree[hovered.node].value.ty));
+                    ui.label(format!(
+                        "{} bytes, {:.2}% of {}, {:.2}% of all",
+                        size_of(hovered.node),
+                        size_of(hovered.node) as f32 / size_of(self.zoom_node).max(1) as f32
+                            * 100.0,
+                        name_of(self.zoom_node),
+                        size_of(hovered.node) as f32 / total_size as f32 * 100.0
+                    ));
+                },
+            );
+        });
+
+        if let Some(zoom_node) = new_zoom_node {
+            self.zoom_node = zoom_node;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    analysis::inlining::InliningReport,
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::{appearance::row_height, function_list::show_function_list},
+};
+
+pub struct InliningViewer;
+
+impl InliningViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &InliningReport,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        if report.functions.is_empty() {
+            ui.label("No inlined subroutines found, the binary needs DWARF debug info.");
+            return None;
+        }
+
+        egui::CollapsingHeader::new(format!(
+            "Functions with inlined code ({})",
+            report.functions.len()
+        ))
+        .default_open(true)
+        .show(ui, |ui| {
+            let inlined_bytes = |row: usize| {
+                let function_inlining = &report.functions[row];
+                format!(
+                    "{} ({:.1}%, {} calls)",
+                    function_inlining.inlined_bytes,
+                    function_inlining.inlined_percent,
+                    function_inlining.inlined_call_count
+                )
+            };
+
+            clicked_function = show_function_list(
+                ui,
+                "inlining_functions",
+                data_provider,
+                report.functions.len(),
+                |row| report.functions[row].function,
+                Some(("Inlined bytes", &inlined_bytes)),
+            );
+        });
+
+        egui::CollapsingHeader::new(format!("Most inlined functions ({})", report.callees.len()))
+            .show(ui, |ui| {
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("inlining_callees")
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Total bytes");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Copies");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Inlined into");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Name");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Hint");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, report.callees.len(), |mut row| {
+                            let callee = &report.callees[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(callee.total_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(callee.instance_count.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(format!("{} functions", callee.caller_count));
+                            });
+                            row.col(|ui| {
+                                ui.label(&callee.name);
+                            });
+                            row.col(|ui| {
+                                if callee.is_inline_never_candidate() {
+                                    ui.label("#[inline(never)] candidate");
+                                }
+                            });
+                        });
+                    });
+            });
+
+        clicked_function
+    }
+}
@@ -1 +1,29 @@
+pub mod appearance;
+pub mod arena_usage_viewer;
+pub mod bookmarks_viewer;
+pub mod byte_map;
+pub mod cfg_viewer;
+pub mod code_origin_viewer;
+pub mod compilation_units_viewer;
+pub mod crate_treemap_viewer;
+pub mod dead_exports_viewer;
+pub mod dominators_viewer;
+pub mod duplicate_data_viewer;
+pub mod feature_attribution_viewer;
+pub mod function_breakdown_viewer;
+pub mod function_list;
+pub mod generic_bloat_viewer;
+pub mod icicle_viewer;
+pub mod inlining_viewer;
+pub mod navigation_history;
+pub mod padding_viewer;
+pub mod panic_machinery_viewer;
+pub mod profiler_viewer;
+pub mod recursion_viewer;
+pub mod section_summary_viewer;
+pub mod size_diff_viewer;
+pub mod suggestions_viewer;
 pub mod tree_view;
+pub mod treemap;
+pub mod unwinding_viewer;
+pub mod vtables_viewer;
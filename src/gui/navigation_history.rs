@@ -0,0 +1,88 @@
+/// Entries kept before the oldest ones are dropped.
+const MAX_ENTRIES: usize = 256;
+
+/// Functions selected in the explorer and the views, in order, to go back to
+/// the previous ones like in a browser.
+#[derive(Debug, Default)]
+pub struct NavigationHistory {
+    functions: Vec<usize>,
+    /// Index of the selected function in `functions`.
+    current: usize,
+}
+
+impl NavigationHistory {
+    /// Records a newly selected function, forgetting the functions that were
+    /// gone back from.
+    pub fn visit(&mut self, function: usize) {
+        if self.functions.get(self.current) == Some(&function) {
+            return;
+        }
+
+        if !self.functions.is_empty() {
+            self.functions.truncate(self.current + 1);
+        }
+        self.functions.push(function);
+        if self.functions.len() > MAX_ENTRIES {
+            self.functions.remove(0);
+        }
+        self.current = self.functions.len() - 1;
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.current + 1 < self.functions.len()
+    }
+
+    /// Returns the function selected before the current one.
+    pub fn back(&mut self) -> Option<usize> {
+        if !self.can_go_back() {
+            return None;
+        }
+        self.current -= 1;
+        Some(self.functions[self.current])
+    }
+
+    /// Returns the function that was gone back from.
+    pub fn forward(&mut self) -> Option<usize> {
+        if !self.can_go_forward() {
+            return None;
+        }
+        self.current += 1;
+        Some(self.functions[self.current])
+    }
+
+    pub fn clear(&mut self) {
+        self.functions.clear();
+        self.current = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NavigationHistory;
+
+    #[test]
+    fn visiting_drops_the_forward_entries() {
+        let mut history = NavigationHistory::default();
+        history.visit(1);
+        history.visit(2);
+        history.visit(3);
+
+        assert_eq!(history.back(), Some(2));
+        assert_eq!(history.back(), Some(1));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some(2));
+
+        // Selecting the function gone back to doesn't change anything.
+        history.visit(2);
+        assert!(history.can_go_forward());
+
+        history.visit(4);
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(), Some(2));
+        assert_eq!(history.back(), Some(1));
+    }
+}
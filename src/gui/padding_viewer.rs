@@ -0,0 +1,96 @@
+use crate::{
+    analysis::padding::PaddingReport,
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::{appearance::row_height, function_list::show_function_list},
+};
+
+pub struct PaddingViewer;
+
+impl PaddingViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &PaddingReport,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.strong(format!(
+            "Padding: {:.1} KB in the file, {:.1} KB more between segments in memory",
+            report.file_bytes() as f32 / 1024.0,
+            report.segment_gap_bytes as f32 / 1024.0
+        ));
+        ui.label(format!(
+            "Padded immediates: {} bytes, padded body sizes: {} bytes, zero fill in data: {} bytes",
+            report.instruction_padding_bytes,
+            report.body_size_padding_bytes,
+            report.zero_fill_bytes
+        ));
+
+        ui.separator();
+
+        egui::CollapsingHeader::new(format!(
+            "Padded functions ({})",
+            report.padded_functions.len()
+        ))
+        .default_open(true)
+        .show(ui, |ui| {
+            let padding_bytes = |row: usize| report.padded_functions[row].padding_bytes.to_string();
+
+            clicked_function = clicked_function.or(show_function_list(
+                ui,
+                "padding_functions",
+                data_provider,
+                report.padded_functions.len(),
+                |row| report.padded_functions[row].function,
+                Some(("Padding (bytes)", &padding_bytes)),
+            ));
+        });
+
+        egui::CollapsingHeader::new(format!("Wasted runs ({})", report.runs.len())).show(
+            ui,
+            |ui| {
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("padding_runs")
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Size (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Location");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Kind");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, report.runs.len(), |mut row| {
+                            let run = &report.runs[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(run.size_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(match run.address {
+                                    Some(address) => format!("0x{:08x}", address),
+                                    None => format!("segment {}", run.segment),
+                                });
+                            });
+                            row.col(|ui| {
+                                ui.label(run.kind.as_str());
+                            });
+                        });
+                    });
+            },
+        );
+
+        clicked_function
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{
+    analysis::panic_machinery::PanicMachineryReport, data_provider_twiggy::DataProviderTwiggy,
+    gui::function_list::show_function_list,
+};
+
+pub struct PanicMachineryViewer;
+
+impl PanicMachineryViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &PanicMachineryReport,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.strong(format!(
+            "fmt/panic machinery: {:.1} KB, retained by these {} call sites",
+            report.machinery_size_bytes as f32 / 1024.0,
+            report.retainers.len()
+        ));
+
+        ui.separator();
+
+        egui::CollapsingHeader::new(format!("Call sites ({})", report.retainers.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                let machinery_callees =
+                    |row: usize| report.retainers[row].machinery_callees.to_string();
+
+                clicked_function = clicked_function.or(show_function_list(
+                    ui,
+                    "panic_machinery_retainers",
+                    data_provider,
+                    report.retainers.len(),
+                    |row| report.retainers[row].function,
+                    Some(("Machinery callees", &machinery_callees)),
+                ));
+            });
+
+        egui::CollapsingHeader::new(format!("Machinery ({})", report.machinery.len())).show(
+            ui,
+            |ui| {
+                clicked_function = clicked_function.or(show_function_list(
+                    ui,
+                    "panic_machinery_functions",
+                    data_provider,
+                    report.machinery.len(),
+                    |row| report.machinery[row],
+                    None,
+                ));
+            },
+        );
+
+        clicked_function
+    }
+}
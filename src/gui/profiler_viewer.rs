@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use crate::timings::Timings;
+
+pub struct ProfilerViewer;
+
+impl ProfilerViewer {
+    /// Shows the time spent in the last frames, in seconds, and the steps of
+    /// the analysis of the files, by name.
+    pub fn show(ui: &mut egui::Ui, frame_times: &[f32], files: &[(String, &Timings)]) {
+        let ms = |duration: Duration| format!("{:.1} ms", duration.as_secs_f64() * 1000.0);
+
+        if let Some(&last) = frame_times.last() {
+            let average = frame_times.iter().sum::<f32>() / frame_times.len() as f32;
+            let max = frame_times.iter().copied().fold(0.0, f32::max);
+            ui.label(format!(
+                "Frame: {:.1} ms, average {:.1} ms, max {:.1} ms over the last {} frames",
+                last * 1000.0,
+                average * 1000.0,
+                max * 1000.0,
+                frame_times.len()
+            ));
+            Self::show_frame_times(ui, frame_times, max);
+        }
+
+        ui.separator();
+        if files.is_empty() {
+            ui.weak("No file analyzed");
+            return;
+        }
+
+        egui::Grid::new("profiler_timings")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, timings) in files {
+                    let total = timings.total();
+                    ui.strong(name);
+                    ui.strong(ms(total));
+                    ui.end_row();
+
+                    for &(step, duration) in timings.steps() {
+                        ui.label(step);
+                        ui.label(ms(duration));
+                        ui.add(
+                            egui::ProgressBar::new(
+                                duration.as_secs_f32() / total.as_secs_f32().max(f32::EPSILON),
+                            )
+                            .desired_width(120.0),
+                        );
+                        ui.end_row();
+                    }
+                }
+            });
+    }
+
+    /// One bar per frame, scaled to the longest.
+    fn show_frame_times(ui: &mut egui::Ui, frame_times: &[f32], max: f32) {
+        let (rect, _) =
+            ui.allocate_exact_size(egui::vec2(ui.available_width(), 48.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        let bar_width = rect.width() / frame_times.len() as f32;
+        for (idx, &frame_time) in frame_times.iter().enumerate() {
+            let height = rect.height() * frame_time / max.max(f32::EPSILON);
+            let left = rect.left() + idx as f32 * bar_width;
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(left, rect.bottom() - height),
+                    egui::pos2(left + bar_width, rect.bottom()),
+                ),
+                0.0,
+                ui.visuals().selection.bg_fill,
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    analysis::recursion::{DEEP_CHAIN_DEPTH, RecursionReport},
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::{appearance::row_height, function_list::show_function_list},
+};
+
+pub struct RecursionViewer;
+
+impl RecursionViewer {
+    /// Shows the depth of the exports, the deepest chain of calls of
+    /// `selected_export`, and the recursive cycles.
+    ///
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &RecursionReport,
+        selected_export: &mut Option<usize>,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.strong(format!(
+            "{} recursive cycles ({} functions), {} exports with chains of {} calls or more",
+            report.cycles.len(),
+            report.recursive_function_count,
+            report.deep_exports().count(),
+            DEEP_CHAIN_DEPTH
+        ));
+        ui.label("Depths count a recursive cycle as a single call, stack sizes are estimated from the function prologues.");
+
+        ui.separator();
+
+        egui::CollapsingHeader::new(format!("Exports ({})", report.exports.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                let old_selectable_labels = ui.style().interaction.selectable_labels;
+                ui.style_mut().interaction.selectable_labels = false;
+
+                let warn_color = ui.visuals().warn_fg_color;
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("recursion_exports")
+                    .striped(true)
+                    .resizable(true)
+                    .max_scroll_height(300.0)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .sense(egui::Sense::click())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Depth");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Stack (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Recursive");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Export");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, report.exports.len(), |mut row| {
+                            let export_idx = row.index();
+                            let export = &report.exports[export_idx];
+                            row.set_selected(*selected_export == Some(export_idx));
+
+                            row.col(|ui| {
+                                if export.is_deep() {
+                                    ui.colored_label(warn_color, export.depth.to_string());
+                                } else {
+                                    ui.label(export.depth.to_string());
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(export.stack_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                if export.reaches_recursion {
+                                    ui.colored_label(warn_color, "yes");
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(&export.name);
+                            });
+
+                            if row.response().clicked() {
+                                *selected_export = Some(export_idx);
+                                clicked_function = Some(export.function);
+                            }
+                        });
+                    });
+
+                ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+            });
+
+        if let Some(export) = selected_export.and_then(|idx| report.exports.get(idx)) {
+            egui::CollapsingHeader::new(format!("Deepest chain from {}", export.name))
+                .id_salt("recursion_deepest_chain")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let depth = |row: usize| (row + 1).to_string();
+
+                    clicked_function = clicked_function.or(show_function_list(
+                        ui,
+                        "recursion_chain",
+                        data_provider,
+                        export.deepest_chain.len(),
+                        |row| export.deepest_chain[row],
+                        Some(("Depth", &depth)),
+                    ));
+                });
+        }
+
+        egui::CollapsingHeader::new(format!("Recursive cycles ({})", report.cycles.len())).show(
+            ui,
+            |ui| {
+                let rows: Vec<(usize, usize)> = report
+                    .cycles
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(cycle_idx, cycle)| {
+                        cycle
+                            .functions
+                            .iter()
+                            .map(move |&function| (cycle_idx, function))
+                    })
+                    .collect();
+                let cycle = |row: usize| {
+                    let (cycle_idx, _) = rows[row];
+                    format!(
+                        "#{} ({} functions)",
+                        cycle_idx + 1,
+                        report.cycles[cycle_idx].functions.len()
+                    )
+                };
+
+                clicked_function = clicked_function.or(show_function_list(
+                    ui,
+                    "recursion_cycles",
+                    data_provider,
+                    rows.len(),
+                    |row| rows[row].1,
+                    Some(("Cycle", &cycle)),
+                ));
+            },
+        );
+
+        clicked_function
+    }
+}
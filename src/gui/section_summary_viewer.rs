@@ -0,0 +1,207 @@
+use std::f32::consts::TAU;
+
+use egui::{Color32, Mesh, Sense, Shape, Stroke, vec2};
+
+use crate::{
+    analysis::section_summary::{SectionCategory, SectionSummary},
+    gui::{appearance::row_height, treemap::color_for_name},
+};
+
+const PIE_CHART_RADIUS: f32 = 80.0;
+
+const SIZE_BAR_SIZE: egui::Vec2 = vec2(240.0, 14.0);
+
+pub struct SectionSummaryViewer;
+
+impl SectionSummaryViewer {
+    pub fn show(ui: &mut egui::Ui, summary: &SectionSummary) {
+        let dark_mode = ui.visuals().dark_mode;
+        let colors =
+            SectionCategory::ALL.map(|category| color_for_name(category.as_str(), dark_mode));
+
+        ui.horizontal(|ui| {
+            show_pie_chart(ui, &summary.category_bytes, &colors);
+
+            ui.vertical(|ui| {
+                ui.heading(format!("{:.1} KB", summary.total_bytes as f32 / 1024.0));
+
+                egui::Grid::new("section_summary_categories")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (category, color) in SectionCategory::ALL.iter().zip(colors) {
+                            let size_bytes = summary.bytes_of(*category);
+
+                            ui.colored_label(color, format!("⏺ {}", category.as_str()));
+                            ui.label(format!("{:.1} KB", size_bytes as f32 / 1024.0));
+                            ui.label(format!(
+                                "{:.1}%",
+                                size_bytes as f32 / summary.total_bytes.max(1) as f32 * 100.0
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+        egui::CollapsingHeader::new(format!("Sections ({})", summary.sections.len())).show(
+            ui,
+            |ui| {
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("section_summary_sections")
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Size (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Category");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Section");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, summary.sections.len(), |mut row| {
+                            let section = &summary.sections[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(section.size_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(section.category.as_str());
+                            });
+                            row.col(|ui| {
+                                ui.label(&section.name);
+                            });
+                        });
+                    });
+            },
+        );
+    }
+}
+
+/// Shows the categories of `summary` as a small stacked bar, for the bottom
+/// panel. The part of the code matching the filter of the functions explorer,
+/// `filtered_code_bytes`, is hatched over the code.
+pub fn show_size_bar(
+    ui: &mut egui::Ui,
+    summary: &SectionSummary,
+    filtered_code_bytes: Option<u64>,
+) {
+    let dark_mode = ui.visuals().dark_mode;
+    let total_bytes = summary.total_bytes.max(1) as f32;
+    let (response, painter) = ui.allocate_painter(SIZE_BAR_SIZE, Sense::hover());
+    let rect = response.rect;
+
+    let mut hovered = None;
+    let mut left = rect.left();
+    for category in SectionCategory::ALL {
+        let width = rect.width() * summary.bytes_of(category) as f32 / total_bytes;
+        let segment = egui::Rect::from_x_y_ranges(left..=left + width, rect.y_range());
+        painter.rect_filled(segment, 0.0, color_for_name(category.as_str(), dark_mode));
+
+        if category == SectionCategory::Code
+            && let Some(filtered_code_bytes) = filtered_code_bytes
+        {
+            let filtered_width = rect.width() * filtered_code_bytes as f32 / total_bytes;
+            let filtered = egui::Rect::from_x_y_ranges(
+                left..=left + filtered_width.min(width),
+                rect.top() + rect.height() / 3.0..=rect.bottom() - rect.height() / 3.0,
+            );
+            painter.rect_filled(filtered, 0.0, ui.visuals().strong_text_color());
+        }
+
+        if response
+            .hover_pos()
+            .is_some_and(|pos| segment.contains(pos))
+        {
+            hovered = Some(category);
+        }
+        left += width;
+    }
+
+    painter.rect_stroke(
+        rect,
+        0.0,
+        Stroke {
+            width: 1.0,
+            color: ui.visuals().extreme_bg_color,
+        },
+        egui::StrokeKind::Outside,
+    );
+
+    if let Some(filtered_code_bytes) = filtered_code_bytes {
+        ui.label(format!(
+            "Filter: {:.1} KB ({:.1}% of code)",
+            filtered_code_bytes as f32 / 1024.0,
+            filtered_code_bytes as f32 / summary.bytes_of(SectionCategory::Code).max(1) as f32
+                * 100.0
+        ));
+    }
+
+    if let Some(category) = hovered {
+        response.on_hover_ui_at_pointer(|ui| {
+            ui.strong(category.as_str());
+            ui.label(format!(
+                "{:.1} KB ({:.1}%)",
+                summary.bytes_of(category) as f32 / 1024.0,
+                summary.bytes_of(category) as f32 / total_bytes * 100.0
+            ));
+        });
+    }
+}
+
+/// Draws a pie chart of `values`, starting at the top and going clockwise.
+fn show_pie_chart(ui: &mut egui::Ui, values: &[u64], colors: &[Color32]) {
+    let (response, painter) = ui.allocate_painter(
+        vec2(PIE_CHART_RADIUS, PIE_CHART_RADIUS) * 2.0,
+        Sense::hover(),
+    );
+    let center = response.rect.center();
+
+    let total: u64 = values.iter().sum();
+    if total == 0 {
+        return;
+    }
+
+    let mut start_angle = -TAU / 4.0;
+    for (value, color) in values.iter().zip(colors) {
+        let sweep = *value as f32 / total as f32 * TAU;
+        if sweep <= 0.0 {
+            continue;
+        }
+
+        // A fan of triangles around the center, one every few degrees.
+        let segment_count = (sweep / TAU * 90.0).ceil().max(1.0) as u32;
+        let mut mesh = Mesh::default();
+        mesh.colored_vertex(center, *color);
+        for segment in 0..=segment_count {
+            let angle = start_angle + sweep * segment as f32 / segment_count as f32;
+            mesh.colored_vertex(
+                center + PIE_CHART_RADIUS * vec2(angle.cos(), angle.sin()),
+                *color,
+            );
+            if segment > 0 {
+                mesh.add_triangle(0, segment, segment + 1);
+            }
+        }
+        painter.add(Shape::mesh(mesh));
+
+        start_angle += sweep;
+    }
+
+    painter.circle_stroke(
+        center,
+        PIE_CHART_RADIUS,
+        Stroke {
+            width: 1.0,
+            color: ui.visuals().extreme_bg_color,
+        },
+    );
+}
@@ -0,0 +1,102 @@
+use crate::{
+    analysis::diff::{SizeDiffEntry, SizeDiffReport},
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::appearance::row_height,
+};
+
+pub struct SizeDiffViewer;
+
+impl SizeDiffViewer {
+    /// Shows the functions that changed between the `old` and `new` builds.
+    ///
+    /// Returns the clicked entry, if any.
+    pub fn show<'r>(
+        ui: &mut egui::Ui,
+        report: &'r SizeDiffReport,
+        old: &DataProviderTwiggy,
+        new: &DataProviderTwiggy,
+    ) -> Option<&'r SizeDiffEntry> {
+        let mut clicked_entry = None;
+
+        ui.strong(format!(
+            "Total: {} -> {} bytes ({:+})",
+            report.old_total_bytes,
+            report.new_total_bytes,
+            report.new_total_bytes as i64 - report.old_total_bytes as i64
+        ));
+
+        let old_selectable_labels = ui.style().interaction.selectable_labels;
+        ui.style_mut().interaction.selectable_labels = false;
+
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .sense(egui::Sense::click())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Delta (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Old");
+                });
+                header.col(|ui| {
+                    ui.strong("New");
+                });
+                header.col(|ui| {
+                    ui.strong("Matched by");
+                });
+                header.col(|ui| {
+                    ui.strong("Name");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, report.entries.len(), |mut row| {
+                    let entry = &report.entries[row.index()];
+
+                    row.col(|ui| {
+                        let delta = entry.delta_bytes();
+                        let color = if delta > 0 {
+                            ui.visuals().error_fg_color
+                        } else {
+                            ui.visuals().text_color()
+                        };
+                        ui.colored_label(color, format!("{:+}", delta));
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.old_size_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(entry.new_size_bytes.to_string());
+                    });
+                    row.col(|ui| match (entry.match_kind, entry.new) {
+                        (Some(match_kind), _) => _ = ui.label(match_kind.as_str()),
+                        (None, Some(_)) => _ = ui.label("added"),
+                        (None, None) => _ = ui.label("removed"),
+                    });
+                    row.col(|ui| {
+                        let name = match (entry.new, entry.old) {
+                            (Some(idx), _) => new.function_name(idx),
+                            (None, Some(idx)) => old.function_name(idx),
+                            (None, None) => "",
+                        };
+                        ui.label(name);
+                    });
+
+                    if row.response().clicked() {
+                        clicked_entry = Some(entry);
+                    }
+                });
+            });
+
+        ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+
+        clicked_entry
+    }
+}
@@ -0,0 +1,60 @@
+use crate::analysis::suggestions::SuggestionsReport;
+use crate::gui::appearance::row_height;
+
+pub struct SuggestionsViewer;
+
+impl SuggestionsViewer {
+    pub fn show(ui: &mut egui::Ui, report: &SuggestionsReport) {
+        if report.suggestions.is_empty() {
+            ui.label("Nothing to suggest.");
+            return;
+        }
+
+        let total_savings_bytes: u64 = report
+            .suggestions
+            .iter()
+            .map(|suggestion| suggestion.estimated_savings_bytes)
+            .sum();
+        ui.strong(format!(
+            "Estimated savings: up to {:.1} KB",
+            total_savings_bytes as f32 / 1024.0
+        ));
+
+        ui.separator();
+
+        egui_extras::TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::auto())
+            .column(egui_extras::Column::remainder())
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Est. savings (bytes)");
+                });
+                header.col(|ui| {
+                    ui.strong("Suggestion");
+                });
+                header.col(|ui| {
+                    ui.strong("Action");
+                });
+            })
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
+                body.rows(row_height, report.suggestions.len(), |mut row| {
+                    let suggestion = &report.suggestions[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(suggestion.estimated_savings_bytes.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(suggestion.title);
+                    });
+                    row.col(|ui| {
+                        ui.label(&suggestion.action);
+                    });
+                });
+            });
+    }
+}
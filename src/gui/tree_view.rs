@@ -1,227 +1,102 @@
-use std::{cmp::Ordering, time::Instant};
+use egui::{
+    Event, EventFilter, Id, Key, Modifiers, Rect, Response, Sense, Ui, UiBuilder, pos2, vec2,
+};
 
-use egui::{Id, Rect, Response, Sense, Ui, UiBuilder, pos2, scroll_area::ScrollAreaOutput, vec2};
+use crate::{
+    arena::tree::Tree,
+    tree_state::{TreeItemStateFlags, TreeNavigation, TreeState},
+};
 
-use crate::arena::{Arena, array::Array, scratch::scratch_arena, tree::Tree, vec::Vec};
-
-bitflags::bitflags! {
-    pub struct TreeItemStateFlags: u8 {
-        const OPENED = 0b00000001;
-        const FORCE_OPENED = 0b00000010;
-        const VISIBLE = 0b00000100;
-    }
-}
-
-pub struct TreeItemState {
-    // pub descendants_count: u32,
-    pub flags: TreeItemStateFlags,
-    pub depth: u8,
-}
-
-impl TreeItemState {
-    #[inline(always)]
-    pub fn visible(&self) -> bool {
-        self.flags.contains(TreeItemStateFlags::VISIBLE)
-    }
-}
-
-pub struct TreeState<'a, T, D> {
-    /// The input tree used to create this tree state.
-    pub tree: Tree<'a, T>,
-
-    /// This state of the
-    pub items_state: Array<'a, TreeItemState>,
-
-    /// Associated UI data for each item in the tree.
-    pub items_ui_data: Array<'a, D>,
-
-    /// The indices to display the visible items (in order)
-    /// This allows us to compute the order of indices
-    /// to use when displaying each item one after the other.
-    pub row_indices: Array<'a, usize>,
-
-    /// The minimum depth in the tree to start displaying items.
-    /// This is useful in case where the first few levels of
-    /// the tree are not meant to be displayed in the UI.
-    pub min_depth_to_display: u8,
-
-    pub sort_fn: fn((&T, &D), (&T, &D)) -> Ordering,
-
-    pub hovered_index: usize,
-    pub selected_index: usize,
-}
-
-impl<'a, T, D> TreeState<'a, T, D> {
-    pub fn from_tree(
-        arena: &'a Arena,
-        tree: Tree<'a, T>,
-        min_depth_to_display: u8,
-        state: fn(&T, usize) -> D,
-        sort: fn((&T, &D), (&T, &D)) -> Ordering,
-    ) -> Self {
-        let mut items_state: Array<'_, TreeItemState> = Array::new(arena, tree.len());
-        let mut items_ui_data: Array<'_, D> = Array::new(arena, tree.len());
-
-        for idx in 0..tree.len() {
-            items_state.push(TreeItemState {
-                flags: TreeItemStateFlags::VISIBLE,
-                depth: 0,
-            });
-            items_ui_data.push(state(tree.get(idx), idx));
-        }
-
-        fn add_tree_item<T, D>(
-            items_state: &mut Array<'_, TreeItemState>,
-            items_ui_data: &mut Array<'_, D>,
-            tree: &Tree<'_, T>,
-            state: fn(&T, usize) -> D,
-            sort: fn((&T, &D), (&T, &D)) -> Ordering,
-            item_idx: usize,
-            depth: u8,
-        ) {
-            items_state[item_idx].depth = depth;
-
-            let scratch = scratch_arena(&[]);
-            let mut children_idx = Vec::new(&scratch, 1024);
-
-            for child_idx in tree.get_children(item_idx) {
-                children_idx.push(child_idx);
-            }
-
-            children_idx.sort_by(|a, b| {
-                sort(
-                    (&tree[*a].value, &items_ui_data[*a]),
-                    (&tree[*b].value, &items_ui_data[*b]),
-                )
-            });
-
-            for child_idx in children_idx {
-                add_tree_item(
-                    items_state,
-                    items_ui_data,
-                    tree,
-                    state,
-                    sort,
-                    child_idx,
-                    depth + 1,
-                );
-            }
-        }
-
-        add_tree_item(
-            &mut items_state,
-            &mut items_ui_data,
-            &tree,
-            state,
-            sort,
-            0,
-            0,
-        );
-
-        if !items_state.is_empty() {
-            items_state[0].flags.set(TreeItemStateFlags::OPENED, true);
-        }
-
-        let row_indices = Array::new(arena, items_state.len());
-
-        let mut result = Self {
-            tree,
-            items_state,
-            items_ui_data,
-            row_indices,
-            min_depth_to_display,
-            sort_fn: sort,
-            hovered_index: usize::MAX,
-            selected_index: usize::MAX,
-        };
-
-        result.recompute_indices();
-        result
-    }
-
-    pub(crate) fn recompute_indices(&mut self) {
-        let start = Instant::now();
-        self.row_indices.clear();
-
-        let scratch = scratch_arena(&[]);
-        let mut node_stack = Vec::new(&scratch, 1024 * 1024);
-
-        node_stack.push(0);
-
-        while let Some(idx) = node_stack.pop() {
-            if self.items_state[idx].depth >= self.min_depth_to_display {
-                self.row_indices.push(idx);
-            }
-
-            if !self.items_state[idx]
-                .flags
-                .intersects(TreeItemStateFlags::OPENED | TreeItemStateFlags::FORCE_OPENED)
-            {
-                continue;
-            }
-
-            let base_index = node_stack.len();
-            for child_idx in self.tree.get_children(idx) {
-                if !self.items_state[child_idx].visible() {
-                    continue;
-                }
-
-                node_stack.push(child_idx);
-            }
-
-            // Sort
-            node_stack[base_index..].sort_by(|a, b| {
-                (self.sort_fn)(
-                    (&self.tree[*b].value, &self.items_ui_data[*b]),
-                    (&self.tree[*a].value, &self.items_ui_data[*a]),
-                )
-            });
-        }
-
-        println!(
-            "Time to compute indices {}",
-            (Instant::now() - start).as_secs_f32()
-        );
-    }
-}
+/// Seconds during which the typed characters add up to the searched name.
+const TYPE_AHEAD_TIMEOUT: f64 = 1.0;
 
 pub struct TreeItem<'a, T, S> {
+    pub index: usize,
+    pub tree: &'a Tree<'a, T>,
     pub item: &'a T,
     pub item_state: &'a S,
     pub selected: bool,
     pub response: &'a Response,
 }
 
+/// Right-aligned column shown after the tree, like the sizes of the items.
+pub struct TreeColumn<'c> {
+    pub title: &'c str,
+    pub width: f32,
+}
+
 pub struct TreeView;
 
 impl TreeView {
+    /// `add_cell` fills the cells of the `columns` of an item. Returns the
+    /// column whose title was clicked, to sort by it.
     pub fn body<T, S>(
         &mut self,
         ui: &mut Ui,
         state: &mut TreeState<T, S>,
         row_height_sans_spacing: f32,
+        columns: &[TreeColumn],
         mut add_item: impl FnMut(&mut Ui, TreeItem<'_, T, S>),
-    ) -> ScrollAreaOutput<()> {
+        mut add_cell: impl FnMut(&mut Ui, usize, &T, &S),
+    ) -> Option<usize> {
+        // The tree takes the keyboard focus when a row is clicked.
+        let tree_id = ui.make_persistent_id("tree_view");
+        let layer_id = ui.layer_id();
+        ui.memory_mut(|memory| memory.interested_in_focus(tree_id, layer_id));
+        if ui.memory(|memory| memory.has_focus(tree_id)) {
+            handle_keyboard(ui, tree_id, state);
+        }
+
         let items_count = state.row_indices.len();
         let available_height = ui.available_height();
         let available_width = ui.available_width();
 
+        let tree_column = if columns.is_empty() {
+            egui_extras::Column::exact(available_width)
+        } else {
+            egui_extras::Column::remainder()
+        };
         let mut table = egui_extras::TableBuilder::new(ui)
             .striped(true)
             .resizable(true)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-            .column(egui_extras::Column::exact(available_width))
+            .column(tree_column)
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height);
+        for column in columns {
+            table = table.column(egui_extras::Column::exact(column.width));
+        }
 
         // Prepare it so it is clickable and we see when we hover rows.
         table = table.sense(egui::Sense::click());
 
-        let mut item_state_changed = false;
+        if let Some(index) = state.scroll_to_index.take()
+            && let Some(row) = state.row_indices.iter().position(|&item| item == index)
+        {
+            table = table.scroll_to_row(row, Some(egui::Align::Center));
+        }
+
+        let mut toggled_item = None;
+        let mut clicked_column = None;
 
-        let scroll_area_output = table
-            .header(0.0, |mut header| {
+        let header_height = if columns.is_empty() { 0.0 } else { 20.0 };
+        table
+            .header(header_height, |mut header| {
                 header.col(|_| {});
+                for (column_index, column) in columns.iter().enumerate() {
+                    header.col(|ui| {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let title = egui::RichText::new(column.title).strong();
+                            if ui
+                                .add(egui::Label::new(title).sense(Sense::click()))
+                                .on_hover_text("Click to sort")
+                                .clicked()
+                            {
+                                clicked_column = Some(column_index);
+                            }
+                        });
+                    });
+                }
             })
             .body(|body| {
                 body.rows(18.0, items_count, |mut row| {
@@ -241,19 +116,10 @@ impl TreeView {
                         let header_response = ui.interact(rect, id, Sense::click());
 
                         if header_response.clicked() {
-                            let node = &mut state.items_state[item_index];
-                            if node.flags.intersects(
-                                TreeItemStateFlags::OPENED | TreeItemStateFlags::FORCE_OPENED,
-                            ) {
-                                node.flags.remove(TreeItemStateFlags::OPENED);
-                                node.flags.remove(TreeItemStateFlags::FORCE_OPENED);
-                            } else {
-                                node.flags.insert(TreeItemStateFlags::OPENED);
-                            }
-
                             state.selected_index = item_index;
+                            ui.memory_mut(|memory| memory.request_focus(tree_id));
 
-                            item_state_changed = true;
+                            toggled_item = Some(item_index);
                         }
 
                         if header_response.hovered() {
@@ -294,7 +160,8 @@ impl TreeView {
                         add_item(
                             &mut child_ui,
                             TreeItem {
-                                // index: state.row_indices[item_index],
+                                index: item_index,
+                                tree: &state.tree,
                                 item: &state.tree[item_index].value,
                                 item_state: &state.items_ui_data[item_index],
                                 selected: state.selected_index == item_index,
@@ -302,20 +169,91 @@ impl TreeView {
                             },
                         );
                     });
+
+                    for column_index in 0..columns.len() {
+                        row.col(|ui| {
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    add_cell(
+                                        ui,
+                                        column_index,
+                                        &state.tree[item_index].value,
+                                        &state.items_ui_data[item_index],
+                                    )
+                                },
+                            );
+                        });
+                    }
                 });
             });
 
         // State is changed after processing all rows because the item count changes and we can't simply interrupt
         // the table widget.
         // Once we fully implement this withouth relying on TableView, we can make this a lot better
-        if item_state_changed {
-            state.recompute_indices();
+        if let Some(item_index) = toggled_item {
+            state.set_opened(item_index, !state.is_opened(item_index));
         }
 
-        scroll_area_output
+        clicked_column
     }
 }
 
+fn handle_keyboard<T, S>(ui: &mut Ui, tree_id: Id, state: &mut TreeState<T, S>) {
+    ui.memory_mut(|memory| {
+        memory.set_focus_lock_filter(
+            tree_id,
+            EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            },
+        )
+    });
+
+    for (key, navigation) in [
+        (Key::ArrowDown, TreeNavigation::Down),
+        (Key::ArrowUp, TreeNavigation::Up),
+        (Key::ArrowLeft, TreeNavigation::Parent),
+        (Key::ArrowRight, TreeNavigation::Children),
+        (Key::Home, TreeNavigation::First),
+        (Key::End, TreeNavigation::Last),
+    ] {
+        if ui.input_mut(|input| input.consume_key(Modifiers::NONE, key)) {
+            state.navigate(navigation);
+        }
+    }
+
+    let typed: String = ui.input(|input| {
+        input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    });
+    if typed.is_empty() {
+        return;
+    }
+
+    // Typing quickly searches for the whole word, typing again later starts
+    // a new search from the next item.
+    let now = ui.input(|input| input.time);
+    let (prefix, continued) = ui.data_mut(|data| {
+        let (prefix, last_typed) = data.get_temp_mut_or_default::<(String, f64)>(tree_id);
+        let continued = now - *last_typed < TYPE_AHEAD_TIMEOUT;
+        if !continued {
+            prefix.clear();
+        }
+        prefix.push_str(&typed);
+        *last_typed = now;
+        (prefix.clone(), continued)
+    });
+    state.select_by_prefix(&prefix, continued);
+}
+
 fn paint_tree_icon(ui: &mut egui::Ui, openness: f32, paint: bool, response: &egui::Response) {
     let visuals = ui.style().interact(response);
     let rect = response.rect;
@@ -341,3 +279,55 @@ fn paint_tree_icon(ui: &mut egui::Ui, openness: f32, paint: bool, response: &egu
         ui.allocate_space(rect.size());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::TreeState;
+    use crate::arena::{Arena, tree::Tree};
+
+    #[test]
+    fn opening_and_closing_updates_the_rows_under_the_item() {
+        let arena = Arena::new(1024 * 1024);
+        // 0
+        // ├── 1 (size 10)
+        // │   ├── 3 (size 4)
+        // │   └── 4 (size 6)
+        // └── 2 (size 20)
+        //     └── 5 (size 20)
+        let mut tree = Tree::new(&arena, 6, 30);
+        tree.add_child(0, 10);
+        tree.add_child(0, 20);
+        tree.add_child(1, 4);
+        tree.add_child(1, 6);
+        tree.add_child(2, 20);
+
+        let mut state = TreeState::from_tree(
+            &arena,
+            tree,
+            1,
+            |size: &u32, _| *size,
+            |(_, a), (_, b)| b.cmp(a),
+            |_| "",
+        );
+        assert_eq!(&state.row_indices[..], &[2, 1]);
+
+        state.set_opened(1, true);
+        assert_eq!(&state.row_indices[..], &[2, 1, 4, 3]);
+        state.set_opened(2, true);
+        assert_eq!(&state.row_indices[..], &[2, 5, 1, 4, 3]);
+        state.set_opened(1, false);
+        assert_eq!(&state.row_indices[..], &[2, 5, 1]);
+
+        // The children stay open under closed parents.
+        state.set_opened(1, true);
+        state.set_opened(2, false);
+        state.set_opened(5, true);
+        assert_eq!(&state.row_indices[..], &[2, 1, 4, 3]);
+        state.set_opened(2, true);
+        assert_eq!(&state.row_indices[..], &[2, 5, 1, 4, 3]);
+
+        let rows = state.row_indices.to_vec();
+        state.recompute_indices();
+        assert_eq!(&state.row_indices[..], &rows[..]);
+    }
+}
@@ -0,0 +1,175 @@
+use egui::{Color32, Rect, Sense, Stroke, StrokeKind, pos2, vec2};
+
+pub struct TreemapResponse {
+    /// Rectangle of each item, empty for the items with a size of 0.
+    pub rects: Vec<Rect>,
+    pub hovered: Option<usize>,
+    pub clicked: Option<usize>,
+}
+
+/// Shows `sizes` as a squarified treemap filling the available space.
+///
+/// `label(idx)` is drawn on the items large enough to fit it, and
+/// `color(idx)` is used to fill them.
+pub fn show_treemap<'l>(
+    ui: &mut egui::Ui,
+    sizes: &[u64],
+    label: impl Fn(usize) -> &'l str,
+    color: impl Fn(usize) -> Color32,
+) -> TreemapResponse {
+    let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click());
+    let rects = squarify(response.rect, sizes);
+
+    let hovered = response
+        .hover_pos()
+        .and_then(|pos| rects.iter().position(|rect| rect.contains(pos)));
+    let clicked = hovered.filter(|_| response.clicked());
+
+    let text_color = ui.visuals().strong_text_color();
+    let stroke = Stroke {
+        width: 1.0,
+        color: ui.visuals().extreme_bg_color,
+    };
+    let font_id = egui::TextStyle::Small.resolve(ui.style());
+
+    for (idx, rect) in rects.iter().enumerate() {
+        if !rect.is_positive() {
+            continue;
+        }
+
+        let mut fill = color(idx);
+        if hovered == Some(idx) {
+            fill = fill.gamma_multiply(1.4);
+        }
+        painter.rect(*rect, 0.0, fill, stroke, StrokeKind::Inside);
+
+        let galley = painter.layout_no_wrap(label(idx).to_string(), font_id.clone(), text_color);
+        if galley.size().x + 4.0 < rect.width() && galley.size().y + 4.0 < rect.height() {
+            painter.galley(rect.min + vec2(2.0, 2.0), galley, text_color);
+        }
+    }
+
+    TreemapResponse {
+        rects,
+        hovered,
+        clicked,
+    }
+}
+
+/// A stable color for an item, derived from its name.
+pub fn color_for_name(name: &str, dark_mode: bool) -> Color32 {
+    let hash = name.bytes().fold(0xcbf29ce484222325u64, |hash, c| {
+        (hash ^ c as u64).wrapping_mul(0x100000001b3)
+    });
+    let hue = (hash % 360) as f32 / 360.0;
+    let value = if dark_mode { 0.45 } else { 0.85 };
+
+    egui::ecolor::Hsva::new(hue, 0.45, value, 1.0).into()
+}
+
+/// Lays out `sizes` in `rect` with the squarified treemap algorithm
+/// (Bruls, Huizing, van Wijk), which keeps the rectangles close to squares.
+///
+/// Returns the rectangle of each size, in the same order as `sizes`.
+pub fn squarify(rect: Rect, sizes: &[u64]) -> Vec<Rect> {
+    let mut rects = vec![Rect::NOTHING; sizes.len()];
+
+    let mut order: Vec<usize> = (0..sizes.len()).filter(|&idx| sizes[idx] > 0).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(sizes[idx]));
+
+    let total_size: u64 = order.iter().map(|&idx| sizes[idx]).sum();
+    if total_size == 0 || rect.area() <= 0.0 {
+        return rects;
+    }
+
+    let scale = rect.area() / total_size as f32;
+    let area_of = |idx: usize| sizes[idx] as f32 * scale;
+
+    let mut remaining = rect;
+    let mut row_start = 0;
+    while row_start < order.len() {
+        let short_side = remaining.width().min(remaining.height());
+
+        // Grow the row while it makes the worst aspect ratio better.
+        // Sizes are sorted, so the first item of the row is the largest and the last the smallest.
+        let max_area = area_of(order[row_start]);
+        let mut row_end = row_start + 1;
+        let mut row_area = max_area;
+        while row_end < order.len() {
+            let area = area_of(order[row_end]);
+            let current =
+                worst_aspect_ratio(row_area, max_area, area_of(order[row_end - 1]), short_side);
+            let grown = worst_aspect_ratio(row_area + area, max_area, area, short_side);
+            if grown > current {
+                break;
+            }
+
+            row_area += area;
+            row_end += 1;
+        }
+
+        // The row is laid out along the short side of the remaining space.
+        let thickness = row_area / short_side;
+        let mut offset = 0.0;
+        for &idx in &order[row_start..row_end] {
+            let length = area_of(idx) / thickness;
+            rects[idx] = if remaining.width() >= remaining.height() {
+                Rect::from_min_size(
+                    pos2(remaining.min.x, remaining.min.y + offset),
+                    vec2(thickness, length),
+                )
+            } else {
+                Rect::from_min_size(
+                    pos2(remaining.min.x + offset, remaining.min.y),
+                    vec2(length, thickness),
+                )
+            };
+            offset += length;
+        }
+
+        if remaining.width() >= remaining.height() {
+            remaining.min.x += thickness;
+        } else {
+            remaining.min.y += thickness;
+        }
+
+        row_start = row_end;
+    }
+
+    rects
+}
+
+/// The worst aspect ratio of a row with a total area of `row_area`, laid out
+/// along `side`, whose largest and smallest items have `max_area` and `min_area`.
+fn worst_aspect_ratio(row_area: f32, max_area: f32, min_area: f32, side: f32) -> f32 {
+    let side_squared = side * side;
+    let row_area_squared = row_area * row_area;
+
+    (side_squared * max_area / row_area_squared).max(row_area_squared / (side_squared * min_area))
+}
+
+#[cfg(test)]
+mod test {
+    use super::squarify;
+    use egui::{Rect, pos2};
+
+    #[test]
+    fn squarify_fills_the_rect_proportionally() {
+        let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(600.0, 400.0));
+        let sizes = [6, 0, 6, 4, 3, 2, 2, 1];
+        let rects = squarify(rect, &sizes);
+
+        assert_eq!(rects[1], Rect::NOTHING);
+
+        let total_size: u64 = sizes.iter().sum();
+        for (idx, item_rect) in rects.iter().enumerate() {
+            if sizes[idx] == 0 {
+                continue;
+            }
+
+            let expected_area = rect.area() * sizes[idx] as f32 / total_size as f32;
+            assert!((item_rect.area() - expected_area).abs() < 1.0);
+            assert!(rect.expand(0.01).contains_rect(*item_rect));
+        }
+    }
+}
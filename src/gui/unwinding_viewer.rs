@@ -0,0 +1,135 @@
+use crate::{
+    analysis::unwinding::UnwindingReport,
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::{appearance::row_height, function_list::show_function_list},
+};
+
+pub struct UnwindingViewer;
+
+impl UnwindingViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &UnwindingReport,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.strong(format!(
+            "panic=abort would save about {:.1} KB",
+            report.savings_bytes() as f32 / 1024.0
+        ));
+        ui.label(format!(
+            "Landing pads: {:.1} KB in {} functions, unwinder: {:.1} KB, sections: {:.1} KB",
+            report.landing_pad_bytes as f32 / 1024.0,
+            report.landing_pads.len(),
+            report.runtime_bytes as f32 / 1024.0,
+            report.section_bytes as f32 / 1024.0
+        ));
+        if report.landing_pads.is_empty() {
+            ui.label("No exception handling instructions, the landing pads were already removed.");
+        }
+
+        ui.separator();
+
+        egui::CollapsingHeader::new(format!("Crates ({})", report.crates.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("unwinding_crates")
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Savings (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Landing pads");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Unwinder");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Crate");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, report.crates.len(), |mut row| {
+                            let crate_unwinding = &report.crates[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(crate_unwinding.savings_bytes().to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(crate_unwinding.landing_pad_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(crate_unwinding.runtime_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(&crate_unwinding.crate_name);
+                            });
+                        });
+                    });
+            });
+
+        egui::CollapsingHeader::new(format!("Landing pads ({})", report.landing_pads.len())).show(
+            ui,
+            |ui| {
+                let landing_pad_bytes = |row: usize| {
+                    let landing_pads = &report.landing_pads[row];
+                    format!(
+                        "{} ({} try)",
+                        landing_pads.size_bytes, landing_pads.try_count
+                    )
+                };
+
+                clicked_function = clicked_function.or(show_function_list(
+                    ui,
+                    "unwinding_landing_pads",
+                    data_provider,
+                    report.landing_pads.len(),
+                    |row| report.landing_pads[row].function,
+                    Some(("Landing pads (bytes)", &landing_pad_bytes)),
+                ));
+            },
+        );
+
+        egui::CollapsingHeader::new(format!("Unwinder ({})", report.runtime.len())).show(
+            ui,
+            |ui| {
+                clicked_function = clicked_function.or(show_function_list(
+                    ui,
+                    "unwinding_runtime",
+                    data_provider,
+                    report.runtime.len(),
+                    |row| report.runtime[row],
+                    None,
+                ));
+            },
+        );
+
+        egui::CollapsingHeader::new(format!("Sections ({})", report.sections.len())).show(
+            ui,
+            |ui| {
+                egui::Grid::new("unwinding_sections")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for section in &report.sections {
+                            ui.label(&section.name);
+                            ui.label(format!("{} bytes", section.size_bytes));
+                            ui.end_row();
+                        }
+                    });
+            },
+        );
+
+        clicked_function
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    analysis::vtables::VtableReport,
+    data_provider_twiggy::DataProviderTwiggy,
+    gui::{appearance::row_height, function_list::show_function_list},
+};
+
+pub struct VtablesViewer;
+
+impl VtablesViewer {
+    /// Returns the index of the function to select when a row is clicked.
+    pub fn show(
+        ui: &mut egui::Ui,
+        report: &VtableReport,
+        data_provider: &DataProviderTwiggy,
+    ) -> Option<usize> {
+        let mut clicked_function = None;
+
+        ui.strong(format!(
+            "{} vtables: {:.1} KB of data, {} indirect calls in {} functions",
+            report.vtables.len(),
+            report.vtable_bytes as f32 / 1024.0,
+            report.call_indirect_count,
+            report.dyn_dispatches.len()
+        ));
+
+        ui.separator();
+
+        egui::CollapsingHeader::new(format!("Trait objects ({})", report.trait_objects.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                let old_selectable_labels = ui.style().interaction.selectable_labels;
+                ui.style_mut().interaction.selectable_labels = false;
+
+                egui_extras::TableBuilder::new(ui)
+                    .id_salt("vtables_trait_objects")
+                    .striped(true)
+                    .resizable(true)
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::auto())
+                    .column(egui_extras::Column::remainder())
+                    .sense(egui::Sense::click())
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Retained code (bytes)");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Vtables");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Vtable bytes");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Trait");
+                        });
+                    })
+                    .body(|mut body| {
+                        let row_height = row_height(body.ui_mut());
+                        body.rows(row_height, report.trait_objects.len(), |mut row| {
+                            let trait_object = &report.trait_objects[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(trait_object.retained_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(trait_object.vtable_count.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(trait_object.vtable_bytes.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(&trait_object.trait_name);
+                            });
+
+                            if row.response().clicked() {
+                                clicked_function = Some(trait_object.largest_method);
+                            }
+                        });
+                    });
+
+                ui.style_mut().interaction.selectable_labels = old_selectable_labels;
+            });
+
+        egui::CollapsingHeader::new(format!(
+            "Dynamic dispatch sites ({})",
+            report.dyn_dispatches.len()
+        ))
+        .show(ui, |ui| {
+            let call_indirect_count =
+                |row: usize| report.dyn_dispatches[row].call_indirect_count.to_string();
+
+            clicked_function = clicked_function.or(show_function_list(
+                ui,
+                "vtables_dyn_dispatches",
+                data_provider,
+                report.dyn_dispatches.len(),
+                |row| report.dyn_dispatches[row].function,
+                Some(("Indirect calls", &call_indirect_count)),
+            ));
+        });
+
+        egui::CollapsingHeader::new(format!("Vtables ({})", report.vtables.len())).show(ui, |ui| {
+            egui_extras::TableBuilder::new(ui)
+                .id_salt("vtables_vtables")
+                .striped(true)
+                .resizable(true)
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .column(egui_extras::Column::auto())
+                .column(egui_extras::Column::auto())
+                .column(egui_extras::Column::auto())
+                .column(egui_extras::Column::remainder())
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        ui.strong("Address");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Size (bytes)");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Trait");
+                    });
+                    header.col(|ui| {
+                        ui.strong("Type");
+                    });
+                })
+                .body(|mut body| {
+                    let row_height = row_height(body.ui_mut());
+                    body.rows(row_height, report.vtables.len(), |mut row| {
+                        let vtable = &report.vtables[row.index()];
+
+                        row.col(|ui| {
+                            ui.label(format!("0x{:08x}", vtable.address));
+                        });
+                        row.col(|ui| {
+                            ui.label(vtable.size_bytes.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(&vtable.trait_name);
+                        });
+                        row.col(|ui| {
+                            ui.label(vtable.type_name.as_deref().unwrap_or(""));
+                        });
+                    });
+                });
+        });
+
+        clicked_function
+    }
+}
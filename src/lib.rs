@@ -1,14 +1,23 @@
-#![feature(allocator_api)]
-
 mod app;
-pub mod arena;
+mod bookmarks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cargo_build;
+pub mod cli;
 mod code_viewer;
-mod data_provider;
-mod data_provider_twiggy;
-mod dwarf;
+mod file_watcher;
 mod functions_explorer;
 mod gui;
 mod memory_viewer;
-mod path;
-mod wasm;
+mod notes;
+mod source_cache;
+mod tags;
 pub use app::TemplateApp;
+#[cfg(not(target_arch = "wasm32"))]
+pub use app::run_native;
+
+// The analysis lives in its own crate, the paths of its modules are kept.
+pub use binary_size_explorer_core::arena;
+use binary_size_explorer_core::{
+    analysis, data_provider, data_provider_twiggy, dwarf, path, progress, provider_registry,
+    timings, tree_state, wasm,
+};
@@ -1,17 +1,84 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::{ops::ControlFlow, process::ExitCode};
+
 // When compiling natively:
-fn main() -> eframe::Result {
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> ExitCode {
+    attach_console();
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
-    let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([600.0, 400.0]),
-        ..Default::default()
+    // The commands print their results without opening the window.
+    let file = match binary_size_explorer::cli::run() {
+        ControlFlow::Break(exit_code) => return exit_code,
+        ControlFlow::Continue(file) => file,
     };
-    eframe::run_native(
-        "Binary Size Explorer",
-        native_options,
-        Box::new(|cc| Ok(Box::new(binary_size_explorer::TemplateApp::new(cc)))),
-    )
+
+    match binary_size_explorer::run_native(file) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Release builds on Windows are GUI applications, without a console of their
+/// own. The commands print to the one of the terminal they are run from.
+#[cfg(not(target_arch = "wasm32"))]
+fn attach_console() {
+    #[cfg(all(windows, not(debug_assertions)))]
+    // SAFETY: It takes no pointer, and fails without a parent console.
+    unsafe {
+        use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+// When compiling to web using trunk:
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use eframe::wasm_bindgen::JsCast as _;
+
+    // Redirect `log` message to `console.log` and friends:
+    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+
+    let web_options = eframe::WebOptions::default();
+
+    wasm_bindgen_futures::spawn_local(async {
+        let document = web_sys::window()
+            .expect("No window")
+            .document()
+            .expect("No document");
+
+        let canvas = document
+            .get_element_by_id("the_canvas_id")
+            .expect("Failed to find the_canvas_id")
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .expect("the_canvas_id was not a HtmlCanvasElement");
+
+        let start_result = eframe::WebRunner::new()
+            .start(
+                canvas,
+                web_options,
+                Box::new(|cc| Ok(Box::new(binary_size_explorer::TemplateApp::new(cc)))),
+            )
+            .await;
+
+        // Remove the loading text and spinner:
+        if let Some(loading_text) = document.get_element_by_id("loading_text") {
+            match start_result {
+                Ok(()) => loading_text.remove(),
+                Err(err) => {
+                    loading_text.set_inner_html(
+                        "<p> The app has crashed. See the developer console for details. </p>",
+                    );
+                    panic!("Failed to start eframe: {err:?}");
+                }
+            }
+        }
+    });
 }
@@ -1,45 +1,258 @@
 use egui::{
-    Align, Color32, Label, Layout, Response, RichText, Shape, Stroke, StrokeKind, Vec2, WidgetText,
+    Align, Color32, Key, KeyboardShortcut, Label, Layout, Modifiers, Response, RichText, Shape,
+    Stroke, StrokeKind, TextEdit, Vec2, WidgetText,
 };
 use egui_extras::{Column, TableBuilder};
 use std::fmt::Write;
+use std::ops::Range;
 use std::usize;
 
 use crate::arena::{scratch::scratch_arena, string::String};
+use crate::gui::appearance::row_height;
 
 const CELLS_PER_ROW: usize = 16;
 const CELL_SIZE: Vec2 = Vec2::new(30.0, 16.0);
 
-#[derive(Clone, Copy)]
+const GO_TO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::G);
+/// For how long the byte gone to is flashed.
+const FLASH_SECONDS: f64 = 1.5;
+
+#[derive(Clone)]
 struct MemoryViewerState {
     selected_offset: usize,
+    /// Text of the go to address box.
+    address: std::string::String,
+    /// Offset gone to with the time its flashing ends at.
+    flashed_offset: Option<(usize, f64)>,
+    /// Offset to scroll to, gone to after the table was shown.
+    scroll_to: Option<usize>,
+    /// Offsets of the byte the selection started from and of its last byte.
+    selection: Option<(usize, usize)>,
+    /// The selection follows the pointer until the button is released.
+    selecting: bool,
+}
+
+impl MemoryViewerState {
+    fn selected_range(&self) -> Option<Range<usize>> {
+        let (anchor, end) = self.selection?;
+        Some(anchor.min(end)..anchor.max(end) + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CopyFormat {
+    /// `0a 1b 2c`
+    Hex,
+    /// The printable characters, with dots for the other bytes.
+    Ascii,
+    /// `[0x0a, 0x1b, 0x2c]`
+    RustArray,
+}
+
+impl CopyFormat {
+    const ALL: [CopyFormat; 3] = [CopyFormat::Hex, CopyFormat::Ascii, CopyFormat::RustArray];
+
+    fn title(self) -> &'static str {
+        match self {
+            CopyFormat::Hex => "Copy as hex",
+            CopyFormat::Ascii => "Copy as ASCII",
+            CopyFormat::RustArray => "Copy as Rust array",
+        }
+    }
+
+    fn format(self, bytes: &[u8]) -> std::string::String {
+        let mut text = std::string::String::with_capacity(bytes.len() * 6);
+        match self {
+            CopyFormat::Hex => {
+                for (idx, byte) in bytes.iter().enumerate() {
+                    if idx > 0 {
+                        text.push(' ');
+                    }
+                    _ = write!(text, "{:02x}", byte);
+                }
+            }
+            CopyFormat::Ascii => {
+                text.extend(bytes.iter().map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                }));
+            }
+            CopyFormat::RustArray => {
+                text.push('[');
+                for (idx, byte) in bytes.iter().enumerate() {
+                    if idx > 0 {
+                        text.push_str(", ");
+                    }
+                    _ = write!(text, "{:#04x}", byte);
+                }
+                text.push(']');
+            }
+        }
+        text
+    }
+}
+
+impl Default for MemoryViewerState {
+    fn default() -> Self {
+        Self {
+            selected_offset: usize::MAX,
+            address: std::string::String::new(),
+            flashed_offset: None,
+            scroll_to: None,
+            selection: None,
+            selecting: false,
+        }
+    }
+}
+
+/// Parses an offset in hexadecimal, like `0x1f00`, or in decimal.
+fn parse_address(text: &str) -> Option<usize> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// What covers an address, for the address column to link to it.
+pub enum AddressOwner<'a> {
+    Function { index: usize, name: &'a str },
+    Section { name: &'a str, start: usize },
 }
 
 pub struct MemoryViewer;
 
 impl MemoryViewer {
-    pub fn show(ui: &mut egui::Ui, data: &[u8]) {
+    /// `data` starts at `base_address` in the file, the addresses shown and
+    /// gone to being the ones in the file. The `highlighted` bytes, like the
+    /// ones of the selected function, are colored over the background colors
+    /// of the `regions`.
+    ///
+    /// The addresses of the rows link to the first function or section
+    /// returned by `owner_of` for their bytes. The sections are gone to, and
+    /// the function clicked is returned to be selected.
+    pub fn show<'a>(
+        ui: &mut egui::Ui,
+        data: &[u8],
+        base_address: usize,
+        highlighted: Option<Range<usize>>,
+        regions: &[(Range<usize>, Color32)],
+        owner_of: &dyn Fn(usize) -> Option<AddressOwner<'a>>,
+    ) -> Option<usize> {
+        let to_offsets = |range: &Range<usize>| {
+            range.start.saturating_sub(base_address)..range.end.saturating_sub(base_address)
+        };
+        let highlighted = highlighted.as_ref().map(to_offsets);
+        let regions: Vec<(Range<usize>, Color32)> = regions
+            .iter()
+            .map(|(range, color)| (to_offsets(range), *color))
+            .collect();
+        let mut clicked_function = None;
+        let highlight_color = ui.visuals().selection.bg_fill.gamma_multiply(0.5);
+
         let scratch = scratch_arena(&[]);
 
         let id = ui.make_persistent_id("__memory_viewer_state");
 
-        let mut selected_offset = ui.data_mut(|map| {
-            map.get_temp::<MemoryViewerState>(id)
-                .map(|state| state.selected_offset)
-                .unwrap_or(usize::MAX)
+        let mut state =
+            ui.data_mut(|map| map.get_temp::<MemoryViewerState>(id).unwrap_or_default());
+        let mut selected_offset = state.selected_offset;
+
+        let now = ui.input(|input| input.time);
+        if state
+            .flashed_offset
+            .is_some_and(|(_, flash_end)| flash_end <= now)
+        {
+            state.flashed_offset = None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Go to");
+            let response = ui
+                .add(
+                    TextEdit::singleline(&mut state.address)
+                        .hint_text("0x1f00")
+                        .desired_width(120.0),
+                )
+                .on_hover_text(format!(
+                    "Offset in hexadecimal or decimal ({})",
+                    ui.ctx().format_shortcut(&GO_TO_SHORTCUT)
+                ));
+            if ui.input_mut(|input| input.consume_shortcut(&GO_TO_SHORTCUT)) {
+                response.request_focus();
+            }
+
+            let address = parse_address(&state.address)
+                .and_then(|address| address.checked_sub(base_address))
+                .filter(|&offset| offset < data.len());
+            let submitted =
+                response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+            if (ui.button("Go").clicked() || submitted)
+                && let Some(offset) = address
+            {
+                state.scroll_to = Some(offset);
+                selected_offset = offset;
+                state.flashed_offset = Some((offset, now + FLASH_SECONDS));
+            }
+            if address.is_none() && !state.address.trim().is_empty() {
+                ui.colored_label(ui.visuals().error_fg_color, "Not in the data");
+            }
+
+            if let Some(range) = state.selected_range()
+                && let Some(bytes) = data.get(range.clone())
+            {
+                ui.separator();
+                ui.label(format!(
+                    "Selected {:#x}..{:#x} ({} bytes)",
+                    base_address + range.start,
+                    base_address + range.end,
+                    bytes.len()
+                ));
+                for format in CopyFormat::ALL {
+                    if ui.button(format.title()).clicked() {
+                        ui.ctx().copy_text(format.format(bytes));
+                    }
+                }
+                if ui.button("Clear").clicked() {
+                    state.selection = None;
+                }
+            }
         });
+        let selected_range = state.selected_range();
+        let selection_color = ui.visuals().selection.bg_fill;
+        // Byte under the pointer, even while dragging.
+        let mut pointed_offset = None;
+
+        let flashed_offset = state.flashed_offset;
+        if flashed_offset.is_some() {
+            ui.ctx().request_repaint();
+        }
+
+        egui::SidePanel::right(id.with("data_inspector"))
+            .resizable(false)
+            .show_inside(ui, |ui| {
+                Self::show_data_inspector(ui, data, base_address, selected_offset);
+            });
 
         let main_column_width = CELLS_PER_ROW as f32 * CELL_SIZE.x
             + (CELLS_PER_ROW as f32 - 1.0) * ui.spacing().item_spacing.x;
 
-        let table = TableBuilder::new(ui)
+        let mut table = TableBuilder::new(ui)
             .column(Column::exact(80.0))
             .column(Column::exact(main_column_width))
             .column(Column::exact(120.0))
             .cell_layout(egui::Layout::left_to_right(Align::Center))
             .striped(true)
             .min_scrolled_height(500.0)
-            .resizable(false);
+            .resizable(false)
+            .drag_to_scroll(false);
+        if let Some(offset) = state.scroll_to.take() {
+            table = table.scroll_to_row(offset / CELLS_PER_ROW, Some(Align::Center));
+        }
+        let mut gone_to_section = None;
 
         table
             .header(20.0, |mut header| {
@@ -74,22 +287,43 @@ impl MemoryViewer {
                     });
                 });
             })
-            .body(|body| {
+            .body(|mut body| {
+                let row_height = row_height(body.ui_mut());
                 let mut buffer = String::new(&scratch, CELLS_PER_ROW);
 
                 body.rows(
-                    20.0,
+                    row_height,
                     (data.len() + CELLS_PER_ROW - 1) / CELLS_PER_ROW,
                     |mut row| {
                         let offset = row.index() * CELLS_PER_ROW;
                         let len = (data.len() - offset).min(CELLS_PER_ROW);
                         let data = &data[offset..(offset + len)];
 
+                        let address = base_address + offset;
                         buffer.clear();
-                        _ = write!(&mut buffer, "{:#08x}", offset);
+                        _ = write!(&mut buffer, "{:#08x}", address);
 
                         row.col(|ui| {
-                            ui.monospace(buffer.as_str());
+                            let address_text = RichText::new(buffer.as_str()).monospace();
+                            match owner_of(address) {
+                                Some(AddressOwner::Function { index, name }) => {
+                                    if ui.link(address_text).on_hover_text(name).clicked() {
+                                        clicked_function = Some(index);
+                                    }
+                                }
+                                Some(AddressOwner::Section { name, start }) => {
+                                    if ui
+                                        .link(address_text)
+                                        .on_hover_text(format!("Go to the start of {}", name))
+                                        .clicked()
+                                    {
+                                        gone_to_section = Some(start);
+                                    }
+                                }
+                                None => {
+                                    ui.label(address_text);
+                                }
+                            }
                         });
 
                         row.col(|ui| {
@@ -100,6 +334,41 @@ impl MemoryViewer {
                                 if response.hovered() {
                                     selected_offset = offset + i;
                                 }
+                                if response.contains_pointer() && i < len {
+                                    pointed_offset = Some(offset + i);
+                                }
+
+                                if let Some((_, color)) = regions
+                                    .iter()
+                                    .find(|(range, _)| range.contains(&(offset + i)))
+                                {
+                                    ui.painter().rect_filled(response.rect, 0.0, *color);
+                                }
+                                if highlighted
+                                    .as_ref()
+                                    .is_some_and(|range| range.contains(&(offset + i)))
+                                {
+                                    ui.painter()
+                                        .rect_filled(response.rect, 0.0, highlight_color);
+                                }
+                                if selected_range
+                                    .as_ref()
+                                    .is_some_and(|range| range.contains(&(offset + i)))
+                                {
+                                    ui.painter()
+                                        .rect_filled(response.rect, 0.0, selection_color);
+                                }
+
+                                if let Some((flashed_offset, flash_end)) = flashed_offset
+                                    && flashed_offset == offset + i
+                                {
+                                    let fade = ((flash_end - now) / FLASH_SECONDS) as f32;
+                                    ui.painter().rect_filled(
+                                        response.rect,
+                                        0.0,
+                                        Color32::YELLOW.gamma_multiply(0.6 * fade),
+                                    );
+                                }
 
                                 if selected_offset == offset + i {
                                     ui.painter().add(Shape::rect_stroke(
@@ -124,10 +393,43 @@ impl MemoryViewer {
                                 }
                             }
 
-                            let response = ui.monospace(buffer.as_str());
+                            let response = ui.add(
+                                Label::new(RichText::new(buffer.as_str()).monospace())
+                                    .selectable(false),
+                            );
                             let mut rect = response.rect;
                             let pixels_per_byte = rect.width() / CELLS_PER_ROW as f32;
 
+                            let colored_ranges = regions
+                                .iter()
+                                .map(|(range, color)| (range, *color))
+                                .chain(highlighted.iter().map(|range| (range, highlight_color)))
+                                .chain(selected_range.iter().map(|range| (range, selection_color)));
+                            for (range, color) in colored_ranges {
+                                let start = range.start.clamp(offset, offset + len);
+                                let end = range.end.clamp(offset, offset + len);
+                                if start < end {
+                                    let mut range_rect = rect;
+                                    range_rect.min.x =
+                                        rect.min.x + pixels_per_byte * (start - offset) as f32;
+                                    range_rect.max.x =
+                                        rect.min.x + pixels_per_byte * (end - offset) as f32;
+                                    ui.painter().rect_filled(range_rect, 0.0, color);
+                                }
+                            }
+
+                            if response.contains_pointer()
+                                && let Some(pointer_pos) = ui.ctx().pointer_latest_pos()
+                            {
+                                let i = (((pointer_pos.x - rect.min.x) / rect.width())
+                                    .clamp(0.0, 0.99)
+                                    * CELLS_PER_ROW as f32)
+                                    as usize;
+                                if i < len {
+                                    pointed_offset = Some(offset + i);
+                                }
+                            }
+
                             if response.hovered() {
                                 if let Some(hover_pos) = response.hover_pos() {
                                     let i = (((hover_pos.x - rect.min.x) / rect.width()).min(0.99)
@@ -157,16 +459,73 @@ impl MemoryViewer {
                 );
             });
 
-        // Stores new selected_offset
-        ui.data_mut(|map| {
-            map.get_temp_mut_or::<MemoryViewerState>(
-                id,
-                MemoryViewerState {
-                    selected_offset: usize::MAX,
-                },
+        // Dragging over the bytes selects them.
+        let (pressed, down, escape) = ui.input(|input| {
+            (
+                input.pointer.primary_pressed(),
+                input.pointer.primary_down(),
+                input.key_pressed(Key::Escape),
             )
-            .selected_offset = selected_offset
         });
+        if let Some(pointed_offset) = pointed_offset {
+            if pressed {
+                state.selection = Some((pointed_offset, pointed_offset));
+                state.selecting = true;
+            } else if down
+                && state.selecting
+                && let Some((anchor, _)) = state.selection
+            {
+                state.selection = Some((anchor, pointed_offset));
+            }
+        }
+        if !down {
+            state.selecting = false;
+        }
+        if escape {
+            state.selection = None;
+        }
+
+        if let Some(offset) = gone_to_section
+            .and_then(|start| start.checked_sub(base_address))
+            .filter(|&offset| offset < data.len())
+        {
+            state.scroll_to = Some(offset);
+            selected_offset = offset;
+            state.flashed_offset = Some((offset, now + FLASH_SECONDS));
+        }
+
+        // Stores new selected_offset
+        state.selected_offset = selected_offset;
+        ui.data_mut(|map| map.insert_temp(id, state));
+
+        clicked_function
+    }
+
+    /// The bytes at the offset read as the usual types, like in hex editors.
+    fn show_data_inspector(ui: &mut egui::Ui, data: &[u8], base_address: usize, offset: usize) {
+        ui.strong("Data inspector");
+        let Some(bytes) = data.get(offset..) else {
+            ui.label("Hover or go to a byte");
+            return;
+        };
+
+        egui::Grid::new("data_inspector")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Offset");
+                ui.monospace(format!("{:#x}", base_address + offset));
+                ui.end_row();
+
+                for (name, value) in inspect_bytes(bytes) {
+                    ui.label(name);
+                    match value {
+                        Some(value) => ui.monospace(value),
+                        None => ui.weak("-"),
+                    };
+                    ui.end_row();
+                }
+            });
     }
 
     fn show_cell(
@@ -180,7 +539,108 @@ impl MemoryViewer {
         _ = write!(buffer, "{:#04x}", byte);
         ui.add_sized(
             CELL_SIZE,
-            Label::new(RichText::new(buffer.as_str()).monospace()),
+            Label::new(RichText::new(buffer.as_str()).monospace()).selectable(false),
         )
     }
 }
+
+/// Values of the types starting with `bytes`, little endian like in wasm,
+/// `None` when there aren't enough bytes for them.
+fn inspect_bytes(bytes: &[u8]) -> [(&'static str, Option<std::string::String>); 9] {
+    fn read<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+        bytes.get(..N)?.try_into().ok()
+    }
+
+    [
+        (
+            "u8",
+            read::<1>(bytes).map(|b| u8::from_le_bytes(b).to_string()),
+        ),
+        (
+            "i8",
+            read::<1>(bytes).map(|b| i8::from_le_bytes(b).to_string()),
+        ),
+        (
+            "i32",
+            read::<4>(bytes).map(|b| i32::from_le_bytes(b).to_string()),
+        ),
+        (
+            "u32",
+            read::<4>(bytes).map(|b| u32::from_le_bytes(b).to_string()),
+        ),
+        (
+            "i64",
+            read::<8>(bytes).map(|b| i64::from_le_bytes(b).to_string()),
+        ),
+        (
+            "f32",
+            read::<4>(bytes).map(|b| f32::from_le_bytes(b).to_string()),
+        ),
+        (
+            "f64",
+            read::<8>(bytes).map(|b| f64::from_le_bytes(b).to_string()),
+        ),
+        (
+            "LEB128",
+            read_uleb128(bytes).map(|(value, len)| format!("{} ({} bytes)", value, len)),
+        ),
+        ("UTF-8", utf8_char(bytes).map(|c| format!("{:?}", c))),
+    ]
+}
+
+/// Unsigned LEB128 value with the number of bytes it takes.
+fn read_uleb128(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (idx, &byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * idx);
+        if byte & 0x80 == 0 {
+            return Some((value, idx + 1));
+        }
+    }
+
+    None
+}
+
+/// First character of `bytes`, if they start with a valid UTF-8 sequence.
+fn utf8_char(bytes: &[u8]) -> Option<char> {
+    (1..=bytes.len().min(4))
+        .find_map(|len| std::str::from_utf8(&bytes[..len]).ok())
+        .and_then(|text| text.chars().next())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CopyFormat, inspect_bytes, parse_address, read_uleb128, utf8_char};
+
+    #[test]
+    fn addresses_are_hexadecimal_or_decimal() {
+        assert_eq!(parse_address("0x1f00"), Some(0x1f00));
+        assert_eq!(parse_address(" 0XFF "), Some(0xff));
+        assert_eq!(parse_address("256"), Some(256));
+        assert_eq!(parse_address("0x"), None);
+        assert_eq!(parse_address("1f00"), None);
+    }
+
+    #[test]
+    fn bytes_are_read_as_the_usual_types() {
+        assert_eq!(read_uleb128(&[0xe5, 0x8e, 0x26, 0xff]), Some((624485, 3)));
+        assert_eq!(read_uleb128(&[0x80, 0x80]), None);
+
+        assert_eq!(utf8_char("é!".as_bytes()), Some('é'));
+        assert_eq!(utf8_char(&[0xff, b'a']), None);
+
+        let values = inspect_bytes(&[0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(values[1].1.as_deref(), Some("-1"));
+        assert_eq!(values[3].1.as_deref(), Some("4294967295"));
+        // Not enough bytes for an i64.
+        assert_eq!(values[4].1, None);
+    }
+
+    #[test]
+    fn selections_are_copied_in_every_format() {
+        let bytes = [0x0a, b'a', 0xff];
+        assert_eq!(CopyFormat::Hex.format(&bytes), "0a 61 ff");
+        assert_eq!(CopyFormat::Ascii.format(&bytes), ".a.");
+        assert_eq!(CopyFormat::RustArray.format(&bytes), "[0x0a, 0x61, 0xff]");
+    }
+}
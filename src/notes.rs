@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use crate::analysis::symbol;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Note {
+    /// Demangled name without the hash, to find the function in the
+    /// dominators which don't have the mangled names.
+    demangled_name: String,
+    text: String,
+}
+
+/// Free text notes attached to functions, saved with the session.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Notes {
+    /// Notes by mangled name, which tells the instances of a generic function
+    /// apart.
+    notes: BTreeMap<String, Note>,
+}
+
+impl Notes {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn get(&self, mangled_name: &str) -> Option<&str> {
+        self.notes.get(mangled_name).map(|note| note.text.as_str())
+    }
+
+    /// Note of the function at `path` in the dominators.
+    pub fn get_by_path(&self, path: &str) -> Option<&str> {
+        self.notes
+            .values()
+            .find(|note| note.demangled_name == path)
+            .map(|note| note.text.as_str())
+    }
+
+    /// Sets the note of a function, an empty note removing it.
+    pub fn set(&mut self, mangled_name: &str, demangled_name: &str, text: String) {
+        if text.trim().is_empty() {
+            self.notes.remove(mangled_name);
+            return;
+        }
+
+        self.notes.insert(
+            mangled_name.to_owned(),
+            Note {
+                demangled_name: symbol::strip_hash(demangled_name).to_owned(),
+                text,
+            },
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{code_viewer::RowData, path::PathExt};
+
+/// Rows of the source files shown by the source code viewers, by path, read
+/// again once the file changes on disk.
+#[derive(Debug, Default)]
+pub struct SourceCache {
+    files: HashMap<PathBuf, CachedSource>,
+}
+
+#[derive(Debug)]
+struct CachedSource {
+    modified: Option<SystemTime>,
+    rows: Vec<RowData>,
+}
+
+impl SourceCache {
+    /// Returns the rows of the file at `path`, `None` if it can't be read.
+    pub fn rows(&mut self, path: &Path) -> Option<&[RowData]> {
+        let modified = PathExt::modified_time(path);
+        let is_stale = self
+            .files
+            .get(path)
+            .is_none_or(|cached| modified.is_none() || cached.modified != modified);
+        if is_stale {
+            let Ok(source) = fs::read_to_string(path) else {
+                self.files.remove(path);
+                return None;
+            };
+            self.files.insert(
+                path.to_path_buf(),
+                CachedSource {
+                    modified,
+                    rows: source_rows(&source),
+                },
+            );
+        }
+
+        self.files.get(path).map(|cached| cached.rows.as_slice())
+    }
+}
+
+/// One row per line of `source`, with its 0-based index.
+pub fn source_rows(source: &str) -> Vec<RowData> {
+    source
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| RowData {
+            cells: vec![format!("{:?}", idx), line.to_string()],
+            bg_color: None,
+            tooltip: None,
+        })
+        .collect()
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use hashbrown::HashSet;
+
+use crate::data_provider_twiggy::DataProviderTwiggy;
+
+/// Colors given to the new tags, in turn.
+const TAG_COLORS: [[u8; 3]; 8] = [
+    [220, 80, 80],
+    [80, 140, 220],
+    [90, 180, 90],
+    [220, 160, 60],
+    [170, 100, 210],
+    [60, 180, 180],
+    [210, 110, 160],
+    [150, 150, 150],
+];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub color: [u8; 3],
+}
+
+impl Tag {
+    pub fn color32(&self) -> egui::Color32 {
+        let [r, g, b] = self.color;
+        egui::Color32::from_rgb(r, g, b)
+    }
+}
+
+/// Number and size of the functions with a tag.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TagTotal {
+    pub function_count: usize,
+    pub size_bytes: u64,
+}
+
+/// Colored labels put on functions to triage them, like `to-remove` or
+/// `vendor`, saved with the session.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Tags {
+    tags: Vec<Tag>,
+    /// Names of the tags of the functions by mangled name.
+    functions: BTreeMap<String, Vec<String>>,
+    /// Incremented on every change, for the views caching the filtered
+    /// functions and the totals.
+    #[serde(skip)]
+    generation: u64,
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        let mut tags = Self {
+            tags: Vec::new(),
+            functions: BTreeMap::new(),
+            generation: 0,
+        };
+        for name in ["to-remove", "vendor", "accepted"] {
+            tags.add_tag(name);
+        }
+        tags
+    }
+}
+
+impl Tags {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    /// Returns false if the name is empty or already taken.
+    pub fn add_tag(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.get(name).is_some() {
+            return false;
+        }
+
+        self.tags.push(Tag {
+            name: name.to_owned(),
+            color: TAG_COLORS[self.tags.len() % TAG_COLORS.len()],
+        });
+        self.generation += 1;
+        true
+    }
+
+    /// Removes the tag from all the functions too.
+    pub fn remove_tag(&mut self, name: &str) {
+        self.tags.retain(|tag| tag.name != name);
+        self.functions.retain(|_, tags| {
+            tags.retain(|tag| tag != name);
+            !tags.is_empty()
+        });
+        self.generation += 1;
+    }
+
+    pub fn set_color(&mut self, name: &str, color: [u8; 3]) {
+        if let Some(tag) = self.tags.iter_mut().find(|tag| tag.name == name) {
+            tag.color = color;
+        }
+    }
+
+    /// Tags of a function, in the order they were created.
+    pub fn tags_of<'t>(&'t self, mangled_name: &str) -> impl Iterator<Item = &'t Tag> {
+        let function_tags = self.functions.get(mangled_name);
+        self.tags.iter().filter(move |tag| {
+            function_tags.is_some_and(|function_tags| function_tags.contains(&tag.name))
+        })
+    }
+
+    pub fn has_tag(&self, mangled_name: &str, tag: &str) -> bool {
+        self.functions
+            .get(mangled_name)
+            .is_some_and(|tags| tags.iter().any(|function_tag| function_tag == tag))
+    }
+
+    pub fn toggle(&mut self, mangled_name: &str, tag: &str) {
+        let tags = self.functions.entry(mangled_name.to_owned()).or_default();
+        match tags.iter().position(|function_tag| function_tag == tag) {
+            Some(idx) => {
+                tags.remove(idx);
+                if tags.is_empty() {
+                    self.functions.remove(mangled_name);
+                }
+            }
+            None => tags.push(tag.to_owned()),
+        }
+        self.generation += 1;
+    }
+
+    /// Mangled names of the functions with the tag.
+    pub fn functions_with(&self, tag: &str) -> HashSet<&str> {
+        self.functions
+            .iter()
+            .filter(|(_, tags)| tags.iter().any(|function_tag| function_tag == tag))
+            .map(|(mangled_name, _)| mangled_name.as_str())
+            .collect()
+    }
+
+    /// Totals of the tags over `functions`, in the order of `tags()`.
+    pub fn totals(&self, data_provider: &DataProviderTwiggy, functions: &[usize]) -> Vec<TagTotal> {
+        let mut totals = vec![TagTotal::default(); self.tags.len()];
+        if self.functions.is_empty() {
+            return totals;
+        }
+
+        let names = &data_provider
+            .wasm_data
+            .functions_section
+            .function_original_names;
+        for &function in functions {
+            let Some(function_tags) = self.functions.get(names[function]) else {
+                continue;
+            };
+
+            let size = data_provider.raw_data[function]
+                .function_property
+                .retained_size_bytes;
+            for (tag, total) in self.tags.iter().zip(totals.iter_mut()) {
+                if function_tags.contains(&tag.name) {
+                    total.function_count += 1;
+                    total.size_bytes += size as u64;
+                }
+            }
+        }
+
+        totals
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tags;
+
+    #[test]
+    fn removing_a_tag_untags_the_functions() {
+        let mut tags = Tags::default();
+        assert!(tags.add_tag("bloat"));
+        assert!(!tags.add_tag("bloat"));
+
+        tags.toggle("_ZN3foo3bar", "bloat");
+        tags.toggle("_ZN3foo3bar", "vendor");
+        tags.toggle("_ZN3foo3baz", "bloat");
+        assert!(tags.has_tag("_ZN3foo3bar", "bloat"));
+        assert_eq!(tags.functions_with("bloat").len(), 2);
+
+        // Tags are listed in their creation order.
+        let names: Vec<&str> = tags
+            .tags_of("_ZN3foo3bar")
+            .map(|tag| tag.name.as_str())
+            .collect();
+        assert_eq!(names, ["vendor", "bloat"]);
+
+        tags.remove_tag("bloat");
+        assert!(tags.get("bloat").is_none());
+        assert!(tags.functions_with("bloat").is_empty());
+        assert!(tags.has_tag("_ZN3foo3bar", "vendor"));
+
+        tags.toggle("_ZN3foo3bar", "vendor");
+        assert!(tags.functions.is_empty());
+    }
+}